@@ -0,0 +1,275 @@
+//! Pluggable authentication for connections whose credentials expire.
+//!
+//! An [`AuthProvider`] produces the username/password to connect with and
+//! how long they stay valid. [`refresh_credentials`] keeps a connection
+//! alive across rotations by reconnecting with fresh credentials before the
+//! old ones expire.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::server::{McpRedisServer, LAZY_CONNECT_TIMEOUT};
+use crate::time;
+
+/// Lifetime of an ElastiCache/MemoryDB IAM token
+const IAM_TOKEN_TTL_SECS: u64 = 900;
+
+/// IAM tokens are regenerated this long before they expire
+const IAM_REFRESH_MARGIN_SECS: u64 = 300;
+
+/// Credentials to AUTH with.
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub username: Option<String>,
+    pub password: String,
+}
+
+/// Source of short-lived credentials for a connection.
+pub trait AuthProvider: Send + Sync {
+    /// Fresh credentials for a new connection.
+    fn credentials(&self) -> Result<Credentials, String>;
+
+    /// How often to reconnect with new credentials.
+    fn refresh_interval(&self) -> Duration;
+}
+
+/// AWS service issuing the IAM token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum IamService {
+    Elasticache,
+    Memorydb,
+}
+
+impl IamService {
+    fn signing_name(self) -> &'static str {
+        match self {
+            IamService::Elasticache => "elasticache",
+            IamService::Memorydb => "memorydb",
+        }
+    }
+}
+
+/// Static AWS credentials used to sign tokens.
+#[derive(Debug, Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// Read `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and the optional
+    /// `AWS_SESSION_TOKEN`.
+    pub fn from_env() -> Result<Self, String> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        Ok(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID")
+                .ok_or_else(|| "AWS_ACCESS_KEY_ID is not set".to_string())?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")
+                .ok_or_else(|| "AWS_SECRET_ACCESS_KEY is not set".to_string())?,
+            session_token: var("AWS_SESSION_TOKEN"),
+        })
+    }
+}
+
+/// IAM authentication for ElastiCache and MemoryDB: the password is a
+/// SigV4-presigned `connect` request valid for 15 minutes.
+#[derive(Debug, Clone)]
+pub struct IamAuth {
+    /// IAM-enabled user id, also sent as the AUTH username
+    pub user_id: String,
+    /// Replication group, serverless cache, or MemoryDB cluster name
+    pub cluster_name: String,
+    pub region: String,
+    pub service: IamService,
+    /// ElastiCache Serverless caches need `ResourceType=ServerlessCache`
+    pub serverless: bool,
+}
+
+impl IamAuth {
+    /// Build the token for `credentials` at `now` (seconds since the epoch).
+    pub fn token(&self, credentials: &AwsCredentials, now: u64) -> String {
+        let (date, amz_date) = format_amz_date(now);
+        let service = self.service.signing_name();
+        let host = self.cluster_name.to_lowercase();
+        let scope = format!("{}/{}/{}/aws4_request", date, self.region, service);
+
+        let mut query: Vec<(&str, String)> = vec![
+            ("Action", "connect".to_string()),
+            ("User", self.user_id.clone()),
+            ("X-Amz-Algorithm", "AWS4-HMAC-SHA256".to_string()),
+            (
+                "X-Amz-Credential",
+                format!("{}/{}", credentials.access_key_id, scope),
+            ),
+            ("X-Amz-Date", amz_date.clone()),
+            ("X-Amz-Expires", IAM_TOKEN_TTL_SECS.to_string()),
+            ("X-Amz-SignedHeaders", "host".to_string()),
+        ];
+        if self.serverless {
+            query.push(("ResourceType", "ServerlessCache".to_string()));
+        }
+        if let Some(token) = &credentials.session_token {
+            query.push(("X-Amz-Security-Token", token.clone()));
+        }
+        query.sort();
+        let canonical_query = query
+            .iter()
+            .map(|(k, v)| format!("{}={}", uri_encode(k), uri_encode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let canonical_request = format!(
+            "GET\n/\n{}\nhost:{}\n\nhost\n{}",
+            canonical_query,
+            host,
+            hex(&Sha256::digest(b""))
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(&credentials.secret_access_key, &date, &self.region, service);
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        format!(
+            "{}/?{}&X-Amz-Signature={}",
+            host, canonical_query, signature
+        )
+    }
+}
+
+impl AuthProvider for IamAuth {
+    fn credentials(&self) -> Result<Credentials, String> {
+        let aws = AwsCredentials::from_env()?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Ok(Credentials {
+            username: Some(self.user_id.clone()),
+            password: self.token(&aws, now),
+        })
+    }
+
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(IAM_TOKEN_TTL_SECS - IAM_REFRESH_MARGIN_SECS)
+    }
+}
+
+/// SigV4 signing key for `date` (YYYYMMDD), region and service.
+pub fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac_sha256(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    hmac_sha256(&k_service, b"aws4_request")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Percent-encode everything except unreserved characters, as SigV4 requires.
+fn uri_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// `(YYYYMMDD, YYYYMMDDTHHMMSSZ)` for a Unix timestamp, in UTC.
+fn format_amz_date(secs: u64) -> (String, String) {
+    let (year, month, day) = time::civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    let (h, m, s) = (rem / 3600, (rem % 3600) / 60, rem % 60);
+
+    let date = format!("{:04}{:02}{:02}", year, month, day);
+    let amz_date = format!("{}T{:02}{:02}{:02}Z", date, h, m, s);
+    (date, amz_date)
+}
+
+/// Reconnect `name` with fresh credentials from `provider` every
+/// [`AuthProvider::refresh_interval`]. `base` is the connection info the
+/// credentials are applied to.
+pub async fn refresh_credentials(
+    server: McpRedisServer,
+    name: String,
+    base: redis::ConnectionInfo,
+    provider: std::sync::Arc<dyn AuthProvider>,
+) {
+    let mut interval = tokio::time::interval(provider.refresh_interval());
+    interval.tick().await;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = server.shutdown().stopping() => return,
+        }
+        match server.connection_addr(&name) {
+            Some(addr) if addr != base.addr => {
+                // Repointed elsewhere, e.g. by replace_connection
+                tracing::info!(connection = %name, "Connection repointed, credential refresh stopped");
+                return;
+            }
+            Some(_) => {}
+            None if server.is_pending(&name) => continue,
+            None => return,
+        }
+        let credentials = match provider.credentials() {
+            Ok(credentials) => credentials,
+            Err(e) => {
+                tracing::warn!(connection = %name, error = %e, "Cannot refresh credentials");
+                continue;
+            }
+        };
+        let result = async {
+            let client = redis::Client::open(with_credentials(base.clone(), credentials))?;
+            // A hung endpoint must not stop the rotation: the next tick retries
+            let conn = tokio::time::timeout(
+                LAZY_CONNECT_TIMEOUT,
+                redis::aio::ConnectionManager::new(client.clone()),
+            )
+            .await
+            .map_err(|_| {
+                redis::RedisError::from(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "connect timed out",
+                ))
+            })??;
+            Ok::<_, redis::RedisError>((conn, client))
+        }
+        .await;
+        match result {
+            Ok((conn, client)) => {
+                tracing::debug!(connection = %name, "Reconnected with refreshed credentials");
+                server.swap_connection(&name, conn, client).await;
+            }
+            Err(e) => {
+                tracing::warn!(connection = %name, error = %e, "Reconnect with refreshed credentials failed");
+            }
+        }
+    }
+}
+
+/// Apply `credentials` to `info`.
+pub fn with_credentials(
+    mut info: redis::ConnectionInfo,
+    credentials: Credentials,
+) -> redis::ConnectionInfo {
+    info.redis.username = credentials.username;
+    info.redis.password = Some(credentials.password);
+    info
+}
@@ -0,0 +1,475 @@
+//! Assessment behind `backlog_health`: replication backlog coverage, AOF
+//! buffers and client output buffer pressure, with the replicas at risk of
+//! a full resync flagged. Also the per-client breakdown behind
+//! `client_buffer_report`.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// A replica lagging by more than this share of the backlog is flagged
+const LAG_WARNING_PERCENT: f64 = 50.0;
+/// Replication lag, in seconds, worth a warning
+const LAG_WARNING_SECS: i64 = 10;
+/// Output buffers above this share of the soft (or hard) limit are flagged
+const BUFFER_WARNING_PERCENT: f64 = 50.0;
+/// AOF buffer size worth a warning: fsync is not keeping up
+const AOF_BUFFER_WARNING_BYTES: u64 = 32 * 1024 * 1024;
+/// Clients listed by output buffer size
+const TOP_CLIENTS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Ok,
+    Warning,
+    Critical,
+}
+
+/// One `client-output-buffer-limit` class. Zero means no limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BufferLimit {
+    pub hard: u64,
+    pub soft: u64,
+    pub soft_secs: u64,
+}
+
+/// Parse `client-output-buffer-limit` as returned by CONFIG GET, e.g.
+/// `normal 0 0 0 slave 268435456 67108864 60 pubsub 33554432 8388608 60`.
+/// The `slave` class is reported as `replica`.
+pub fn parse_output_limits(raw: &str) -> HashMap<String, BufferLimit> {
+    let parts: Vec<&str> = raw.split_whitespace().collect();
+    parts
+        .chunks_exact(4)
+        .filter_map(|chunk| {
+            let class = match chunk[0] {
+                "slave" => "replica",
+                other => other,
+            };
+            Some((
+                class.to_string(),
+                BufferLimit {
+                    hard: parse_bytes(chunk[1])?,
+                    soft: parse_bytes(chunk[2])?,
+                    soft_secs: chunk[3].parse().ok()?,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Bytes with an optional k/kb/m/mb/g/gb suffix, as in redis.conf.
+fn parse_bytes(raw: &str) -> Option<u64> {
+    let lower = raw.to_ascii_lowercase();
+    let split = lower
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(lower.len());
+    let (digits, unit) = lower.split_at(split);
+    let multiplier = match unit {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    digits.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+fn int(section: &Value, field: &str) -> Option<i64> {
+    section.get(field)?.as_i64()
+}
+
+fn percent(part: u64, total: u64) -> Option<f64> {
+    (total > 0).then(|| (part as f64 * 1000.0 / total as f64).round() / 10.0)
+}
+
+/// Findings collected while assessing, with the worst level seen.
+#[derive(Default)]
+struct Findings {
+    level: Option<Level>,
+    messages: Vec<Value>,
+}
+
+impl Findings {
+    fn add(&mut self, level: Level, message: String) {
+        self.level = self.level.max(Some(level));
+        self.messages
+            .push(json!({"level": level, "message": message}));
+    }
+
+    fn level(&self) -> Level {
+        self.level.unwrap_or(Level::Ok)
+    }
+}
+
+/// Output buffer pressure of one client against its class limit.
+fn buffer_pressure(omem: u64, limit: Option<&BufferLimit>) -> (Level, Option<String>) {
+    let Some(limit) = limit else {
+        return (Level::Ok, None);
+    };
+    if limit.hard > 0 && percent(omem, limit.hard).unwrap_or(0.0) >= BUFFER_WARNING_PERCENT {
+        return (
+            Level::Critical,
+            Some(format!(
+                "output buffer {} bytes is {}% of the hard limit {}: the client is disconnected when it is reached",
+                omem,
+                percent(omem, limit.hard).unwrap_or(0.0),
+                limit.hard
+            )),
+        );
+    }
+    if limit.soft > 0 && omem >= limit.soft {
+        return (
+            Level::Critical,
+            Some(format!(
+                "output buffer {} bytes is over the soft limit {}: the client is disconnected if it stays there for {}s",
+                omem, limit.soft, limit.soft_secs
+            )),
+        );
+    }
+    if limit.soft > 0 && percent(omem, limit.soft).unwrap_or(0.0) >= BUFFER_WARNING_PERCENT {
+        return (
+            Level::Warning,
+            Some(format!(
+                "output buffer {} bytes is {}% of the soft limit {}",
+                omem,
+                percent(omem, limit.soft).unwrap_or(0.0),
+                limit.soft
+            )),
+        );
+    }
+    (Level::Ok, None)
+}
+
+fn client_class(flags: &str) -> &'static str {
+    if flags.contains('S') {
+        "replica"
+    } else if flags.contains('P') {
+        "pubsub"
+    } else {
+        "normal"
+    }
+}
+
+/// Assess replication and buffer health from parsed INFO sections
+/// ([`crate::info::parse_sections`]), CLIENT LIST entries (`field=value`
+/// objects) and the output buffer limits, when CONFIG GET is allowed.
+pub fn assess(
+    info: &Map<String, Value>,
+    clients: &[Value],
+    limits: Option<&HashMap<String, BufferLimit>>,
+) -> Value {
+    let mut findings = Findings::default();
+    let replication = info
+        .get("replication")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    let persistence = info
+        .get("persistence")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+
+    let master_offset = int(&replication, "master_repl_offset").unwrap_or(0);
+    let backlog_size = int(&replication, "repl_backlog_size").unwrap_or(0).max(0) as u64;
+    let histlen = int(&replication, "repl_backlog_histlen")
+        .unwrap_or(0)
+        .max(0) as u64;
+    let first_byte = int(&replication, "repl_backlog_first_byte_offset").unwrap_or(0);
+    let backlog_active = int(&replication, "repl_backlog_active") == Some(1);
+    let backlog = json!({
+        "active": backlog_active,
+        "size": backlog_size,
+        "histlen": histlen,
+        "usage_percent": percent(histlen, backlog_size),
+        "first_byte_offset": first_byte,
+        "master_repl_offset": master_offset,
+    });
+
+    // Replicas as seen by the master: `slaveN:ip=..,port=..,state=..,offset=..,lag=..`
+    let mut replicas = Vec::new();
+    if let Some(fields) = replication.as_object() {
+        let mut entries: Vec<(&String, &Value)> = fields
+            .iter()
+            .filter(|(k, v)| {
+                k.strip_prefix("slave")
+                    .is_some_and(|n| n.parse::<u32>().is_ok())
+                    && v.is_object()
+            })
+            .collect();
+        entries.sort_by_key(|(k, _)| k[5..].parse::<u32>().unwrap_or(0));
+        for (_, replica) in entries {
+            let addr = format!(
+                "{}:{}",
+                replica.get("ip").and_then(|v| v.as_str()).unwrap_or("?"),
+                replica
+                    .get("port")
+                    .map(|v| v.to_string())
+                    .unwrap_or_default()
+            );
+            let state = replica
+                .get("state")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            let offset = int(replica, "offset").unwrap_or(0);
+            let lag_bytes = (master_offset - offset).max(0) as u64;
+            let lag_secs = int(replica, "lag");
+            let mut reasons = Findings::default();
+            if state != "online" {
+                reasons.add(
+                    Level::Warning,
+                    format!("state is {}: a full sync is in progress", state),
+                );
+            } else if backlog_active && backlog_size > 0 && offset < first_byte {
+                reasons.add(
+                    Level::Critical,
+                    format!(
+                        "offset {} is no longer in the backlog (starts at {}): a reconnect needs a full resync",
+                        offset, first_byte
+                    ),
+                );
+            } else if percent(lag_bytes, backlog_size).unwrap_or(0.0) >= LAG_WARNING_PERCENT {
+                reasons.add(
+                    Level::Warning,
+                    format!(
+                        "{} bytes behind, {}% of the backlog: a disconnect now may force a full resync",
+                        lag_bytes,
+                        percent(lag_bytes, backlog_size).unwrap_or(0.0)
+                    ),
+                );
+            }
+            if lag_secs.is_some_and(|lag| lag >= LAG_WARNING_SECS) {
+                reasons.add(
+                    Level::Warning,
+                    format!("last ack {}s ago", lag_secs.unwrap_or(0)),
+                );
+            }
+            findings.level = findings.level.max(reasons.level);
+            replicas.push(json!({
+                "addr": addr,
+                "state": state,
+                "offset": offset,
+                "lag_bytes": lag_bytes,
+                "lag_secs": lag_secs,
+                "risk": reasons.level(),
+                "reasons": reasons.messages,
+            }));
+        }
+    }
+    if !replicas.is_empty() && !backlog_active {
+        findings.add(
+            Level::Warning,
+            "replicas are connected but the backlog is inactive".to_string(),
+        );
+    }
+
+    // Output buffers of replica links, Pub/Sub and normal clients
+    let mut replica_buffers = Vec::new();
+    let mut buffers: Vec<(u64, &Value)> = Vec::new();
+    let mut total_omem = 0u64;
+    for client in clients {
+        let field = |name: &str| client.get(name).and_then(|v| v.as_str()).unwrap_or("");
+        let omem: u64 = field("omem").parse().unwrap_or(0);
+        total_omem += omem;
+        let class = client_class(field("flags"));
+        let (level, message) = buffer_pressure(omem, limits.and_then(|limits| limits.get(class)));
+        if class == "replica" {
+            replica_buffers.push(json!({
+                "addr": field("addr"),
+                "omem": omem,
+                "risk": level,
+                "reason": message,
+            }));
+        } else if let Some(message) = message {
+            findings.add(
+                level,
+                format!(
+                    "{} client {} ({}): {}",
+                    class,
+                    field("id"),
+                    field("addr"),
+                    message
+                ),
+            );
+        }
+        if class == "replica" && level > Level::Ok {
+            findings.level = findings.level.max(Some(level));
+        }
+        buffers.push((omem, client));
+    }
+    buffers.sort_by_key(|(omem, _)| std::cmp::Reverse(*omem));
+    let top_clients: Vec<Value> = buffers
+        .iter()
+        .take(TOP_CLIENTS)
+        .filter(|(omem, _)| *omem > 0)
+        .map(|(omem, client)| {
+            json!({
+                "id": client.get("id"),
+                "addr": client.get("addr"),
+                "name": client.get("name"),
+                "flags": client.get("flags"),
+                "omem": omem,
+                "oll": client.get("oll"),
+            })
+        })
+        .collect();
+
+    let aof_enabled = int(&persistence, "aof_enabled") == Some(1);
+    let aof_buffer = int(&persistence, "aof_buffer_length").unwrap_or(0).max(0) as u64;
+    let aof = json!({
+        "enabled": aof_enabled,
+        "buffer_length": persistence.get("aof_buffer_length"),
+        "rewrite_buffer_length": persistence.get("aof_rewrite_buffer_length"),
+        "rewrite_in_progress": int(&persistence, "aof_rewrite_in_progress") == Some(1),
+        "pending_bio_fsync": persistence.get("aof_pending_bio_fsync"),
+        "delayed_fsync": persistence.get("aof_delayed_fsync"),
+        "last_write_status": persistence.get("aof_last_write_status"),
+    });
+    if aof_enabled {
+        if persistence
+            .get("aof_last_write_status")
+            .and_then(|v| v.as_str())
+            .is_some_and(|s| s != "ok")
+        {
+            findings.add(Level::Critical, "the last AOF write failed".to_string());
+        }
+        if aof_buffer >= AOF_BUFFER_WARNING_BYTES {
+            findings.add(
+                Level::Warning,
+                format!(
+                    "AOF buffer holds {} bytes: fsync is falling behind",
+                    aof_buffer
+                ),
+            );
+        }
+        if int(&persistence, "aof_delayed_fsync").is_some_and(|n| n > 0) {
+            findings.add(
+                Level::Warning,
+                "fsync has been delayed: the disk is too slow for appendfsync everysec".to_string(),
+            );
+        }
+    }
+
+    json!({
+        "status": findings.level(),
+        "role": replication.get("role"),
+        "backlog": backlog,
+        "replicas": replicas,
+        "replica_buffers": replica_buffers,
+        "output_buffers": {
+            "total_omem": total_omem,
+            "limits": limits,
+            "top_clients": top_clients,
+        },
+        "aof": aof,
+        "findings": findings.messages,
+    })
+}
+
+/// Order of the clients in [`buffer_report`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferSort {
+    /// Output buffer memory (`omem`)
+    Memory,
+    /// Queued reply objects (`oll`)
+    Length,
+}
+
+/// Clients holding output buffers, largest first, each checked against its
+/// class limit and with the command it last ran. Clients with empty buffers
+/// are counted but not listed; at most `count` are listed.
+pub fn buffer_report(
+    clients: &[Value],
+    limits: Option<&HashMap<String, BufferLimit>>,
+    sort: BufferSort,
+    count: usize,
+) -> Value {
+    let mut findings = Findings::default();
+    let mut by_class: Map<String, Value> = Map::new();
+    let mut total_omem = 0u64;
+    let mut buffered = Vec::new();
+    for client in clients {
+        let field = |name: &str| client.get(name).and_then(|v| v.as_str()).unwrap_or("");
+        let number = |name: &str| field(name).parse::<u64>().unwrap_or(0);
+        let (omem, oll) = (number("omem"), number("oll"));
+        total_omem += omem;
+        let class = client_class(field("flags"));
+        let limit = limits.and_then(|limits| limits.get(class));
+        let (level, reason) = buffer_pressure(omem, limit);
+
+        let stats = by_class
+            .entry(class)
+            .or_insert_with(|| json!({"clients": 0, "omem": 0, "flagged": 0}));
+        stats["clients"] = json!(stats["clients"].as_u64().unwrap_or(0) + 1);
+        stats["omem"] = json!(stats["omem"].as_u64().unwrap_or(0) + omem);
+        if level > Level::Ok {
+            stats["flagged"] = json!(stats["flagged"].as_u64().unwrap_or(0) + 1);
+        }
+        if let Some(reason) = &reason {
+            findings.add(
+                level,
+                format!(
+                    "{} client {} ({}) running {}: {}",
+                    class,
+                    field("id"),
+                    field("addr"),
+                    field("cmd"),
+                    reason
+                ),
+            );
+        }
+        if omem == 0 && oll == 0 {
+            continue;
+        }
+        let key = match sort {
+            BufferSort::Memory => (omem, oll),
+            BufferSort::Length => (oll, omem),
+        };
+        buffered.push((
+            key,
+            json!({
+                "id": field("id"),
+                "addr": field("addr"),
+                "name": field("name"),
+                "class": class,
+                "flags": field("flags"),
+                "cmd": field("cmd"),
+                "age": number("age"),
+                "idle": number("idle"),
+                "omem": omem,
+                "oll": oll,
+                "obl": number("obl"),
+                "tot_mem": number("tot-mem"),
+                "subscriptions": number("sub") + number("psub") + number("ssub"),
+                "limit": limit,
+                "risk": level,
+                "reason": reason,
+            }),
+        ));
+    }
+    buffered.sort_by_key(|(key, _)| std::cmp::Reverse(*key));
+    let listed: Vec<Value> = buffered
+        .iter()
+        .take(count)
+        .map(|(_, client)| client.clone())
+        .collect();
+
+    json!({
+        "status": findings.level(),
+        "sorted_by": match sort {
+            BufferSort::Memory => "omem",
+            BufferSort::Length => "oll",
+        },
+        "total_clients": clients.len(),
+        "buffered_clients": buffered.len(),
+        "total_omem": total_omem,
+        "by_class": by_class,
+        "limits": limits,
+        "clients": listed,
+        "truncated": buffered.len() > count,
+        "findings": findings.messages,
+    })
+}
@@ -0,0 +1,25 @@
+//! Bearer-token check for the HTTP transport.
+//!
+//! Without a token, anyone who can reach `--listen` can run tools against
+//! every configured Redis instance. With `--auth-token`, each request must
+//! carry `Authorization: Bearer <token>`.
+
+/// Whether an `Authorization` header value carries `token`. The comparison
+/// takes the same time wherever the first difference is.
+pub fn authorized(header: Option<&str>, token: &str) -> bool {
+    let Some(presented) = header.and_then(|h| {
+        let (scheme, value) = h.trim().split_once(' ')?;
+        scheme.eq_ignore_ascii_case("bearer").then(|| value.trim())
+    }) else {
+        return false;
+    };
+    let (presented, token) = (presented.as_bytes(), token.as_bytes());
+    if presented.len() != token.len() {
+        return false;
+    }
+    presented
+        .iter()
+        .zip(token)
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
@@ -0,0 +1,68 @@
+//! Size limit on values returned by `get`, so a 50 MB string or a
+//! million-member set is cut short instead of filling the MCP response.
+
+/// Default for `--max-value-bytes`
+pub const DEFAULT_MAX_VALUE_BYTES: u64 = 1024 * 1024;
+
+/// Bytes of value data taken so far against a limit.
+#[derive(Debug, Clone)]
+pub struct Budget {
+    max: u64,
+    used: u64,
+    exhausted: bool,
+}
+
+impl Budget {
+    pub fn new(max: u64) -> Self {
+        Self {
+            max,
+            used: 0,
+            exhausted: false,
+        }
+    }
+
+    /// Take `len` bytes for one element. Once an element does not fit, it
+    /// and every later one are refused.
+    pub fn admit(&mut self, len: usize) -> bool {
+        if self.exhausted || self.used + len as u64 > self.max {
+            self.exhausted = true;
+            return false;
+        }
+        self.used += len as u64;
+        true
+    }
+
+    /// Whether an element has been refused, i.e. the value is truncated.
+    pub fn exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+}
+
+/// The longest valid UTF-8 prefix of `bytes`, replacing invalid sequences
+/// before it. A multi-byte character cut by GETRANGE is dropped.
+pub fn utf8_prefix(bytes: &[u8]) -> String {
+    let end = match std::str::from_utf8(bytes) {
+        Ok(_) => bytes.len(),
+        // An incomplete sequence at the end has no error length
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => bytes.len(),
+    };
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// Where to read the rest of a truncated value of `key_type`.
+pub fn paging_hint(key_type: &str) -> &'static str {
+    match key_type {
+        "string" => "Use get_string_range with an offset to read the rest of the string",
+        "list" => "Use get_list_range to page through the list",
+        "set" | "zset" => {
+            "Use get_set_members to page through the members, or find_member to search them"
+        }
+        "hash" => "Use hash_overview for the field names and get_hash_fields for specific values",
+        _ => "Use a range or paging tool to read the rest",
+    }
+}
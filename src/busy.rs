@@ -0,0 +1,127 @@
+//! The busy-server check run before expensive tools. A server stuck in a
+//! script or a long command answers nothing else, so instead of queueing
+//! more work behind it the call reports what it is busy with.
+
+use std::time::Duration;
+
+use redis::Value;
+
+use crate::resp;
+
+/// Characters of each command argument shown for a running script
+const MAX_ARG_CHARS: usize = 40;
+
+/// Why a server cannot serve a tool right now.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Busy {
+    /// PING was refused with BUSY: a script or function has run past
+    /// `busy-reply-threshold`. Details come from FUNCTION STATS (Redis 7+).
+    Script {
+        name: Option<String>,
+        command: Option<String>,
+        duration_ms: Option<i64>,
+    },
+    /// PING got no reply in time, e.g. during DEBUG SLEEP or a slow KEYS
+    Unresponsive { waited: Duration },
+}
+
+impl Busy {
+    /// The script named by a FUNCTION STATS reply (`running_script`), or an
+    /// anonymous one when the reply is missing or has no details.
+    pub fn from_function_stats(stats: Option<&Value>) -> Self {
+        let running = stats
+            .and_then(resp::to_object)
+            .and_then(|stats| stats.get("running_script").cloned())
+            .unwrap_or_default();
+        // RESP2 leaves the nested map as a flat [key, value, ...] array
+        let field = |name: &str| match &running {
+            serde_json::Value::Object(map) => map.get(name).cloned(),
+            serde_json::Value::Array(items) => items
+                .chunks(2)
+                .find(|pair| pair[0] == name)
+                .and_then(|pair| pair.get(1).cloned()),
+            _ => None,
+        };
+        let command = field("command").and_then(|command| match command {
+            serde_json::Value::Array(args) => {
+                // The command and its first argument: a function name, or
+                // the start of an EVAL script
+                let args: Vec<String> = args
+                    .iter()
+                    .take(2)
+                    .map(|a| {
+                        let arg = a
+                            .as_str()
+                            .map(str::to_string)
+                            .unwrap_or_else(|| a.to_string());
+                        match arg.char_indices().nth(MAX_ARG_CHARS) {
+                            Some((at, _)) => format!("{}...", &arg[..at]),
+                            None => arg,
+                        }
+                    })
+                    .collect();
+                (!args.is_empty()).then(|| args.join(" "))
+            }
+            serde_json::Value::String(command) => Some(command),
+            _ => None,
+        });
+        Busy::Script {
+            name: field("name").and_then(|n| n.as_str().map(str::to_string)),
+            command,
+            duration_ms: field("duration_ms").and_then(|d| d.as_i64()),
+        }
+    }
+
+    /// One line for the tool result, e.g. "server busy executing FCALL
+    /// cleanup for 12.5s".
+    pub fn message(&self) -> String {
+        match self {
+            Busy::Script {
+                name,
+                command,
+                duration_ms,
+            } => {
+                let what = match (command, name) {
+                    (Some(command), _) => command.clone(),
+                    (None, Some(name)) => format!("script {}", name),
+                    (None, None) => "a script".to_string(),
+                };
+                match duration_ms {
+                    Some(ms) => format!(
+                        "server busy executing {} for {:.1}s",
+                        what,
+                        *ms as f64 / 1000.0
+                    ),
+                    None => format!("server busy executing {}", what),
+                }
+            }
+            Busy::Unresponsive { waited } => format!(
+                "server did not answer PING within {}ms: it is blocked by a long-running command (DEBUG SLEEP, KEYS, a large DEL, ...)",
+                waited.as_millis()
+            ),
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Busy::Script {
+                name,
+                command,
+                duration_ms,
+            } => serde_json::json!({
+                "reason": "script",
+                "name": name,
+                "command": command,
+                "duration_ms": duration_ms,
+                "message": self.message(),
+                "hint": "Retry when the script finishes. SCRIPT KILL (or FUNCTION KILL) stops it if it has not written anything yet",
+            }),
+            Busy::Unresponsive { waited } => serde_json::json!({
+                "reason": "unresponsive",
+                "waited_ms": waited.as_millis() as u64,
+                "message": self.message(),
+                "hint": "Retry later; check the slow log once the server answers again",
+            }),
+        }
+    }
+}
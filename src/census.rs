@@ -0,0 +1,214 @@
+//! Aggregation behind `namespace_census` (type mix, sizes, TTL coverage and
+//! encodings over a sample of keys), `analyze_namespaces` (keys and memory
+//! per top-level prefix) and `memory_by_pattern` (estimated memory per
+//! pattern).
+
+use std::collections::BTreeMap;
+
+/// What was read for one sampled key.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeySample {
+    pub key_type: String,
+    /// OBJECT ENCODING
+    pub encoding: Option<String>,
+    /// MEMORY USAGE in bytes, when the server allows the command
+    pub memory: Option<u64>,
+    /// Elements in the collection, or bytes for strings
+    pub length: Option<u64>,
+    /// PTTL: -1 for keys without an expiry
+    pub ttl_ms: i64,
+}
+
+/// Command returning the length of a key of `key_type`.
+pub fn length_command(key_type: &str) -> Option<&'static str> {
+    match key_type {
+        "string" => Some("STRLEN"),
+        "hash" => Some("HLEN"),
+        "list" => Some("LLEN"),
+        "set" => Some("SCARD"),
+        "zset" => Some("ZCARD"),
+        "stream" => Some("XLEN"),
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+struct TypeTally {
+    count: usize,
+    memory: Vec<u64>,
+    lengths: Vec<u64>,
+    encodings: BTreeMap<String, usize>,
+}
+
+/// `{"types", "ttl", "memory"}` for `samples`. Types are keyed by name with
+/// their share of the sample, average size, and encoding counts.
+pub fn summarize(samples: &[KeySample]) -> serde_json::Value {
+    let mut types: BTreeMap<&str, TypeTally> = BTreeMap::new();
+    for sample in samples {
+        let tally = types.entry(sample.key_type.as_str()).or_default();
+        tally.count += 1;
+        tally.memory.extend(sample.memory);
+        tally.lengths.extend(sample.length);
+        if let Some(encoding) = &sample.encoding {
+            *tally.encodings.entry(encoding.clone()).or_default() += 1;
+        }
+    }
+
+    let types: serde_json::Map<String, serde_json::Value> = types
+        .into_iter()
+        .map(|(name, tally)| {
+            let mut item = serde_json::json!({
+                "count": tally.count,
+                "percent": percent(tally.count, samples.len()),
+                "encodings": tally.encodings,
+            });
+            if let Some(avg) = average(&tally.memory) {
+                item["avg_memory_bytes"] = serde_json::json!(avg.round() as u64);
+            }
+            if let Some(avg) = average(&tally.lengths) {
+                item["avg_length"] = serde_json::json!((avg * 10.0).round() / 10.0);
+            }
+            (name.to_string(), item)
+        })
+        .collect();
+
+    let ttls: Vec<u64> = samples
+        .iter()
+        .filter(|s| s.ttl_ms >= 0)
+        .map(|s| s.ttl_ms as u64 / 1000)
+        .collect();
+    let mut ttl = serde_json::json!({
+        "with_ttl": ttls.len(),
+        "percent": percent(ttls.len(), samples.len()),
+    });
+    if let Some(avg) = average(&ttls) {
+        ttl["avg_secs"] = serde_json::json!(avg.round() as u64);
+        ttl["min_secs"] = serde_json::json!(ttls.iter().min());
+        ttl["max_secs"] = serde_json::json!(ttls.iter().max());
+    }
+
+    let memory: Vec<u64> = samples.iter().filter_map(|s| s.memory).collect();
+    let memory = match average(&memory) {
+        Some(avg) => serde_json::json!({
+            "sampled_bytes": memory.iter().sum::<u64>(),
+            "avg_bytes": avg.round() as u64,
+        }),
+        None => serde_json::Value::Null,
+    };
+
+    serde_json::json!({
+        "types": types,
+        "ttl": ttl,
+        "memory": memory,
+    })
+}
+
+/// Top-level prefix of `key`: the part before the first `delimiter`, or
+/// `None` if the key has none.
+pub fn top_prefix<'a>(key: &'a str, delimiter: &str) -> Option<&'a str> {
+    if delimiter.is_empty() {
+        return None;
+    }
+    key.split_once(delimiter).map(|(prefix, _)| prefix)
+}
+
+/// Sampled keys and memory per top-level prefix, most keys first. `memory`
+/// holds MEMORY USAGE per key, parallel to `keys`, when it is available.
+/// Keys without the delimiter are grouped under a `null` prefix.
+pub fn prefix_breakdown(
+    keys: &[String],
+    memory: &[Option<u64>],
+    delimiter: &str,
+) -> Vec<serde_json::Value> {
+    let mut groups: BTreeMap<Option<&str>, (usize, Vec<u64>)> = BTreeMap::new();
+    for (i, key) in keys.iter().enumerate() {
+        let group = groups.entry(top_prefix(key, delimiter)).or_default();
+        group.0 += 1;
+        group.1.extend(memory.get(i).copied().flatten());
+    }
+
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then(a.0.cmp(&b.0)));
+    groups
+        .into_iter()
+        .map(|(prefix, (count, memory))| {
+            let mut item = serde_json::json!({
+                "prefix": prefix,
+                "count": count,
+                "percent": percent(count, keys.len()),
+            });
+            if let Some(avg) = average(&memory) {
+                item["sampled_memory_bytes"] = serde_json::json!(memory.iter().sum::<u64>());
+                item["avg_memory_bytes"] = serde_json::json!(avg.round() as u64);
+            }
+            item
+        })
+        .collect()
+}
+
+/// Estimated keys and memory per pattern from `keys`, a uniform sample of
+/// `dbsize` keys (all of them when `exact`) with MEMORY USAGE in `memory`.
+/// Totals extrapolate the matched keys' average size by the estimated count,
+/// with the count's 95% interval. A key matching several patterns counts in
+/// each.
+pub fn pattern_memory(
+    patterns: &[String],
+    keys: &[String],
+    memory: &[Option<u64>],
+    dbsize: u64,
+    exact: bool,
+) -> Vec<serde_json::Value> {
+    let sampled_bytes: u64 = memory.iter().flatten().sum();
+    patterns
+        .iter()
+        .map(|pattern| {
+            let matched: Vec<u64> = keys
+                .iter()
+                .zip(memory)
+                .filter(|(key, _)| crate::glob::matches(pattern, key))
+                .filter_map(|(_, bytes)| *bytes)
+                .collect();
+            let matched_bytes: u64 = matched.iter().sum();
+            let mut item = serde_json::json!({
+                "pattern": pattern,
+                "sampled_keys": matched.len(),
+                "share_percent": percent(matched_bytes as usize, sampled_bytes as usize),
+            });
+            if exact {
+                item["keys"] = serde_json::json!(matched.len());
+                item["memory_bytes"] = serde_json::json!(matched_bytes);
+                return item;
+            }
+            let ratio = if keys.is_empty() {
+                0.0
+            } else {
+                matched.len() as f64 / keys.len() as f64
+            };
+            let (low, high) = crate::scan::wilson_interval(matched.len(), keys.len(), 1.96);
+            let estimate = (ratio * dbsize as f64).round();
+            item["estimated_keys"] = serde_json::json!(estimate as u64);
+            item["ci_low_keys"] = serde_json::json!((low * dbsize as f64).floor() as u64);
+            item["ci_high_keys"] = serde_json::json!((high * dbsize as f64).ceil() as u64);
+            if let Some(avg) = average(&matched) {
+                item["avg_memory_bytes"] = serde_json::json!(avg.round() as u64);
+                item["estimated_memory_bytes"] = serde_json::json!((avg * estimate).round() as u64);
+            }
+            item
+        })
+        .collect()
+}
+
+fn average(values: &[u64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<u64>() as f64 / values.len() as f64)
+}
+
+/// Share of `part` in `total`, as a percentage with one decimal.
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    (part as f64 * 1000.0 / total as f64).round() / 10.0
+}
@@ -0,0 +1,148 @@
+//! Client filters and grouping behind `client_list`. A busy server has
+//! thousands of clients; filtering on name, flags and idle time, or folding
+//! them into groups by name or host, keeps the answer small.
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use crate::glob;
+
+/// Types CLIENT LIST TYPE accepts
+pub const TYPES: &[&str] = &["normal", "master", "replica", "pubsub"];
+
+/// Commands listed per group, most frequent first
+const GROUP_COMMANDS: usize = 5;
+
+/// Which clients of a CLIENT LIST to keep. Unset criteria keep everyone.
+#[derive(Debug, Clone, Default)]
+pub struct ClientFilter {
+    /// Glob over the client name; `""` matches unnamed clients
+    pub name: Option<String>,
+    /// Flag letters the client must all have, e.g. `"P"` for Pub/Sub or
+    /// `"b"` for blocked
+    pub flags: Option<String>,
+    pub min_idle_secs: Option<u64>,
+}
+
+impl ClientFilter {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.flags.is_none() && self.min_idle_secs.is_none()
+    }
+
+    /// Whether a client (a parsed CLIENT LIST line) passes every criterion.
+    pub fn keeps(&self, client: &Value) -> bool {
+        let field = |name: &str| client.get(name).and_then(Value::as_str).unwrap_or("");
+        if let Some(pattern) = &self.name {
+            let name = field("name");
+            let matches = if pattern.is_empty() {
+                name.is_empty()
+            } else {
+                glob::matches(pattern, name)
+            };
+            if !matches {
+                return false;
+            }
+        }
+        if let Some(flags) = &self.flags {
+            let has = field("flags");
+            if !flags.chars().all(|flag| has.contains(flag)) {
+                return false;
+            }
+        }
+        if let Some(min) = self.min_idle_secs {
+            if number(client, "idle") < min {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// What `client_list` groups clients by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// The client name, unnamed clients together
+    Name,
+    /// The host part of `addr`, so the connections of one app server group
+    Host,
+}
+
+impl GroupBy {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "name" => Some(Self::Name),
+            "addr" | "host" => Some(Self::Host),
+            _ => None,
+        }
+    }
+
+    fn key(self, client: &Value) -> String {
+        let field = |name: &str| client.get(name).and_then(Value::as_str).unwrap_or("");
+        match self {
+            Self::Name => field("name").to_string(),
+            Self::Host => {
+                let addr = field("addr");
+                addr.rsplit_once(':')
+                    .map_or(addr, |(host, _)| host)
+                    .to_string()
+            }
+        }
+    }
+}
+
+fn number(client: &Value, field: &str) -> u64 {
+    client
+        .get(field)
+        .and_then(Value::as_str)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Clients folded into groups with their count, summed output buffer
+/// (`omem`) and total memory (`tot-mem`), the longest idle time and the
+/// commands they ran last, most common first. Largest groups first.
+pub fn group(clients: &[Value], by: GroupBy) -> Vec<Value> {
+    #[derive(Default)]
+    struct Group {
+        count: u64,
+        omem: u64,
+        tot_mem: u64,
+        max_idle: u64,
+        commands: HashMap<String, u64>,
+    }
+
+    let mut groups: HashMap<String, Group> = HashMap::new();
+    for client in clients {
+        let group = groups.entry(by.key(client)).or_default();
+        group.count += 1;
+        group.omem += number(client, "omem");
+        group.tot_mem += number(client, "tot-mem");
+        group.max_idle = group.max_idle.max(number(client, "idle"));
+        if let Some(cmd) = client.get("cmd").and_then(Value::as_str) {
+            *group.commands.entry(cmd.to_string()).or_default() += 1;
+        }
+    }
+
+    let mut groups: Vec<(String, Group)> = groups.into_iter().collect();
+    groups.sort_by(|a, b| b.1.count.cmp(&a.1.count).then_with(|| a.0.cmp(&b.0)));
+    groups
+        .into_iter()
+        .map(|(key, group)| {
+            let mut commands: Vec<(String, u64)> = group.commands.into_iter().collect();
+            commands.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            json!({
+                "group": key,
+                "count": group.count,
+                "omem": group.omem,
+                "tot_mem": group.tot_mem,
+                "max_idle": group.max_idle,
+                "commands": commands
+                    .into_iter()
+                    .take(GROUP_COMMANDS)
+                    .map(|(cmd, count)| json!({"cmd": cmd, "count": count}))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,209 @@
+//! Parsing of Redis Cluster topology replies (CLUSTER NODES / CLUSTER SHARDS).
+
+use redis::Value;
+
+use crate::resp;
+
+/// Parse the text reply of CLUSTER NODES into one JSON object per node.
+///
+/// Each line has the form
+/// `<id> <ip:port@cport[,hostname]> <flags> <master> <ping-sent> <pong-recv>
+/// <config-epoch> <link-state> <slot> ...`.
+pub fn parse_nodes(text: &str) -> Vec<serde_json::Value> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(parse_node_line)
+        .collect()
+}
+
+fn parse_node_line(line: &str) -> Option<serde_json::Value> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 8 {
+        return None;
+    }
+
+    let (addr, hostname) = match parts[1].split_once(',') {
+        Some((addr, host)) => (addr, Some(host)),
+        None => (parts[1], None),
+    };
+    let (address, bus_port) = match addr.split_once('@') {
+        Some((address, bus)) => (address, bus.parse::<u16>().ok()),
+        None => (addr, None),
+    };
+
+    let flags: Vec<&str> = parts[2].split(',').collect();
+    let role = if flags.contains(&"master") {
+        "master"
+    } else if flags.contains(&"slave") {
+        "replica"
+    } else {
+        "unknown"
+    };
+    let master_id = (parts[3] != "-").then_some(parts[3]);
+
+    let mut slots = Vec::new();
+    let mut slot_count: u64 = 0;
+    let mut migrating = Vec::new();
+    let mut importing = Vec::new();
+    for slot in &parts[8..] {
+        if let Some(inner) = slot.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            // [slot->-target] is migrating out, [slot-<-source] is importing in
+            if let Some((s, node)) = inner.split_once("->-") {
+                migrating.push(serde_json::json!({"slot": s, "to": node}));
+            } else if let Some((s, node)) = inner.split_once("-<-") {
+                importing.push(serde_json::json!({"slot": s, "from": node}));
+            }
+            continue;
+        }
+        slot_count += match slot.split_once('-') {
+            Some((start, end)) => match (start.parse::<u64>(), end.parse::<u64>()) {
+                (Ok(start), Ok(end)) if end >= start => end - start + 1,
+                _ => 0,
+            },
+            None => 1,
+        };
+        slots.push(*slot);
+    }
+
+    Some(serde_json::json!({
+        "id": parts[0],
+        "address": address,
+        "bus_port": bus_port,
+        "hostname": hostname,
+        "role": role,
+        "flags": flags,
+        "myself": flags.contains(&"myself"),
+        "master_id": master_id,
+        "ping_sent": parts[4].parse::<u64>().unwrap_or(0),
+        "pong_recv": parts[5].parse::<u64>().unwrap_or(0),
+        "config_epoch": parts[6].parse::<u64>().unwrap_or(0),
+        "link_state": parts[7],
+        "slots": slots,
+        "slot_count": slot_count,
+        "migrating": migrating,
+        "importing": importing,
+    }))
+}
+
+/// Convert the reply of CLUSTER SHARDS into JSON. Each shard becomes
+/// `{"slots": [[start, end], ...], "nodes": [{...}, ...]}`.
+pub fn parse_shards(value: &Value) -> Vec<serde_json::Value> {
+    let shards = match value {
+        Value::Array(shards) => shards,
+        _ => return Vec::new(),
+    };
+
+    shards
+        .iter()
+        .filter_map(|shard| {
+            let fields = as_pairs(shard)?;
+            let mut slots = Vec::new();
+            let mut nodes = Vec::new();
+            for (name, value) in fields {
+                match name.as_str() {
+                    "slots" => {
+                        let flat: Vec<i64> = match value {
+                            Value::Array(items) => items
+                                .iter()
+                                .filter_map(|v| match v {
+                                    Value::Int(i) => Some(*i),
+                                    _ => None,
+                                })
+                                .collect(),
+                            _ => Vec::new(),
+                        };
+                        slots = flat.chunks(2).map(|r| serde_json::json!(r)).collect();
+                    }
+                    "nodes" => {
+                        if let Value::Array(items) = value {
+                            nodes = items
+                                .iter()
+                                .filter_map(resp::to_object)
+                                .map(serde_json::Value::Object)
+                                .collect();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some(serde_json::json!({"slots": slots, "nodes": nodes}))
+        })
+        .collect()
+}
+
+/// Split a map-like reply into (name, value) pairs without converting values.
+fn as_pairs(value: &Value) -> Option<Vec<(String, &Value)>> {
+    let name = |v: &Value| match v {
+        Value::BulkString(b) => Some(String::from_utf8_lossy(b).into_owned()),
+        Value::SimpleString(s) => Some(s.clone()),
+        _ => None,
+    };
+    match value {
+        Value::Map(pairs) => pairs.iter().map(|(k, v)| Some((name(k)?, v))).collect(),
+        Value::Array(items) if items.len() % 2 == 0 => items
+            .chunks(2)
+            .map(|pair| Some((name(&pair[0])?, &pair[1])))
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Split a `host:port` node address as reported by CLUSTER NODES. Returns
+/// `None` for nodes without a known address (e.g. `:0` for `noaddr`).
+pub fn split_address(address: &str) -> Option<(&str, u16)> {
+    let (host, port) = address.rsplit_once(':')?;
+    let port = port.parse::<u16>().ok().filter(|p| *p != 0)?;
+    if host.is_empty() {
+        return None;
+    }
+    Some((host, port))
+}
+
+/// Connection info for another node of the same cluster: same credentials
+/// and TLS settings as `base`, pointed at `host:port`.
+pub fn node_connection_info(
+    base: &redis::ConnectionInfo,
+    host: &str,
+    port: u16,
+) -> redis::ConnectionInfo {
+    let addr = match &base.addr {
+        redis::ConnectionAddr::TcpTls {
+            insecure,
+            tls_params,
+            ..
+        } => redis::ConnectionAddr::TcpTls {
+            host: host.to_string(),
+            port,
+            insecure: *insecure,
+            tls_params: tls_params.clone(),
+        },
+        _ => redis::ConnectionAddr::Tcp(host.to_string(), port),
+    };
+    redis::ConnectionInfo {
+        addr,
+        redis: base.redis.clone(),
+    }
+}
+
+/// Whether a slot spec from CLUSTER NODES (`"5461"` or `"0-5460"`) covers `slot`.
+pub fn slot_in_range(spec: &str, slot: u16) -> bool {
+    match spec.split_once('-') {
+        Some((start, end)) => match (start.parse::<u16>(), end.parse::<u16>()) {
+            (Ok(start), Ok(end)) => start <= slot && slot <= end,
+            _ => false,
+        },
+        None => spec.parse::<u16>() == Ok(slot),
+    }
+}
+
+/// The master node (as parsed by [`parse_nodes`]) serving `slot`.
+pub fn slot_owner(nodes: &[serde_json::Value], slot: u16) -> Option<&serde_json::Value> {
+    nodes.iter().find(|node| {
+        node["role"] == "master"
+            && node["slots"].as_array().is_some_and(|slots| {
+                slots
+                    .iter()
+                    .any(|s| s.as_str().is_some_and(|s| slot_in_range(s, slot)))
+            })
+    })
+}
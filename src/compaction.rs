@@ -0,0 +1,285 @@
+//! Analysis behind `compaction_advisor`: sampled hashes, sets and sorted sets
+//! that just missed a compact encoding (listpack, intset), and what raising
+//! the threshold or splitting the key would save.
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+
+use crate::census::KeySample;
+
+/// How far above its entry threshold a collection may be, in percent, and
+/// still count as just above it
+pub const DEFAULT_MARGIN_PERCENT: u64 = 100;
+
+/// Share of its hashtable or skiplist size a collection is assumed to take
+/// once compact, when the sample has no compact key of the same type to
+/// measure
+pub const FALLBACK_COMPACT_RATIO: f64 = 0.25;
+
+/// Keys listed under `candidates`, largest first
+pub const MAX_CANDIDATES: usize = 20;
+
+/// Thresholds of a Redis 7.2 server with default settings, for servers that
+/// do not allow CONFIG GET
+const DEFAULT_THRESHOLDS: [(&str, u64); 7] = [
+    ("hash-max-listpack-entries", 128),
+    ("hash-max-listpack-value", 64),
+    ("zset-max-listpack-entries", 128),
+    ("zset-max-listpack-value", 64),
+    ("set-max-intset-entries", 512),
+    ("set-max-listpack-entries", 128),
+    ("set-max-listpack-value", 64),
+];
+
+/// Encoding thresholds from a flat CONFIG GET reply (`name, value, ...`).
+/// Older servers name them `ziplist` instead of `listpack`; both are kept
+/// as the server reports them. Falls back to the defaults when the reply
+/// has none.
+pub fn thresholds(config: &[String]) -> BTreeMap<String, u64> {
+    let found: BTreeMap<String, u64> = config
+        .chunks(2)
+        .filter(|pair| pair.len() == 2)
+        .filter(|pair| {
+            let name = pair[0].as_str();
+            ["hash-", "zset-", "set-"]
+                .iter()
+                .any(|t| name.starts_with(t))
+                && ["listpack", "ziplist", "intset"]
+                    .iter()
+                    .any(|e| name.contains(e))
+        })
+        .filter_map(|pair| Some((pair[0].clone(), pair[1].parse().ok()?)))
+        .collect();
+    if found.is_empty() {
+        return DEFAULT_THRESHOLDS
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect();
+    }
+    found
+}
+
+/// Whether `encoding` is one of the compact encodings.
+pub fn is_compact(encoding: &str) -> bool {
+    matches!(encoding, "listpack" | "listpackex" | "ziplist" | "intset")
+}
+
+/// `<key_type>-max-listpack-<suffix>`, or its `ziplist` name on older
+/// servers, with its value.
+fn setting(
+    thresholds: &BTreeMap<String, u64>,
+    key_type: &str,
+    suffix: &str,
+) -> Option<(String, u64)> {
+    ["listpack", "ziplist"].iter().find_map(|encoding| {
+        let name = format!("{}-max-{}-{}", key_type, encoding, suffix);
+        thresholds.get(&name).map(|value| (name, *value))
+    })
+}
+
+/// The entry threshold a collection of `length` elements crossed. A set
+/// small enough for an intset is only too long for a listpack (Redis 7.2
+/// and later); a longer one crossed `set-max-intset-entries`.
+fn entry_threshold(
+    thresholds: &BTreeMap<String, u64>,
+    key_type: &str,
+    length: u64,
+) -> Option<(String, u64)> {
+    if key_type != "set" {
+        return setting(thresholds, key_type, "entries");
+    }
+    let intset = thresholds
+        .get("set-max-intset-entries")
+        .map(|value| ("set-max-intset-entries".to_string(), *value));
+    match (setting(thresholds, "set", "entries"), intset) {
+        (Some(listpack), Some(intset)) if length <= intset.1 => Some(listpack),
+        (_, Some(intset)) => Some(intset),
+        (listpack, None) => listpack,
+    }
+}
+
+/// Next multiple of 64 at or above `length`.
+fn round_up(length: u64) -> u64 {
+    length.div_ceil(64) * 64
+}
+
+#[derive(Default)]
+struct Group {
+    keys: usize,
+    memory: u64,
+    savings: u64,
+    max_length: u64,
+    threshold: u64,
+    /// Largest key of the group, with its length and memory
+    example: Option<(String, u64, u64)>,
+}
+
+impl Group {
+    fn add(&mut self, key: &str, length: u64, memory: u64, savings: u64) {
+        self.keys += 1;
+        self.memory += memory;
+        self.savings += savings;
+        self.max_length = self.max_length.max(length);
+        if !matches!(&self.example, Some((_, _, largest)) if *largest >= memory) {
+            self.example = Some((key.to_string(), length, memory));
+        }
+    }
+}
+
+/// Candidates and recommendations for `samples` (parallel to `keys`).
+/// Keys with a hashtable or skiplist encoding up to `margin_percent` above
+/// their entry threshold are candidates for raising it; hashes of any size
+/// past it can be split into buckets under it; collections under it that
+/// are not compact hold a value too long for the `-value` threshold.
+/// Savings assume the compact bytes per element measured on compact keys
+/// of the same type in the sample, or [`FALLBACK_COMPACT_RATIO`].
+pub fn advise(
+    keys: &[String],
+    samples: &[KeySample],
+    thresholds: &BTreeMap<String, u64>,
+    margin_percent: u64,
+) -> Value {
+    // Bytes per element of compact keys, per type
+    let mut compact: BTreeMap<&str, (u64, u64)> = BTreeMap::new();
+    for sample in samples {
+        if let (Some(encoding), Some(memory), Some(length)) =
+            (&sample.encoding, sample.memory, sample.length)
+        {
+            if is_compact(encoding) && length > 0 {
+                let tally = compact.entry(sample.key_type.as_str()).or_default();
+                tally.0 += memory;
+                tally.1 += length;
+            }
+        }
+    }
+    let per_element: BTreeMap<&str, f64> = compact
+        .into_iter()
+        .map(|(key_type, (memory, length))| (key_type, memory as f64 / length as f64))
+        .collect();
+    let savings = |key_type: &str, length: u64, memory: u64| {
+        let compact = per_element
+            .get(key_type)
+            .map(|bytes| bytes * length as f64)
+            .unwrap_or(memory as f64 * FALLBACK_COMPACT_RATIO);
+        memory.saturating_sub(compact.round() as u64)
+    };
+
+    let mut raise: BTreeMap<String, Group> = BTreeMap::new();
+    let mut split = Group::default();
+    let mut split_parameter = None;
+    let mut long_values: BTreeMap<String, Group> = BTreeMap::new();
+    let mut candidates = Vec::new();
+    for (key, sample) in keys.iter().zip(samples) {
+        let key_type = sample.key_type.as_str();
+        if !matches!(key_type, "hash" | "set" | "zset") {
+            continue;
+        }
+        let (Some(encoding), Some(length)) = (&sample.encoding, sample.length) else {
+            continue;
+        };
+        if is_compact(encoding) {
+            continue;
+        }
+        let Some((parameter, threshold)) = entry_threshold(thresholds, key_type, length) else {
+            continue;
+        };
+        let memory = sample.memory.unwrap_or(0);
+        if length <= threshold {
+            let parameter = setting(thresholds, key_type, "value")
+                .map(|(name, _)| name)
+                .unwrap_or_else(|| format!("{}-max-listpack-value", key_type));
+            let group = long_values.entry(parameter).or_default();
+            group.threshold = setting(thresholds, key_type, "value").map_or(0, |(_, v)| v);
+            group.add(key, length, memory, 0);
+            continue;
+        }
+        let saved = savings(key_type, length, memory);
+        if key_type == "hash" {
+            split.threshold = threshold;
+            split_parameter = Some(parameter.clone());
+            split.add(key, length, memory, saved);
+        }
+        if length * 100 > threshold * (100 + margin_percent) {
+            continue;
+        }
+        let group = raise.entry(parameter.clone()).or_default();
+        group.threshold = threshold;
+        group.add(key, length, memory, saved);
+        candidates.push(json!({
+            "key": key,
+            "type": key_type,
+            "encoding": encoding,
+            "length": length,
+            "memory_bytes": sample.memory,
+            "threshold": {"parameter": parameter, "value": threshold},
+            "over_by": length - threshold,
+        }));
+    }
+    candidates.sort_by_key(|c| std::cmp::Reverse(c["memory_bytes"].as_u64()));
+    candidates.truncate(MAX_CANDIDATES);
+
+    let mut recommendations: Vec<Value> = raise
+        .into_iter()
+        .map(|(parameter, group)| {
+            let proposed = round_up(group.max_length);
+            json!({
+                "kind": "raise_threshold",
+                "parameter": parameter,
+                "current": group.threshold,
+                "proposed": proposed,
+                "command": format!("CONFIG SET {} {}", parameter, proposed),
+                "affected_keys": group.keys,
+                "memory_bytes": group.memory,
+                "estimated_savings_bytes": group.savings,
+                "note": "Existing keys keep their encoding until the server loads them again from RDB or AOF; new and rewritten keys are compact right away. Listpack operations are O(n), so keep thresholds in the hundreds to low thousands",
+            })
+        })
+        .collect();
+    if let (Some(parameter), Some((key, length, _))) = (split_parameter, &split.example) {
+        let buckets = length.div_ceil(split.threshold.max(1));
+        recommendations.push(json!({
+            "kind": "split_hashes",
+            "parameter": parameter,
+            "current": split.threshold,
+            "affected_keys": split.keys,
+            "memory_bytes": split.memory,
+            "estimated_savings_bytes": split.savings,
+            "example": {
+                "key": key,
+                "fields": length,
+                "buckets": buckets,
+                "fields_per_bucket": length.div_ceil(buckets),
+            },
+            "note": format!(
+                "Spread each hash over buckets that stay under {} fields, e.g. '<key>:<n>' with n = crc32(field) % buckets, so every bucket is a listpack",
+                split.threshold
+            ),
+        }));
+    }
+    for (parameter, group) in long_values {
+        recommendations.push(json!({
+            "kind": "long_values",
+            "parameter": parameter,
+            "current": group.threshold,
+            "affected_keys": group.keys,
+            "memory_bytes": group.memory,
+            "example": group.example.map(|(key, _, _)| key),
+            "note": format!(
+                "Few enough elements for a listpack, but a field, value or member is longer than {} bytes. Raise it, or move long values to keys of their own",
+                parameter
+            ),
+        }));
+    }
+    recommendations.sort_by_key(|r| std::cmp::Reverse(r["estimated_savings_bytes"].as_u64()));
+
+    json!({
+        "thresholds": thresholds,
+        "candidates": candidates,
+        "recommendations": recommendations,
+        "savings_basis": per_element
+            .iter()
+            .map(|(key_type, bytes)| (key_type.to_string(), json!((bytes * 10.0).round() / 10.0)))
+            .collect::<serde_json::Map<_, _>>(),
+    })
+}
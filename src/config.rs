@@ -0,0 +1,651 @@
+//! `--config` TOML file.
+//!
+//! ```toml
+//! [server]
+//! allow_write = false
+//! scan_count = 200
+//!
+//! [limits]
+//! max_subscribe_secs = 5
+//!
+//! [[connections]]
+//! name = "cache-eu"
+//! url_env = "CACHE_EU_URL"
+//! password_env = "CACHE_EU_PASSWORD"
+//! read_only = true
+//! tags = ["eu", "cache"]
+//!
+//! [[presets]]
+//! name = "check_sessions"
+//! description = "Session keys with their types, plus the total key count"
+//!
+//! [[presets.steps]]
+//! tool = "search_keys"
+//! arguments = { pattern = "session:*", count = 50 }
+//!
+//! [[presets.steps]]
+//! tool = "dbsize"
+//!
+//! [[views]]
+//! name = "user_overview"
+//! description = "A user's profile, latest orders and feature flags"
+//! params = ["user_id"]
+//! keys = [
+//!     { name = "profile", key = "user:{user_id}", type = "hash" },
+//!     { name = "orders", key = "user:{user_id}:orders", type = "zset", limit = 10, reverse = true },
+//!     { name = "flags", key = "user:{user_id}:flags", type = "string" },
+//! ]
+//!
+//! [[contracts]]
+//! pattern = "session:*"
+//! type = "hash"
+//! required_fields = ["user_id", "created_at"]
+//! min_ttl_secs = 60
+//! max_ttl_secs = 86400
+//!
+//! [[references]]
+//! pattern = 'user:\d+'
+//! description = "Users named in orders and sessions"
+//!
+//! [config_set]
+//! allow = ["maxmemory", "maxmemory-policy", "slowlog-*"]
+//!
+//! [analysis_replica]
+//! command = ["/usr/local/bin/provision-replica", "--ttl", "2h"]
+//!
+//! [tool_defaults]
+//! "*" = { connection = "cache-eu" }
+//! scan_keys = { pattern = "app:*", count = 50 }
+//! ```
+//!
+//! Command-line flags take precedence over `[server]` settings.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::scan::ScanBudget;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub server: ServerOptions,
+    #[serde(default)]
+    pub limits: Limits,
+    #[serde(default)]
+    pub connections: Vec<ConnectionConfig>,
+    /// Named tool sequences exposed as additional MCP tools
+    #[serde(default)]
+    pub presets: Vec<Preset>,
+    /// Named reads of several related keys, exposed as additional MCP tools
+    #[serde(default)]
+    pub views: Vec<View>,
+    /// Expected key shapes checked by `validate_key` and `validate_namespace`
+    #[serde(default)]
+    pub contracts: Vec<KeyContract>,
+    /// Key names inside values, followed by `get` with `follow_references`
+    #[serde(default)]
+    pub references: Vec<KeyReference>,
+    /// Parameters `config_set` may change
+    #[serde(default)]
+    pub config_set: ConfigSetPolicy,
+    /// Hook behind `create_analysis_replica`
+    #[serde(default)]
+    pub analysis_replica: Option<ReplicaHook>,
+    /// Arguments used when a call leaves them out
+    #[serde(default)]
+    pub tool_defaults: ToolDefaults,
+}
+
+/// Default arguments by tool name. The `"*"` entry applies to every tool
+/// that takes the argument; a tool's own entry wins over it, and arguments
+/// passed in the call win over both.
+pub type ToolDefaults = BTreeMap<String, serde_json::Map<String, serde_json::Value>>;
+
+/// Server-wide settings, mirroring the command-line flags.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerOptions {
+    #[serde(default)]
+    pub allow_write: bool,
+    #[serde(default)]
+    pub allow_trace: bool,
+    #[serde(default)]
+    pub allow_admin: bool,
+    #[serde(default)]
+    pub export_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub scan_count: Option<u32>,
+    #[serde(default)]
+    pub tls_insecure: bool,
+    #[serde(default)]
+    pub connect_lazy: bool,
+    /// Minimum Redis version, e.g. "6.2"
+    #[serde(default)]
+    pub require_min_version: Option<String>,
+    /// Webhook receiving an event for every write or admin tool call
+    #[serde(default)]
+    pub notify_webhook: Option<String>,
+    /// Value data `get` returns before truncating
+    #[serde(default)]
+    pub max_value_bytes: Option<u64>,
+    /// Keep a transcript of every tool call for `export_session`
+    #[serde(default)]
+    pub record_session: bool,
+}
+
+/// Upper bounds applied to tool parameters, and the safety caps of the
+/// scanning and batching loops behind them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    /// Random SCAN samples taken by `estimate_matching_keys`
+    pub max_estimate_samples: u32,
+    /// Field names sampled by `hash_overview`
+    pub max_hash_sample: u64,
+    /// Entries claimed by a single `stream_autoclaim` call
+    pub max_autoclaim_count: u64,
+    /// Keys sampled into a diagnostics bundle
+    pub max_diagnostic_keys: u32,
+    /// How long `subscribe_sample` may listen, in seconds
+    pub max_subscribe_secs: u64,
+    /// Messages captured by a single `subscribe_sample` call
+    pub max_subscribe_messages: usize,
+    /// Keys UNLINKed per pipeline by `bulk_delete`
+    pub max_delete_batch: usize,
+    /// Round trips measured per connection by `ping`
+    pub max_ping_count: u32,
+    /// Members returned by a single `zset_lex_range` call
+    pub max_lex_range_count: u64,
+    /// Matches returned by a single `find_member` call
+    pub max_member_matches: usize,
+    /// Entries returned by a single `stream_range` call
+    pub max_stream_entries: u64,
+    /// Keys sampled by `namespace_census` and `analyze_namespaces`
+    pub max_census_sample: u32,
+    /// Keys sampled by `validate_namespace`
+    pub max_validate_sample: u32,
+    /// Keys listed by a single `rdb_scan` call
+    pub max_rdb_keys: u32,
+    /// Collection elements returned by `rdb_get`
+    pub max_rdb_elements: usize,
+    /// How long a `set_maintenance_note` note may last, in seconds
+    pub max_maintenance_secs: u64,
+    /// Resource subscriptions served at once, across all sessions
+    pub max_resource_subscriptions: usize,
+    /// Keys measured by a single `find_big_keys` call
+    pub max_big_keys_scan: usize,
+    /// Calls kept per session transcript; older ones are dropped
+    pub max_transcript_entries: usize,
+    /// Bytes of each call's result kept in a session transcript
+    pub max_transcript_result_bytes: usize,
+    /// Keys counted together by a single `estimate_cardinality` call
+    pub max_cardinality_keys: usize,
+    /// Members `estimate_cardinality` reads across its keys
+    pub max_cardinality_elements: u64,
+    /// How long `monitor_sample` may run MONITOR, in seconds
+    pub max_monitor_secs: u64,
+    /// Commands read by a single `monitor_sample` call
+    pub max_monitor_commands: usize,
+    /// How long a cursor saved by `save_cursor` may live, in seconds
+    pub max_cursor_ttl_secs: u64,
+    /// Keys compared by a single `check_replica_consistency` call
+    pub max_consistency_keys: u32,
+    /// Elements of a collection `check_replica_consistency` reads from each
+    /// side to compare values; larger keys are compared by length only
+    pub max_compare_elements: u64,
+    /// Referenced keys a single `get` with `follow_references` reads
+    pub max_followed_references: usize,
+    /// How long a `client_pause` may pause clients, in milliseconds
+    pub max_client_pause_ms: u64,
+    /// Keys read by a single `get_multiple` call
+    pub max_get_keys: usize,
+    /// Keys checked by a single `exists` call
+    pub max_exists_keys: usize,
+    /// Elements of a list, set, zset or hash beyond which `get` refuses to
+    /// read it and points at the paging tools
+    pub max_get_elements: u64,
+    /// Bytes of a string beyond which `get` refuses to read it
+    pub max_get_string_bytes: u64,
+    /// Members returned by a single `scan_set_members` or
+    /// `scan_zset_members` call
+    pub max_scan_members: usize,
+    /// Random members returned by a single `sample_members` call
+    pub max_sample_members: u64,
+    /// SCAN calls per node before a listing or count stops and reports a
+    /// partial result
+    pub max_scan_iterations: usize,
+    /// COUNT hint of each SCAN call in key listings and random samples
+    pub scan_batch_count: u32,
+    /// Elements per LRANGE/ZRANGE/SSCAN/HSCAN call when `get` reads a
+    /// collection
+    pub get_batch: usize,
+    /// Keys per MEMORY USAGE pipeline when measuring many keys
+    pub pipeline_batch: usize,
+    /// Keyspaces up to this size are counted or measured in full rather
+    /// than sampled
+    pub exact_estimate_threshold: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_estimate_samples: 100,
+            max_hash_sample: 1000,
+            max_autoclaim_count: 1000,
+            max_diagnostic_keys: 100,
+            max_subscribe_secs: 10,
+            max_subscribe_messages: 1000,
+            max_delete_batch: 5000,
+            max_ping_count: 100,
+            max_lex_range_count: 1000,
+            max_member_matches: 1000,
+            max_stream_entries: 1000,
+            max_census_sample: 1000,
+            max_validate_sample: 1000,
+            max_rdb_keys: 1000,
+            max_rdb_elements: 1000,
+            max_maintenance_secs: 86400,
+            max_resource_subscriptions: 32,
+            max_big_keys_scan: 10000,
+            max_transcript_entries: 1000,
+            max_transcript_result_bytes: 16 * 1024,
+            max_cardinality_keys: 100,
+            max_cardinality_elements: 1_000_000,
+            max_monitor_secs: 10,
+            max_monitor_commands: 100_000,
+            max_cursor_ttl_secs: 7 * 86400,
+            max_consistency_keys: 1000,
+            max_compare_elements: 10_000,
+            max_followed_references: 50,
+            max_client_pause_ms: 30_000,
+            max_get_keys: 100,
+            max_exists_keys: 1000,
+            max_get_elements: 100_000,
+            max_get_string_bytes: 64 * 1024 * 1024,
+            max_scan_members: 1000,
+            max_sample_members: 1000,
+            max_scan_iterations: 1000,
+            scan_batch_count: 100,
+            get_batch: 500,
+            pipeline_batch: 500,
+            exact_estimate_threshold: 1000,
+        }
+    }
+}
+
+impl Limits {
+    /// These limits with `overrides` (`--limit name=value`) applied. Fails
+    /// on an unknown name or a value out of range for the limit.
+    pub fn with_overrides(&self, overrides: &[(String, u64)]) -> Result<Limits, String> {
+        if overrides.is_empty() {
+            return Ok(self.clone());
+        }
+        let mut fields = match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(fields)) => fields,
+            _ => return Err("cannot apply limit overrides".to_string()),
+        };
+        for (name, value) in overrides {
+            match fields.get_mut(name) {
+                Some(field) => *field = serde_json::json!(value),
+                None => return Err(format!("unknown limit '{}'", name)),
+            }
+        }
+        serde_json::from_value(serde_json::Value::Object(fields))
+            .map_err(|e| format!("invalid limit value: {}", e))
+    }
+
+    /// How far the SCAN loops may go.
+    pub fn scan_budget(&self) -> ScanBudget {
+        ScanBudget {
+            max_iterations: self.max_scan_iterations.max(1),
+            batch_count: self.scan_batch_count.max(1),
+        }
+    }
+}
+
+/// A named connection. Exactly one of `url` and `url_env` must be set.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionConfig {
+    pub name: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Environment variable holding the URL
+    #[serde(default)]
+    pub url_env: Option<String>,
+    /// Environment variable holding the ACL username
+    #[serde(default)]
+    pub username_env: Option<String>,
+    /// Environment variable holding the password
+    #[serde(default)]
+    pub password_env: Option<String>,
+    /// Reject write tools on this connection even with --allow-write
+    #[serde(default)]
+    pub read_only: bool,
+    /// Free-form labels, shown by `list_connections`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Logical database to use instead of the one in the URL
+    #[serde(default)]
+    pub db: Option<i64>,
+}
+
+/// A saved sequence of tool invocations, exposed as a tool of its own.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Preset {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub steps: Vec<PresetStep>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PresetStep {
+    /// Name of the tool to call
+    pub tool: String,
+    /// Arguments passed to the tool. The preset's `connection` argument is
+    /// added unless the step sets its own.
+    #[serde(default)]
+    pub arguments: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Several related keys read together, exposed as a tool taking `params`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct View {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Arguments of the tool, substituted for `{param}` in key templates
+    #[serde(default)]
+    pub params: Vec<String>,
+    pub keys: Vec<ViewKey>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ViewKey {
+    /// Field of the result holding this key
+    pub name: String,
+    /// Key template, e.g. `user:{user_id}:orders`. Braces around anything
+    /// but a declared param are kept, so hash tags still work.
+    pub key: String,
+    /// string, hash, list, set or zset
+    #[serde(rename = "type")]
+    pub key_type: String,
+    /// Elements read from a list, set or zset (default: 100)
+    #[serde(default)]
+    pub limit: Option<u64>,
+    /// Read a list from its tail or a zset from the highest score
+    #[serde(default)]
+    pub reverse: bool,
+    /// Hash fields to read instead of the whole hash
+    #[serde(default)]
+    pub fields: Vec<String>,
+}
+
+/// Expected shape of the keys matching `pattern`. A key is checked against
+/// the first contract whose pattern matches it; unset rules are not checked.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeyContract {
+    /// Glob pattern, as for SCAN MATCH
+    pub pattern: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Expected Redis type: string, hash, list, set, zset or stream
+    #[serde(default, rename = "type")]
+    pub key_type: Option<String>,
+    /// Fields every matching hash must have
+    #[serde(default)]
+    pub required_fields: Vec<String>,
+    /// Keys must have a TTL of at least this many seconds
+    #[serde(default)]
+    pub min_ttl_secs: Option<u64>,
+    /// Keys must have a TTL of at most this many seconds
+    #[serde(default)]
+    pub max_ttl_secs: Option<u64>,
+    /// Keys must not have a TTL
+    #[serde(default)]
+    pub persistent: bool,
+    /// Maximum elements in a collection, or bytes in a string
+    #[serde(default)]
+    pub max_length: Option<u64>,
+    /// Maximum MEMORY USAGE in bytes
+    #[serde(default)]
+    pub max_memory_bytes: Option<u64>,
+}
+
+/// Strings inside values that name other keys, e.g. `user:\d+` for the
+/// user ids an order hash points at.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeyReference {
+    /// Regular expression; each match in a value is read as a key name
+    pub pattern: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// `[config_set]`: the CONFIG parameters `config_set` may change, by name
+/// or glob. Defaults to [`crate::settings::DEFAULT_ALLOWED`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigSetPolicy {
+    #[serde(default = "crate::settings::default_allowed")]
+    pub allow: Vec<String>,
+}
+
+impl Default for ConfigSetPolicy {
+    fn default() -> Self {
+        Self {
+            allow: crate::settings::default_allowed(),
+        }
+    }
+}
+
+/// External command that provisions (or points at) a replica for heavy
+/// analysis. It runs with `MCP_REDIS_SOURCE_CONNECTION`,
+/// `MCP_REDIS_SOURCE_ADDR` and `MCP_REDIS_REPLICA_NAME` set and must print
+/// the replica's URL as the last line of its stdout.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReplicaHook {
+    /// Program followed by its arguments
+    pub command: Vec<String>,
+    /// The command is killed after this many seconds
+    #[serde(default = "default_hook_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_hook_timeout() -> u64 {
+    600
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(text).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut names = std::collections::HashSet::new();
+        for connection in &self.connections {
+            if connection.name.is_empty() {
+                return Err("connection name must not be empty".to_string());
+            }
+            if !names.insert(connection.name.as_str()) {
+                return Err(format!("duplicate connection '{}'", connection.name));
+            }
+            if connection.url.is_some() == connection.url_env.is_some() {
+                return Err(format!(
+                    "connection '{}' needs exactly one of 'url' and 'url_env'",
+                    connection.name
+                ));
+            }
+            if connection.db.is_some_and(|db| db < 0) {
+                return Err(format!(
+                    "connection '{}' has a negative 'db'",
+                    connection.name
+                ));
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for preset in &self.presets {
+            if !is_tool_name(&preset.name) {
+                return Err(format!(
+                    "preset name '{}' must be non-empty and use only letters, digits, '_' or '-'",
+                    preset.name
+                ));
+            }
+            if !seen.insert(preset.name.as_str()) {
+                return Err(format!("duplicate preset '{}'", preset.name));
+            }
+            if preset.steps.is_empty() {
+                return Err(format!("preset '{}' has no steps", preset.name));
+            }
+        }
+
+        for view in &self.views {
+            if !is_tool_name(&view.name) {
+                return Err(format!(
+                    "view name '{}' must be non-empty and use only letters, digits, '_' or '-'",
+                    view.name
+                ));
+            }
+            if !seen.insert(view.name.as_str()) {
+                return Err(format!(
+                    "view '{}' clashes with another view or preset",
+                    view.name
+                ));
+            }
+            if view.keys.is_empty() {
+                return Err(format!("view '{}' has no keys", view.name));
+            }
+            let mut params = std::collections::HashSet::new();
+            for param in &view.params {
+                if !is_tool_name(param) || param == "connection" || !params.insert(param) {
+                    return Err(format!(
+                        "view '{}' has an invalid or duplicate param '{}'",
+                        view.name, param
+                    ));
+                }
+            }
+            let mut labels = std::collections::HashSet::new();
+            for key in &view.keys {
+                if key.name.is_empty() || !labels.insert(key.name.as_str()) {
+                    return Err(format!(
+                        "view '{}' has an empty or duplicate key name '{}'",
+                        view.name, key.name
+                    ));
+                }
+                let collection = matches!(key.key_type.as_str(), "list" | "set" | "zset");
+                if !collection && !matches!(key.key_type.as_str(), "string" | "hash") {
+                    return Err(format!(
+                        "view '{}' key '{}' has unknown type '{}': expected string, hash, list, set or zset",
+                        view.name, key.name, key.key_type
+                    ));
+                }
+                if (key.limit.is_some() && !collection) || key.limit == Some(0) {
+                    return Err(format!(
+                        "view '{}' key '{}': limit must be positive and is only for lists, sets and zsets",
+                        view.name, key.name
+                    ));
+                }
+                if key.reverse && !matches!(key.key_type.as_str(), "list" | "zset") {
+                    return Err(format!(
+                        "view '{}' key '{}': reverse is only for lists and zsets",
+                        view.name, key.name
+                    ));
+                }
+                if !key.fields.is_empty() && key.key_type != "hash" {
+                    return Err(format!(
+                        "view '{}' key '{}': fields are only for hashes",
+                        view.name, key.name
+                    ));
+                }
+            }
+        }
+
+        for contract in &self.contracts {
+            if contract.pattern.is_empty() {
+                return Err("contract pattern must not be empty".to_string());
+            }
+            if let Some(key_type) = &contract.key_type {
+                if crate::census::length_command(key_type).is_none() {
+                    return Err(format!(
+                        "contract '{}' has unknown type '{}'",
+                        contract.pattern, key_type
+                    ));
+                }
+            }
+            if !contract.required_fields.is_empty()
+                && contract.key_type.as_deref().is_some_and(|t| t != "hash")
+            {
+                return Err(format!(
+                    "contract '{}' sets required_fields but its type is not hash",
+                    contract.pattern
+                ));
+            }
+            if let (Some(min), Some(max)) = (contract.min_ttl_secs, contract.max_ttl_secs) {
+                if min > max {
+                    return Err(format!(
+                        "contract '{}' has min_ttl_secs above max_ttl_secs",
+                        contract.pattern
+                    ));
+                }
+            }
+            if contract.persistent
+                && (contract.min_ttl_secs.is_some() || contract.max_ttl_secs.is_some())
+            {
+                return Err(format!(
+                    "contract '{}' cannot be persistent and set a TTL range",
+                    contract.pattern
+                ));
+            }
+        }
+
+        if self.references.iter().any(|r| r.pattern.is_empty()) {
+            return Err("reference pattern must not be empty".to_string());
+        }
+        crate::references::Matcher::new(&self.references)?;
+
+        for parameter in &self.config_set.allow {
+            if crate::settings::is_forbidden(parameter) {
+                return Err(format!(
+                    "config_set.allow: '{}' can never be changed with config_set",
+                    parameter
+                ));
+            }
+        }
+
+        if let Some(hook) = &self.analysis_replica {
+            if hook.command.first().map_or(true, |c| c.is_empty()) {
+                return Err("analysis_replica.command must name a program".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `name` can name a preset, view or view param: letters, digits,
+/// `_` and `-`.
+fn is_tool_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
@@ -0,0 +1,87 @@
+//! Comparison behind `check_replica_consistency`: each sampled key as read
+//! from the master and from the replica, reduced to its type, whether it
+//! has a TTL, its length and a digest of its value, and what differs.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use serde_json::{json, Value};
+
+use crate::resp;
+
+/// One key as read from one side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyState {
+    /// TYPE, `none` when the key does not exist
+    pub key_type: String,
+    /// Whether the key has a TTL. TTLs themselves are not compared: the
+    /// replica holds the same expiry, read a moment later
+    pub volatile: bool,
+    /// Element count, or byte length for strings
+    pub length: Option<u64>,
+    /// DEBUG DIGEST-VALUE, or [`value_digest`] of the value read back;
+    /// `None` for values too large to read
+    pub digest: Option<String>,
+}
+
+impl KeyState {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "type": self.key_type,
+            "ttl": self.volatile,
+            "length": self.length,
+        })
+    }
+}
+
+/// How the master and replica states of a key differ, if they do.
+pub fn mismatch(master: &KeyState, replica: &KeyState) -> Option<String> {
+    match (master.key_type.as_str(), replica.key_type.as_str()) {
+        ("none", "none") => return None,
+        (_, "none") => return Some("missing on the replica".to_string()),
+        ("none", _) => return Some("missing on the master".to_string()),
+        (m, r) if m != r => return Some(format!("type {} on the master, {} on the replica", m, r)),
+        _ => {}
+    }
+    if master.volatile != replica.volatile {
+        return Some(if master.volatile {
+            "has a TTL on the master only".to_string()
+        } else {
+            "has a TTL on the replica only".to_string()
+        });
+    }
+    if master.length != replica.length {
+        return Some(format!(
+            "length {} on the master, {} on the replica",
+            master.length.map_or("?".to_string(), |l| l.to_string()),
+            replica.length.map_or("?".to_string(), |l| l.to_string())
+        ));
+    }
+    match (&master.digest, &replica.digest) {
+        (Some(m), Some(r)) if m != r => Some("values differ".to_string()),
+        _ => None,
+    }
+}
+
+/// Digest of a value read with GET, LRANGE, SMEMBERS, ZRANGE WITHSCORES,
+/// HGETALL or XRANGE. Set members and hash fields are sorted first: their
+/// order depends on each server's hash table, not on the contents.
+pub fn value_digest(key_type: &str, reply: &redis::Value) -> String {
+    let json = resp::to_json(reply);
+    let mut items: Vec<String> = match (&json, key_type) {
+        (Value::Array(items), "set") => items.iter().map(Value::to_string).collect(),
+        (Value::Array(items), "hash") => items
+            .chunks(2)
+            .map(|pair| Value::Array(pair.to_vec()).to_string())
+            .collect(),
+        (Value::Object(fields), "hash") => fields
+            .iter()
+            .map(|(field, value)| json!([field, value]).to_string())
+            .collect(),
+        _ => vec![json.to_string()],
+    };
+    items.sort();
+    let mut hasher = DefaultHasher::new();
+    items.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
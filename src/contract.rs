@@ -0,0 +1,102 @@
+//! Key contracts: checking a key against the shape configured for its
+//! pattern in `[[contracts]]`.
+
+use serde::Serialize;
+
+use crate::census::KeySample;
+use crate::config::KeyContract;
+use crate::glob;
+
+/// A broken rule of a contract.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    /// `exists`, `type`, `required_fields`, `ttl`, `length` or `memory`
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl Violation {
+    fn new(rule: &'static str, message: String) -> Self {
+        Self { rule, message }
+    }
+}
+
+/// The first contract whose pattern matches `key`.
+pub fn find<'a>(contracts: &'a [KeyContract], key: &str) -> Option<&'a KeyContract> {
+    contracts.iter().find(|c| glob::matches(&c.pattern, key))
+}
+
+/// Rules of `contract` that `sample` breaks. `missing_fields` are the
+/// required hash fields the key lacks. Size rules are skipped when the size
+/// could not be read.
+pub fn check(
+    contract: &KeyContract,
+    sample: &KeySample,
+    missing_fields: &[String],
+) -> Vec<Violation> {
+    if sample.key_type == "none" {
+        return vec![Violation::new("exists", "key does not exist".to_string())];
+    }
+
+    let mut violations = Vec::new();
+    if let Some(expected) = &contract.key_type {
+        if *expected != sample.key_type {
+            violations.push(Violation::new(
+                "type",
+                format!("expected {}, found {}", expected, sample.key_type),
+            ));
+        }
+    }
+    if !missing_fields.is_empty() {
+        violations.push(Violation::new(
+            "required_fields",
+            format!("missing {}", missing_fields.join(", ")),
+        ));
+    }
+
+    let ttl_secs = (sample.ttl_ms >= 0).then_some(sample.ttl_ms as u64 / 1000);
+    match ttl_secs {
+        Some(ttl) if contract.persistent => violations.push(Violation::new(
+            "ttl",
+            format!("expected no TTL, expires in {}s", ttl),
+        )),
+        None if contract.min_ttl_secs.is_some() || contract.max_ttl_secs.is_some() => violations
+            .push(Violation::new(
+                "ttl",
+                "expected a TTL, has none".to_string(),
+            )),
+        Some(ttl) => {
+            if let Some(min) = contract.min_ttl_secs.filter(|min| ttl < *min) {
+                violations.push(Violation::new(
+                    "ttl",
+                    format!("expires in {}s, below the minimum of {}s", ttl, min),
+                ));
+            }
+            if let Some(max) = contract.max_ttl_secs.filter(|max| ttl > *max) {
+                violations.push(Violation::new(
+                    "ttl",
+                    format!("expires in {}s, above the maximum of {}s", ttl, max),
+                ));
+            }
+        }
+        None => {}
+    }
+
+    if let (Some(max), Some(length)) = (contract.max_length, sample.length) {
+        if length > max {
+            violations.push(Violation::new(
+                "length",
+                format!("length {} exceeds {}", length, max),
+            ));
+        }
+    }
+    if let (Some(max), Some(memory)) = (contract.max_memory_bytes, sample.memory) {
+        if memory > max {
+            violations.push(Violation::new(
+                "memory",
+                format!("uses {} bytes, above {}", memory, max),
+            ));
+        }
+    }
+    violations
+}
@@ -0,0 +1,137 @@
+//! Named cursors: the place a paged scan (`scan_keys`, `search_keys`,
+//! `find_member`) stopped, saved under a name an operator can see, so a
+//! later session or another client can resume a long audit. Cursors live in
+//! server memory with a TTL, and optionally in a Redis key under
+//! [`KEY_PREFIX`] to outlive the server.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::restart::format_ago;
+
+/// Tools whose cursor can be saved
+pub const TOOLS: &[&str] = &["scan_keys", "search_keys", "find_member"];
+
+/// Prefix of the Redis keys holding persisted cursors
+pub const KEY_PREFIX: &str = "mcp-redis:cursor:";
+
+/// Cursors kept in memory; past it the one expiring first is dropped
+pub const MAX_CURSORS: usize = 1000;
+
+/// Longest cursor name
+const MAX_NAME_LEN: usize = 128;
+
+/// A saved place in a paged scan. Times are seconds since the epoch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedCursor {
+    pub tool: String,
+    pub connection: String,
+    /// Arguments to repeat with the cursor (pattern, type, key, ...)
+    pub arguments: Map<String, Value>,
+    pub cursor: String,
+    pub note: Option<String>,
+    pub saved_at: u64,
+    pub expires_at: u64,
+}
+
+impl SavedCursor {
+    /// The cursor with the call that resumes the scan.
+    pub fn to_json(&self, name: &str, now: u64) -> Value {
+        let mut arguments = self.arguments.clone();
+        arguments.insert("connection".to_string(), json!(self.connection));
+        arguments.insert(
+            "cursor".to_string(),
+            match self.tool.as_str() {
+                // find_member takes the HSCAN/SSCAN/ZSCAN cursor as a number
+                "find_member" => self
+                    .cursor
+                    .parse::<u64>()
+                    .map_or_else(|_| json!(self.cursor), |c| json!(c)),
+                _ => json!(self.cursor),
+            },
+        );
+        json!({
+            "name": name,
+            "tool": self.tool,
+            "connection": self.connection,
+            "cursor": self.cursor,
+            "note": self.note,
+            "saved": format!("{} ago", format_ago(now.saturating_sub(self.saved_at))),
+            "expires_in_secs": self.expires_at.saturating_sub(now),
+            "resume": {"tool": self.tool, "arguments": arguments},
+        })
+    }
+}
+
+/// Why `name` cannot name a cursor, if it cannot.
+pub fn invalid_name(name: &str) -> Option<String> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Some(format!(
+            "Cursor names are 1 to {} characters long",
+            MAX_NAME_LEN
+        ));
+    }
+    if name.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Some(format!(
+            "Invalid cursor name '{}': no spaces or control characters",
+            name
+        ));
+    }
+    None
+}
+
+/// Cursors by name, shared by all clones of the server.
+#[derive(Debug, Clone, Default)]
+pub struct Cursors {
+    inner: Arc<Mutex<HashMap<String, SavedCursor>>>,
+}
+
+impl Cursors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Save `cursor` under `name`, returning the one it replaces if that
+    /// was still active.
+    pub fn save(&self, name: &str, cursor: SavedCursor, now: u64) -> Option<SavedCursor> {
+        let mut cursors = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        cursors.retain(|_, c| c.expires_at > now);
+        if !cursors.contains_key(name) && cursors.len() >= MAX_CURSORS {
+            let first = cursors
+                .iter()
+                .min_by_key(|(_, c)| c.expires_at)
+                .map(|(n, _)| n.clone());
+            if let Some(first) = first {
+                cursors.remove(&first);
+            }
+        }
+        cursors.insert(name.to_string(), cursor)
+    }
+
+    /// Remove the cursor `name`, returning it if it was still active.
+    pub fn remove(&self, name: &str, now: u64) -> Option<SavedCursor> {
+        let mut cursors = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        cursors.remove(name).filter(|c| c.expires_at > now)
+    }
+
+    /// The active cursor `name`.
+    pub fn get(&self, name: &str, now: u64) -> Option<SavedCursor> {
+        let cursors = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        cursors.get(name).filter(|c| c.expires_at > now).cloned()
+    }
+
+    /// Active cursors by name. Expired ones are dropped.
+    pub fn list(&self, now: u64) -> Vec<(String, SavedCursor)> {
+        let mut cursors = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        cursors.retain(|_, c| c.expires_at > now);
+        let mut list: Vec<(String, SavedCursor)> = cursors
+            .iter()
+            .map(|(name, c)| (name.clone(), c.clone()))
+            .collect();
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+        list
+    }
+}
@@ -14,6 +14,9 @@ pub enum McpRedisError {
     #[error("Write operation rejected: {0}")]
     ReadOnly(String),
 
+    #[error("Admin operation rejected: {0}")]
+    Admin(String),
+
     #[error("{0}")]
     Other(String),
 }
@@ -24,7 +27,9 @@ impl McpRedisError {
             McpRedisError::ConnectionNotFound(_) | McpRedisError::AmbiguousConnection => {
                 ErrorData::invalid_params(self.to_string(), None)
             }
-            McpRedisError::ReadOnly(_) => ErrorData::invalid_params(self.to_string(), None),
+            McpRedisError::ReadOnly(_) | McpRedisError::Admin(_) => {
+                ErrorData::invalid_params(self.to_string(), None)
+            }
             McpRedisError::Redis(_) | McpRedisError::Other(_) => {
                 ErrorData::internal_error(self.to_string(), None)
             }
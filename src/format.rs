@@ -0,0 +1,116 @@
+//! Compact text renderings of list-shaped tool results, for models with
+//! small context windows. Structured content always stays JSON.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// How a tool renders its text result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Pretty-printed JSON
+    #[default]
+    Json,
+    /// Markdown table, one row per item
+    Table,
+    /// One line per item
+    Plain,
+}
+
+/// Render `value` as text. The array in the `rows` field becomes a table or
+/// one line per item, showing the `columns` that any row has; rows that are
+/// not objects fill the first column. The remaining scalar fields follow on
+/// a summary line. Other nested fields are only kept in the JSON form.
+pub fn render(value: &Value, format: Format, rows: &str, columns: &[&str]) -> String {
+    if format == Format::Json {
+        return serde_json::to_string_pretty(value).unwrap_or_else(|_| "{}".to_string());
+    }
+    let items: &[Value] = value
+        .get(rows)
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let columns: Vec<&str> = columns
+        .iter()
+        .enumerate()
+        .filter(|(i, column)| {
+            items.iter().any(|item| match item {
+                Value::Object(map) => map.contains_key(**column),
+                _ => *i == 0,
+            })
+        })
+        .map(|(_, column)| *column)
+        .collect();
+    let cell = |item: &Value, i: usize, column: &str| -> Option<String> {
+        match item {
+            Value::Object(map) => map.get(column).map(scalar),
+            other if i == 0 => Some(scalar(other)),
+            _ => None,
+        }
+    };
+
+    let mut lines = Vec::new();
+    match format {
+        Format::Table => {
+            lines.push(format!("| {} |", columns.join(" | ")));
+            lines.push(format!("|{}", "---|".repeat(columns.len())));
+            for item in items {
+                let cells: Vec<String> = columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, column)| {
+                        cell(item, i, column)
+                            .unwrap_or_default()
+                            .replace('|', "\\|")
+                            .replace('\n', "\\n")
+                    })
+                    .collect();
+                lines.push(format!("| {} |", cells.join(" | ")));
+            }
+        }
+        _ => {
+            for item in items {
+                let line = if columns.len() == 1 {
+                    cell(item, 0, columns[0]).unwrap_or_default()
+                } else {
+                    columns
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, column)| {
+                            let text = cell(item, i, column)?;
+                            Some(if text.is_empty() || text.contains(char::is_whitespace) {
+                                format!("{}={:?}", column, text)
+                            } else {
+                                format!("{}={}", column, text)
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                };
+                lines.push(line.replace('\n', "\\n"));
+            }
+        }
+    }
+
+    let summary: Vec<String> = value
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(name, v)| name.as_str() != rows && !v.is_array() && !v.is_object())
+        .map(|(name, v)| format!("{}: {}", name, scalar(v)))
+        .collect();
+    if !summary.is_empty() {
+        lines.push(String::new());
+        lines.push(summary.join(", "));
+    }
+    lines.join("\n")
+}
+
+/// A value as cell text: strings unquoted, null empty, the rest as JSON.
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,154 @@
+//! Client-side glob matching with the same semantics as Redis MATCH patterns
+//! (`*`, `?`, `[abc]`, `[^a-z]`, and `\` escapes).
+
+/// Returns true if `text` matches the Redis-style glob `pattern`.
+pub fn matches(pattern: &str, text: &str) -> bool {
+    match_bytes(pattern.as_bytes(), text.as_bytes())
+}
+
+/// Escape `text` so it matches only itself inside a pattern.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Pattern matching every key that starts with `text`, taken literally.
+pub fn prefix(text: &str) -> String {
+    format!("{}*", escape(text))
+}
+
+/// The literal text every key matching `pattern` starts with: everything
+/// before its first unescaped `*`, `?` or `[`, with escapes removed.
+pub fn literal_prefix(pattern: &str) -> String {
+    let mut prefix = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' | '?' | '[' => break,
+            '\\' => match chars.next() {
+                Some(escaped) => prefix.push(escaped),
+                None => break,
+            },
+            other => prefix.push(other),
+        }
+    }
+    prefix
+}
+
+/// The longest literal text that keys matching any of `patterns` all start
+/// with, so one SCAN with MATCH [`prefix`] of it covers every pattern.
+pub fn shared_prefix(patterns: &[String]) -> String {
+    let mut prefixes = patterns.iter().map(|p| literal_prefix(p));
+    let Some(mut shared) = prefixes.next() else {
+        return String::new();
+    };
+    for prefix in prefixes {
+        let common = shared
+            .char_indices()
+            .zip(prefix.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(shared.len().min(prefix.len()), |((at, _), _)| at);
+        shared.truncate(common);
+    }
+    shared
+}
+
+fn match_bytes(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Backtracking point for the most recent '*': (pattern index after it, text index)
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                b'*' => {
+                    // Collapse consecutive stars
+                    while p < pattern.len() && pattern[p] == b'*' {
+                        p += 1;
+                    }
+                    if p == pattern.len() {
+                        return true;
+                    }
+                    star = Some((p, t));
+                    continue;
+                }
+                b'?' => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                b'[' => {
+                    if let Some((matched, next)) = match_class(pattern, p, text[t]) {
+                        if matched {
+                            p = next;
+                            t += 1;
+                            continue;
+                        }
+                    }
+                }
+                b'\\' if p + 1 < pattern.len() => {
+                    if pattern[p + 1] == text[t] {
+                        p += 2;
+                        t += 1;
+                        continue;
+                    }
+                }
+                c => {
+                    if c == text[t] {
+                        p += 1;
+                        t += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+        // Mismatch: let the last '*' swallow one more byte, or fail
+        match star {
+            Some((sp, st)) => {
+                p = sp;
+                t = st + 1;
+                star = Some((sp, st + 1));
+            }
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Match `c` against the character class starting at `pattern[start] == '['`.
+/// Returns whether it matched and the pattern index just past the class.
+fn match_class(pattern: &[u8], start: usize, c: u8) -> Option<(bool, usize)> {
+    let mut i = start + 1;
+    let negate = pattern.get(i) == Some(&b'^');
+    if negate {
+        i += 1;
+    }
+    let mut matched = false;
+    loop {
+        match pattern.get(i)? {
+            b']' => break,
+            b'\\' if i + 1 < pattern.len() => {
+                matched |= pattern[i + 1] == c;
+                i += 2;
+            }
+            &lo if pattern.get(i + 1) == Some(&b'-') && i + 2 < pattern.len() => {
+                let hi = pattern[i + 2];
+                let (lo, hi) = if lo <= hi { (lo, hi) } else { (hi, lo) };
+                matched |= lo <= c && c <= hi;
+                i += 3;
+            }
+            &other => {
+                matched |= other == c;
+                i += 1;
+            }
+        }
+    }
+    Some((matched != negate, i + 1))
+}
@@ -0,0 +1,279 @@
+//! Bundled explanations and healthy ranges for INFO metrics.
+//!
+//! Report tools attach these next to the raw numbers so clients interpret
+//! them against documented thresholds rather than guessing.
+
+use serde_json::{json, Map, Value};
+
+/// Severity of a metric reading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl Level {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Ok => "ok",
+            Level::Warning => "warning",
+            Level::Critical => "critical",
+        }
+    }
+}
+
+/// A condition on a metric value.
+#[derive(Debug, Clone, Copy)]
+pub enum Check {
+    Above(f64),
+    Below(f64),
+    Equals(&'static str),
+    /// Flagged whenever the field is reported at all
+    Present,
+}
+
+impl Check {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            Check::Above(limit) => value.as_f64().is_some_and(|v| v > limit),
+            Check::Below(limit) => value.as_f64().is_some_and(|v| v < limit),
+            Check::Present => true,
+            Check::Equals(expected) => value.as_str() == Some(expected),
+        }
+    }
+}
+
+/// Glossary entry for one INFO field.
+#[derive(Debug)]
+pub struct Metric {
+    pub name: &'static str,
+    /// `memory`, `replication` or `eviction`
+    pub category: &'static str,
+    pub meaning: &'static str,
+    pub healthy: &'static str,
+    /// Conditions that flag the value, with the level and a short reason
+    pub checks: &'static [(Check, Level, &'static str)],
+}
+
+pub const METRICS: &[Metric] = &[
+    // Memory
+    Metric {
+        name: "used_memory",
+        category: "memory",
+        meaning: "Bytes allocated by Redis for data and internal structures",
+        healthy: "Comfortably below maxmemory (when set) and the host's RAM",
+        checks: &[],
+    },
+    Metric {
+        name: "used_memory_rss",
+        category: "memory",
+        meaning: "Resident set size: memory the OS has actually given the process",
+        healthy: "Close to used_memory; much larger means fragmentation",
+        checks: &[],
+    },
+    Metric {
+        name: "mem_fragmentation_ratio",
+        category: "memory",
+        meaning: "used_memory_rss divided by used_memory",
+        healthy: "1.0 to 1.5",
+        checks: &[
+            (
+                Check::Above(1.5),
+                Level::Warning,
+                "Fragmented: RSS well above allocated memory; consider activedefrag",
+            ),
+            (
+                Check::Above(2.0),
+                Level::Critical,
+                "Heavily fragmented; RSS is more than twice the allocated memory",
+            ),
+            (
+                Check::Below(1.0),
+                Level::Warning,
+                "RSS below allocated memory: the OS is likely swapping Redis pages",
+            ),
+        ],
+    },
+    Metric {
+        name: "allocator_frag_ratio",
+        category: "memory",
+        meaning: "Fragmentation inside the allocator (allocator_active / allocator_allocated)",
+        healthy: "Below 1.1",
+        checks: &[(
+            Check::Above(1.1),
+            Level::Warning,
+            "Allocator fragmentation; active defrag can reclaim it",
+        )],
+    },
+    Metric {
+        name: "maxmemory",
+        category: "memory",
+        meaning: "Configured memory limit in bytes; 0 means no limit",
+        healthy: "Set on caches so the eviction policy applies before the host runs out of memory",
+        checks: &[],
+    },
+    Metric {
+        name: "maxmemory_policy",
+        category: "memory",
+        meaning: "What happens when maxmemory is reached",
+        healthy:
+            "An allkeys-*/volatile-* policy for caches; noeviction makes writes fail at the limit",
+        checks: &[],
+    },
+    Metric {
+        name: "lazyfree_pending_objects",
+        category: "memory",
+        meaning: "Objects waiting to be freed in the background (UNLINK, FLUSHALL ASYNC)",
+        healthy: "0 or quickly returning to 0",
+        checks: &[(
+            Check::Above(0.0),
+            Level::Warning,
+            "Background freeing in progress; memory will drop once done",
+        )],
+    },
+    // Replication
+    Metric {
+        name: "role",
+        category: "replication",
+        meaning: "Whether this node is a master or a replica",
+        healthy: "As expected for the node",
+        checks: &[],
+    },
+    Metric {
+        name: "connected_slaves",
+        category: "replication",
+        meaning: "Number of replicas currently attached to this master",
+        healthy: "Equal to the number of replicas you deployed",
+        checks: &[],
+    },
+    Metric {
+        name: "master_link_status",
+        category: "replication",
+        meaning: "Replica only: state of the link to the master",
+        healthy: "up",
+        checks: &[(
+            Check::Equals("down"),
+            Level::Critical,
+            "Replica is disconnected from its master and serving stale data",
+        )],
+    },
+    Metric {
+        name: "master_last_io_seconds_ago",
+        category: "replication",
+        meaning: "Replica only: seconds since the last interaction with the master",
+        healthy: "Below 10 (the master pings replicas every 10s by default)",
+        checks: &[
+            (
+                Check::Above(10.0),
+                Level::Warning,
+                "Master has been silent longer than the ping period",
+            ),
+            (
+                Check::Above(60.0),
+                Level::Critical,
+                "Master silent for over a minute; the link is probably broken",
+            ),
+        ],
+    },
+    Metric {
+        name: "master_sync_in_progress",
+        category: "replication",
+        meaning: "Replica only: 1 while a full resynchronization is running",
+        healthy: "0",
+        checks: &[(
+            Check::Above(0.0),
+            Level::Warning,
+            "Full sync in progress; the replica's data is not current",
+        )],
+    },
+    Metric {
+        name: "master_link_down_since_seconds",
+        category: "replication",
+        meaning: "Replica only: seconds the link to the master has been down",
+        healthy: "Absent (only reported while the link is down)",
+        checks: &[(
+            Check::Present,
+            Level::Critical,
+            "Link to the master is down",
+        )],
+    },
+    Metric {
+        name: "repl_backlog_size",
+        category: "replication",
+        meaning: "Bytes of replication stream kept for partial resyncs",
+        healthy: "Large enough to cover write volume during a typical replica disconnect",
+        checks: &[],
+    },
+    // Eviction
+    Metric {
+        name: "evicted_keys",
+        category: "eviction",
+        meaning: "Keys removed because maxmemory was reached, since startup",
+        healthy: "0 unless Redis is deliberately used as an LRU/LFU cache",
+        checks: &[(
+            Check::Above(0.0),
+            Level::Warning,
+            "Keys are being evicted under memory pressure",
+        )],
+    },
+    Metric {
+        name: "current_eviction_exceeded_time",
+        category: "eviction",
+        meaning: "Milliseconds used_memory has currently been above maxmemory",
+        healthy: "0",
+        checks: &[(
+            Check::Above(0.0),
+            Level::Warning,
+            "Memory is above maxmemory right now",
+        )],
+    },
+    Metric {
+        name: "expired_keys",
+        category: "eviction",
+        meaning: "Keys removed because their TTL elapsed, since startup",
+        healthy: "Any value; this is normal expiry, not memory pressure",
+        checks: &[],
+    },
+    Metric {
+        name: "rejected_connections",
+        category: "eviction",
+        meaning: "Connections refused because maxclients was reached, since startup",
+        healthy: "0",
+        checks: &[(
+            Check::Above(0.0),
+            Level::Warning,
+            "Clients have been refused; raise maxclients or find the leak",
+        )],
+    },
+];
+
+/// Explanations for the glossary metrics present in `fields` (parsed INFO),
+/// each with its current value and evaluated status.
+pub fn explain(fields: &Map<String, Value>) -> Map<String, Value> {
+    METRICS
+        .iter()
+        .filter_map(|metric| {
+            let value = fields.get(metric.name)?;
+            let mut level = Level::Ok;
+            let mut notes = Vec::new();
+            for (check, check_level, note) in metric.checks {
+                if check.matches(value) {
+                    level = level.max(*check_level);
+                    notes.push(*note);
+                }
+            }
+            let mut entry = json!({
+                "value": value,
+                "category": metric.category,
+                "meaning": metric.meaning,
+                "healthy": metric.healthy,
+                "status": level.as_str(),
+            });
+            if !notes.is_empty() {
+                entry["notes"] = json!(notes);
+            }
+            Some((metric.name.to_string(), entry))
+        })
+        .collect()
+}
@@ -0,0 +1,342 @@
+//! Parsing of INFO-style `field:value` text replies.
+
+/// Parse a scalar value, turning integers and floats into JSON numbers.
+pub fn parse_value(raw: &str) -> serde_json::Value {
+    if let Ok(i) = raw.parse::<i64>() {
+        return serde_json::json!(i);
+    }
+    if let Ok(f) = raw.parse::<f64>() {
+        if f.is_finite() {
+            return serde_json::json!(f);
+        }
+    }
+    serde_json::Value::String(raw.to_string())
+}
+
+/// Parse `field:value` lines (as returned by INFO or CLUSTER INFO) into a
+/// flat JSON object. Comment lines and blank lines are skipped.
+pub fn parse_fields(text: &str) -> serde_json::Map<String, serde_json::Value> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(field, value)| (field.to_string(), parse_value(value)))
+        .collect()
+}
+
+/// Parse a full INFO reply into `{section: {field: value}}`. Section names
+/// are lowercased (`# Server` becomes `server`). Compound values such as
+/// `db0:keys=1,expires=0` or `cmdstat_get:calls=3,usec=12` are decomposed
+/// into nested objects.
+pub fn parse_sections(text: &str) -> serde_json::Map<String, serde_json::Value> {
+    let mut sections = serde_json::Map::new();
+    let mut current = String::from("default");
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('#') {
+            current = name.trim().to_lowercase();
+            sections
+                .entry(current.clone())
+                .or_insert_with(|| serde_json::json!({}));
+            continue;
+        }
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+        if let Some(section) = sections
+            .entry(current.clone())
+            .or_insert_with(|| serde_json::json!({}))
+            .as_object_mut()
+        {
+            section.insert(field.to_string(), parse_compound(value));
+        }
+    }
+
+    sections
+}
+
+/// Parse `a=1,b=2` into an object; anything else is a scalar.
+fn parse_compound(raw: &str) -> serde_json::Value {
+    if !raw.contains('=') {
+        return parse_value(raw);
+    }
+    let pairs: Option<serde_json::Map<String, serde_json::Value>> = raw
+        .split(',')
+        .map(|part| {
+            part.split_once('=')
+                .map(|(k, v)| (k.to_string(), parse_value(v)))
+        })
+        .collect();
+    match pairs {
+        Some(map) => serde_json::Value::Object(map),
+        None => parse_value(raw),
+    }
+}
+
+/// Parse `INFO keyspace` into one entry per populated database, ordered
+/// by index: `{"db": 0, "keys": 12, "expires": 3, "avg_ttl_ms": 1500}`.
+/// Other fields (such as `subexpiry`) are kept as they are.
+pub fn parse_keyspace(text: &str) -> Vec<serde_json::Value> {
+    let mut databases: Vec<(i64, serde_json::Value)> = text
+        .lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .filter_map(|(field, value)| {
+            let db = field.strip_prefix("db")?.parse::<i64>().ok()?;
+            let serde_json::Value::Object(fields) = parse_compound(value) else {
+                return None;
+            };
+            let mut entry = serde_json::json!({ "db": db });
+            for (name, value) in fields {
+                match name.as_str() {
+                    "avg_ttl" => entry["avg_ttl_ms"] = value,
+                    _ => entry[name] = value,
+                }
+            }
+            Some((db, entry))
+        })
+        .collect();
+    databases.sort_by_key(|(db, _)| *db);
+    databases.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// `(prefix, count)` per line of `INFO errorstats`
+/// (`errorstat_WRONGTYPE:count=3`), most frequent first.
+pub fn parse_errorstats(text: &str) -> Vec<(String, u64)> {
+    let mut errors: Vec<(String, u64)> = text
+        .lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .filter_map(|(field, value)| {
+            let prefix = field.strip_prefix("errorstat_")?;
+            let count = value
+                .strip_prefix("count=")?
+                .split(',')
+                .next()?
+                .parse()
+                .ok()?;
+            Some((prefix.to_string(), count))
+        })
+        .collect();
+    errors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    errors
+}
+
+/// `{command, calls, failed_calls, rejected_calls}` for each command in
+/// `INFO commandstats` with failed or rejected calls (Redis 7.0 and later),
+/// most failures first.
+pub fn command_failures(text: &str) -> Vec<serde_json::Value> {
+    let mut commands: Vec<(u64, serde_json::Value)> = text
+        .lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .filter_map(|(field, value)| {
+            let command = field.strip_prefix("cmdstat_")?;
+            let serde_json::Value::Object(stats) = parse_compound(value) else {
+                return None;
+            };
+            let count = |name: &str| stats.get(name).and_then(|v| v.as_u64()).unwrap_or(0);
+            let failures = count("failed_calls") + count("rejected_calls");
+            (failures > 0).then(|| {
+                (
+                    failures,
+                    serde_json::json!({
+                        "command": command,
+                        "calls": count("calls"),
+                        "failed_calls": count("failed_calls"),
+                        "rejected_calls": count("rejected_calls"),
+                    }),
+                )
+            })
+        })
+        .collect();
+    commands.sort_by_key(|(failures, _)| std::cmp::Reverse(*failures));
+    commands.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// Sections with one line per command, often longer than all the others
+/// together. They are only rendered when asked for.
+pub const LARGE_SECTIONS: &[&str] = &["commandstats", "latencystats"];
+
+/// Parsed INFO sections picked by [`select_sections`].
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// Sections rendered together
+    pub sections: serde_json::Map<String, serde_json::Value>,
+    /// Large sections that were asked for, rendered one by one
+    pub large: Vec<(String, serde_json::Value)>,
+    /// Large sections left out
+    pub omitted: Vec<String>,
+    /// Requested sections the server did not return
+    pub missing: Vec<String>,
+}
+
+/// Keep the sections named in `wanted` (all of them when `None`), setting
+/// the [`LARGE_SECTIONS`] apart. A large section is left out unless
+/// `include_large` is set or `wanted` names it.
+pub fn select_sections(
+    parsed: serde_json::Map<String, serde_json::Value>,
+    wanted: Option<&[String]>,
+    include_large: bool,
+) -> Selection {
+    let wanted: Option<Vec<String>> =
+        wanted.map(|names| names.iter().map(|n| n.to_lowercase()).collect());
+    let mut selection = Selection::default();
+    if let Some(wanted) = &wanted {
+        selection.missing = wanted
+            .iter()
+            .filter(|name| !parsed.contains_key(name.as_str()))
+            .cloned()
+            .collect();
+    }
+    for (name, fields) in parsed {
+        let named = wanted.as_ref().map(|w| w.contains(&name));
+        if named == Some(false) {
+            continue;
+        }
+        if !LARGE_SECTIONS.contains(&name.as_str()) {
+            selection.sections.insert(name, fields);
+        } else if include_large || named == Some(true) {
+            selection.large.push((name, fields));
+        } else {
+            selection.omitted.push(name);
+        }
+    }
+    selection
+}
+
+/// Rates between two INFO snapshots (flat, from [`parse_fields`]) taken
+/// `secs` apart: commands, keyspace hits, network traffic, expired and
+/// evicted keys, connections and errors per second. A counter that went
+/// down (CONFIG RESETSTAT, a restart) counts as zero and sets
+/// `counters_reset`.
+pub fn delta(
+    before: &serde_json::Map<String, serde_json::Value>,
+    after: &serde_json::Map<String, serde_json::Value>,
+    secs: f64,
+) -> serde_json::Value {
+    let mut reset = false;
+    let mut change = |field: &str| {
+        let (Some(a), Some(b)) = (
+            before.get(field).and_then(|v| v.as_u64()),
+            after.get(field).and_then(|v| v.as_u64()),
+        ) else {
+            return None;
+        };
+        if b < a {
+            reset = true;
+        }
+        Some(b.saturating_sub(a))
+    };
+    let rate = |count: Option<u64>| {
+        count
+            .filter(|_| secs > 0.0)
+            .map(|c| (c as f64 * 100.0 / secs).round() / 100.0)
+    };
+
+    let commands = change("total_commands_processed");
+    let hits = change("keyspace_hits");
+    let misses = change("keyspace_misses");
+    let net_in = change("total_net_input_bytes");
+    let net_out = change("total_net_output_bytes");
+    let expired = change("expired_keys");
+    let evicted = change("evicted_keys");
+    let received = change("total_connections_received");
+    let rejected = change("rejected_connections");
+    let errors = change("total_error_replies");
+    let hit_ratio = match (hits, misses) {
+        (Some(h), Some(m)) if h + m > 0 => {
+            Some((h as f64 * 1000.0 / (h + m) as f64).round() / 10.0)
+        }
+        _ => None,
+    };
+    let gauge = |field: &str| {
+        serde_json::json!({
+            "before": before.get(field),
+            "after": after.get(field),
+        })
+    };
+    let memory_change = match (
+        before.get("used_memory").and_then(|v| v.as_i64()),
+        after.get("used_memory").and_then(|v| v.as_i64()),
+    ) {
+        (Some(a), Some(b)) => Some(b - a),
+        _ => None,
+    };
+
+    serde_json::json!({
+        "interval_secs": (secs * 100.0).round() / 100.0,
+        "ops_per_sec": rate(commands),
+        "instantaneous_ops_per_sec": after.get("instantaneous_ops_per_sec"),
+        "commands": commands,
+        "keyspace": {
+            "hits": hits,
+            "misses": misses,
+            "hit_ratio_percent": hit_ratio,
+        },
+        "network": {
+            "input_bytes_per_sec": rate(net_in),
+            "output_bytes_per_sec": rate(net_out),
+        },
+        "keys": {
+            "expired_per_sec": rate(expired),
+            "evicted_per_sec": rate(evicted),
+        },
+        "connections": {
+            "received_per_sec": rate(received),
+            "rejected": rejected,
+            "connected_clients": gauge("connected_clients"),
+            "blocked_clients": gauge("blocked_clients"),
+        },
+        "errors_per_sec": rate(errors),
+        "used_memory": {
+            "before": before.get("used_memory"),
+            "after": after.get("used_memory"),
+            "change_bytes": memory_change,
+        },
+        "counters_reset": reset,
+    })
+}
+
+/// INFO fields `sample_stats` polls by default
+pub const SAMPLE_FIELDS: &[&str] = &[
+    "instantaneous_ops_per_sec",
+    "used_memory",
+    "connected_clients",
+];
+
+/// First, last, min, max and average of each of `fields` over a series of
+/// INFO snapshots (flat, from [`parse_fields`]). Snapshots missing a field,
+/// or holding a non-numeric value for it, are skipped for that field; a
+/// field never seen numerically summarizes to `null`.
+pub fn series_summary(
+    fields: &[String],
+    samples: &[serde_json::Map<String, serde_json::Value>],
+) -> serde_json::Value {
+    let mut summary = serde_json::Map::new();
+    for field in fields {
+        let values: Vec<f64> = samples
+            .iter()
+            .filter_map(|s| s.get(field).and_then(|v| v.as_f64()))
+            .collect();
+        let (Some(&first), Some(&last)) = (values.first(), values.last()) else {
+            summary.insert(field.clone(), serde_json::Value::Null);
+            continue;
+        };
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let avg = values.iter().sum::<f64>() / values.len() as f64;
+        summary.insert(
+            field.clone(),
+            serde_json::json!({
+                "first": first,
+                "last": last,
+                "min": min,
+                "max": max,
+                "avg": (avg * 100.0).round() / 100.0,
+                "change": last - first,
+            }),
+        );
+    }
+    serde_json::Value::Object(summary)
+}
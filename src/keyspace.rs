@@ -0,0 +1,79 @@
+//! Keyspace notifications behind resource subscriptions. A client subscribes
+//! to `redis://{connection}/{pattern}` and receives
+//! `notifications/resources/updated` with the URI of each matching key that
+//! changes, driven by the server's `__keyspace@<db>__:` channels.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::task::AbortHandle;
+
+/// Event classes of `notify-keyspace-events` (`A` is the alias for most)
+const EVENT_CLASSES: &str = "g$lshzxetdmnA";
+
+/// Whether a `notify-keyspace-events` value publishes keyspace events: `K`
+/// plus at least one event class.
+pub fn events_enabled(flags: &str) -> bool {
+    flags.contains('K') && flags.chars().any(|c| EVENT_CLASSES.contains(c))
+}
+
+/// PSUBSCRIBE pattern for keys matching `pattern` in `db`.
+pub fn channel_pattern(db: i64, pattern: &str) -> String {
+    format!("__keyspace@{}__:{}", db, pattern)
+}
+
+/// The key a keyspace channel reports on, e.g. `session:1` for
+/// `__keyspace@0__:session:1`.
+pub fn key_from_channel(channel: &str) -> Option<&str> {
+    let (db, key) = channel.strip_prefix("__keyspace@")?.split_once("__:")?;
+    db.bytes().all(|b| b.is_ascii_digit()).then_some(key)
+}
+
+/// Active resource subscriptions by MCP session and URI, each served by a
+/// background task that ends when it is removed.
+#[derive(Debug, Clone, Default)]
+pub struct Subscriptions {
+    inner: Arc<Mutex<HashMap<(String, String), AbortHandle>>>,
+}
+
+impl Subscriptions {
+    pub fn contains(&self, session: &str, uri: &str) -> bool {
+        let subscriptions = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        subscriptions
+            .get(&(session.to_string(), uri.to_string()))
+            .is_some_and(|task| !task.is_finished())
+    }
+
+    /// Register the task serving `uri` for `session`, stopping any it
+    /// replaces.
+    pub fn insert(&self, session: &str, uri: &str, task: AbortHandle) {
+        let mut subscriptions = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(old) = subscriptions.insert((session.to_string(), uri.to_string()), task) {
+            old.abort();
+        }
+    }
+
+    /// Stop serving `uri` for `session`. Returns whether it was subscribed.
+    pub fn remove(&self, session: &str, uri: &str) -> bool {
+        let mut subscriptions = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        match subscriptions.remove(&(session.to_string(), uri.to_string())) {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Subscriptions still being served. Ones whose session went away are
+    /// dropped.
+    pub fn len(&self) -> usize {
+        let mut subscriptions = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        subscriptions.retain(|_, task| !task.is_finished());
+        subscriptions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,285 @@
+//! Timeline behind `latency_correlation`: LATENCY HISTORY samples, slowlog
+//! entries and RDB/AOF persistence windows merged in time order, with the
+//! spikes that happened while a save or rewrite was running called out.
+//! Fork stalls at the start of a BGSAVE are the usual finding. Also the
+//! parsing behind `latency_latest` and `latency_history`.
+
+use serde_json::{json, Map, Value};
+
+/// A latency sample or slow command at a point in time (seconds since the
+/// epoch, server clock).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub at: u64,
+    /// `latency` (a LATENCY HISTORY sample) or `slowlog`
+    pub source: &'static str,
+    /// Latency event name (`fork`, `command`, `aof-fsync-always`, ...) or the
+    /// slow command line
+    pub name: String,
+    pub duration_ms: f64,
+}
+
+impl Event {
+    /// An event from a parsed slowlog entry (`timestamp`, `duration_us`,
+    /// `command`).
+    pub fn from_slowlog(entry: &Value) -> Option<Self> {
+        Some(Self {
+            at: entry["timestamp"].as_u64()?,
+            source: "slowlog",
+            name: entry["command"].as_str().unwrap_or("unknown").to_string(),
+            duration_ms: entry["duration_us"].as_u64()? as f64 / 1000.0,
+        })
+    }
+
+    /// Whether this is a latency event raised by persistence itself rather
+    /// than by a command.
+    pub fn is_persistence(&self) -> bool {
+        self.source == "latency"
+            && (self.name == "fork"
+                || self.name.starts_with("aof-")
+                || self.name.starts_with("rdb-"))
+    }
+}
+
+/// A BGSAVE or AOF rewrite, from `start` to `end` (seconds since the epoch).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Window {
+    /// `bgsave` or `aof_rewrite`
+    pub kind: &'static str,
+    pub start: u64,
+    pub end: u64,
+    pub in_progress: bool,
+}
+
+impl Window {
+    fn label(&self) -> &'static str {
+        match self.kind {
+            "bgsave" => "the RDB save",
+            _ => "the AOF rewrite",
+        }
+    }
+}
+
+fn int(fields: &Map<String, Value>, field: &str) -> Option<i64> {
+    fields.get(field)?.as_i64()
+}
+
+/// Persistence windows that can be placed on the timeline, from INFO
+/// persistence fields. `now` is the server time. INFO has the end time of
+/// the last BGSAVE (`rdb_last_save_time`) but not of the last AOF rewrite,
+/// so only a rewrite in progress is placed.
+pub fn persistence_windows(fields: &Map<String, Value>, now: u64) -> Vec<Window> {
+    let mut windows = Vec::new();
+    let last_save = int(fields, "rdb_last_save_time").unwrap_or(0);
+    let last_duration = int(fields, "rdb_last_bgsave_time_sec").unwrap_or(-1);
+    // -1 means no BGSAVE since startup; rdb_last_save_time is then the load
+    if last_save > 0 && last_duration >= 0 {
+        let end = last_save as u64;
+        windows.push(Window {
+            kind: "bgsave",
+            start: end.saturating_sub(last_duration as u64),
+            end,
+            in_progress: false,
+        });
+    }
+    let running = [
+        (
+            "bgsave",
+            "rdb_bgsave_in_progress",
+            "rdb_current_bgsave_time_sec",
+        ),
+        (
+            "aof_rewrite",
+            "aof_rewrite_in_progress",
+            "aof_current_rewrite_time_sec",
+        ),
+    ];
+    for (kind, flag, elapsed) in running {
+        if int(fields, flag) == Some(1) {
+            let elapsed = int(fields, elapsed).unwrap_or(0).max(0) as u64;
+            windows.push(Window {
+                kind,
+                start: now.saturating_sub(elapsed),
+                end: now,
+                in_progress: true,
+            });
+        }
+    }
+    windows.sort_by_key(|w| w.start);
+    windows
+}
+
+fn event_json(event: &Event) -> Value {
+    json!({
+        "at": event.at,
+        "source": event.source,
+        "name": event.name,
+        "duration_ms": event.duration_ms,
+    })
+}
+
+/// Spikes that coincide with something else, within `tolerance_secs`:
+/// latency events and slow commands during a persistence window, and slow
+/// commands next to a latency event. Sorted by time.
+pub fn correlate(events: &[Event], windows: &[Window], tolerance_secs: u64) -> Vec<Value> {
+    let near = |at: u64, start: u64, end: u64| {
+        at + tolerance_secs >= start && at <= end.saturating_add(tolerance_secs)
+    };
+    let mut found = Vec::new();
+    for event in events {
+        for window in windows.iter().filter(|w| near(event.at, w.start, w.end)) {
+            let note = if event.source == "latency" && event.name == "fork" {
+                format!(
+                    "fork stall of {} ms when {} started",
+                    event.duration_ms,
+                    window.label()
+                )
+            } else if event.is_persistence() {
+                format!(
+                    "{} latency of {} ms during {}",
+                    event.name,
+                    event.duration_ms,
+                    window.label()
+                )
+            } else {
+                format!(
+                    "{} of {} ms during {}; copy-on-write and disk load slow commands",
+                    if event.source == "slowlog" {
+                        "slow command"
+                    } else {
+                        "latency spike"
+                    },
+                    event.duration_ms,
+                    window.label()
+                )
+            };
+            found.push(json!({
+                "at": event.at,
+                "event": event_json(event),
+                "with": {
+                    "persistence": window.kind,
+                    "start": window.start,
+                    "end": window.end,
+                },
+                "note": note,
+            }));
+        }
+        if event.source != "slowlog" {
+            continue;
+        }
+        for spike in events
+            .iter()
+            .filter(|e| e.source == "latency" && near(event.at, e.at, e.at))
+        {
+            found.push(json!({
+                "at": event.at,
+                "event": event_json(event),
+                "with": {
+                    "latency_event": spike.name,
+                    "at": spike.at,
+                    "duration_ms": spike.duration_ms,
+                },
+                "note": format!(
+                    "slow command next to a {} latency spike of {} ms",
+                    spike.name, spike.duration_ms
+                ),
+            }));
+        }
+    }
+    found.sort_by_key(|c| c["at"].as_u64());
+    found
+}
+
+/// Events and window boundaries since `since`, in time order. Only the
+/// latest `max` entries are kept; the flag says whether any were dropped.
+pub fn timeline(
+    events: &[Event],
+    windows: &[Window],
+    since: u64,
+    max: usize,
+) -> (Vec<Value>, bool) {
+    let mut entries: Vec<(u64, Value)> = events
+        .iter()
+        .filter(|e| e.at >= since)
+        .map(|e| (e.at, event_json(e)))
+        .collect();
+    for window in windows.iter().filter(|w| w.end >= since) {
+        entries.push((
+            window.start,
+            json!({
+                "at": window.start,
+                "source": "persistence",
+                "name": format!("{} started", window.kind),
+            }),
+        ));
+        if !window.in_progress {
+            entries.push((
+                window.end,
+                json!({
+                    "at": window.end,
+                    "source": "persistence",
+                    "name": format!("{} finished", window.kind),
+                    "duration_ms": (window.end - window.start) * 1000,
+                }),
+            ));
+        }
+    }
+    entries.sort_by_key(|(at, _)| *at);
+    let dropped = entries.len().saturating_sub(max);
+    let entries = entries.into_iter().skip(dropped).map(|(at, mut entry)| {
+        entry["time"] = json!(crate::time::iso8601_ms(at * 1000));
+        entry
+    });
+    (entries.collect(), dropped > 0)
+}
+
+/// `{event, timestamp, time, latest_ms, max_ms}` per LATENCY LATEST row
+/// (`[event, timestamp, latest_ms, max_ms]`), worst first.
+pub fn parse_latest(rows: &[Vec<redis::Value>]) -> Vec<Value> {
+    let int = |row: &[redis::Value], i: usize| {
+        row.get(i)
+            .and_then(|v| redis::from_redis_value::<u64>(v).ok())
+    };
+    let mut events: Vec<Value> = rows
+        .iter()
+        .filter_map(|row| {
+            let event: String = redis::from_redis_value(row.first()?).ok()?;
+            let timestamp = int(row, 1)?;
+            Some(json!({
+                "event": event,
+                "timestamp": timestamp,
+                "time": crate::time::iso8601_ms(timestamp * 1000),
+                "latest_ms": int(row, 2),
+                "max_ms": int(row, 3),
+            }))
+        })
+        .collect();
+    events.sort_by_key(|e| std::cmp::Reverse(e["max_ms"].as_u64()));
+    events
+}
+
+/// LATENCY HISTORY samples of `event` (`(timestamp, latency_ms)`, oldest
+/// first) with their count, max, average and time span.
+pub fn history_summary(event: &str, samples: &[(u64, u64)]) -> Value {
+    let latencies: Vec<u64> = samples.iter().map(|(_, ms)| *ms).collect();
+    let mut result = json!({
+        "event": event,
+        "count": samples.len(),
+        "samples": samples
+            .iter()
+            .map(|(at, ms)| json!({
+                "timestamp": at,
+                "time": crate::time::iso8601_ms(at * 1000),
+                "latency_ms": ms,
+            }))
+            .collect::<Vec<_>>(),
+    });
+    if let (Some(first), Some(last)) = (samples.first(), samples.last()) {
+        let total: u64 = latencies.iter().sum();
+        result["max_ms"] = json!(latencies.iter().max());
+        result["avg_ms"] = json!((total as f64 * 10.0 / samples.len() as f64).round() / 10.0);
+        result["first"] = json!(first.0);
+        result["last"] = json!(last.0);
+    }
+    result
+}
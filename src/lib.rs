@@ -3,5 +3,50 @@
 //! Provides tools for scanning keys, reading values of any type (string, hash,
 //! list, set, zset), inspecting key metadata, and querying server statistics.
 
+pub mod acl;
+pub mod auth;
+pub mod backlog;
+pub mod bearer;
+pub mod budget;
+pub mod busy;
+pub mod cardinality;
+pub mod census;
+pub mod clients;
+pub mod cluster;
+pub mod compaction;
+pub mod config;
+pub mod consistency;
+pub mod contract;
+pub mod cursors;
 pub mod error;
+pub mod format;
+pub mod glob;
+pub mod glossary;
+pub mod info;
+pub mod keyspace;
+pub mod latency;
+pub mod maintenance;
+pub mod monitor;
+pub mod notify;
+pub mod prompts;
+pub mod rdb;
+pub mod redact;
+pub mod references;
+pub mod replication;
+pub mod resource;
+pub mod resp;
+pub mod restart;
+pub mod scan;
+pub mod sentinel;
 pub mod server;
+pub mod settings;
+pub mod shutdown;
+pub mod stats;
+pub mod stream;
+pub mod time;
+pub mod tls;
+pub mod trace;
+pub mod transcript;
+pub mod verify;
+pub mod version;
+pub mod view;
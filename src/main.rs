@@ -1,6 +1,28 @@
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
 use anyhow::{bail, Result};
 use clap::Parser;
+use mcp_redis::auth::{self, AuthProvider, IamAuth, IamService};
+use mcp_redis::bearer;
+use mcp_redis::budget;
+use mcp_redis::config::{
+    Config, ConnectionConfig, Limits, Preset, ReplicaHook, ServerOptions, View,
+};
+use mcp_redis::notify::Notifier;
+use mcp_redis::rdb;
+use mcp_redis::redact;
+use mcp_redis::restart;
+use mcp_redis::sentinel::{self, SentinelUrl};
 use mcp_redis::server;
+use mcp_redis::tls;
+use mcp_redis::transcript::Transcripts;
+use mcp_redis::version::{self, ServerVersion, Version};
+use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+use rmcp::transport::streamable_http_server::{StreamableHttpServerConfig, StreamableHttpService};
 use rmcp::{transport::stdio, ServiceExt};
 use tracing_subscriber::EnvFilter;
 
@@ -10,6 +32,8 @@ use tracing_subscriber::EnvFilter;
 struct Cli {
     /// Redis connection URL (repeatable for multiple instances).
     /// Example: redis://127.0.0.1:6379
+    /// Sentinel: redis+sentinel://host1:26379,host2:26379/mymaster[/db]
+    /// Unix socket: redis+unix:///run/redis.sock[?db=1&pass=secret]
     #[arg(long = "url")]
     urls: Vec<String>,
 
@@ -18,14 +42,398 @@ struct Cli {
     #[arg(long = "url-env")]
     url_envs: Vec<String>,
 
+    /// Read the password for the Nth URL from this environment variable
+    /// (repeatable, paired by position with --url then --url-env; pass ""
+    /// to skip a URL). Keeps secrets out of process listings.
+    #[arg(long = "password-env")]
+    password_envs: Vec<String>,
+
+    /// Read the ACL username for the Nth URL from this environment variable
+    /// (repeatable, paired by position like --password-env).
+    #[arg(long = "username-env")]
+    username_envs: Vec<String>,
+
+    /// Logical database to select on every --url, instead of the one in
+    /// the URL. Key tools can still pick another with their `db` argument.
+    #[arg(long, value_parser = clap::value_parser!(i64).range(0..))]
+    db: Option<i64>,
+
+    /// Authenticate with short-lived AWS IAM tokens as this IAM-enabled
+    /// ElastiCache/MemoryDB user id. Applies to every --url; AWS credentials
+    /// are read from AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY /
+    /// AWS_SESSION_TOKEN and tokens are refreshed before they expire.
+    #[arg(long, requires = "aws_iam_cluster")]
+    aws_iam_user: Option<String>,
+
+    /// Replication group, serverless cache, or MemoryDB cluster name the
+    /// IAM token is issued for.
+    #[arg(long)]
+    aws_iam_cluster: Option<String>,
+
+    /// AWS region of the cache (default: AWS_REGION).
+    #[arg(long)]
+    aws_region: Option<String>,
+
+    /// Service issuing the IAM token.
+    #[arg(long, value_enum, default_value = "elasticache")]
+    aws_iam_service: IamService,
+
+    /// The IAM cluster is an ElastiCache Serverless cache.
+    #[arg(long)]
+    aws_iam_serverless: bool,
+
+    /// TOML configuration file: named connections, server options, tool
+    /// limits, and presets. Command-line flags take precedence.
+    #[arg(long)]
+    config: Option<std::path::PathBuf>,
+
     /// Allow write operations (SET, DEL, FLUSHDB, etc.).
     /// By default, only read operations are permitted.
     #[arg(long)]
     allow_write: bool,
 
+    /// Allow per-call `trace: true` on any tool, attaching the Redis
+    /// commands issued (values redacted) and their timings to the result.
+    #[arg(long)]
+    allow_trace: bool,
+
+    /// Allow tools that provision infrastructure or change the running
+    /// configuration (create_analysis_replica, reload_config).
+    #[arg(long)]
+    allow_admin: bool,
+
+    /// POST a JSON event to this URL for every write or admin tool call (who,
+    /// what, connection, key digests before and after), e.g. for a
+    /// change-management system. Delivery never blocks the call.
+    #[arg(long)]
+    notify_webhook: Option<String>,
+
+    /// Keep every tool call of a session with its arguments and result
+    /// (size-capped) in memory, for export_session.
+    #[arg(long)]
+    record_session: bool,
+
+    /// Directory where file-producing tools (capture_diagnostics) may write.
+    /// Those tools are disabled when not set.
+    #[arg(long)]
+    export_dir: Option<std::path::PathBuf>,
+
+    /// Local RDB dump (e.g. yesterday's backup) that rdb_scan and rdb_get
+    /// read, for comparing a snapshot with the live keyspace.
+    #[arg(long)]
+    rdb: Option<std::path::PathBuf>,
+
+    /// Skip TLS certificate verification for rediss:// URLs (self-signed
+    /// certificates on dev/staging). Never use against production.
+    #[arg(long)]
+    tls_insecure: bool,
+
+    /// Refuse to connect to servers older than this Redis version
+    /// (e.g. 6.2). Without it, old servers are only warned about.
+    #[arg(long, value_parser = parse_min_version)]
+    require_min_version: Option<Version>,
+
+    /// Connect to each instance on first use instead of at startup, retrying
+    /// with backoff. Unreachable instances no longer prevent startup.
+    #[arg(long)]
+    connect_lazy: bool,
+
     /// Number of keys per SCAN iteration (default: 100)
-    #[arg(long, default_value = "100")]
-    scan_count: u32,
+    #[arg(long)]
+    scan_count: Option<u32>,
+
+    /// Override a limit from `[limits]`, e.g. `--limit max_scan_iterations=5000`
+    /// for a huge keyspace. Repeatable; takes precedence over the config file.
+    #[arg(long = "limit", value_name = "NAME=VALUE", value_parser = parse_limit)]
+    limits: Vec<(String, u64)>,
+
+    /// Bytes of value data `get` returns before truncating (default: 1 MiB).
+    /// Calls can pass their own max_bytes.
+    #[arg(long)]
+    max_value_bytes: Option<u64>,
+
+    /// How MCP clients reach the server: stdio (one client, launched by it)
+    /// or http (streamable HTTP on --listen, shared by many clients).
+    #[arg(long, value_enum, default_value = "stdio")]
+    transport: Transport,
+
+    /// Address the http transport listens on. The MCP endpoint is /mcp.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// Require `Authorization: Bearer <token>` on every http transport
+    /// request. Prefer --auth-token-env to keep it out of process listings.
+    #[arg(long, conflicts_with = "auth_token_env")]
+    auth_token: Option<String>,
+
+    /// Read the http transport bearer token from this environment variable.
+    #[arg(long)]
+    auth_token_env: Option<String>,
+
+    /// Seconds in-flight tool calls get to finish on SIGTERM or Ctrl-C before
+    /// the server exits anyway.
+    #[arg(long, default_value = "10")]
+    drain_timeout: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Transport {
+    Stdio,
+    Http,
+}
+
+/// A connection to open, from the command line or the config file.
+struct Target {
+    /// Explicit name from the config file; derived from the URL otherwise
+    name: Option<String>,
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+    read_only: bool,
+    tags: Vec<String>,
+    /// Overrides the database of the URL
+    db: Option<i64>,
+}
+
+/// Where a target connects to, once its URL and credentials are resolved.
+enum Endpoint {
+    Sentinel(SentinelUrl),
+    Direct {
+        info: redis::ConnectionInfo,
+        /// Short-lived credentials applied at connect time
+        auth: Option<Arc<dyn AuthProvider>>,
+    },
+}
+
+/// Settings of a target that carry over to its connection.
+struct ConnectionSettings {
+    name: String,
+    redacted: String,
+    read_only: bool,
+    tags: Vec<String>,
+}
+
+/// Process-wide options deciding how targets are opened, at startup and on
+/// reload.
+struct OpenSettings {
+    tls_insecure: bool,
+    auth_provider: Option<Arc<dyn AuthProvider>>,
+    require_min_version: Option<Version>,
+    connect_lazy: bool,
+}
+
+/// A target with its URL and credentials resolved, ready to open.
+struct Prepared {
+    endpoint: Endpoint,
+    settings: ConnectionSettings,
+}
+
+enum Opened {
+    Connected(Box<server::RedisConnection>),
+    /// Opened on first use (--connect-lazy)
+    Pending(server::PendingConnection),
+}
+
+/// Background tasks a connection needs for as long as it exists.
+struct Watchers {
+    name: String,
+    /// Follow failovers of this Sentinel-managed master
+    sentinel: Option<SentinelUrl>,
+    /// Reconnect with fresh credentials before short-lived tokens expire
+    refresh: Option<(redis::ConnectionInfo, Arc<dyn AuthProvider>)>,
+}
+
+impl Watchers {
+    fn of(prepared: &Prepared) -> Self {
+        let (sentinel, refresh) = match &prepared.endpoint {
+            Endpoint::Sentinel(url) => (Some(url.clone()), None),
+            Endpoint::Direct { info, auth } => {
+                (None, auth.clone().map(|auth| (info.clone(), auth)))
+            }
+        };
+        Self {
+            name: prepared.settings.name.clone(),
+            sentinel,
+            refresh,
+        }
+    }
+
+    fn spawn(self, service: &server::McpRedisServer) -> Vec<tokio::task::AbortHandle> {
+        let mut handles = Vec::new();
+        if let Some(url) = self.sentinel {
+            let task = sentinel::follow_failovers(service.clone(), self.name.clone(), url);
+            handles.push(tokio::spawn(task).abort_handle());
+        }
+        if let Some((info, provider)) = self.refresh {
+            let task = auth::refresh_credentials(service.clone(), self.name, info, provider);
+            handles.push(tokio::spawn(task).abort_handle());
+        }
+        handles
+    }
+}
+
+/// What a config reload compares the re-read file against.
+struct ReloadState {
+    path: PathBuf,
+    open: Arc<OpenSettings>,
+    /// Names of --url connections, which a reload leaves alone
+    fixed: HashSet<String>,
+    /// Config file connections as last applied
+    connections: HashMap<String, ConnectionConfig>,
+    /// Limits in effect: the config file's with the --limit overrides
+    limits: Limits,
+    /// --limit overrides, applied again on every reload
+    limit_overrides: Vec<(String, u64)>,
+    /// Settings that only take effect at startup
+    server: ServerOptions,
+    analysis_replica: Option<ReplicaHook>,
+    /// Watcher tasks per connection, stopped when it is removed or replaced
+    tasks: HashMap<String, Vec<tokio::task::AbortHandle>>,
+}
+
+impl ReloadState {
+    /// Re-read the config file and apply it: connections are added, removed,
+    /// or reopened when their settings changed, and limits and presets are
+    /// replaced. A connection that cannot be opened keeps its previous
+    /// state and is reported under `failed`.
+    async fn reload(
+        &mut self,
+        service: &server::McpRedisServer,
+    ) -> Result<serde_json::Value, String> {
+        let config = Config::load(&self.path)?;
+        let limits = config.limits.with_overrides(&self.limit_overrides)?;
+        check_presets(service, &config.presets)?;
+        check_views(service, &config.views)?;
+        service.check_tool_defaults(&config.tool_defaults)?;
+        if let Some(clash) = config
+            .connections
+            .iter()
+            .find(|c| self.fixed.contains(&c.name))
+        {
+            return Err(format!(
+                "Connection '{}' clashes with a --url connection",
+                clash.name
+            ));
+        }
+
+        let mut removed = Vec::new();
+        let stale: Vec<String> = self
+            .connections
+            .keys()
+            .filter(|name| !config.connections.iter().any(|c| &c.name == *name))
+            .cloned()
+            .collect();
+        for name in stale {
+            self.stop_tasks(&name);
+            service.remove_connection(&name).await;
+            self.connections.remove(&name);
+            tracing::info!(connection = %name, "Connection removed by config reload");
+            removed.push(name);
+        }
+
+        let mut added = Vec::new();
+        let mut reopened = Vec::new();
+        let mut failed = Vec::new();
+        for (i, connection) in config.connections.iter().enumerate() {
+            let previous = self.connections.get(&connection.name);
+            if previous == Some(connection) {
+                continue;
+            }
+            let existed = previous.is_some();
+            let opened = async {
+                let prepared = prepare(config_target(connection)?, i, false, &self.open)?;
+                let watchers = Watchers::of(&prepared);
+                Ok::<_, anyhow::Error>((open_prepared(prepared, &self.open).await?, watchers))
+            }
+            .await;
+            let (opened, watchers) = match opened {
+                Ok(opened) => opened,
+                Err(e) => {
+                    tracing::warn!(connection = %connection.name, error = %e, "Config reload cannot open connection");
+                    failed.push(serde_json::json!({
+                        "connection": connection.name,
+                        "error": e.to_string(),
+                    }));
+                    continue;
+                }
+            };
+            if existed {
+                self.stop_tasks(&connection.name);
+                service.remove_connection(&connection.name).await;
+            }
+            let registered = match opened {
+                Opened::Connected(c) => service.add_connection(*c),
+                Opened::Pending(c) => service.add_pending(c),
+            };
+            if let Err(e) = registered {
+                failed.push(serde_json::json!({
+                    "connection": connection.name,
+                    "error": e.to_string(),
+                }));
+                continue;
+            }
+            service.set_sentinel(&connection.name, watchers.sentinel.clone());
+            self.tasks
+                .insert(connection.name.clone(), watchers.spawn(service));
+            self.connections
+                .insert(connection.name.clone(), connection.clone());
+            tracing::info!(connection = %connection.name, "Connection applied by config reload");
+            if existed {
+                reopened.push(connection.name.clone());
+            } else {
+                added.push(connection.name.clone());
+            }
+        }
+
+        let limits_changed = limits != self.limits;
+        service.set_limits(limits.clone());
+        self.limits = limits;
+        let presets: Vec<&str> = config.presets.iter().map(|p| p.name.as_str()).collect();
+        let contracts: Vec<&str> = config
+            .contracts
+            .iter()
+            .map(|c| c.pattern.as_str())
+            .collect();
+        let summary = serde_json::json!({
+            "added": added,
+            "removed": removed,
+            "reopened": reopened,
+            "failed": failed,
+            "limits_changed": limits_changed,
+            "presets": presets,
+            "views": config.views.iter().map(|v| v.name.as_str()).collect::<Vec<_>>(),
+            "contracts": contracts,
+            "references": config.references.iter().map(|r| r.pattern.as_str()).collect::<Vec<_>>(),
+            "config_set_allow": config.config_set.allow,
+            "tool_defaults": config.tool_defaults.keys().collect::<Vec<_>>(),
+            "restart_required": self.restart_required(&config),
+        });
+        service.set_presets(config.presets);
+        service.set_views(config.views);
+        service.set_contracts(config.contracts);
+        service.set_references(config.references);
+        service.set_config_set_allow(config.config_set.allow);
+        service.set_tool_defaults(config.tool_defaults);
+        Ok(summary)
+    }
+
+    fn stop_tasks(&mut self, name: &str) {
+        for handle in self.tasks.remove(name).unwrap_or_default() {
+            handle.abort();
+        }
+    }
+
+    /// Sections whose changes are ignored until the next start.
+    fn restart_required(&self, config: &Config) -> Vec<&'static str> {
+        let mut sections = Vec::new();
+        if config.server != self.server {
+            sections.push("server");
+        }
+        if config.analysis_replica != self.analysis_replica {
+            sections.push("analysis_replica");
+        }
+        sections
+    }
 }
 
 #[tokio::main]
@@ -37,72 +445,705 @@ async fn main() -> Result<()> {
         .with_writer(std::io::stderr)
         .init();
 
+    let auth_token = match (&cli.auth_token, &cli.auth_token_env) {
+        (Some(token), _) => Some(token.clone()),
+        (None, Some(env_name)) => Some(read_env(env_name)?),
+        (None, None) => None,
+    };
+    if auth_token.as_deref() == Some("") {
+        bail!("The bearer token must not be empty");
+    }
+    if auth_token.is_some() && cli.transport != Transport::Http {
+        bail!("--auth-token and --auth-token-env require --transport http");
+    }
+
+    let config = match &cli.config {
+        Some(path) => Config::load(path).map_err(|e| anyhow::anyhow!("Invalid config: {}", e))?,
+        None => Config::default(),
+    };
+
+    let limits = config
+        .limits
+        .with_overrides(&cli.limits)
+        .map_err(|e| anyhow::anyhow!("Invalid --limit: {}", e))?;
+    let allow_write = cli.allow_write || config.server.allow_write;
+    let allow_trace = cli.allow_trace || config.server.allow_trace;
+    let allow_admin = cli.allow_admin || config.server.allow_admin;
+    let connect_lazy = cli.connect_lazy || config.server.connect_lazy;
+    let tls_insecure = cli.tls_insecure || config.server.tls_insecure;
+    let record_session = cli.record_session || config.server.record_session;
+    let export_dir = cli.export_dir.clone().or(config.server.export_dir.clone());
+    let scan_count = cli.scan_count.or(config.server.scan_count).unwrap_or(100);
+    let max_value_bytes = cli
+        .max_value_bytes
+        .or(config.server.max_value_bytes)
+        .unwrap_or(budget::DEFAULT_MAX_VALUE_BYTES);
+    let notifier = match cli
+        .notify_webhook
+        .as_deref()
+        .or(config.server.notify_webhook.as_deref())
+    {
+        Some(url) => {
+            let notifier = Notifier::new(url).map_err(|e| anyhow::anyhow!(e))?;
+            tracing::info!(webhook = %notifier.url_redacted(), "Notifying write and admin tool calls");
+            Some(notifier)
+        }
+        None => None,
+    };
+    if let Some(path) = &cli.rdb {
+        // Fail fast on a wrong path rather than on the first rdb_* call
+        let reader = rdb::Reader::open(path)
+            .map_err(|e| anyhow::anyhow!("cannot read RDB file {}: {}", path.display(), e))?;
+        tracing::info!(path = %path.display(), version = reader.version(), "RDB snapshot available");
+    }
+    let require_min_version = match (cli.require_min_version, &config.server.require_min_version) {
+        (Some(v), _) => Some(v),
+        (None, Some(raw)) => Some(parse_min_version(raw).map_err(|e| anyhow::anyhow!(e))?),
+        (None, None) => None,
+    };
+
     // Collect URLs from --url and --url-env
     let mut all_urls = cli.urls.clone();
 
     for env_name in &cli.url_envs {
-        match std::env::var(env_name) {
-            Ok(url) => {
-                tracing::info!(env = env_name, "Read Redis URL from environment variable");
-                all_urls.push(url);
-            }
-            Err(_) => {
-                bail!("Environment variable '{env_name}' is not set");
-            }
-        }
+        all_urls.push(read_env(env_name)?);
+        tracing::info!(env = env_name, "Read Redis URL from environment variable");
     }
 
-    if all_urls.is_empty() {
+    if tls_insecure {
+        tracing::warn!("--tls-insecure: TLS certificates will not be verified");
+    }
+
+    if all_urls.is_empty() && config.connections.is_empty() {
         // Default to localhost
         all_urls.push("redis://127.0.0.1:6379".to_string());
         tracing::info!("No URL provided, defaulting to redis://127.0.0.1:6379");
     }
 
+    let auth_provider: Option<Arc<dyn AuthProvider>> = match &cli.aws_iam_user {
+        Some(user_id) => {
+            let region = cli
+                .aws_region
+                .clone()
+                .or_else(|| std::env::var("AWS_REGION").ok());
+            let Some(region) = region else {
+                bail!("--aws-iam-user requires --aws-region or AWS_REGION");
+            };
+            Some(Arc::new(IamAuth {
+                user_id: user_id.clone(),
+                cluster_name: cli.aws_iam_cluster.clone().unwrap_or_default(),
+                region,
+                service: cli.aws_iam_service,
+                serverless: cli.aws_iam_serverless,
+            }))
+        }
+        None => None,
+    };
+
+    let usernames = read_credential_envs(&cli.username_envs, all_urls.len(), "--username-env")?;
+    let passwords = read_credential_envs(&cli.password_envs, all_urls.len(), "--password-env")?;
+
+    // Command-line URLs first, then the connections declared in the config file
+    let mut targets: Vec<Target> = all_urls
+        .iter()
+        .enumerate()
+        .map(|(i, url)| Target {
+            name: None,
+            url: url.clone(),
+            username: usernames.get(i).cloned().flatten(),
+            password: passwords.get(i).cloned().flatten(),
+            read_only: false,
+            tags: Vec::new(),
+            db: cli.db,
+        })
+        .collect();
+    for connection in &config.connections {
+        targets.push(config_target(connection)?);
+    }
+    let single = targets.len() == 1;
+    let open = Arc::new(OpenSettings {
+        tls_insecure,
+        auth_provider,
+        require_min_version,
+        connect_lazy,
+    });
+
     // Connect to all Redis instances
-    let mut connections = Vec::new();
-    for (i, url_str) in all_urls.iter().enumerate() {
-        let client = redis::Client::open(url_str.as_str())
-            .map_err(|e| anyhow::anyhow!("Invalid Redis URL '{}': {}", url_str, e))?;
+    let mut connections: Vec<server::RedisConnection> = Vec::new();
+    let mut pending: Vec<server::PendingConnection> = Vec::new();
+    let mut names = HashSet::new();
+    let mut watchers = Vec::new();
+    for (i, target) in targets.into_iter().enumerate() {
+        // Everything that needs no network is checked here, so configuration
+        // errors fail at startup even with --connect-lazy
+        let prepared = prepare(target, i, single, &open)?;
+        if !names.insert(prepared.settings.name.clone()) {
+            bail!("Duplicate connection name '{}'", prepared.settings.name);
+        }
+        watchers.push(Watchers::of(&prepared));
+        match open_prepared(prepared, &open).await? {
+            Opened::Connected(connection) => connections.push(*connection),
+            Opened::Pending(connection) => pending.push(connection),
+        }
+    }
+    let sentinels: HashMap<String, SentinelUrl> = watchers
+        .iter()
+        .filter_map(|w| Some((w.name.clone(), w.sentinel.clone()?)))
+        .collect();
 
-        let conn = redis::aio::ConnectionManager::new(client)
-            .await
-            .map_err(|e| anyhow::anyhow!("Cannot connect to '{}': {}", url_str, e))?;
+    tracing::info!(
+        connections = connections.len(),
+        pending = pending.len(),
+        allow_write,
+        allow_trace,
+        allow_admin,
+        scan_count,
+        "Starting mcp-redis server"
+    );
 
-        let name = if all_urls.len() == 1 {
-            "redis".to_string()
-        } else {
-            // Extract host:port for meaningful names (like mcp-sql's extract_db_name)
-            extract_connection_name(url_str, i)
-        };
+    let service = server::McpRedisServer::new(connections, allow_write, scan_count)
+        .with_trace(allow_trace)
+        .with_admin(allow_admin)
+        .with_notifier(notifier)
+        .with_transcripts(record_session.then(Transcripts::new))
+        .with_max_value_bytes(max_value_bytes)
+        .with_replica_hook(config.analysis_replica.clone())
+        .with_export_dir(export_dir)
+        .with_rdb(cli.rdb.clone())
+        .with_limits(limits.clone())
+        .with_sentinels(sentinels)
+        .with_pending(pending)
+        .with_contracts(config.contracts.clone())
+        .with_references(config.references.clone())
+        .with_config_set_allow(config.config_set.allow.clone());
+    check_presets(&service, &config.presets).map_err(|e| anyhow::anyhow!(e))?;
+    check_views(&service, &config.views).map_err(|e| anyhow::anyhow!(e))?;
+    service
+        .check_tool_defaults(&config.tool_defaults)
+        .map_err(|e| anyhow::anyhow!(e))?;
+    let mut service = service
+        .with_presets(config.presets.clone())
+        .with_views(config.views.clone())
+        .with_tool_defaults(config.tool_defaults.clone());
+
+    // Flag tool results after a server restart resets its counters
+    tokio::spawn(restart::watch(service.clone()));
+
+    // Follow Sentinel failovers and refresh short-lived credentials for as
+    // long as each connection exists
+    let mut tasks = HashMap::new();
+    for watcher in watchers {
+        tasks.insert(watcher.name.clone(), watcher.spawn(&service));
+    }
+
+    if let Some(path) = &cli.config {
+        let state = Arc::new(tokio::sync::Mutex::new(ReloadState {
+            path: path.clone(),
+            open,
+            fixed: names
+                .iter()
+                .filter(|n| !config.connections.iter().any(|c| &c.name == *n))
+                .cloned()
+                .collect(),
+            connections: config
+                .connections
+                .iter()
+                .map(|c| (c.name.clone(), c.clone()))
+                .collect(),
+            limits,
+            limit_overrides: cli.limits.clone(),
+            server: config.server,
+            analysis_replica: config.analysis_replica,
+            tasks,
+        }));
+        let reloader: server::Reloader = Arc::new(move |service| {
+            let state = state.clone();
+            Box::pin(async move { state.lock().await.reload(&service).await })
+        });
+        service = service.with_reloader(reloader);
+        #[cfg(unix)]
+        tokio::spawn(reload_on_sighup(service.clone()));
+    }
+
+    let drain_timeout = Duration::from_secs(cli.drain_timeout);
+    match cli.transport {
+        Transport::Stdio => serve_stdio(service.clone(), drain_timeout).await?,
+        Transport::Http => {
+            serve_http(service.clone(), cli.listen, auth_token, drain_timeout).await?
+        }
+    }
+
+    // Stop background loops and close Redis connections cleanly
+    service.shutdown().drain(Duration::ZERO).await;
+    service.close_connections().await;
+    tracing::info!("mcp-redis stopped");
+    if cli.transport == Transport::Stdio {
+        // A pending stdin read would keep the runtime alive until the client
+        // closes the pipe
+        std::process::exit(0);
+    }
+
+    Ok(())
+}
+
+/// Serve MCP over stdin/stdout until the client disconnects, Ctrl-C, or
+/// SIGTERM.
+async fn serve_stdio(service: server::McpRedisServer, drain_timeout: Duration) -> Result<()> {
+    let signal = shutdown_signal();
+    tokio::pin!(signal);
+    let running = tokio::select! {
+        running = service.clone().serve(stdio()) => running?,
+        // No client yet, so nothing to drain
+        _ = &mut signal => return Ok(()),
+    };
+    let stop = running.cancellation_token();
+    let waiting = running.waiting();
+    tokio::pin!(waiting);
+    tokio::select! {
+        quit = &mut waiting => {
+            quit?;
+        }
+        _ = &mut signal => {
+            drain(&service, drain_timeout).await;
+            stop.cancel();
+            waiting.await?;
+        }
+    }
+    Ok(())
+}
+
+/// Resolves on Ctrl-C, or SIGTERM on Unix.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+            return;
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Refuse new tool calls and give the ones in flight until `timeout` to
+/// finish. Long-running tools and background loops stop right away.
+async fn drain(service: &server::McpRedisServer, timeout: Duration) {
+    let shutdown = service.shutdown();
+    if !shutdown.is_stopping() {
+        tracing::info!(
+            in_flight = shutdown.in_flight(),
+            "Shutting down, finishing in-flight tool calls"
+        );
+    }
+    let abandoned = shutdown.drain(timeout).await;
+    if abandoned > 0 {
+        tracing::warn!(abandoned, "Drain deadline reached, abandoning tool calls");
+    }
+}
+
+/// Serve MCP over streamable HTTP at `/mcp` until Ctrl-C or SIGTERM. Every client
+/// session shares the same connections, limits, and telemetry. With a
+/// token, requests without it are refused with 401.
+async fn serve_http(
+    service: server::McpRedisServer,
+    listen: SocketAddr,
+    auth_token: Option<String>,
+    drain_timeout: Duration,
+) -> Result<()> {
+    let draining = service.clone();
+    let config = StreamableHttpServerConfig::default();
+    // Cancelling ends open sessions, so shutdown does not wait on their streams
+    let shutdown = config.cancellation_token.clone();
+    let mcp = StreamableHttpService::new(
+        move || Ok(service.clone()),
+        LocalSessionManager::default().into(),
+        config,
+    );
+    let mut router = axum::Router::new().nest_service("/mcp", mcp);
+    match auth_token {
+        Some(token) => {
+            router = router.layer(axum::middleware::from_fn_with_state(
+                Arc::new(token),
+                require_bearer,
+            ));
+        }
+        None if !listen.ip().is_loopback() => {
+            tracing::warn!(listen = %listen, "Listening beyond loopback without --auth-token: anyone who can reach this address can use every configured Redis connection");
+        }
+        None => {}
+    }
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .map_err(|e| anyhow::anyhow!("Cannot listen on {}: {}", listen, e))?;
+    tracing::info!(listen = %listen, "Serving MCP over streamable HTTP at /mcp");
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            drain(&draining, drain_timeout).await;
+            shutdown.cancel();
+        })
+        .await?;
+    Ok(())
+}
+
+/// Resolve `--username-env` / `--password-env` values, paired by position
+/// with the URLs. An empty variable name leaves that URL's credentials as is.
+fn read_credential_envs(
+    env_names: &[String],
+    url_count: usize,
+    flag: &str,
+) -> Result<Vec<Option<String>>> {
+    if env_names.len() > url_count {
+        bail!(
+            "{flag} given {} times but only {url_count} URL(s) configured",
+            env_names.len()
+        );
+    }
+    env_names
+        .iter()
+        .map(|env_name| {
+            if env_name.is_empty() {
+                return Ok(None);
+            }
+            read_env(env_name).map(Some)
+        })
+        .collect()
+}
+
+fn read_env(env_name: &str) -> Result<String> {
+    match std::env::var(env_name) {
+        Ok(value) => Ok(value),
+        Err(_) => bail!("Environment variable '{env_name}' is not set"),
+    }
+}
+
+/// The target for a `[[connections]]` entry, with its environment variables
+/// read.
+fn config_target(connection: &ConnectionConfig) -> Result<Target> {
+    let url = match (&connection.url, &connection.url_env) {
+        (Some(url), _) => url.clone(),
+        (None, Some(env_name)) => read_env(env_name)?,
+        (None, None) => unreachable!("validated by Config::parse"),
+    };
+    Ok(Target {
+        name: Some(connection.name.clone()),
+        url,
+        username: connection
+            .username_env
+            .as_deref()
+            .map(read_env)
+            .transpose()?,
+        password: connection
+            .password_env
+            .as_deref()
+            .map(read_env)
+            .transpose()?,
+        read_only: connection.read_only,
+        tags: connection.tags.clone(),
+        db: connection.db,
+    })
+}
+
+/// Presets must not shadow built-in tools and may only call built-in tools.
+fn check_presets(service: &server::McpRedisServer, presets: &[Preset]) -> Result<(), String> {
+    for preset in presets {
+        if service.is_builtin_tool(&preset.name) {
+            return Err(format!(
+                "Preset '{}' clashes with a built-in tool",
+                preset.name
+            ));
+        }
+        if let Some(step) = preset
+            .steps
+            .iter()
+            .find(|s| !service.is_builtin_tool(&s.tool))
+        {
+            return Err(format!(
+                "Preset '{}' calls unknown tool '{}'",
+                preset.name, step.tool
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Views must not shadow built-in tools; clashes with presets are caught
+/// when the config is parsed.
+fn check_views(service: &server::McpRedisServer, views: &[View]) -> Result<(), String> {
+    match views.iter().find(|v| service.is_builtin_tool(&v.name)) {
+        Some(view) => Err(format!("View '{}' clashes with a built-in tool", view.name)),
+        None => Ok(()),
+    }
+}
+
+/// Resolve a target's URL and credentials. Needs no network, so
+/// configuration errors surface before anything is opened.
+fn prepare(target: Target, index: usize, single: bool, open: &OpenSettings) -> Result<Prepared> {
+    let Target {
+        name,
+        url: url_str,
+        username,
+        password,
+        read_only,
+        tags,
+        db,
+    } = target;
 
+    let (endpoint, redacted, name) = if sentinel::is_sentinel_url(&url_str) {
+        let mut url = SentinelUrl::parse(&url_str)
+            .map_err(|e| anyhow::anyhow!("Invalid Sentinel URL '{}': {}", url_str, e))?;
+        url.insecure = open.tls_insecure;
+        if username.is_some() {
+            url.username = username;
+        }
+        if password.is_some() {
+            url.password = password;
+        }
+        if let Some(db) = db {
+            url.db = db;
+        }
+        let redacted = url.redacted();
+        let name = name.unwrap_or_else(|| {
+            if single {
+                "redis".to_string()
+            } else {
+                url.master_name.clone()
+            }
+        });
+        (Endpoint::Sentinel(url), redacted, name)
+    } else {
+        let client = redis::Client::open(url_str.as_str())
+            .map_err(|e| anyhow::anyhow!("Invalid Redis URL '{}': {}", url_str, e))?;
+        let mut info = client.get_connection_info().clone();
+        if username.is_some() {
+            info.redis.username = username;
+        }
+        if password.is_some() {
+            info.redis.password = password;
+        }
+        if let Some(db) = db {
+            info.redis.db = db;
+        }
+        if open.tls_insecure {
+            info = tls::insecure(info);
+        }
+        let name = name.unwrap_or_else(|| {
+            if single {
+                "redis".to_string()
+            } else {
+                // Extract host:port for meaningful names (like mcp-sql's extract_db_name)
+                extract_connection_name(&url_str, index)
+            }
+        });
         // Redact password from URL for display
-        let redacted = redact_url(url_str);
+        let redacted = redact::url(&url_str);
+        let endpoint = Endpoint::Direct {
+            info,
+            auth: open.auth_provider.clone(),
+        };
+        (endpoint, redacted, name)
+    };
 
-        connections.push(server::RedisConnection {
+    Ok(Prepared {
+        endpoint,
+        settings: ConnectionSettings {
             name,
-            url_redacted: redacted,
-            conn,
-        });
+            redacted,
+            read_only,
+            tags,
+        },
+    })
+}
+
+/// Connect now, or wrap the target for a first-use connection with
+/// --connect-lazy.
+async fn open_prepared(prepared: Prepared, open: &OpenSettings) -> Result<Opened> {
+    let Prepared { endpoint, settings } = prepared;
+    let require_min = open.require_min_version;
+    if !open.connect_lazy {
+        return Ok(Opened::Connected(Box::new(
+            connect(&endpoint, &settings, require_min).await?,
+        )));
+    }
+    let endpoint = Arc::new(endpoint);
+    let url_redacted = settings.redacted.clone();
+    let name = settings.name.clone();
+    let settings = Arc::new(settings);
+    let connector: server::Connector = Arc::new(move || {
+        let endpoint = endpoint.clone();
+        let settings = settings.clone();
+        Box::pin(async move {
+            connect(&endpoint, &settings, require_min)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    });
+    Ok(Opened::Pending(server::PendingConnection::new(
+        name,
+        url_redacted,
+        connector,
+    )))
+}
+
+/// Refuse requests that do not carry the bearer token.
+async fn require_bearer(
+    axum::extract::State(token): axum::extract::State<Arc<String>>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let header = request
+        .headers()
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok());
+    if bearer::authorized(header, &token) {
+        return next.run(request).await;
+    }
+    let mut response = axum::response::Response::new(axum::body::Body::from("Unauthorized"));
+    *response.status_mut() = axum::http::StatusCode::UNAUTHORIZED;
+    response.headers_mut().insert(
+        axum::http::header::WWW_AUTHENTICATE,
+        axum::http::HeaderValue::from_static("Bearer"),
+    );
+    response
+}
+
+/// Reload the config file on every SIGHUP.
+#[cfg(unix)]
+async fn reload_on_sighup(service: server::McpRedisServer) {
+    use tokio::signal::unix::{signal, SignalKind};
 
-        tracing::info!(url = %redact_url(url_str), "Connected to Redis");
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            tracing::warn!(error = %e, "Cannot listen for SIGHUP; config reload stays available as a tool");
+            return;
+        }
+    };
+    loop {
+        tokio::select! {
+            hangup = hangups.recv() => {
+                if hangup.is_none() {
+                    return;
+                }
+            }
+            _ = service.shutdown().stopping() => return,
+        }
+        match service.reload().await {
+            Ok(summary) => tracing::info!(summary = %summary, "Config reloaded on SIGHUP"),
+            Err(e) => tracing::warn!(error = %e, "Config reload on SIGHUP failed"),
+        }
     }
+}
 
+/// Open `endpoint`, identify the server, and build the connection entry.
+async fn connect(
+    endpoint: &Endpoint,
+    target: &ConnectionSettings,
+    require_min: Option<Version>,
+) -> Result<server::RedisConnection> {
+    let redacted = &target.redacted;
+    let client = match endpoint {
+        Endpoint::Sentinel(url) => url.resolve_master().await.map_err(|e| {
+            anyhow::anyhow!("Cannot resolve master via Sentinel '{}': {}", redacted, e)
+        })?,
+        Endpoint::Direct { info, auth } => {
+            let mut info = info.clone();
+            if let Some(provider) = auth {
+                let credentials = provider
+                    .credentials()
+                    .map_err(|e| anyhow::anyhow!("Cannot create IAM token: {}", e))?;
+                info = auth::with_credentials(info, credentials);
+            }
+            redis::Client::open(info)?
+        }
+    };
+    let mut conn = redis::aio::ConnectionManager::new(client.clone())
+        .await
+        .map_err(|e| anyhow::anyhow!("Cannot connect to '{}': {}", redacted, e))?;
+    let version = check_version(&mut conn, redacted, require_min).await?;
     tracing::info!(
-        connections = connections.len(),
-        allow_write = cli.allow_write,
-        scan_count = cli.scan_count,
-        "Starting mcp-redis server"
+        url = %redacted,
+        addr = %client.get_connection_info().addr,
+        "Connected to Redis"
     );
 
-    let service = server::McpRedisServer::new(connections, cli.allow_write, cli.scan_count);
-    let running = service.serve(stdio()).await?;
-    running.waiting().await?;
+    let mut connection =
+        server::RedisConnection::new(target.name.clone(), redacted.clone(), conn, client);
+    connection.version = version;
+    connection.read_only = target.read_only;
+    connection.tags = target.tags.clone();
+    connection.identity = restart::detect(&mut connection.conn.clone()).await;
+    Ok(connection)
+}
+
+/// Identify the server behind `conn`, log compatibility warnings, and enforce
+/// `--require-min-version`.
+async fn check_version(
+    conn: &mut redis::aio::ConnectionManager,
+    redacted: &str,
+    require_min: Option<Version>,
+) -> Result<Option<ServerVersion>> {
+    let detected = match version::detect(conn).await {
+        Ok(detected) => detected,
+        Err(e) => {
+            if require_min.is_some() {
+                bail!("Cannot determine the version of '{redacted}': {e}");
+            }
+            tracing::warn!(url = %redacted, error = %e, "Cannot determine Redis version");
+            return Ok(None);
+        }
+    };
+    if let Some(min) = require_min {
+        if !detected.satisfies(min) {
+            bail!(
+                "'{}' runs {} {}, but --require-min-version is {}",
+                redacted,
+                detected.flavor,
+                detected.version,
+                min
+            );
+        }
+    }
+    for warning in detected.warnings() {
+        tracing::warn!(url = %redacted, "{}", warning);
+    }
+    Ok(Some(detected))
+}
+
+fn parse_limit(raw: &str) -> Result<(String, u64), String> {
+    let (name, value) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("invalid limit '{raw}', expected NAME=VALUE"))?;
+    let value = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value for limit '{name}': expected a whole number"))?;
+    Ok((name.trim().to_string(), value))
+}
 
-    Ok(())
+fn parse_min_version(raw: &str) -> Result<Version, String> {
+    Version::parse(raw).ok_or_else(|| format!("invalid version '{raw}', expected e.g. 6.2"))
 }
 
 fn extract_connection_name(url_str: &str, index: usize) -> String {
     if let Ok(parsed) = url::Url::parse(url_str) {
+        if is_unix_url(&parsed) {
+            // redis+unix:///run/redis.sock?db=2 -> unix:/run/redis.sock/2
+            let db = parsed
+                .query_pairs()
+                .find(|(k, _)| k == "db")
+                .map(|(_, v)| v.into_owned())
+                .unwrap_or_default();
+            return if db.is_empty() || db == "0" {
+                format!("unix:{}", parsed.path())
+            } else {
+                format!("unix:{}/{}", parsed.path(), db)
+            };
+        }
         let host = parsed.host_str().unwrap_or("unknown");
         let port = parsed.port().unwrap_or(6379);
         let db = parsed.path().trim_start_matches('/');
@@ -116,14 +1157,6 @@ fn extract_connection_name(url_str: &str, index: usize) -> String {
     }
 }
 
-fn redact_url(url_str: &str) -> String {
-    match url::Url::parse(url_str) {
-        Ok(mut parsed) => {
-            if parsed.password().is_some() {
-                let _ = parsed.set_password(Some("***"));
-            }
-            parsed.to_string()
-        }
-        Err(_) => url_str.to_string(),
-    }
+fn is_unix_url(url: &url::Url) -> bool {
+    matches!(url.scheme(), "unix" | "redis+unix")
 }
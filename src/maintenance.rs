@@ -0,0 +1,69 @@
+//! Maintenance notes: an admin marks a connection ("failover in progress,
+//! expect stale reads") and every tool result for it carries the note until
+//! it expires. Notes live in server memory only, so every client of a shared
+//! HTTP server sees them.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::restart::format_ago;
+
+/// A note on one connection. Times are seconds since the epoch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    pub message: String,
+    pub set_at: u64,
+    pub expires_at: u64,
+}
+
+impl Note {
+    pub fn to_json(&self, connection: &str, now: u64) -> serde_json::Value {
+        serde_json::json!({
+            "connection": connection,
+            "message": self.message,
+            "set": format!("{} ago", format_ago(now.saturating_sub(self.set_at))),
+            "expires_in_secs": self.expires_at.saturating_sub(now),
+        })
+    }
+}
+
+/// Notes by connection name, shared by all clones of the server.
+#[derive(Debug, Clone, Default)]
+pub struct Notes {
+    inner: Arc<Mutex<HashMap<String, Note>>>,
+}
+
+impl Notes {
+    /// Set the note on `connection` for `ttl_secs`, returning the one it
+    /// replaces if that was still active.
+    pub fn set(&self, connection: &str, message: String, ttl_secs: u64, now: u64) -> Option<Note> {
+        let note = Note {
+            message,
+            set_at: now,
+            expires_at: now.saturating_add(ttl_secs),
+        };
+        let mut notes = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        notes
+            .insert(connection.to_string(), note)
+            .filter(|old| old.expires_at > now)
+    }
+
+    /// Remove the note on `connection`, returning it if it was still active.
+    pub fn clear(&self, connection: &str, now: u64) -> Option<Note> {
+        let mut notes = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        notes.remove(connection).filter(|old| old.expires_at > now)
+    }
+
+    /// The active note on `connection`. An expired one is dropped.
+    pub fn get(&self, connection: &str, now: u64) -> Option<Note> {
+        let mut notes = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        match notes.get(connection) {
+            Some(note) if note.expires_at > now => Some(note.clone()),
+            Some(_) => {
+                notes.remove(connection);
+                None
+            }
+            None => None,
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! MONITOR output behind `monitor_sample`: each line parsed into its
+//! timestamp, client and command, and a window of lines folded into a
+//! histogram of commands with the keys and clients seen most. The raw
+//! stream is far too large to return as is; the aggregate is what answers
+//! "what is hitting this server".
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+/// Entries listed under each `top_*` of the aggregate by default
+pub const DEFAULT_TOP: usize = 10;
+
+/// One command as MONITOR reports it:
+/// `1339518083.107412 [0 127.0.0.1:60866] "set" "key" "value"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorLine {
+    /// Seconds since the epoch, server clock
+    pub time: f64,
+    pub db: i64,
+    /// `ip:port`, `unix:<path>` or `lua` for calls from a script
+    pub client: String,
+    /// Command name, upper case
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl MonitorLine {
+    /// The keys among `args`, by the usual key positions of the command.
+    /// Commands without keys (PING, INFO, CLIENT, ...) have none.
+    pub fn keys(&self) -> Vec<&str> {
+        let args: Vec<&str> = self.args.iter().map(String::as_str).collect();
+        match self.command.as_str() {
+            "PING" | "ECHO" | "INFO" | "CLIENT" | "CONFIG" | "SELECT" | "AUTH" | "HELLO"
+            | "MULTI" | "EXEC" | "DISCARD" | "UNWATCH" | "SCAN" | "DBSIZE" | "COMMAND"
+            | "SLOWLOG" | "LATENCY" | "MEMORY" | "CLUSTER" | "SCRIPT" | "FUNCTION" | "ACL"
+            | "PUBLISH" | "SPUBLISH" | "SUBSCRIBE" | "PSUBSCRIBE" | "UNSUBSCRIBE"
+            | "PUNSUBSCRIBE" | "FLUSHDB" | "FLUSHALL" | "TIME" | "READONLY" | "READWRITE"
+            | "WAIT" | "QUIT" | "RESET" | "LASTSAVE" | "SAVE" | "BGSAVE" | "BGREWRITEAOF"
+            | "XINFO" => Vec::new(),
+            "DEL" | "UNLINK" | "EXISTS" | "TOUCH" | "MGET" | "WATCH" | "SINTER" | "SUNION"
+            | "SDIFF" | "PFCOUNT" | "PFMERGE" | "SINTERSTORE" | "SUNIONSTORE" | "SDIFFSTORE" => {
+                args
+            }
+            "MSET" | "MSETNX" => args.into_iter().step_by(2).collect(),
+            "EVAL" | "EVALSHA" | "EVAL_RO" | "EVALSHA_RO" | "FCALL" | "FCALL_RO" => {
+                let numkeys = args
+                    .get(1)
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .unwrap_or(0);
+                args.into_iter().skip(2).take(numkeys).collect()
+            }
+            "BLPOP" | "BRPOP" | "BZPOPMIN" | "BZPOPMAX" => {
+                // The last argument is the timeout
+                let keys = args.len().saturating_sub(1);
+                args.into_iter().take(keys).collect()
+            }
+            _ => args.into_iter().take(1).collect(),
+        }
+    }
+}
+
+/// Parse one MONITOR line, or `None` for anything else (the `OK` reply to
+/// MONITOR itself).
+pub fn parse_line(line: &str) -> Option<MonitorLine> {
+    let (time, rest) = line.split_once(' ')?;
+    let time: f64 = time.parse().ok()?;
+    let rest = rest.strip_prefix('[')?;
+    let (source, rest) = rest.split_once("] ")?;
+    let (db, client) = source.split_once(' ')?;
+    let mut words = unquote_all(rest)?.into_iter();
+    let command = words.next()?.to_ascii_uppercase();
+    Some(MonitorLine {
+        time,
+        db: db.parse().ok()?,
+        client: client.to_string(),
+        command,
+        args: words.collect(),
+    })
+}
+
+/// The double-quoted, backslash-escaped words of a MONITOR line.
+fn unquote_all(text: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.peek() == Some(&' ') {
+            chars.next();
+        }
+        match chars.next() {
+            None => return Some(words),
+            Some('"') => {}
+            Some(_) => return None,
+        }
+        let mut word = Vec::new();
+        loop {
+            match chars.next()? {
+                '"' => break,
+                '\\' => match chars.next()? {
+                    'n' => word.push(b'\n'),
+                    'r' => word.push(b'\r'),
+                    't' => word.push(b'\t'),
+                    'a' => word.push(0x07),
+                    'b' => word.push(0x08),
+                    'x' => {
+                        let hex: String = [chars.next()?, chars.next()?].iter().collect();
+                        word.push(u8::from_str_radix(&hex, 16).ok()?);
+                    }
+                    other => {
+                        let mut buf = [0; 4];
+                        word.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+                    }
+                },
+                other => {
+                    let mut buf = [0; 4];
+                    word.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+                }
+            }
+        }
+        words.push(String::from_utf8_lossy(&word).into_owned());
+    }
+}
+
+/// Commands, keys and clients counted over a window of MONITOR lines.
+#[derive(Debug, Clone, Default)]
+pub struct Aggregate {
+    pub total: u64,
+    commands: HashMap<String, u64>,
+    keys: HashMap<String, u64>,
+    clients: HashMap<String, u64>,
+    first: Option<f64>,
+    last: Option<f64>,
+}
+
+impl Aggregate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, line: &MonitorLine) {
+        self.total += 1;
+        *self.commands.entry(line.command.clone()).or_default() += 1;
+        for key in line.keys() {
+            *self.keys.entry(key.to_string()).or_default() += 1;
+        }
+        *self.clients.entry(line.client.clone()).or_default() += 1;
+        self.first.get_or_insert(line.time);
+        self.last = Some(line.time);
+    }
+
+    /// `{commands, top_keys, top_clients, ...}`: every command with its
+    /// count and share, and the `top` keys and clients seen most.
+    pub fn to_json(&self, top: usize) -> Value {
+        let total = self.total;
+        let percent = |count: u64| {
+            if total == 0 {
+                0.0
+            } else {
+                (count as f64 * 1000.0 / total as f64).round() / 10.0
+            }
+        };
+        let ranked = |counts: &HashMap<String, u64>, limit: usize, field: &str| {
+            let mut entries: Vec<(&String, &u64)> = counts.iter().collect();
+            entries.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            entries
+                .into_iter()
+                .take(limit)
+                .map(|(name, count)| {
+                    json!({field: name, "count": count, "percent": percent(*count)})
+                })
+                .collect::<Vec<_>>()
+        };
+        let span = match (self.first, self.last) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0.0,
+        };
+        json!({
+            "total_commands": total,
+            "commands_per_sec": if span > 0.0 {
+                Some((total as f64 * 10.0 / span).round() / 10.0)
+            } else {
+                None
+            },
+            "commands": ranked(&self.commands, usize::MAX, "command"),
+            "distinct_keys": self.keys.len(),
+            "top_keys": ranked(&self.keys, top, "key"),
+            "distinct_clients": self.clients.len(),
+            "top_clients": ranked(&self.clients, top, "addr"),
+        })
+    }
+}
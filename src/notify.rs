@@ -0,0 +1,104 @@
+//! Change notifications for `--notify-webhook`: every executed write or
+//! admin tool call is posted as a JSON event, so a change-management system
+//! records what agents modified without scraping logs.
+
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+/// How long a single delivery may take before it is abandoned
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Posts change events to a webhook. Delivery runs in the background and
+/// never delays or fails the tool call; failures are logged.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    url: reqwest::Url,
+    client: reqwest::Client,
+}
+
+impl Notifier {
+    /// A notifier for an `http://` or `https://` URL.
+    pub fn new(url: &str) -> Result<Self, String> {
+        let url = reqwest::Url::parse(url).map_err(|e| format!("invalid webhook URL: {}", e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!(
+                "webhook URL must be http:// or https://, got {}://",
+                url.scheme()
+            ));
+        }
+        let client = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .user_agent(concat!("mcp-redis/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| format!("cannot create webhook client: {}", e))?;
+        Ok(Self { url, client })
+    }
+
+    /// The webhook URL with any password masked, for logs.
+    pub fn url_redacted(&self) -> String {
+        crate::redact::url(self.url.as_str())
+    }
+
+    /// POST `event` and wait for a 2xx reply.
+    pub async fn deliver(&self, event: &serde_json::Value) -> Result<(), String> {
+        let response = self
+            .client
+            .post(self.url.clone())
+            .json(event)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("webhook answered {}", status));
+        }
+        Ok(())
+    }
+
+    /// Deliver `event` in the background.
+    pub fn send(&self, event: serde_json::Value) {
+        let notifier = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = notifier.deliver(&event).await {
+                tracing::warn!(
+                    webhook = %notifier.url_redacted(),
+                    tool = event["tool"].as_str().unwrap_or_default(),
+                    error = %e,
+                    "Change notification not delivered"
+                );
+            }
+        });
+    }
+}
+
+/// Arguments whose values are key names, shown in events as given
+const KEY_ARGUMENTS: &[&str] = &["connection", "key", "keys", "pattern"];
+
+/// Tool arguments as posted in an event: key names as given, booleans as
+/// they are, and every other value (the data a write stores, among others)
+/// replaced by its size, as traces do.
+pub fn redact_arguments(
+    arguments: &serde_json::Map<String, serde_json::Value>,
+) -> serde_json::Value {
+    arguments
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                _ if KEY_ARGUMENTS.contains(&name.as_str()) => value.clone(),
+                serde_json::Value::Bool(_) | serde_json::Value::Null => value.clone(),
+                serde_json::Value::String(text) => format!("<{} bytes>", text.len()).into(),
+                other => format!("<{} bytes>", other.to_string().len()).into(),
+            };
+            (name.clone(), value)
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// Digest of a key's serialized value (DUMP), e.g. "sha256:9f86d0...".
+pub fn digest(dump: &[u8]) -> String {
+    let hash = Sha256::digest(dump);
+    let hex: String = hash.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256:{}", hex)
+}
@@ -0,0 +1,229 @@
+//! Guided diagnostics exposed through the MCP prompts capability.
+//!
+//! Each prompt turns a few arguments into instructions that chain the
+//! built-in tools in a sensible order, so a client can start an
+//! investigation without knowing which tools to combine.
+
+use rmcp::model::{JsonObject, Prompt, PromptArgument};
+
+/// An argument of a [`GuidedPrompt`].
+#[derive(Debug)]
+pub struct PromptArg {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub required: bool,
+}
+
+/// A parameterized workflow over the built-in tools.
+#[derive(Debug)]
+pub struct GuidedPrompt {
+    pub name: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub arguments: &'static [PromptArg],
+    /// Built-in tools the instructions call, in order
+    pub tools: &'static [&'static str],
+    render: fn(&Args) -> String,
+}
+
+const CONNECTION: PromptArg = PromptArg {
+    name: "connection",
+    description: "Connection to investigate (optional if only one Redis instance is connected)",
+    required: false,
+};
+
+const PREFIX: PromptArg = PromptArg {
+    name: "prefix",
+    description: "Key prefix to focus on, e.g. 'session:' (default: discover namespaces)",
+    required: false,
+};
+
+pub const PROMPTS: &[GuidedPrompt] = &[
+    GuidedPrompt {
+        name: "analyze_memory_usage",
+        title: "Analyze memory usage",
+        description: "Find out where memory goes: overall usage and fragmentation, the largest namespaces, and what to change",
+        arguments: &[CONNECTION, PREFIX],
+        tools: &["info", "dbsize", "search_keys", "namespace_census", "hash_overview"],
+        render: analyze_memory_usage,
+    },
+    GuidedPrompt {
+        name: "investigate_slow_queries",
+        title: "Investigate slow queries",
+        description: "Explain recent slow commands: which commands and keys are involved, who issues them, and how to avoid them",
+        arguments: &[
+            CONNECTION,
+            PromptArg {
+                name: "count",
+                description: "Slow log entries to examine (default: 25)",
+                required: false,
+            },
+        ],
+        tools: &["ping", "slowlog", "info", "key_info", "client_list"],
+        render: investigate_slow_queries,
+    },
+    GuidedPrompt {
+        name: "audit_ttl_hygiene",
+        title: "Audit TTL hygiene",
+        description: "Check which namespaces lack expiries, whether the eviction policy can reclaim memory, and which keys should expire",
+        arguments: &[CONNECTION, PREFIX],
+        tools: &["info", "search_keys", "namespace_census", "key_info"],
+        render: audit_ttl_hygiene,
+    },
+];
+
+/// The prompt called `name`.
+pub fn find(name: &str) -> Option<&'static GuidedPrompt> {
+    PROMPTS.iter().find(|p| p.name == name)
+}
+
+/// Arguments of a `prompts/get` request, as strings.
+pub struct Args {
+    connection: Option<String>,
+    values: JsonObject,
+}
+
+impl Args {
+    fn get(&self, name: &str) -> Option<String> {
+        match self.values.get(name)? {
+            serde_json::Value::String(s) if s.is_empty() => None,
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Null => None,
+            other => Some(other.to_string()),
+        }
+    }
+
+    /// Sentence pinning every tool call to the chosen connection.
+    fn connection_note(&self) -> String {
+        match &self.connection {
+            Some(name) => format!("Pass connection=\"{}\" to every tool call.\n\n", name),
+            None => String::new(),
+        }
+    }
+}
+
+impl GuidedPrompt {
+    pub fn to_prompt(&self) -> Prompt {
+        let arguments = self
+            .arguments
+            .iter()
+            .map(|arg| PromptArgument {
+                name: arg.name.to_string(),
+                title: None,
+                description: Some(arg.description.to_string()),
+                required: Some(arg.required),
+            })
+            .collect();
+        let mut prompt = Prompt::new(self.name, Some(self.description), Some(arguments));
+        prompt.title = Some(self.title.to_string());
+        prompt
+    }
+
+    /// The instructions for `arguments`. Unknown arguments are rejected so
+    /// typos do not silently widen the investigation.
+    pub fn render(&self, arguments: JsonObject) -> Result<String, String> {
+        if let Some(unknown) = arguments
+            .keys()
+            .find(|k| !self.arguments.iter().any(|a| a.name == k.as_str()))
+        {
+            return Err(format!(
+                "Prompt '{}' has no argument '{}'",
+                self.name, unknown
+            ));
+        }
+        let mut args = Args {
+            connection: None,
+            values: arguments,
+        };
+        args.connection = args.get("connection");
+        for arg in self.arguments.iter().filter(|a| a.required) {
+            if args.get(arg.name).is_none() {
+                return Err(format!(
+                    "Prompt '{}' requires argument '{}'",
+                    self.name, arg.name
+                ));
+            }
+        }
+        Ok((self.render)(&args))
+    }
+}
+
+fn analyze_memory_usage(args: &Args) -> String {
+    let scope = match args.get("prefix") {
+        Some(prefix) => format!(
+            "3. Run `namespace_census` with prefix=\"{prefix}\" and note the type mix, \
+             average memory per key, and encodings.\n"
+        ),
+        None => "3. Run `search_keys` to sample key names and group them by their \
+                 prefix up to the first ':'. Run `namespace_census` on the three to five \
+                 largest prefixes.\n"
+            .to_string(),
+    };
+    format!(
+        "Analyze the memory usage of this Redis instance.\n\n{}\
+         1. Call `info` with section=\"memory\": report used_memory_human, \
+         used_memory_peak_human, maxmemory and maxmemory_policy, and \
+         mem_fragmentation_ratio (above 1.5 suggests fragmentation; below 1 suggests \
+         swapping).\n\
+         2. Call `dbsize` for the total key count.\n\
+         {}\
+         4. For hashes with a high average length, run `hash_overview` on one or two \
+         sample keys to see whether fields could be split or trimmed.\n\
+         5. Summarize which namespaces dominate memory and why (large values, many \
+         keys, or non-compact encodings such as hashtable or skiplist where listpack \
+         would do). Recommend concrete changes with the expected impact, and say \
+         which figures are estimates from samples.",
+        args.connection_note(),
+        scope
+    )
+}
+
+fn investigate_slow_queries(args: &Args) -> String {
+    let count = args.get("count").unwrap_or_else(|| "25".to_string());
+    format!(
+        "Investigate slow queries on this Redis instance.\n\n{}\
+         1. Call `ping` to get a latency baseline, so network delay is not mistaken \
+         for slow commands.\n\
+         2. Call `slowlog` with count={count}. Group the entries by command and note \
+         the slowest durations and how often each appears.\n\
+         3. Call `info` with section=\"commandstats\" to see whether the slow commands \
+         are slow on average or only occasionally.\n\
+         4. For the keys named in slow entries, call `key_info` to get their type and \
+         size. Commands that are O(N) on large keys (HGETALL, SMEMBERS, LRANGE 0 -1, \
+         ZRANGE over a whole set, KEYS) are the usual culprits.\n\
+         5. Call `client_list` and match client names and addresses to the slow \
+         entries to find which application issues them.\n\
+         6. Summarize the causes in order of impact and recommend fixes (SCAN-based \
+         iteration, pagination, splitting large keys, pipelining).",
+        args.connection_note()
+    )
+}
+
+fn audit_ttl_hygiene(args: &Args) -> String {
+    let scope = match args.get("prefix") {
+        Some(prefix) => format!(
+            "2. Run `namespace_census` with prefix=\"{prefix}\" and note its TTL coverage \
+             and TTL range.\n"
+        ),
+        None => "2. Run `search_keys` to sample key names and group them by their prefix \
+                 up to the first ':'. Run `namespace_census` on each of the largest \
+                 prefixes and note their TTL coverage and TTL range.\n"
+            .to_string(),
+    };
+    format!(
+        "Audit the TTL hygiene of this Redis instance.\n\n{}\
+         1. Call `info` with section=\"keyspace\" and compare keys with expires per \
+         database. Call `info` with section=\"memory\" for maxmemory_policy, and with \
+         section=\"stats\" for expired_keys and evicted_keys.\n\
+         {}\
+         3. For namespaces with little or no TTL coverage, call `key_info` on a few \
+         sample keys to judge from their idle time whether they are still used.\n\
+         4. Flag the risks: with a volatile-* policy, keys without a TTL can never be \
+         evicted; with noeviction, writes fail once maxmemory is reached; very long \
+         TTLs on cache-like data waste memory.\n\
+         5. Recommend which namespaces should get a TTL, and suggest values based on \
+         how the data appears to be used.",
+        args.connection_note(),
+        scope
+    )
+}
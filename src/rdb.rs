@@ -0,0 +1,715 @@
+//! Offline reader for RDB dump files, behind `rdb_scan` and `rdb_get`.
+//!
+//! Decodes the value encodings written up to RDB version 12: plain,
+//! ziplist, listpack, intset, zipmap and quicklist, with LZF-compressed
+//! strings. Streams and module values are skipped and reported without
+//! their contents; hashes with field expirations (Redis 7.4) are rejected.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+/// Newest RDB format this reader understands
+const MAX_VERSION: u32 = 12;
+
+const OP_SLOT_INFO: u8 = 0xF4;
+const OP_FUNCTION2: u8 = 0xF5;
+const OP_MODULE_AUX: u8 = 0xF7;
+const OP_IDLE: u8 = 0xF8;
+const OP_FREQ: u8 = 0xF9;
+const OP_AUX: u8 = 0xFA;
+const OP_RESIZEDB: u8 = 0xFB;
+const OP_EXPIRETIME_MS: u8 = 0xFC;
+const OP_EXPIRETIME: u8 = 0xFD;
+const OP_SELECTDB: u8 = 0xFE;
+const OP_EOF: u8 = 0xFF;
+
+/// A key read from the dump.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub db: u64,
+    pub key: Vec<u8>,
+    /// Absolute expiry in Unix milliseconds
+    pub expire_ms: Option<i64>,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(Vec<u8>),
+    List(Vec<Vec<u8>>),
+    Set(Vec<Vec<u8>>),
+    ZSet(Vec<(Vec<u8>, f64)>),
+    Hash(Vec<(Vec<u8>, Vec<u8>)>),
+    /// Entries are skipped, only the length is kept
+    Stream {
+        length: u64,
+    },
+    /// Module values are opaque
+    Module,
+}
+
+impl Value {
+    /// Type name as reported by TYPE
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::Set(_) => "set",
+            Value::ZSet(_) => "zset",
+            Value::Hash(_) => "hash",
+            Value::Stream { .. } => "stream",
+            Value::Module => "module",
+        }
+    }
+
+    /// Elements in a collection, or bytes in a string
+    pub fn length(&self) -> Option<u64> {
+        match self {
+            Value::String(v) => Some(v.len() as u64),
+            Value::List(v) | Value::Set(v) => Some(v.len() as u64),
+            Value::ZSet(v) => Some(v.len() as u64),
+            Value::Hash(v) => Some(v.len() as u64),
+            Value::Stream { length } => Some(*length),
+            Value::Module => None,
+        }
+    }
+}
+
+/// Reads a dump one key at a time, so memory use stays bounded by the
+/// largest value rather than the file.
+pub struct Reader<R> {
+    input: R,
+    version: u32,
+    db: u64,
+    done: bool,
+    /// AUX fields seen so far (redis-ver, ctime, used-mem, ...). Redis
+    /// writes them before the first key.
+    pub aux: BTreeMap<String, String>,
+}
+
+impl Reader<BufReader<File>> {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+enum Length {
+    Len(u64),
+    /// Special string encoding: integer or LZF
+    Encoded(u8),
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+impl<R: Read> Reader<R> {
+    pub fn new(mut input: R) -> io::Result<Self> {
+        let mut magic = [0u8; 9];
+        input.read_exact(&mut magic)?;
+        if &magic[..5] != b"REDIS" {
+            return Err(invalid("not an RDB file"));
+        }
+        let version: u32 = std::str::from_utf8(&magic[5..])
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| invalid("malformed RDB version"))?;
+        if version > MAX_VERSION {
+            return Err(invalid(format!(
+                "RDB version {} is not supported (newest: {})",
+                version, MAX_VERSION
+            )));
+        }
+        Ok(Self {
+            input,
+            version,
+            db: 0,
+            done: false,
+            aux: BTreeMap::new(),
+        })
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The next key, or `None` at the end of the dump.
+    pub fn next_entry(&mut self) -> io::Result<Option<Entry>> {
+        if self.done {
+            return Ok(None);
+        }
+        let mut expire_ms = None;
+        loop {
+            match self.u8()? {
+                OP_EOF => {
+                    self.done = true;
+                    return Ok(None);
+                }
+                OP_SELECTDB => self.db = self.length()?,
+                OP_RESIZEDB => {
+                    self.length()?;
+                    self.length()?;
+                }
+                OP_AUX => {
+                    let key = self.string()?;
+                    let value = self.string()?;
+                    self.aux.insert(
+                        String::from_utf8_lossy(&key).into_owned(),
+                        String::from_utf8_lossy(&value).into_owned(),
+                    );
+                }
+                OP_EXPIRETIME => expire_ms = Some(self.le_int(4)? * 1000),
+                OP_EXPIRETIME_MS => expire_ms = Some(self.le_int(8)?),
+                OP_FREQ => {
+                    self.u8()?;
+                }
+                OP_IDLE => {
+                    self.length()?;
+                }
+                OP_MODULE_AUX => {
+                    // Module ID, then when the aux data is loaded
+                    self.length()?;
+                    self.length()?;
+                    self.length()?;
+                    self.skip_module_value()?;
+                }
+                OP_FUNCTION2 => {
+                    self.string()?;
+                }
+                OP_SLOT_INFO => {
+                    self.length()?;
+                    self.length()?;
+                    self.length()?;
+                }
+                kind => {
+                    let key = self.string()?;
+                    let value = self.value(kind)?;
+                    return Ok(Some(Entry {
+                        db: self.db,
+                        key,
+                        expire_ms,
+                        value,
+                    }));
+                }
+            }
+        }
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        let mut byte = [0u8; 1];
+        self.input.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn bytes(&mut self, len: u64) -> io::Result<Vec<u8>> {
+        // Read through `take` so a corrupt length cannot force a huge
+        // allocation up front
+        let mut buf = Vec::new();
+        (&mut self.input).take(len).read_to_end(&mut buf)?;
+        if buf.len() as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(buf)
+    }
+
+    fn skip(&mut self, len: u64) -> io::Result<()> {
+        let skipped = io::copy(&mut (&mut self.input).take(len), &mut io::sink())?;
+        if skipped != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+
+    /// Signed little-endian integer of `len` bytes.
+    fn le_int(&mut self, len: u64) -> io::Result<i64> {
+        let bytes = self.bytes(len)?;
+        Ok(le_signed(&bytes))
+    }
+
+    fn length_or_encoding(&mut self) -> io::Result<Length> {
+        let first = self.u8()?;
+        Ok(match first >> 6 {
+            0 => Length::Len(u64::from(first & 0x3f)),
+            1 => Length::Len(u64::from(first & 0x3f) << 8 | u64::from(self.u8()?)),
+            2 => match first {
+                0x80 => {
+                    let bytes = self.bytes(4)?;
+                    Length::Len(u64::from(u32::from_be_bytes(bytes.try_into().unwrap())))
+                }
+                0x81 => {
+                    let bytes = self.bytes(8)?;
+                    Length::Len(u64::from_be_bytes(bytes.try_into().unwrap()))
+                }
+                other => return Err(invalid(format!("unknown length prefix {:#04x}", other))),
+            },
+            _ => Length::Encoded(first & 0x3f),
+        })
+    }
+
+    fn length(&mut self) -> io::Result<u64> {
+        match self.length_or_encoding()? {
+            Length::Len(len) => Ok(len),
+            Length::Encoded(_) => Err(invalid("expected a length, found a string encoding")),
+        }
+    }
+
+    fn string(&mut self) -> io::Result<Vec<u8>> {
+        match self.length_or_encoding()? {
+            Length::Len(len) => self.bytes(len),
+            Length::Encoded(0) => Ok(self.le_int(1)?.to_string().into_bytes()),
+            Length::Encoded(1) => Ok(self.le_int(2)?.to_string().into_bytes()),
+            Length::Encoded(2) => Ok(self.le_int(4)?.to_string().into_bytes()),
+            Length::Encoded(3) => {
+                let compressed_len = self.length()?;
+                let len = self.length()?;
+                let compressed = self.bytes(compressed_len)?;
+                lzf_decompress(&compressed, len)
+            }
+            Length::Encoded(other) => Err(invalid(format!("unknown string encoding {}", other))),
+        }
+    }
+
+    fn strings(&mut self) -> io::Result<Vec<Vec<u8>>> {
+        let len = self.length()?;
+        (0..len).map(|_| self.string()).collect()
+    }
+
+    /// Score in the pre-RDB 8 text format.
+    fn text_double(&mut self) -> io::Result<f64> {
+        match self.u8()? {
+            253 => Ok(f64::NAN),
+            254 => Ok(f64::INFINITY),
+            255 => Ok(f64::NEG_INFINITY),
+            len => {
+                let text = self.bytes(u64::from(len))?;
+                parse_score(&text)
+            }
+        }
+    }
+
+    fn value(&mut self, kind: u8) -> io::Result<Value> {
+        Ok(match kind {
+            0 => Value::String(self.string()?),
+            1 => Value::List(self.strings()?),
+            2 => Value::Set(self.strings()?),
+            3 | 5 => {
+                let len = self.length()?;
+                let mut members = Vec::new();
+                for _ in 0..len {
+                    let member = self.string()?;
+                    let score = if kind == 3 {
+                        self.text_double()?
+                    } else {
+                        f64::from_le_bytes(self.bytes(8)?.try_into().unwrap())
+                    };
+                    members.push((member, score));
+                }
+                Value::ZSet(members)
+            }
+            4 => {
+                let len = self.length()?;
+                let mut fields = Vec::new();
+                for _ in 0..len {
+                    fields.push((self.string()?, self.string()?));
+                }
+                Value::Hash(fields)
+            }
+            6 => {
+                return Err(invalid(
+                    "module values in the RDB 7 format are not supported",
+                ))
+            }
+            7 => {
+                self.length()?;
+                self.skip_module_value()?;
+                Value::Module
+            }
+            9 => Value::Hash(zipmap(&self.string()?)?),
+            10 => Value::List(ziplist(&self.string()?)?),
+            11 => Value::Set(intset(&self.string()?)?),
+            12 => Value::ZSet(scored(ziplist(&self.string()?)?)?),
+            13 => Value::Hash(paired(ziplist(&self.string()?)?)),
+            14 => {
+                let nodes = self.length()?;
+                let mut items = Vec::new();
+                for _ in 0..nodes {
+                    items.extend(ziplist(&self.string()?)?);
+                }
+                Value::List(items)
+            }
+            15 | 19 | 21 => self.stream(kind)?,
+            16 => Value::Hash(paired(listpack(&self.string()?)?)),
+            17 => Value::ZSet(scored(listpack(&self.string()?)?)?),
+            18 => {
+                let nodes = self.length()?;
+                let mut items = Vec::new();
+                for _ in 0..nodes {
+                    let container = self.length()?;
+                    let node = self.string()?;
+                    // 1: a single plain element, 2: a listpack
+                    if container == 1 {
+                        items.push(node);
+                    } else {
+                        items.extend(listpack(&node)?);
+                    }
+                }
+                Value::List(items)
+            }
+            20 => Value::Set(listpack(&self.string()?)?),
+            22..=25 => return Err(invalid("hashes with field expirations are not supported")),
+            other => return Err(invalid(format!("unknown value type {}", other))),
+        })
+    }
+
+    /// Skip a stream, keeping its length. `kind` 19 adds the first ID,
+    /// deletion bookkeeping and group read counters; 21 adds consumer
+    /// active times.
+    fn stream(&mut self, kind: u8) -> io::Result<Value> {
+        let nodes = self.length()?;
+        for _ in 0..nodes {
+            self.string()?;
+            self.string()?;
+        }
+        let length = self.length()?;
+        // Last ID
+        self.length()?;
+        self.length()?;
+        if kind >= 19 {
+            // First ID, max deleted ID, entries added
+            for _ in 0..5 {
+                self.length()?;
+            }
+        }
+        let groups = self.length()?;
+        for _ in 0..groups {
+            self.string()?;
+            self.length()?;
+            self.length()?;
+            if kind >= 19 {
+                self.length()?;
+            }
+            let pending = self.length()?;
+            for _ in 0..pending {
+                // Raw entry ID and delivery time, then delivery count
+                self.skip(16 + 8)?;
+                self.length()?;
+            }
+            let consumers = self.length()?;
+            for _ in 0..consumers {
+                self.string()?;
+                self.skip(if kind >= 21 { 16 } else { 8 })?;
+                let pending = self.length()?;
+                self.skip(pending.saturating_mul(16))?;
+            }
+        }
+        Ok(Value::Stream { length })
+    }
+
+    fn skip_module_value(&mut self) -> io::Result<()> {
+        loop {
+            match self.length()? {
+                0 => return Ok(()),
+                1 | 2 => {
+                    self.length()?;
+                }
+                3 => self.skip(4)?,
+                4 => self.skip(8)?,
+                5 => {
+                    self.string()?;
+                }
+                other => return Err(invalid(format!("unknown module opcode {}", other))),
+            }
+        }
+    }
+}
+
+fn le_signed(bytes: &[u8]) -> i64 {
+    let mut value = 0u64;
+    for (i, byte) in bytes.iter().enumerate() {
+        value |= u64::from(*byte) << (8 * i);
+    }
+    let shift = 64 - 8 * bytes.len() as u32;
+    ((value << shift) as i64) >> shift
+}
+
+fn parse_score(text: &[u8]) -> io::Result<f64> {
+    std::str::from_utf8(text)
+        .ok()
+        .and_then(|t| match t {
+            "inf" | "+inf" => Some(f64::INFINITY),
+            "-inf" => Some(f64::NEG_INFINITY),
+            _ => t.parse().ok(),
+        })
+        .ok_or_else(|| invalid("malformed score"))
+}
+
+fn paired(items: Vec<Vec<u8>>) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut items = items.into_iter();
+    let mut pairs = Vec::new();
+    while let (Some(field), Some(value)) = (items.next(), items.next()) {
+        pairs.push((field, value));
+    }
+    pairs
+}
+
+fn scored(items: Vec<Vec<u8>>) -> io::Result<Vec<(Vec<u8>, f64)>> {
+    paired(items)
+        .into_iter()
+        .map(|(member, score)| Ok((member, parse_score(&score)?)))
+        .collect()
+}
+
+/// Cursor over an encoded value.
+struct Blob<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Blob<'a> {
+    fn new(data: &'a [u8], header: usize) -> Self {
+        Self { data, pos: header }
+    }
+
+    fn peek(&self) -> io::Result<u8> {
+        self.data
+            .get(self.pos)
+            .copied()
+            .ok_or_else(|| invalid("truncated encoded value"))
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.pos..end))
+            .ok_or_else(|| invalid("truncated encoded value"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn le_int(&mut self, len: usize) -> io::Result<i64> {
+        Ok(le_signed(self.take(len)?))
+    }
+
+    fn le_u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+fn int_item(value: i64) -> Vec<u8> {
+    value.to_string().into_bytes()
+}
+
+fn ziplist(data: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    // zlbytes, zltail, zllen
+    let mut blob = Blob::new(data, 10);
+    let mut items = Vec::new();
+    while blob.peek()? != 0xFF {
+        // Length of the previous entry
+        if blob.u8()? == 0xFE {
+            blob.take(4)?;
+        }
+        let encoding = blob.u8()?;
+        let item = match encoding >> 6 {
+            0 => blob.take(usize::from(encoding & 0x3f))?.to_vec(),
+            1 => {
+                let len = usize::from(encoding & 0x3f) << 8 | usize::from(blob.u8()?);
+                blob.take(len)?.to_vec()
+            }
+            2 => {
+                let len = u32::from_be_bytes(blob.take(4)?.try_into().unwrap());
+                blob.take(len as usize)?.to_vec()
+            }
+            _ => int_item(match encoding {
+                0xC0 => blob.le_int(2)?,
+                0xD0 => blob.le_int(4)?,
+                0xE0 => blob.le_int(8)?,
+                0xF0 => blob.le_int(3)?,
+                0xFE => blob.le_int(1)?,
+                0xF1..=0xFD => i64::from(encoding & 0x0f) - 1,
+                other => return Err(invalid(format!("unknown ziplist encoding {:#04x}", other))),
+            }),
+        };
+        items.push(item);
+    }
+    Ok(items)
+}
+
+fn listpack(data: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    // Total bytes, element count
+    let mut blob = Blob::new(data, 6);
+    let mut items = Vec::new();
+    while blob.peek()? != 0xFF {
+        let start = blob.pos;
+        let encoding = blob.u8()?;
+        let item = if encoding & 0x80 == 0 {
+            int_item(i64::from(encoding))
+        } else if encoding & 0xC0 == 0x80 {
+            blob.take(usize::from(encoding & 0x3f))?.to_vec()
+        } else if encoding & 0xE0 == 0xC0 {
+            let value = i64::from(encoding & 0x1f) << 8 | i64::from(blob.u8()?);
+            int_item(if value >= 1 << 12 {
+                value - (1 << 13)
+            } else {
+                value
+            })
+        } else if encoding & 0xF0 == 0xE0 {
+            let len = usize::from(encoding & 0x0f) << 8 | usize::from(blob.u8()?);
+            blob.take(len)?.to_vec()
+        } else {
+            match encoding {
+                0xF0 => {
+                    let len = blob.le_u32()?;
+                    blob.take(len as usize)?.to_vec()
+                }
+                0xF1 => int_item(blob.le_int(2)?),
+                0xF2 => int_item(blob.le_int(3)?),
+                0xF3 => int_item(blob.le_int(4)?),
+                0xF4 => int_item(blob.le_int(8)?),
+                other => return Err(invalid(format!("unknown listpack encoding {:#04x}", other))),
+            }
+        };
+        // Each entry ends with its own length, in 1 to 5 bytes
+        let entry_len = blob.pos - start;
+        blob.take(match entry_len {
+            0..=127 => 1,
+            128..=16_383 => 2,
+            16_384..=2_097_151 => 3,
+            2_097_152..=268_435_455 => 4,
+            _ => 5,
+        })?;
+        items.push(item);
+    }
+    Ok(items)
+}
+
+fn intset(data: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    let mut blob = Blob::new(data, 0);
+    let width = blob.le_u32()? as usize;
+    if !matches!(width, 2 | 4 | 8) {
+        return Err(invalid(format!("unknown intset width {}", width)));
+    }
+    let len = blob.le_u32()?;
+    (0..len)
+        .map(|_| Ok(int_item(blob.le_int(width)?)))
+        .collect()
+}
+
+fn zipmap(data: &[u8]) -> io::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    fn length(blob: &mut Blob) -> io::Result<Option<usize>> {
+        Ok(match blob.u8()? {
+            0xFF => None,
+            0xFE => Some(blob.le_u32()? as usize),
+            len => Some(usize::from(len)),
+        })
+    }
+
+    let mut blob = Blob::new(data, 1);
+    let mut fields = Vec::new();
+    while let Some(len) = length(&mut blob)? {
+        let field = blob.take(len)?.to_vec();
+        let len = length(&mut blob)?.ok_or_else(|| invalid("truncated zipmap"))?;
+        let free = blob.u8()?;
+        let value = blob.take(len)?.to_vec();
+        blob.take(usize::from(free))?;
+        fields.push((field, value));
+    }
+    Ok(fields)
+}
+
+fn lzf_decompress(input: &[u8], expected: u64) -> io::Result<Vec<u8>> {
+    let corrupt = || invalid("corrupt LZF-compressed string");
+    let mut out: Vec<u8> = Vec::with_capacity(expected.min(1 << 20) as usize);
+    let mut i = 0;
+    while i < input.len() {
+        let ctrl = usize::from(input[i]);
+        i += 1;
+        if ctrl < 32 {
+            let literal = input.get(i..i + ctrl + 1).ok_or_else(corrupt)?;
+            out.extend_from_slice(literal);
+            i += ctrl + 1;
+        } else {
+            let mut len = ctrl >> 5;
+            if len == 7 {
+                len += usize::from(*input.get(i).ok_or_else(corrupt)?);
+                i += 1;
+            }
+            let back = ((ctrl & 0x1f) << 8) + usize::from(*input.get(i).ok_or_else(corrupt)?) + 1;
+            i += 1;
+            let start = out.len().checked_sub(back).ok_or_else(corrupt)?;
+            // Back references may overlap the bytes they produce
+            for k in 0..len + 2 {
+                out.push(out[start + k]);
+            }
+        }
+    }
+    if out.len() as u64 != expected {
+        return Err(corrupt());
+    }
+    Ok(out)
+}
+
+fn text(bytes: &[u8]) -> serde_json::Value {
+    serde_json::Value::String(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// `value` in the shape `get` returns, with collections cut to
+/// `max_elements`. The flag is set when elements were left out.
+pub fn value_json(value: &Value, max_elements: usize) -> (serde_json::Value, bool) {
+    let truncated = match value {
+        Value::String(_) | Value::Stream { .. } | Value::Module => false,
+        _ => value.length().unwrap_or(0) > max_elements as u64,
+    };
+    let json = match value {
+        Value::String(v) => text(v),
+        Value::List(items) | Value::Set(items) => {
+            serde_json::Value::Array(items.iter().take(max_elements).map(|i| text(i)).collect())
+        }
+        Value::ZSet(members) => members
+            .iter()
+            .take(max_elements)
+            .map(|(m, s)| serde_json::json!({"member": text(m), "score": s}))
+            .collect(),
+        Value::Hash(fields) => serde_json::Value::Object(
+            fields
+                .iter()
+                .take(max_elements)
+                .map(|(f, v)| (String::from_utf8_lossy(f).into_owned(), text(v)))
+                .collect(),
+        ),
+        Value::Stream { length } => {
+            serde_json::json!({"length": length, "note": "Stream entries are not read from dumps"})
+        }
+        Value::Module => serde_json::json!({"note": "Module values are opaque"}),
+    };
+    (json, truncated)
+}
+
+/// Whether a snapshot value and a live value from `get` hold the same
+/// contents. Set members and zset entries are compared regardless of order.
+pub fn same_contents(
+    key_type: &str,
+    snapshot: &serde_json::Value,
+    live: &serde_json::Value,
+) -> bool {
+    fn sorted(value: &serde_json::Value) -> Vec<String> {
+        let mut items: Vec<String> = value
+            .as_array()
+            .map(|a| a.iter().map(|i| i.to_string()).collect())
+            .unwrap_or_default();
+        items.sort();
+        items
+    }
+    match key_type {
+        "set" | "zset" => sorted(snapshot) == sorted(live),
+        _ => snapshot == live,
+    }
+}
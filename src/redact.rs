@@ -0,0 +1,49 @@
+//! Redaction of secrets in values returned to the client.
+
+/// Placeholder shown instead of a secret
+pub const REDACTED: &str = "***";
+
+/// Whether a CONFIG parameter holds a secret (passwords, auth tokens).
+pub fn is_sensitive_config(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.contains("pass") || name.contains("auth") || name.contains("secret")
+}
+
+/// Redact a CONFIG parameter value if the parameter holds a secret. Empty
+/// values are kept so "no password set" is still visible.
+pub fn config_value(name: &str, value: &str) -> String {
+    if is_sensitive_config(name) && !value.is_empty() {
+        REDACTED.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Mask the password in a connection URL, including the `?pass=` query
+/// parameter of Unix socket URLs.
+pub fn url(url_str: &str) -> String {
+    match url::Url::parse(url_str) {
+        Ok(mut parsed) => {
+            if parsed.password().is_some() {
+                let _ = parsed.set_password(Some(REDACTED));
+            }
+            let unix = matches!(parsed.scheme(), "unix" | "redis+unix");
+            if unix && parsed.query_pairs().any(|(k, _)| k == "pass") {
+                let pairs: Vec<(String, String)> = parsed
+                    .query_pairs()
+                    .map(|(k, v)| {
+                        let v = if k == "pass" {
+                            REDACTED.into()
+                        } else {
+                            v.into_owned()
+                        };
+                        (k.into_owned(), v)
+                    })
+                    .collect();
+                parsed.query_pairs_mut().clear().extend_pairs(pairs);
+            }
+            parsed.to_string()
+        }
+        Err(_) => url_str.to_string(),
+    }
+}
@@ -0,0 +1,181 @@
+//! Key references behind `get` with `follow_references`: strings inside a
+//! value that name other keys, found with the `[[references]]` regexes of
+//! the config file, and the bounded reads of the keys they name.
+
+use std::collections::HashSet;
+
+use regex_automata::meta::Regex;
+use serde_json::{json, Map, Value};
+
+use crate::config::KeyReference;
+use crate::resp;
+
+/// The `[[references]]` patterns, compiled.
+#[derive(Debug, Clone, Default)]
+pub struct Matcher {
+    patterns: Vec<Regex>,
+}
+
+impl Matcher {
+    pub fn new(references: &[KeyReference]) -> Result<Self, String> {
+        let patterns = references
+            .iter()
+            .map(|r| {
+                Regex::new(&r.pattern)
+                    .map_err(|e| format!("reference pattern '{}': {}", r.pattern, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { patterns })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Key names matched anywhere in `value` (strings, list items, hash
+    /// fields and values, zset members), in order of first appearance and
+    /// without duplicates.
+    pub fn find(&self, value: &Value) -> Vec<String> {
+        let mut found = Vec::new();
+        let mut seen = HashSet::new();
+        self.collect(value, &mut found, &mut seen);
+        found
+    }
+
+    fn collect(&self, value: &Value, found: &mut Vec<String>, seen: &mut HashSet<String>) {
+        match value {
+            Value::String(text) => self.collect_text(text, found, seen),
+            Value::Array(items) => {
+                for item in items {
+                    self.collect(item, found, seen);
+                }
+            }
+            Value::Object(fields) => {
+                for (field, value) in fields {
+                    self.collect_text(field, found, seen);
+                    self.collect(value, found, seen);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn collect_text(&self, text: &str, found: &mut Vec<String>, seen: &mut HashSet<String>) {
+        for regex in &self.patterns {
+            for m in regex.find_iter(text) {
+                let key = &text[m.range()];
+                if !key.is_empty() && seen.insert(key.to_string()) {
+                    found.push(key.to_string());
+                }
+            }
+        }
+    }
+}
+
+/// The command reading at most `entries` elements (or `max_bytes` of a
+/// string) of a referenced key, or `None` for types that are not read.
+pub fn read_command(
+    key_type: &str,
+    key: &str,
+    entries: usize,
+    max_bytes: u64,
+) -> Option<redis::Cmd> {
+    let mut cmd;
+    match key_type {
+        "string" => {
+            cmd = redis::cmd("GETRANGE");
+            cmd.arg(key).arg(0).arg(max_bytes.saturating_sub(1));
+        }
+        "list" => {
+            cmd = redis::cmd("LRANGE");
+            cmd.arg(key).arg(0).arg(entries.saturating_sub(1));
+        }
+        // With a positive count, distinct members: the whole set when small
+        "set" => {
+            cmd = redis::cmd("SRANDMEMBER");
+            cmd.arg(key).arg(entries);
+        }
+        "zset" => {
+            cmd = redis::cmd("ZRANGE");
+            cmd.arg(key)
+                .arg(0)
+                .arg(entries.saturating_sub(1))
+                .arg("WITHSCORES");
+        }
+        "hash" => {
+            cmd = redis::cmd("HSCAN");
+            cmd.arg(key).arg(0).arg("COUNT").arg(entries);
+        }
+        "stream" => {
+            cmd = redis::cmd("XRANGE");
+            cmd.arg(key).arg("-").arg("+").arg("COUNT").arg(entries);
+        }
+        _ => return None,
+    }
+    Some(cmd)
+}
+
+/// The reply to [`read_command`] in the shape `get` returns for the type:
+/// zsets as `{member, score}` items, hashes as an object.
+pub fn value_json(key_type: &str, reply: &redis::Value) -> Value {
+    let json = resp::to_json(reply);
+    match (key_type, json) {
+        ("zset", Value::Array(items)) => {
+            // RESP2 flattens the pairs; RESP3 nests them
+            let pairs: Vec<(Value, Value)> = if items.iter().all(Value::is_array) {
+                items
+                    .into_iter()
+                    .filter_map(|pair| match pair {
+                        Value::Array(mut pair) if pair.len() == 2 => {
+                            let score = pair.pop()?;
+                            Some((pair.pop()?, score))
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            } else {
+                items
+                    .chunks(2)
+                    .filter(|pair| pair.len() == 2)
+                    .map(|pair| (pair[0].clone(), pair[1].clone()))
+                    .collect()
+            };
+            Value::Array(
+                pairs
+                    .into_iter()
+                    .map(|(member, score)| {
+                        let score = score
+                            .as_f64()
+                            .or_else(|| score.as_str().and_then(|s| s.parse().ok()));
+                        json!({"member": member, "score": score})
+                    })
+                    .collect(),
+            )
+        }
+        ("hash", Value::Array(mut page)) if page.len() == 2 => {
+            let fields = match page.pop() {
+                Some(Value::Array(fields)) => fields,
+                Some(Value::Object(fields)) => return Value::Object(fields),
+                _ => Vec::new(),
+            };
+            let mut object = Map::new();
+            for pair in fields.chunks(2) {
+                if let [Value::String(field), value] = pair {
+                    object.insert(field.clone(), value.clone());
+                }
+            }
+            Value::Object(object)
+        }
+        (_, json) => json,
+    }
+}
+
+/// Elements in a value from [`value_json`], or bytes of a string.
+pub fn returned(value: &Value) -> u64 {
+    match value {
+        Value::String(text) => text.len() as u64,
+        Value::Array(items) => items.len() as u64,
+        Value::Object(fields) => fields.len() as u64,
+        _ => 0,
+    }
+}
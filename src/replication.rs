@@ -0,0 +1,177 @@
+//! Replication state behind `replication_status`: the ROLE reply merged
+//! with `INFO replication`, with each replica's lag in bytes (master offset
+//! minus the replica's acknowledged offset) and in seconds (time since its
+//! last ACK). ROLE gives the offsets; INFO adds link state and timings.
+
+use serde_json::{json, Map, Value};
+
+/// A ROLE reply (as JSON, see [`crate::resp::to_json`]) in named fields:
+/// `{role: "master", offset, replicas: [{addr, offset}]}`,
+/// `{role: "replica", master, state, offset}` or
+/// `{role: "sentinel", masters}`. `None` for anything else.
+pub fn parse_role(reply: &Value) -> Option<Value> {
+    let items = reply.as_array()?;
+    let int = |v: Option<&Value>| {
+        v.and_then(|v| {
+            v.as_i64()
+                .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+        })
+    };
+    let text = |v: Option<&Value>| match v {
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    match items.first()?.as_str()? {
+        "master" => {
+            let replicas: Vec<Value> = items
+                .get(2)
+                .and_then(|r| r.as_array())
+                .map(|replicas| {
+                    replicas
+                        .iter()
+                        .filter_map(|r| r.as_array())
+                        .map(|r| {
+                            json!({
+                                "addr": format!("{}:{}", text(r.first()), text(r.get(1))),
+                                "offset": int(r.get(2)),
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(json!({
+                "role": "master",
+                "offset": int(items.get(1)),
+                "replicas": replicas,
+            }))
+        }
+        "slave" | "replica" => Some(json!({
+            "role": "replica",
+            "master": format!("{}:{}", text(items.get(1)), text(items.get(2))),
+            "state": items.get(3).and_then(|s| s.as_str()),
+            "offset": int(items.get(4)),
+        })),
+        "sentinel" => Some(json!({
+            "role": "sentinel",
+            "masters": items.get(1).cloned().unwrap_or_else(|| json!([])),
+        })),
+        _ => None,
+    }
+}
+
+/// Replication status of one server from its parsed ROLE reply (when ROLE
+/// was allowed) and its `replication` INFO section (from
+/// [`crate::info::parse_sections`]).
+pub fn status(role: Option<&Value>, replication: &Map<String, Value>) -> Value {
+    let int = |field: &str| replication.get(field).and_then(|v| v.as_i64());
+    let role_name = match role.and_then(|r| r["role"].as_str()) {
+        Some(name) => name.to_string(),
+        None => match replication.get("role").and_then(|r| r.as_str()) {
+            Some("slave") => "replica".to_string(),
+            Some(other) => other.to_string(),
+            None => "unknown".to_string(),
+        },
+    };
+    let master_offset = role
+        .filter(|r| r["role"] == "master")
+        .and_then(|r| r["offset"].as_i64())
+        .or_else(|| int("master_repl_offset"));
+
+    let mut result = json!({
+        "role": role_name,
+        "master_repl_offset": master_offset,
+        "replication_id": replication.get("master_replid"),
+    });
+
+    if role_name == "replica" {
+        let link_up = replication
+            .get("master_link_status")
+            .and_then(|v| v.as_str())
+            == Some("up");
+        let master = role
+            .and_then(|r| r["master"].as_str())
+            .map(str::to_string)
+            .or_else(|| {
+                Some(format!(
+                    "{}:{}",
+                    replication.get("master_host")?.as_str()?,
+                    replication.get("master_port")?
+                ))
+            });
+        result["master"] = json!({
+            "addr": master,
+            "link_status": replication.get("master_link_status"),
+            "state": role.and_then(|r| r.get("state")),
+            "last_io_secs_ago": int("master_last_io_seconds_ago"),
+            "sync_in_progress": int("master_sync_in_progress") == Some(1),
+            "link_down_secs": int("master_link_down_since_seconds"),
+        });
+        result["offset"] = json!(role
+            .and_then(|r| r["offset"].as_i64())
+            .or_else(|| int("slave_repl_offset")));
+        // A replica only knows when it last heard from its master; the
+        // byte lag is visible from the master's side
+        result["lag_secs"] = json!(if link_up {
+            int("master_last_io_seconds_ago")
+        } else {
+            None
+        });
+        result["read_only"] = json!(int("slave_read_only").map(|v| v == 1));
+    }
+
+    // Replicas attached to this server (a replica may have its own)
+    let mut from_info: Vec<(u32, &Map<String, Value>)> = replication
+        .iter()
+        .filter_map(|(k, v)| Some((k.strip_prefix("slave")?.parse().ok()?, v.as_object()?)))
+        .collect();
+    from_info.sort_by_key(|(n, _)| *n);
+    let mut replicas: Vec<Value> = from_info
+        .into_iter()
+        .map(|(_, r)| {
+            let addr = format!(
+                "{}:{}",
+                r.get("ip").and_then(|v| v.as_str()).unwrap_or("?"),
+                r.get("port").map(|v| v.to_string()).unwrap_or_default()
+            );
+            json!({
+                "addr": addr,
+                "state": r.get("state"),
+                "offset": r.get("offset").and_then(|v| v.as_i64()),
+                "lag_secs": r.get("lag").and_then(|v| v.as_i64()),
+            })
+        })
+        .collect();
+    if let Some(listed) = role.and_then(|r| r["replicas"].as_array()) {
+        for entry in listed {
+            match replicas.iter_mut().find(|r| r["addr"] == entry["addr"]) {
+                // ROLE and INFO are separate reads; keep the later offset
+                Some(known) => {
+                    if entry["offset"].as_i64() > known["offset"].as_i64() {
+                        known["offset"] = entry["offset"].clone();
+                    }
+                }
+                None => replicas.push(json!({
+                    "addr": entry["addr"],
+                    "state": null,
+                    "offset": entry["offset"],
+                    "lag_secs": null,
+                })),
+            }
+        }
+    }
+    for replica in &mut replicas {
+        replica["lag_bytes"] = json!(match (master_offset, replica["offset"].as_i64()) {
+            (Some(master), Some(offset)) => Some((master - offset).max(0)),
+            _ => None,
+        });
+    }
+    result["max_lag_bytes"] = json!(replicas
+        .iter()
+        .filter_map(|r| r["lag_bytes"].as_i64())
+        .max());
+    result["max_lag_secs"] = json!(replicas.iter().filter_map(|r| r["lag_secs"].as_i64()).max());
+    result["connected_replicas"] = json!(int("connected_slaves").unwrap_or(replicas.len() as i64));
+    result["replicas"] = Value::Array(replicas);
+    result
+}
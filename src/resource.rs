@@ -0,0 +1,60 @@
+//! Key URIs for the `redis://{connection}/{key}` resource template.
+//!
+//! The key is everything after the first `/` following the connection name,
+//! so keys may contain `/`. Characters outside the unreserved URI set are
+//! percent-encoded.
+
+/// Template advertised through `resources/templates/list`
+pub const KEY_TEMPLATE: &str = "redis://{connection}/{key}";
+
+/// Template for keyspace patterns, for `resources/subscribe`
+pub const PATTERN_TEMPLATE: &str = "redis://{connection}/{pattern}";
+
+/// URI of `key` on `connection`.
+pub fn key_uri(connection: &str, key: &str) -> String {
+    format!("redis://{}/{}", encode(connection), encode(key))
+}
+
+/// `(connection, key)` from a key URI, or `None` when it is not one.
+pub fn parse_key_uri(uri: &str) -> Option<(String, String)> {
+    let rest = uri.strip_prefix("redis://")?;
+    let (connection, key) = rest.split_once('/')?;
+    if connection.is_empty() || key.is_empty() {
+        return None;
+    }
+    Some((decode(connection)?, decode(key)?))
+}
+
+/// Whether the key part of a URI is a glob pattern rather than a key.
+pub fn is_pattern(key: &str) -> bool {
+    key.contains(['*', '?', '['])
+}
+
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~' | b':') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
@@ -0,0 +1,63 @@
+//! Conversion of raw RESP replies into JSON.
+
+use redis::Value;
+
+/// Convert any reply into JSON. Binary strings are decoded lossily; RESP3
+/// maps become objects.
+pub fn to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Nil => serde_json::Value::Null,
+        Value::Int(i) => serde_json::json!(i),
+        Value::BulkString(bytes) => {
+            serde_json::Value::String(String::from_utf8_lossy(bytes).into_owned())
+        }
+        Value::SimpleString(s) => serde_json::Value::String(s.clone()),
+        Value::Okay => serde_json::Value::String("OK".to_string()),
+        Value::Array(items) | Value::Set(items) => {
+            serde_json::Value::Array(items.iter().map(to_json).collect())
+        }
+        Value::Map(pairs) => serde_json::Value::Object(
+            pairs
+                .iter()
+                .map(|(k, v)| (key_string(k), to_json(v)))
+                .collect(),
+        ),
+        Value::Attribute { data, .. } => to_json(data),
+        Value::Double(d) => serde_json::json!(d),
+        Value::Boolean(b) => serde_json::Value::Bool(*b),
+        Value::VerbatimString { text, .. } => serde_json::Value::String(text.clone()),
+        Value::Push { data, .. } => serde_json::Value::Array(data.iter().map(to_json).collect()),
+        other => serde_json::Value::String(format!("{:?}", other)),
+    }
+}
+
+/// Convert a map-like reply into a JSON object. Accepts RESP3 maps as well as
+/// the RESP2 encoding of a map as a flat `[key, value, key, value, ...]`
+/// array. Nested values are converted with [`to_json`].
+pub fn to_object(value: &Value) -> Option<serde_json::Map<String, serde_json::Value>> {
+    match value {
+        Value::Map(pairs) => Some(
+            pairs
+                .iter()
+                .map(|(k, v)| (key_string(k), to_json(v)))
+                .collect(),
+        ),
+        Value::Array(items) if items.len() % 2 == 0 => items
+            .chunks(2)
+            .map(|pair| match &pair[0] {
+                Value::BulkString(_) | Value::SimpleString(_) => {
+                    Some((key_string(&pair[0]), to_json(&pair[1])))
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+fn key_string(value: &Value) -> String {
+    match to_json(value) {
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,110 @@
+//! Server restart detection.
+//!
+//! Counters in INFO, SLOWLOG and LATENCY start from zero when a server
+//! restarts, so analyses comparing them against earlier readings are wrong
+//! afterwards. Each connection records the server's `run_id` at connect time;
+//! [`watch`] re-reads it periodically and the server flags tool results for a
+//! connection whose `run_id` changed.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use redis::aio::ConnectionLike;
+
+use crate::info;
+use crate::server::McpRedisServer;
+
+/// How often [`watch`] re-reads `run_id`
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Tool results are flagged for this long after a detected restart
+pub const NOTICE_WINDOW_SECS: u64 = 3600;
+
+/// The server process a connection talks to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerIdentity {
+    pub run_id: String,
+    /// Process start, in seconds since the epoch (derived from uptime)
+    pub started_at: u64,
+}
+
+impl ServerIdentity {
+    /// Build from the fields of an INFO server reply, read at `now`.
+    pub fn from_info(
+        fields: &serde_json::Map<String, serde_json::Value>,
+        now: u64,
+    ) -> Option<Self> {
+        let run_id = fields.get("run_id")?.as_str()?.to_string();
+        let uptime = fields
+            .get("uptime_in_seconds")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        Some(Self {
+            run_id,
+            started_at: now.saturating_sub(uptime),
+        })
+    }
+
+    pub fn uptime_secs(&self, now: u64) -> u64 {
+        now.saturating_sub(self.started_at)
+    }
+}
+
+/// A restart seen by [`watch`]: the server now runs with a new `run_id`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Restart {
+    pub previous_run_id: String,
+    /// When the new process started, in seconds since the epoch
+    pub at: u64,
+}
+
+impl Restart {
+    /// Notice attached to tool results, e.g. "server restarted 3m ago —
+    /// counters reset".
+    pub fn notice(&self, now: u64) -> String {
+        format!(
+            "server restarted {} ago — counters reset",
+            format_ago(now.saturating_sub(self.at))
+        )
+    }
+}
+
+/// Read the identity of the server behind `conn`.
+pub async fn detect<C: ConnectionLike + Send>(conn: &mut C) -> Option<ServerIdentity> {
+    let raw: String = redis::cmd("INFO")
+        .arg("server")
+        .query_async(conn)
+        .await
+        .ok()?;
+    ServerIdentity::from_info(&info::parse_fields(&raw), now())
+}
+
+/// Compact duration: `45s`, `3m`, `2h`, `4d`.
+pub fn format_ago(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86_399 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86_400),
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Re-check every connection's `run_id` every [`CHECK_INTERVAL`] for the
+/// lifetime of the server.
+pub async fn watch(server: McpRedisServer) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    interval.tick().await;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = server.shutdown().stopping() => return,
+        }
+        server.check_restarts().await;
+    }
+}
@@ -0,0 +1,386 @@
+//! Shared SCAN loop used by the key-listing tools.
+
+use std::collections::HashSet;
+
+use redis::aio::ConnectionLike;
+
+use crate::glob;
+use crate::version::Version;
+
+/// Maximum number of SCAN iterations as a safety valve, unless
+/// `max_scan_iterations` says otherwise
+pub const MAX_SCAN_ITERATIONS: usize = 1000;
+
+/// COUNT hint passed to each SCAN call, unless `scan_batch_count` says
+/// otherwise
+pub const SCAN_BATCH_COUNT: u32 = 100;
+
+/// First Redis version whose SCAN accepts the TYPE option
+pub const SCAN_TYPE_VERSION: Version = Version(6, 0, 0);
+
+/// Only keys of one type, e.g. `hash` or `stream`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeFilter {
+    pub key_type: String,
+    /// Check every scanned key with TYPE instead of passing TYPE to SCAN,
+    /// for servers older than [`SCAN_TYPE_VERSION`]
+    pub client_side: bool,
+}
+
+impl TypeFilter {
+    /// A filter for `key_type` (as TYPE reports it; case is ignored for the
+    /// core types), or `None` if it is blank.
+    pub fn new(key_type: &str, client_side: bool) -> Option<Self> {
+        let key_type = key_type.trim();
+        if key_type.is_empty() || key_type.contains(char::is_whitespace) {
+            return None;
+        }
+        let lower = key_type.to_ascii_lowercase();
+        let key_type = match lower.as_str() {
+            "string" | "list" | "set" | "zset" | "hash" | "stream" => lower,
+            _ => key_type.to_string(),
+        };
+        Some(Self {
+            key_type,
+            client_side,
+        })
+    }
+}
+
+/// What a paged scan keeps of the keys each SCAN call returns, beyond its
+/// MATCH pattern.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyFilter {
+    pub key_type: Option<TypeFilter>,
+    /// Keep keys matching any of these patterns, checked client-side so one
+    /// SCAN pass serves them all. Empty keeps every key
+    pub any_of: Vec<String>,
+    /// Drop keys matching any of these patterns, checked client-side
+    pub none_of: Vec<String>,
+}
+
+impl KeyFilter {
+    /// Whether `key` matches `pattern` (or, when set, any of `any_of`) and
+    /// none of the exclusions. The type is not checked.
+    pub fn keeps_name(&self, pattern: &str, key: &str) -> bool {
+        let included = if self.any_of.is_empty() {
+            glob::matches(pattern, key)
+        } else {
+            self.any_of.iter().any(|p| glob::matches(p, key))
+        };
+        included && !self.none_of.iter().any(|p| glob::matches(p, key))
+    }
+}
+
+/// How far a SCAN loop may go: the SCAN calls it makes, and the COUNT hint
+/// of each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanBudget {
+    pub max_iterations: usize,
+    pub batch_count: u32,
+}
+
+impl Default for ScanBudget {
+    fn default() -> Self {
+        Self {
+            max_iterations: MAX_SCAN_ITERATIONS,
+            batch_count: SCAN_BATCH_COUNT,
+        }
+    }
+}
+
+/// Keys collected by [`scan_matching`].
+#[derive(Debug, Default)]
+pub struct ScanOutcome {
+    /// Unique keys, in the order SCAN first returned them
+    pub keys: Vec<String>,
+    /// Keys SCAN returned more than once (possible while the dict rehashes)
+    pub duplicates_filtered: usize,
+    /// Keys dropped by the filter's exclusions. A batch resumed by the next
+    /// page is read again, so its exclusions count on both pages
+    pub excluded: usize,
+    /// Cursor to resume from; 0 when the keyspace was fully iterated
+    pub cursor: u64,
+    pub iterations: usize,
+    /// Where a later page continues, including keys of a batch that did not
+    /// fit; `None` when the keyspace was fully iterated
+    pub next: Option<Position>,
+}
+
+/// A point in a SCAN: the cursor of a batch and how many of its keys were
+/// already returned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    pub cursor: u64,
+    pub skip: usize,
+}
+
+/// Resume token of `scan_keys` and `search_keys`: `<cursor>[+<skip>]`,
+/// prefixed with `<node address>/` on a cluster. "0" starts over (and, in
+/// results, means the scan is complete).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanCursor {
+    /// Cluster master being scanned; `None` for standalone instances
+    pub node: Option<String>,
+    pub position: Position,
+}
+
+impl ScanCursor {
+    pub fn encode(&self) -> String {
+        let mut token = self.position.cursor.to_string();
+        if self.position.skip > 0 {
+            token = format!("{}+{}", token, self.position.skip);
+        }
+        match &self.node {
+            Some(node) => format!("{}/{}", node, token),
+            None => token,
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        let (node, token) = match raw.rsplit_once('/') {
+            Some((node, token)) if !node.is_empty() => (Some(node.to_string()), token),
+            Some(_) => return None,
+            None => (None, raw),
+        };
+        let (cursor, skip) = match token.split_once('+') {
+            Some((cursor, skip)) => (cursor, skip.parse().ok()?),
+            None => (token, 0),
+        };
+        Some(Self {
+            node,
+            position: Position {
+                cursor: cursor.parse().ok()?,
+                skip,
+            },
+        })
+    }
+}
+
+/// SCAN for keys matching `pattern` until `max_keys` unique keys are found,
+/// the keyspace is exhausted, or [`MAX_SCAN_ITERATIONS`] is reached, with
+/// the default [`ScanBudget`].
+///
+/// SCAN may return a key more than once, so results are deduplicated. The
+/// seen-set only ever holds the keys being returned, which keeps memory
+/// bounded by `max_keys` plus one batch.
+pub async fn scan_matching<C: ConnectionLike + Send>(
+    conn: &mut C,
+    pattern: &str,
+    max_keys: usize,
+) -> redis::RedisResult<ScanOutcome> {
+    scan_bounded(conn, pattern, max_keys, ScanBudget::default()).await
+}
+
+/// [`scan_matching`] within `budget`: the configured limits, or a quick
+/// partial answer for callers such as completion.
+pub async fn scan_bounded<C: ConnectionLike + Send>(
+    conn: &mut C,
+    pattern: &str,
+    max_keys: usize,
+    budget: ScanBudget,
+) -> redis::RedisResult<ScanOutcome> {
+    scan_page(conn, pattern, None, Position::default(), max_keys, budget).await
+}
+
+/// [`scan_bounded`] starting at `start`, for paging through a keyspace
+/// across calls. Keys are only deduplicated within a page.
+///
+/// A batch cut short by `max_keys` is resumed by repeating its SCAN call
+/// and skipping the keys already returned, so no key is lost between pages
+/// unless the keyspace changes in between (SCAN allows that anyway).
+///
+/// With a client-side `filter`, positions count the keys of a batch that
+/// passed the filter.
+pub async fn scan_page<C: ConnectionLike + Send>(
+    conn: &mut C,
+    pattern: &str,
+    filter: Option<&TypeFilter>,
+    start: Position,
+    max_keys: usize,
+    budget: ScanBudget,
+) -> redis::RedisResult<ScanOutcome> {
+    let filter = KeyFilter {
+        key_type: filter.cloned(),
+        any_of: Vec::new(),
+        none_of: Vec::new(),
+    };
+    scan_page_filtered(conn, pattern, &filter, start, max_keys, budget).await
+}
+
+/// [`scan_page`] keeping only the keys that pass `filter`.
+pub async fn scan_page_filtered<C: ConnectionLike + Send>(
+    conn: &mut C,
+    pattern: &str,
+    filter: &KeyFilter,
+    start: Position,
+    max_keys: usize,
+    budget: ScanBudget,
+) -> redis::RedisResult<ScanOutcome> {
+    let type_filter = filter.key_type.as_ref();
+    let mut outcome = ScanOutcome {
+        cursor: start.cursor,
+        ..ScanOutcome::default()
+    };
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut skip = start.skip;
+
+    loop {
+        let batch_cursor = outcome.cursor;
+        let mut cmd = redis::cmd("SCAN");
+        cmd.arg(batch_cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(budget.batch_count);
+        if let Some(filter) = type_filter.filter(|f| !f.client_side) {
+            cmd.arg("TYPE").arg(&filter.key_type);
+        }
+        let (next_cursor, mut batch): (u64, Vec<String>) = cmd.query_async(conn).await?;
+        if !filter.any_of.is_empty() {
+            batch.retain(|key| filter.any_of.iter().any(|p| glob::matches(p, key)));
+        }
+        if !filter.none_of.is_empty() {
+            let before = batch.len();
+            batch.retain(|key| !filter.none_of.iter().any(|p| glob::matches(p, key)));
+            outcome.excluded += before - batch.len();
+        }
+        if let Some(filter) = type_filter.filter(|f| f.client_side && !batch.is_empty()) {
+            let mut pipe = redis::pipe();
+            for key in &batch {
+                pipe.cmd("TYPE").arg(key);
+            }
+            let types: Vec<String> = pipe.query_async(conn).await?;
+            let mut types = types.into_iter();
+            batch.retain(|_| types.next().as_deref() == Some(filter.key_type.as_str()));
+        }
+
+        let mut taken = skip;
+        for key in batch.iter().skip(skip) {
+            if outcome.keys.len() >= max_keys {
+                break;
+            }
+            taken += 1;
+            if seen.insert(key.clone()) {
+                outcome.keys.push(key.clone());
+            } else {
+                outcome.duplicates_filtered += 1;
+            }
+        }
+        skip = 0;
+        outcome.cursor = next_cursor;
+        outcome.iterations += 1;
+
+        if taken < batch.len() {
+            outcome.next = Some(Position {
+                cursor: batch_cursor,
+                skip: taken,
+            });
+            break;
+        }
+        if outcome.cursor == 0 {
+            break;
+        }
+        if outcome.keys.len() >= max_keys || outcome.iterations >= budget.max_iterations {
+            outcome.next = Some(Position {
+                cursor: next_cursor,
+                skip: 0,
+            });
+            break;
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// Share of the keyspace a SCAN resuming at `cursor` has already covered,
+/// from 0.0 to 1.0. SCAN walks hash table buckets in reverse-binary order,
+/// so the bit-reversed cursor over 2^64 is the share of buckets visited
+/// whatever the table size. A cursor of 0 is the start: use
+/// [`ScanOutcome::next`] to tell a finished scan from one not begun.
+pub fn scanned_fraction(cursor: u64) -> f64 {
+    cursor.reverse_bits() as f64 / 2f64.powi(64)
+}
+
+/// Collect keys from `samples` SCAN calls, each started at a random cursor.
+///
+/// SCAN cursors address hash table buckets, so a random cursor below the
+/// table size lands on a random region of the keyspace. Keys seen by more
+/// than one sample are counted once.
+pub async fn sample_random_keys<C: ConnectionLike + Send>(
+    conn: &mut C,
+    dbsize: u64,
+    samples: usize,
+    batch_count: u32,
+) -> redis::RedisResult<Vec<String>> {
+    let table_size = dbsize.max(1).next_power_of_two();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut keys = Vec::new();
+
+    for _ in 0..samples {
+        let cursor = fastrand::u64(0..table_size);
+        let (_, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("COUNT")
+            .arg(batch_count)
+            .query_async(conn)
+            .await?;
+        for key in batch {
+            if seen.insert(key.clone()) {
+                keys.push(key);
+            }
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Wilson score interval for a proportion of `hits` out of `n` trials, at the
+/// confidence level given by the normal quantile `z` (1.96 for 95%).
+pub fn wilson_interval(hits: usize, n: usize, z: f64) -> (f64, f64) {
+    if n == 0 {
+        return (0.0, 1.0);
+    }
+    let n = n as f64;
+    let p = hits as f64 / n;
+    let z2 = z * z;
+    let denom = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denom;
+    let margin = z * ((p * (1.0 - p) / n) + z2 / (4.0 * n * n)).sqrt() / denom;
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+/// The keys matching each of `patterns`, in pattern order. A key matching
+/// several patterns is listed under each.
+pub fn group_by_pattern<'a>(patterns: &'a [String], keys: &[&str]) -> Vec<(&'a str, Vec<String>)> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            let matching = keys
+                .iter()
+                .filter(|key| glob::matches(pattern, key))
+                .map(|key| key.to_string())
+                .collect();
+            (pattern.as_str(), matching)
+        })
+        .collect()
+}
+
+/// Namespace prefixes one segment past `typed`: for keys starting with
+/// `typed`, everything up to and including the next `:`, or the whole key
+/// when there is none. Sorted and deduplicated.
+pub fn next_prefixes(keys: &[String], typed: &str) -> Vec<String> {
+    let mut prefixes: Vec<String> = keys
+        .iter()
+        .filter_map(|key| {
+            let rest = key.strip_prefix(typed)?;
+            Some(match rest.find(':') {
+                Some(at) => format!("{}{}", typed, &rest[..=at]),
+                None => key.clone(),
+            })
+        })
+        .collect();
+    prefixes.sort();
+    prefixes.dedup();
+    prefixes
+}
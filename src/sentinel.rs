@@ -0,0 +1,289 @@
+//! Redis Sentinel support: `redis+sentinel://` URLs, master discovery, and
+//! following failovers.
+//!
+//! URL format:
+//! `redis+sentinel://[[user]:password@]host:port[,host:port...]/master_name[/db]`
+//! (`rediss+sentinel://` for TLS, `sentinel://` is accepted as an alias). Credentials apply to the master; the
+//! sentinels themselves are contacted without authentication.
+
+use std::time::Duration;
+
+use redis::sentinel::{Sentinel, SentinelNodeConnectionInfo};
+
+use crate::server::{McpRedisServer, LAZY_CONNECT_TIMEOUT};
+use crate::{info, resp};
+
+/// How often the master address is re-resolved through Sentinel
+pub const FAILOVER_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default Sentinel port when a host is given without one
+const DEFAULT_SENTINEL_PORT: u16 = 26379;
+
+/// SENTINEL reply fields kept as strings even when they look numeric
+const TEXT_FIELDS: &[&str] = &["name", "ip", "runid", "master-host"];
+
+/// A parsed `redis+sentinel://` URL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SentinelUrl {
+    pub sentinels: Vec<(String, u16)>,
+    pub master_name: String,
+    pub db: i64,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub tls: bool,
+    /// Skip TLS certificate verification (`--tls-insecure`)
+    pub insecure: bool,
+}
+
+/// Whether `url` uses a Sentinel scheme.
+pub fn is_sentinel_url(url: &str) -> bool {
+    ["redis+sentinel://", "rediss+sentinel://", "sentinel://"]
+        .iter()
+        .any(|scheme| url.starts_with(scheme))
+}
+
+impl SentinelUrl {
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let (rest, tls) = if let Some(rest) = url
+            .strip_prefix("redis+sentinel://")
+            .or_else(|| url.strip_prefix("sentinel://"))
+        {
+            (rest, false)
+        } else if let Some(rest) = url.strip_prefix("rediss+sentinel://") {
+            (rest, true)
+        } else {
+            return Err("expected a redis+sentinel:// or rediss+sentinel:// URL".to_string());
+        };
+
+        let (authority, path) = rest
+            .split_once('/')
+            .ok_or_else(|| "missing master name (e.g. /mymaster)".to_string())?;
+
+        let (credentials, hosts) = match authority.rsplit_once('@') {
+            Some((credentials, hosts)) => (Some(credentials), hosts),
+            None => (None, authority),
+        };
+        let (username, password) = match credentials {
+            Some(credentials) => match credentials.split_once(':') {
+                Some((user, pass)) => (
+                    Some(user.to_string()).filter(|u| !u.is_empty()),
+                    Some(pass.to_string()).filter(|p| !p.is_empty()),
+                ),
+                None => (
+                    Some(credentials.to_string()).filter(|u| !u.is_empty()),
+                    None,
+                ),
+            },
+            None => (None, None),
+        };
+
+        let sentinels = hosts
+            .split(',')
+            .filter(|h| !h.is_empty())
+            .map(|host| match host.rsplit_once(':') {
+                Some((h, p)) => p
+                    .parse::<u16>()
+                    .map(|p| (h.to_string(), p))
+                    .map_err(|_| format!("invalid sentinel port in '{}'", host)),
+                None => Ok((host.to_string(), DEFAULT_SENTINEL_PORT)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if sentinels.is_empty() {
+            return Err("at least one sentinel host is required".to_string());
+        }
+
+        let mut segments = path.split('/').filter(|s| !s.is_empty());
+        let master_name = segments
+            .next()
+            .ok_or_else(|| "missing master name (e.g. /mymaster)".to_string())?
+            .to_string();
+        let db = match segments.next() {
+            Some(db) => db
+                .parse::<i64>()
+                .map_err(|_| format!("invalid database index '{}'", db))?,
+            None => 0,
+        };
+
+        Ok(Self {
+            sentinels,
+            master_name,
+            db,
+            username,
+            password,
+            tls,
+            insecure: false,
+        })
+    }
+
+    /// The URL with the password masked, for display.
+    pub fn redacted(&self) -> String {
+        let scheme = if self.tls {
+            "rediss+sentinel"
+        } else {
+            "redis+sentinel"
+        };
+        let credentials = match (&self.username, &self.password) {
+            (Some(user), Some(_)) => format!("{}:***@", user),
+            (None, Some(_)) => ":***@".to_string(),
+            (Some(user), None) => format!("{}@", user),
+            (None, None) => String::new(),
+        };
+        let hosts: Vec<String> = self
+            .sentinels
+            .iter()
+            .map(|(h, p)| format!("{}:{}", h, p))
+            .collect();
+        let db = if self.db == 0 {
+            String::new()
+        } else {
+            format!("/{}", self.db)
+        };
+        format!(
+            "{}://{}{}/{}{}",
+            scheme,
+            credentials,
+            hosts.join(","),
+            self.master_name,
+            db
+        )
+    }
+
+    fn sentinel_urls(&self) -> Vec<String> {
+        let scheme = if self.tls { "rediss" } else { "redis" };
+        let fragment = if self.tls && self.insecure {
+            "#insecure"
+        } else {
+            ""
+        };
+        self.sentinels
+            .iter()
+            .map(|(h, p)| format!("{}://{}:{}{}", scheme, h, p, fragment))
+            .collect()
+    }
+
+    fn node_connection_info(&self) -> SentinelNodeConnectionInfo {
+        SentinelNodeConnectionInfo {
+            tls_mode: match (self.tls, self.insecure) {
+                (false, _) => None,
+                (true, false) => Some(redis::TlsMode::Secure),
+                (true, true) => Some(redis::TlsMode::Insecure),
+            },
+            redis_connection_info: Some(redis::RedisConnectionInfo {
+                db: self.db,
+                username: self.username.clone(),
+                password: self.password.clone(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Ask the sentinels for the current master and return a client for it.
+    pub async fn resolve_master(&self) -> redis::RedisResult<redis::Client> {
+        let mut sentinel = Sentinel::build(self.sentinel_urls())?;
+        sentinel
+            .async_master_for(&self.master_name, Some(&self.node_connection_info()))
+            .await
+    }
+
+    /// Open a connection to the first reachable sentinel.
+    pub async fn connect_sentinel(&self) -> redis::RedisResult<redis::aio::MultiplexedConnection> {
+        let mut last_err = None;
+        for url in self.sentinel_urls() {
+            let client = redis::Client::open(url.as_str())?;
+            match client.get_multiplexed_async_connection().await {
+                Ok(conn) => return Ok(conn),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            redis::RedisError::from((redis::ErrorKind::IoError, "no sentinels configured"))
+        }))
+    }
+}
+
+/// Parse a SENTINEL MASTERS / SENTINEL REPLICAS reply (a list of field maps).
+/// Numeric fields become numbers (identifiers always stay strings) and
+/// `flags` is split into a list.
+pub fn parse_entries(value: &redis::Value) -> Vec<serde_json::Value> {
+    let redis::Value::Array(items) = value else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(resp::to_object)
+        .map(|fields| {
+            let entry: serde_json::Map<String, serde_json::Value> = fields
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = match value {
+                        serde_json::Value::String(s) if name == "flags" => {
+                            serde_json::json!(s.split(',').collect::<Vec<_>>())
+                        }
+                        serde_json::Value::String(s) if !TEXT_FIELDS.contains(&name.as_str()) => {
+                            info::parse_value(&s)
+                        }
+                        other => other,
+                    };
+                    (name, value)
+                })
+                .collect();
+            serde_json::Value::Object(entry)
+        })
+        .collect()
+}
+
+/// Re-resolve the master of `name` every [`FAILOVER_CHECK_INTERVAL`] and swap
+/// in a fresh connection when Sentinel reports a different address.
+pub async fn follow_failovers(server: McpRedisServer, name: String, url: SentinelUrl) {
+    let mut interval = tokio::time::interval(FAILOVER_CHECK_INTERVAL);
+    interval.tick().await;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = server.shutdown().stopping() => return,
+        }
+        let client = match url.resolve_master().await {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!(connection = %name, error = %e, "Sentinel master lookup failed");
+                continue;
+            }
+        };
+        let Some(current) = server.connection_addr(&name) else {
+            if server.is_pending(&name) {
+                // Not opened yet (--connect-lazy)
+                continue;
+            }
+            // The connection was removed
+            return;
+        };
+        if client.get_connection_info().addr == current {
+            continue;
+        }
+        // A master that accepts TCP but never answers is retried next check
+        let connect = tokio::time::timeout(
+            LAZY_CONNECT_TIMEOUT,
+            redis::aio::ConnectionManager::new(client.clone()),
+        );
+        match connect.await {
+            Ok(Ok(conn)) => {
+                tracing::warn!(
+                    connection = %name,
+                    master = %client.get_connection_info().addr,
+                    "Sentinel failover detected, switched to new master"
+                );
+                server.swap_connection(&name, conn, client).await;
+            }
+            Ok(Err(e)) => {
+                tracing::warn!(connection = %name, error = %e, "Cannot connect to new master");
+            }
+            Err(_) => {
+                tracing::warn!(
+                    connection = %name,
+                    master = %client.get_connection_info().addr,
+                    "Timed out connecting to new master"
+                );
+            }
+        }
+    }
+}
@@ -1,31 +1,462 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
+use futures_util::future::BoxFuture;
+use futures_util::StreamExt;
 use rmcp::handler::server::router::tool::ToolRouter;
+use rmcp::handler::server::tool::ToolCallContext;
 use rmcp::handler::server::wrapper::Parameters;
 use rmcp::model::*;
-use rmcp::{schemars, tool, tool_handler, tool_router, ServerHandler};
+use rmcp::service::RequestContext;
+use rmcp::{schemars, tool, tool_router, Peer, RoleServer, ServerHandler};
 use serde::Deserialize;
 
+use crate::config::{KeyContract, KeyReference, Limits, Preset, ReplicaHook, ToolDefaults, View};
+use crate::cursors::{Cursors, SavedCursor};
 use crate::error::McpRedisError;
+use crate::keyspace::Subscriptions;
+use crate::maintenance::Notes;
+use crate::notify::Notifier;
+use crate::restart::{self, Restart, ServerIdentity};
+use crate::sentinel::SentinelUrl;
+use crate::shutdown::Shutdown;
+use crate::stats::{Cost, ToolStats};
+use crate::trace::{CallTrace, TracedConnection};
+use crate::transcript::{self, TranscriptFormat, Transcripts};
+use crate::version::ServerVersion;
+use crate::{
+    acl, backlog, budget, busy, cardinality, census, clients, cluster, compaction, consistency,
+    contract, cursors, format, glob, glossary, info, keyspace, latency, monitor, prompts, rdb,
+    redact, references, replication, resource, resp, scan, settings, stream, time, verify, version,
+    view,
+};
 
-/// Maximum number of SCAN iterations as a safety valve
-const MAX_SCAN_ITERATIONS: usize = 1000;
+/// Keys returned by `find_big_keys` at most
+const MAX_BIG_KEYS: usize = 100;
+
+/// MEMORY USAGE samples per collection when scans include key metadata
+const METADATA_MEMORY_SAMPLES: u32 = 5;
+
+/// Entries `get` returns with `summary_only`
+const SUMMARY_SAMPLE_ENTRIES: usize = 5;
+
+/// Bytes of a string `get` returns with `summary_only`
+const SUMMARY_STRING_PREFIX: usize = 64;
+
+/// Hops `get` follows with `follow_references` at most
+const MAX_REFERENCE_DEPTH: u32 = 3;
+
+/// Elements read of each key reached with `follow_references`
+const REFERENCE_ENTRIES: usize = 100;
+
+/// Elements read of each collection by `get_multiple`
+const MULTI_GET_ENTRIES: usize = 100;
+
+/// How long `scan_keys` and `search_keys` keep scanning, with a growing
+/// COUNT, after a page came back empty with the scan unfinished
+const EMPTY_SCAN_RETRY_TIME: Duration = Duration::from_secs(2);
+
+/// SCAN calls per retry of an empty page; COUNT grows tenfold each retry
+const EMPTY_SCAN_RETRY_ITERATIONS: usize = 10;
+
+/// Largest COUNT hint a retried empty page grows to
+const MAX_RETRY_SCAN_COUNT: u32 = 10_000;
+
+/// Random SCAN calls sampled to estimate matches when a scan ends with none
+const EMPTY_SCAN_SAMPLES: usize = 20;
+
+/// Patterns matched, or excluded, by a single `scan_keys` or `search_keys`
+const MAX_SCAN_PATTERNS: usize = 20;
+
+/// How long `check_replica_consistency` waits before reading mismatched
+/// keys again, so writes still in flight to the replica are not reported
+const CONSISTENCY_RECHECK_DELAY: Duration = Duration::from_secs(1);
+
+/// First Redis version with ACL DRYRUN, behind `acl_check`
+const ACL_DRYRUN_VERSION: version::Version = version::Version(7, 0, 0);
+
+/// Timeline entries returned by `latency_correlation`; older ones are dropped
+const MAX_TIMELINE: usize = 200;
+
+/// Longest `interval_secs` of `error_stats`
+const MAX_ERROR_STATS_INTERVAL: u64 = 60;
+
+/// Longest `interval_secs` of `info_delta`
+const MAX_INFO_DELTA_INTERVAL: u64 = 10;
+
+/// Longest `duration_secs` of `sample_stats`
+const MAX_SAMPLE_STATS_SECS: u64 = 60;
+
+/// Commands with failures listed by `error_stats`
+const MAX_FAILING_COMMANDS: usize = 20;
+
+/// PINGs timed by `connection_diagnostics`
+const DIAGNOSTIC_PINGS: usize = 5;
+
+/// Round trip above which `connection_diagnostics` calls the connection
+/// slow, in milliseconds
+const SLOW_PING_MS: f64 = 10.0;
+
+/// Patterns `memory_by_pattern` takes in one call
+const MAX_MEMORY_PATTERNS: usize = 20;
+
+/// Prefixes listed by `analyze_namespaces`; the rest are counted
+const MAX_REPORTED_PREFIXES: usize = 50;
+
+/// Invalid keys listed in full by `validate_namespace`; the rest are counted
+const MAX_REPORTED_VIOLATIONS: usize = 50;
+
+/// SCAN calls made to complete a key, prefix or pattern argument; enough to
+/// look at a few thousand keys without holding up the client
+const COMPLETION_SCAN_ITERATIONS: usize = 20;
+
+/// Keys collected while completing a key, prefix or pattern argument
+const COMPLETION_SCAN_KEYS: usize = 1000;
+
+/// Notifications for the same key within this interval are coalesced
+const RESOURCE_NOTIFY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Keys per batch streamed from the RDB reader by `rdb_scan` (and per
+/// TYPE pipeline when comparing against the live server)
+const RDB_BATCH: usize = 100;
+
+/// A key listed by `rdb_scan`, without its value
+struct RdbKey {
+    key: String,
+    key_type: &'static str,
+    length: Option<u64>,
+    expire_ms: Option<i64>,
+}
+
+/// A single `--connect-lazy` connection attempt is abandoned after this, as
+/// are on-demand connections to a database, a cluster node, a newly
+/// promoted Sentinel master or with refreshed credentials
+pub(crate) const LAZY_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Wait after the first failed lazy connection attempt, doubled per failure
+const LAZY_CONNECT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Upper bound for the wait between lazy connection attempts
+const LAZY_CONNECT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A PING taking longer than this counts as failed
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Expensive tools first check that their server answers PING within this
+const BUSY_PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Expensive tools that read local files and need no server to run
+const OFFLINE_TOOLS: &[&str] = &["rdb_scan", "rdb_get"];
+
+/// Tools that take a `db` argument to work on another logical database
+const DB_TOOLS: &[&str] = &[
+    "scan_keys",
+    "get",
+    "get_multiple",
+    "exists",
+    "get_string_range",
+    "key_length",
+    "key_info",
+    "dbsize",
+    "search_keys",
+    "get_hash_fields",
+    "hash_overview",
+    "get_list_range",
+    "get_set_members",
+    "scan_set_members",
+    "scan_zset_members",
+    "sample_members",
+    "zset_lex_range",
+    "find_member",
+    "estimate_cardinality",
+    "estimate_matching_keys",
+    "namespace_census",
+    "analyze_namespaces",
+    "memory_by_pattern",
+    "compaction_advisor",
+    "find_big_keys",
+    "validate_key",
+    "validate_namespace",
+    "stream_range",
+    "stream_autoclaim",
+];
+
+/// Tools that take the `all_dbs` argument to run once per database
+const ALL_DBS_TOOLS: &[&str] = &[
+    "scan_keys",
+    "dbsize",
+    "search_keys",
+    "estimate_matching_keys",
+    "namespace_census",
+    "analyze_namespaces",
+    "find_big_keys",
+    "validate_namespace",
+];
+
+tokio::task_local! {
+    /// Database chosen by the `db` argument of the running call
+    static SELECTED_DB: SelectedDb;
+}
+
+/// How long a `bulk_delete` confirmation token stays valid
+const DELETE_TOKEN_TTL: Duration = Duration::from_secs(300);
+
+/// Matching keys listed per connection in a `bulk_delete` plan
+const DELETE_PLAN_SAMPLE: usize = 10;
 
 #[derive(Clone)]
 pub struct RedisConnection {
     pub name: String,
     pub url_redacted: String,
     pub conn: redis::aio::ConnectionManager,
+    /// Client used to open dedicated connections (e.g. PubSub), which
+    /// ConnectionManager cannot provide.
+    pub client: redis::Client,
+    /// Server flavor and version detected at connect time
+    pub version: Option<ServerVersion>,
+    /// Write tools are refused on this connection even with --allow-write
+    pub read_only: bool,
+    pub tags: Vec<String>,
+    /// Server process seen at connect time, updated by [`restart::watch`]
+    pub identity: Option<ServerIdentity>,
+    /// Set once the server's `run_id` changed while connected
+    pub restart: Option<Restart>,
+    /// Times the connection was repointed (failover, new credentials,
+    /// `replace_connection`)
+    pub replaced: u32,
+}
+
+impl RedisConnection {
+    pub fn new(
+        name: String,
+        url_redacted: String,
+        conn: redis::aio::ConnectionManager,
+        client: redis::Client,
+    ) -> Self {
+        Self {
+            name,
+            url_redacted,
+            conn,
+            client,
+            version: None,
+            read_only: false,
+            tags: Vec::new(),
+            identity: None,
+            restart: None,
+            replaced: 0,
+        }
+    }
+
+    /// Connection handle for issuing commands. Records into the active
+    /// per-call trace when one is running.
+    pub fn connection(&self) -> TracedConnection {
+        TracedConnection::new(self.conn.clone())
+    }
+}
+
+/// A connection with another logical database selected, for the `db`
+/// argument.
+#[derive(Clone)]
+struct DbConnection {
+    /// Connection settings it was derived from; a change (failover, new
+    /// credentials) opens it again
+    base: redis::ConnectionInfo,
+    conn: redis::aio::ConnectionManager,
+    client: redis::Client,
+}
+
+impl DbConnection {
+    fn derived_from(&self, info: &redis::ConnectionInfo) -> bool {
+        self.base.addr == info.addr
+            && self.base.redis.username == info.redis.username
+            && self.base.redis.password == info.redis.password
+    }
+}
+
+/// The database selected for one connection during a call.
+#[derive(Clone)]
+struct SelectedDb {
+    name: String,
+    db: DbConnection,
+}
+
+/// Opens a connection on demand; used for `--connect-lazy` targets.
+pub type Connector =
+    Arc<dyn Fn() -> BoxFuture<'static, Result<RedisConnection, String>> + Send + Sync>;
+
+/// Re-reads the config file and applies it to the running server, returning
+/// a summary of what changed. Used by `reload_config` and on SIGHUP.
+pub type Reloader = Arc<
+    dyn Fn(McpRedisServer) -> BoxFuture<'static, Result<serde_json::Value, String>> + Send + Sync,
+>;
+
+/// A connection that has not been established yet. It is opened the first
+/// time a tool uses it; failed attempts are retried on later use with
+/// exponential backoff.
+pub struct PendingConnection {
+    pub name: String,
+    pub url_redacted: String,
+    connector: Connector,
+    attempts: u32,
+    last_error: Option<String>,
+    /// No new attempt is made before this
+    retry_at: Option<Instant>,
+    in_progress: bool,
+}
+
+impl PendingConnection {
+    pub fn new(name: String, url_redacted: String, connector: Connector) -> Self {
+        Self {
+            name,
+            url_redacted,
+            connector,
+            attempts: 0,
+            last_error: None,
+            retry_at: None,
+            in_progress: false,
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        if self.last_error.is_some() && !self.in_progress {
+            "error"
+        } else {
+            "connecting"
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct McpRedisServer {
-    connections: Arc<Vec<RedisConnection>>,
+    /// Behind a lock so Sentinel-backed entries can be repointed at a new
+    /// master after a failover.
+    connections: Arc<RwLock<Vec<RedisConnection>>>,
+    /// Sentinel configuration of connections opened from `redis+sentinel://`
+    /// URLs, keyed by connection name.
+    sentinels: Arc<RwLock<HashMap<String, SentinelUrl>>>,
     allow_write: bool,
+    allow_trace: bool,
+    /// Enables tools that change infrastructure or the running configuration
+    /// (`create_analysis_replica`, `reload_config`, `replace_connection`)
+    allow_admin: bool,
     scan_count: u32,
+    /// Value data `get` returns before truncating (`--max-value-bytes`)
+    max_value_bytes: u64,
+    /// Directory that file-producing tools may write into
+    export_dir: Option<PathBuf>,
+    /// Dump read by `rdb_scan` and `rdb_get`
+    rdb_path: Option<PathBuf>,
+    /// Saved tool sequences from the config file, exposed as extra tools.
+    /// Replaced as a whole when the config is reloaded.
+    presets: Arc<RwLock<Arc<Vec<Preset>>>>,
+    /// Key sets from the config file read by generated tools, replaced on
+    /// reload
+    views: Arc<RwLock<Arc<Vec<View>>>>,
+    /// Expected key shapes from the config file, replaced on reload
+    contracts: Arc<RwLock<Arc<Vec<KeyContract>>>>,
+    /// Key reference patterns from the config file, replaced on reload
+    references: Arc<RwLock<Arc<references::Matcher>>>,
+    /// Parameters `config_set` may change, replaced on reload
+    config_set_allow: Arc<RwLock<Arc<Vec<String>>>>,
+    /// `[tool_defaults]` from the config file, replaced on reload
+    tool_defaults: Arc<RwLock<Arc<ToolDefaults>>>,
+    /// Command run by `create_analysis_replica`
+    replica_hook: Option<ReplicaHook>,
+    limits: Arc<RwLock<Limits>>,
+    /// Re-reads the config file (`reload_config`, SIGHUP)
+    reloader: Option<Reloader>,
+    /// Connections to the other nodes of clustered instances, keyed by
+    /// `<connection name>/<node address>`, opened on first use.
+    node_conns: Arc<tokio::sync::Mutex<HashMap<String, redis::aio::ConnectionManager>>>,
+    /// Call counts and latencies per tool, behind the cost hints in `get_info`
+    stats: ToolStats,
+    /// Connections opened on first use (`--connect-lazy`)
+    pending: Arc<std::sync::Mutex<Vec<PendingConnection>>>,
+    /// Tracks tool calls in flight so shutdown can drain them
+    shutdown: Shutdown,
+    /// `bulk_delete` plans awaiting confirmation, keyed by token
+    pending_deletes: Arc<std::sync::Mutex<HashMap<String, DeletePlan>>>,
+    /// Notes set by `set_maintenance_note`, attached to results until they
+    /// expire
+    maintenance: Notes,
+    /// Receives an event for every write or admin call (`--notify-webhook`)
+    notifier: Option<Notifier>,
+    /// Tool calls per session, for `export_session` (`--record-session`)
+    transcripts: Option<Transcripts>,
+    /// `resources/subscribe` watchers, by session and URI
+    subscriptions: Subscriptions,
+    /// Cursors saved by `save_cursor`
+    cursors: Cursors,
+    /// Connections opened for the `db` argument, by connection name and
+    /// database
+    db_connections: Arc<std::sync::Mutex<HashMap<(String, i64), DbConnection>>>,
+    /// CLIENT ID last seen by `connection_diagnostics`, by connection name
+    client_ids: Arc<std::sync::Mutex<HashMap<String, SeenClientId>>>,
     tool_router: ToolRouter<Self>,
 }
 
+/// The server-side id of a connection as `connection_diagnostics` last saw
+/// it. A new id means ConnectionManager reconnected in between.
+#[derive(Debug, Clone, Copy)]
+struct SeenClientId {
+    id: i64,
+    changes: u32,
+}
+
+/// A write or admin call in progress, reported to the webhook once it
+/// finishes.
+struct PendingChange {
+    event: serde_json::Value,
+    connection: Option<String>,
+    key: Option<String>,
+    started: Instant,
+}
+
+/// A planned `bulk_delete`, kept until it is confirmed or expires.
+struct DeletePlan {
+    pattern: String,
+    /// Connection names with the number of matches counted when planning
+    matches: Vec<(String, u64)>,
+    created: Instant,
+}
+
+/// Sends progress notifications for a tool call whose client asked for them.
+#[derive(Clone)]
+pub struct ProgressReporter {
+    peer: Peer<RoleServer>,
+    token: ProgressToken,
+}
+
+impl ProgressReporter {
+    pub fn new(peer: Peer<RoleServer>, token: ProgressToken) -> Self {
+        Self { peer, token }
+    }
+
+    /// Best effort: a client that went away must not fail the operation.
+    async fn report(&self, progress: u64, total: Option<u64>, message: String) {
+        let _ = self
+            .peer
+            .notify_progress(ProgressNotificationParam {
+                progress_token: self.token.clone(),
+                progress: progress as f64,
+                total: total.map(|t| t as f64),
+                message: Some(message),
+            })
+            .await;
+    }
+}
+
+/// Result of scanning one node: the connection itself, or one master of a cluster.
+struct ShardScan {
+    /// Node address for cluster masters; `None` for standalone instances
+    shard: Option<String>,
+    conn: TracedConnection,
+    outcome: scan::ScanOutcome,
+}
+
 // -- Tool parameter types --
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -42,10 +473,56 @@ pub struct InfoParams {
     pub connection: Option<String>,
 
     #[schemars(
-        description = "Info section to retrieve (e.g. 'memory', 'stats', 'keyspace', 'server'). Default: all"
+        description = "Single info section to retrieve (e.g. 'memory', 'stats', 'keyspace', 'server'). Default: all sections"
     )]
     #[serde(default)]
     pub section: Option<String>,
+
+    #[schemars(
+        description = "Sections to keep in the parsed form, e.g. ['memory', 'clients'] (default: all). Naming 'commandstats' or 'latencystats' includes them"
+    )]
+    #[serde(default)]
+    pub sections: Option<Vec<String>>,
+
+    #[schemars(
+        description = "Include the long per-command sections (commandstats, latencystats), each as its own block (default: false)"
+    )]
+    #[serde(default)]
+    pub include_large: Option<bool>,
+
+    #[schemars(
+        description = "Return the single 'section' as the raw INFO text rather than parsed JSON (default: false)"
+    )]
+    #[serde(default)]
+    pub raw: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ConfigGetParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Glob over parameter names, e.g. 'maxmemory*', 'save' or '*timeout*' (default: '*')"
+    )]
+    #[serde(default)]
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ConfigSetParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Parameter to change, e.g. 'maxmemory-policy'; must be on the server's config_set allowlist"
+    )]
+    pub parameter: String,
+
+    #[schemars(description = "New value, as CONFIG SET takes it, e.g. 'allkeys-lru' or '2gb'")]
+    pub value: String,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -58,9 +535,51 @@ pub struct ScanParams {
     #[serde(default)]
     pub pattern: Option<String>,
 
+    #[schemars(
+        description = "Several patterns matched in one SCAN pass instead of pattern, e.g. ['app:user:*', 'app:order:*'] (max 20). Results also come grouped per pattern"
+    )]
+    #[serde(default)]
+    pub patterns: Option<Vec<String>>,
+
+    #[schemars(
+        description = "Leave out keys matching any of these patterns, e.g. ['job:archive:*'] with pattern 'job:*' (max 20). The number left out is reported as excluded"
+    )]
+    #[serde(default)]
+    pub exclude_patterns: Option<Vec<String>>,
+
     #[schemars(description = "Maximum number of keys to return")]
     #[serde(default)]
     pub count: Option<u32>,
+
+    #[schemars(
+        description = "Cursor from a previous call with the same pattern, to fetch the next page. Omit (or pass '0') to start from the beginning"
+    )]
+    #[serde(default)]
+    pub cursor: Option<String>,
+
+    #[schemars(
+        description = "Only return keys of this type: string, list, set, zset, hash, stream (or a module type such as ReJSON-RL)"
+    )]
+    #[serde(default, rename = "type")]
+    pub key_type: Option<String>,
+
+    #[schemars(
+        description = "Treat pattern as a literal key name: *, ?, [, ] and \\ are escaped so only that exact key matches. Use for keys containing glob characters"
+    )]
+    #[serde(default)]
+    pub escape_glob: Option<bool>,
+
+    #[schemars(
+        description = "Return each key with its type, TTL in seconds and memory in bytes (MEMORY USAGE, sampled), fetched in one pipeline (default: false)"
+    )]
+    #[serde(default)]
+    pub include_metadata: Option<bool>,
+
+    #[schemars(
+        description = "Text rendering: 'json' (default), 'table' (markdown) or 'plain' (one line per item). Structured content is always JSON"
+    )]
+    #[serde(default)]
+    pub format: Option<format::Format>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -73,6 +592,92 @@ pub struct KeyParams {
     pub key: String,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetMultipleParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Keys to read (at most max_get_keys)")]
+    pub keys: Vec<String>,
+
+    #[schemars(
+        description = "Bytes of value data to return across all keys (default: the server's --max-value-bytes); values past it come back without their value"
+    )]
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ExistsParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Keys to check (at most max_exists_keys)")]
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Key name to read")]
+    pub key: String,
+
+    #[schemars(
+        description = "Bytes of value data to return before truncating (default: the server's --max-value-bytes)"
+    )]
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+
+    #[schemars(
+        description = "Return length, TTL, encoding, memory and a few sampled entries instead of the value (default: false). Cheap on any size of key"
+    )]
+    #[serde(default)]
+    pub summary_only: Option<bool>,
+
+    #[schemars(
+        description = "Also read the keys named inside the value, found with the [[references]] patterns of the config file, in bounded form (default: false; not with summary_only)"
+    )]
+    #[serde(default)]
+    pub follow_references: Option<bool>,
+
+    #[schemars(
+        description = "How many hops of references to follow: 1 reads the keys the value names, 2 also the keys those name (default: 1, max: 3)"
+    )]
+    #[serde(default)]
+    pub reference_depth: Option<u32>,
+}
+
+/// Deserialize a list parameter from a JSON array or, for backward
+/// compatibility, from a string (see [`split_list`]).
+fn string_list<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        List(Vec<String>),
+        Text(String),
+    }
+    Ok(match Raw::deserialize(deserializer)? {
+        Raw::List(items) => items,
+        Raw::Text(text) => split_list(&text),
+    })
+}
+
+/// Split the legacy string form of a list parameter on commas only, so
+/// "first name" stays one item. Items are trimmed and empty ones dropped.
+/// Items that themselves contain commas need the array form.
+pub fn split_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct HashFieldParams {
     #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
@@ -82,8 +687,29 @@ pub struct HashFieldParams {
     #[schemars(description = "Hash key name")]
     pub key: String,
 
-    #[schemars(description = "Comma-separated field names to retrieve")]
-    pub fields: String,
+    #[schemars(description = "Field names to retrieve. A comma-separated string is also accepted")]
+    #[serde(deserialize_with = "string_list")]
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct HashOverviewParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Hash key name")]
+    pub key: String,
+
+    #[schemars(
+        description = "Fields to report existence and value length (HSTRLEN) for. A comma-separated string is also accepted"
+    )]
+    #[serde(default, deserialize_with = "string_list")]
+    pub fields: Vec<String>,
+
+    #[schemars(description = "Number of field names to sample (default: 20, max: 1000)")]
+    #[serde(default)]
+    pub sample: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -104,6 +730,28 @@ pub struct ListRangeParams {
     pub stop: Option<i64>,
 }
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct StringRangeParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "String key name")]
+    pub key: String,
+
+    #[schemars(
+        description = "Byte offset to start at; negative counts from the end, e.g. -100 for the last 100 bytes (default: 0)"
+    )]
+    #[serde(default)]
+    pub offset: Option<i64>,
+
+    #[schemars(
+        description = "Bytes to read (default and maximum: the server's --max-value-bytes)"
+    )]
+    #[serde(default)]
+    pub length: Option<u64>,
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct SetMembersParams {
     #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
@@ -113,768 +761,10883 @@ pub struct SetMembersParams {
     #[schemars(description = "Set or sorted set key name")]
     pub key: String,
 
-    #[schemars(description = "Maximum number of members to return (for large sets)")]
+    #[schemars(
+        description = "Number of members to skip (default: 0). Sorted sets page by rank; on a set the skipped members are scanned again for every page, and pages may repeat or miss members while the set changes, so page sets with scan_set_members instead"
+    )]
+    #[serde(default)]
+    pub offset: Option<u64>,
+
+    #[schemars(description = "Maximum number of members to return (default: server scan count)")]
+    #[serde(default)]
+    pub limit: Option<u64>,
+
+    #[schemars(
+        description = "Sorted sets only: order from highest to lowest score (default: false)"
+    )]
+    #[serde(default)]
+    pub reverse: Option<bool>,
+
+    #[schemars(description = "Deprecated alias for 'limit'")]
     #[serde(default)]
     pub count: Option<i64>,
+
+    #[schemars(
+        description = "Text rendering: 'json' (default), 'table' (markdown) or 'plain' (one line per item). Structured content is always JSON"
+    )]
+    #[serde(default)]
+    pub format: Option<format::Format>,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
-pub struct SlowlogParams {
+pub struct LexRangeParams {
     #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
     #[serde(default)]
     pub connection: Option<String>,
 
-    #[schemars(description = "Number of entries to return (default: 10)")]
+    #[schemars(description = "Sorted set key name (members sharing one score, e.g. 0)")]
+    pub key: String,
+
+    #[schemars(
+        description = "Lower bound: '-', '[value' (inclusive) or '(value' (exclusive); a bare value is inclusive (default: '-')"
+    )]
     #[serde(default)]
-    pub count: Option<u32>,
-}
+    pub min: Option<String>,
 
-impl McpRedisServer {
-    pub fn new(connections: Vec<RedisConnection>, allow_write: bool, scan_count: u32) -> Self {
-        Self {
-            connections: Arc::new(connections),
-            allow_write,
-            scan_count,
-            tool_router: Self::tool_router(),
-        }
-    }
+    #[schemars(
+        description = "Upper bound: '+', '[value' (inclusive) or '(value' (exclusive); a bare value is inclusive (default: '+')"
+    )]
+    #[serde(default)]
+    pub max: Option<String>,
 
-    fn resolve(&self, name: Option<&str>) -> Result<&RedisConnection, McpRedisError> {
-        match name {
-            Some(n) => self
-                .connections
-                .iter()
-                .find(|c| c.name == n)
-                .ok_or_else(|| McpRedisError::ConnectionNotFound(n.to_string())),
-            None if self.connections.len() == 1 => Ok(&self.connections[0]),
-            None => Err(McpRedisError::AmbiguousConnection),
-        }
-    }
+    #[schemars(
+        description = "Only members starting with this prefix (autocomplete lookups); replaces min/max"
+    )]
+    #[serde(default)]
+    pub prefix: Option<String>,
 
-    /// Guard for future write operations. Currently all tools are read-only,
-    /// but any new write tools should call this first.
-    #[allow(dead_code)]
-    fn check_read_only(&self, operation: &str) -> Result<(), McpRedisError> {
-        if !self.allow_write {
-            return Err(McpRedisError::ReadOnly(format!(
-                "'{}' requires --allow-write flag",
-                operation
-            )));
-        }
-        Ok(())
-    }
+    #[schemars(description = "Number of members to skip (default: 0)")]
+    #[serde(default)]
+    pub offset: Option<u64>,
 
-    /// Validate that a pattern doesn't contain null bytes.
-    fn validate_pattern(pattern: &str) -> Result<(), McpRedisError> {
-        if pattern.contains('\0') {
-            return Err(McpRedisError::Other(
-                "Pattern must not contain null bytes".to_string(),
-            ));
-        }
-        Ok(())
-    }
+    #[schemars(
+        description = "Maximum number of members to return (default: server scan count, max: 1000)"
+    )]
+    #[serde(default)]
+    pub limit: Option<u64>,
 
-    fn err(&self, e: McpRedisError) -> ErrorData {
-        e.to_mcp_error()
-    }
+    #[schemars(description = "Return members in descending lexicographic order (default: false)")]
+    #[serde(default)]
+    pub reverse: Option<bool>,
 }
 
-// -- Read-only Redis commands for reference --
-// INFO, SCAN, TYPE, GET, LRANGE, SMEMBERS, ZRANGE, HGETALL, TTL, OBJECT, MEMORY, DBSIZE
-// Write commands that would need check_read_only: SET, DEL, FLUSHDB, EXPIRE, etc.
-
-// -- Public methods for testability --
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MemberScanParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
 
-impl McpRedisServer {
-    pub async fn do_list_connections(&self) -> Result<CallToolResult, ErrorData> {
-        let connections: Vec<serde_json::Value> = self
-            .connections
-            .iter()
-            .map(|c| {
-                serde_json::json!({
-                    "name": c.name,
-                    "url": c.url_redacted,
-                })
-            })
-            .collect();
+    #[schemars(description = "Key name")]
+    pub key: String,
 
-        let text =
-            serde_json::to_string_pretty(&connections).unwrap_or_else(|_| "[]".to_string());
-        Ok(CallToolResult::success(vec![Content::text(text)]))
-    }
+    #[schemars(
+        description = "Cursor returned by a previous call, to continue (default: 0, the start)"
+    )]
+    #[serde(default)]
+    pub cursor: Option<u64>,
 
-    pub async fn do_info(&self, params: InfoParams) -> Result<CallToolResult, ErrorData> {
-        let entry = self.resolve(params.connection.as_deref()).map_err(|e| self.err(e))?;
-        let mut conn = entry.conn.clone();
+    #[schemars(description = "Glob pattern members must match (e.g. 'user:*'); default all")]
+    #[serde(default)]
+    pub pattern: Option<String>,
 
-        let info: String = if let Some(section) = params.section {
-            redis::cmd("INFO")
-                .arg(&section)
-                .query_async(&mut conn)
-                .await
-                .map_err(|e| self.err(McpRedisError::Redis(e)))?
-        } else {
-            redis::cmd("INFO")
-                .query_async(&mut conn)
-                .await
-                .map_err(|e| self.err(McpRedisError::Redis(e)))?
-        };
+    #[schemars(description = "Members to return per call (default: 100, max: max_scan_members)")]
+    #[serde(default)]
+    pub count: Option<usize>,
+}
 
-        Ok(CallToolResult::success(vec![Content::text(info)]))
-    }
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SampleMembersParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
 
-    pub async fn do_scan_keys(&self, params: ScanParams) -> Result<CallToolResult, ErrorData> {
-        let entry = self.resolve(params.connection.as_deref()).map_err(|e| self.err(e))?;
-        let mut conn = entry.conn.clone();
-        let pattern = params.pattern.as_deref().unwrap_or("*");
+    #[schemars(description = "Set, hash or sorted set key name")]
+    pub key: String,
 
-        Self::validate_pattern(pattern).map_err(|e| self.err(e))?;
+    #[schemars(
+        description = "Distinct random members to return (default: 10, max: max_sample_members)"
+    )]
+    #[serde(default)]
+    pub count: Option<u64>,
 
-        // Cap max_keys to scan_count to prevent unbounded iteration
-        let max_keys = std::cmp::min(
-            params.count.unwrap_or(self.scan_count) as usize,
-            self.scan_count as usize,
-        );
+    #[schemars(
+        description = "Include hash values or zset scores with the sampled fields and members (default: false)"
+    )]
+    #[serde(default)]
+    pub with_values: Option<bool>,
+}
 
-        let mut keys: Vec<String> = Vec::new();
-        let mut cursor: u64 = 0;
-        let mut iterations = 0;
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct FindMemberParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
 
-        loop {
-            let (next_cursor, batch): (u64, Vec<String>) = redis::cmd("SCAN")
-                .arg(cursor)
-                .arg("MATCH")
-                .arg(pattern)
-                .arg("COUNT")
-                .arg(100)
-                .query_async(&mut conn)
-                .await
-                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+    #[schemars(description = "Set, sorted set or hash key name")]
+    pub key: String,
 
-            keys.extend(batch);
-            cursor = next_cursor;
-            iterations += 1;
+    #[schemars(description = "Substring to look for in members (hash: field names)")]
+    #[serde(default)]
+    pub contains: Option<String>,
 
-            if cursor == 0 || keys.len() >= max_keys || iterations >= MAX_SCAN_ITERATIONS {
-                break;
-            }
-        }
+    #[schemars(
+        description = "Glob pattern members must match (e.g. '*@gmail.com'); alternative to 'contains'"
+    )]
+    #[serde(default)]
+    pub pattern: Option<String>,
 
-        keys.truncate(max_keys);
+    #[schemars(description = "Cursor returned by a previous call, to continue the search")]
+    #[serde(default)]
+    pub cursor: Option<u64>,
 
-        let text = serde_json::to_string_pretty(&serde_json::json!({
-            "pattern": pattern,
-            "keys": keys,
-            "count": keys.len(),
-        }))
-        .unwrap_or_else(|_| "{}".to_string());
-        Ok(CallToolResult::success(vec![Content::text(text)]))
-    }
+    #[schemars(description = "Maximum number of matches to return (default: 50, max: 1000)")]
+    #[serde(default)]
+    pub count: Option<usize>,
+}
 
-    pub async fn do_get(&self, params: KeyParams) -> Result<CallToolResult, ErrorData> {
-        let entry = self.resolve(params.connection.as_deref()).map_err(|e| self.err(e))?;
-        let mut conn = entry.conn.clone();
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct NamespaceCensusParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
 
-        // Get key type first
-        let key_type: String = redis::cmd("TYPE")
-            .arg(&params.key)
-            .query_async(&mut conn)
-            .await
-            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+    #[schemars(description = "Key prefix of the namespace, e.g. 'session:' (matched literally)")]
+    pub prefix: String,
 
-        let value: serde_json::Value = match key_type.as_str() {
-            "string" => {
-                let v: String = redis::cmd("GET")
-                    .arg(&params.key)
-                    .query_async(&mut conn)
-                    .await
-                    .map_err(|e| self.err(McpRedisError::Redis(e)))?;
-                serde_json::Value::String(v)
-            }
-            "list" => {
-                let v: Vec<String> = redis::cmd("LRANGE")
-                    .arg(&params.key)
-                    .arg(0)
-                    .arg(-1)
-                    .query_async(&mut conn)
-                    .await
-                    .map_err(|e| self.err(McpRedisError::Redis(e)))?;
-                serde_json::json!(v)
-            }
-            "set" => {
-                let v: Vec<String> = redis::cmd("SMEMBERS")
-                    .arg(&params.key)
-                    .query_async(&mut conn)
-                    .await
-                    .map_err(|e| self.err(McpRedisError::Redis(e)))?;
-                serde_json::json!(v)
-            }
-            "zset" => {
-                let v: Vec<(String, f64)> = redis::cmd("ZRANGE")
-                    .arg(&params.key)
-                    .arg(0)
-                    .arg(-1)
-                    .arg("WITHSCORES")
-                    .query_async(&mut conn)
-                    .await
-                    .map_err(|e| self.err(McpRedisError::Redis(e)))?;
-                serde_json::json!(v.iter().map(|(m, s)| serde_json::json!({"member": m, "score": s})).collect::<Vec<_>>())
-            }
-            "hash" => {
-                let v: Vec<(String, String)> = redis::cmd("HGETALL")
-                    .arg(&params.key)
-                    .query_async(&mut conn)
-                    .await
-                    .map_err(|e| self.err(McpRedisError::Redis(e)))?;
-                let map: serde_json::Map<String, serde_json::Value> = v
-                    .into_iter()
-                    .map(|(k, v)| (k, serde_json::Value::String(v)))
-                    .collect();
-                serde_json::Value::Object(map)
-            }
-            "none" => {
-                return Ok(CallToolResult::success(vec![Content::text(
-                    serde_json::json!({"error": "Key does not exist", "key": params.key}).to_string(),
-                )]));
-            }
-            other => serde_json::json!({"type": other, "note": "Unsupported type"}),
-        };
+    #[schemars(description = "Number of keys to sample (default: 200, max: 1000)")]
+    #[serde(default)]
+    pub sample: Option<u32>,
+}
 
-        let text = serde_json::to_string_pretty(&serde_json::json!({
-            "key": params.key,
-            "type": key_type,
-            "value": value,
-        }))
-        .unwrap_or_else(|_| "{}".to_string());
-        Ok(CallToolResult::success(vec![Content::text(text)]))
-    }
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BigKeysParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
 
-    pub async fn do_key_info(&self, params: KeyParams) -> Result<CallToolResult, ErrorData> {
-        let entry = self.resolve(params.connection.as_deref()).map_err(|e| self.err(e))?;
-        let mut conn = entry.conn.clone();
+    #[schemars(description = "Only measure keys matching this pattern. Default: *")]
+    #[serde(default)]
+    pub pattern: Option<String>,
 
-        let key_type: String = redis::cmd("TYPE")
-            .arg(&params.key)
-            .query_async(&mut conn)
-            .await
-            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+    #[schemars(description = "Number of largest keys to return (default: 10, max: 100)")]
+    #[serde(default)]
+    pub top: Option<u32>,
 
-        let ttl: i64 = redis::cmd("TTL")
-            .arg(&params.key)
-            .query_async(&mut conn)
-            .await
-            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+    #[schemars(
+        description = "Elements MEMORY USAGE samples per collection (default: 5; 0 measures every element, exact but slow on large keys)"
+    )]
+    #[serde(default)]
+    pub samples: Option<u32>,
 
-        let encoding: Result<String, _> = redis::cmd("OBJECT")
-            .arg("ENCODING")
-            .arg(&params.key)
-            .query_async(&mut conn)
-            .await;
+    #[schemars(
+        description = "Keys to measure at most (default and max: max_big_keys_scan, 10000)"
+    )]
+    #[serde(default)]
+    pub max_keys: Option<usize>,
+}
 
-        let memory: Result<i64, _> = redis::cmd("MEMORY")
-            .arg("USAGE")
-            .arg(&params.key)
-            .query_async(&mut conn)
-            .await;
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AclCheckParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
 
-        let text = serde_json::to_string_pretty(&serde_json::json!({
-            "key": params.key,
-            "type": key_type,
-            "ttl": if ttl == -1 { "no expiry".to_string() } else if ttl == -2 { "key not found".to_string() } else { format!("{}s", ttl) },
-            "encoding": encoding.unwrap_or_else(|_| "unknown".to_string()),
-            "memory_bytes": memory.unwrap_or(-1),
-        }))
-        .unwrap_or_else(|_| "{}".to_string());
-        Ok(CallToolResult::success(vec![Content::text(text)]))
-    }
+    #[schemars(description = "ACL user to check, e.g. 'app-ro'")]
+    pub user: String,
 
-    pub async fn do_dbsize(&self, params: ConnectionParam) -> Result<CallToolResult, ErrorData> {
-        let entry = self.resolve(params.connection.as_deref()).map_err(|e| self.err(e))?;
-        let mut conn = entry.conn.clone();
+    #[schemars(description = "Command to check, e.g. 'DEL'")]
+    pub command: String,
 
-        let size: i64 = redis::cmd("DBSIZE")
-            .query_async(&mut conn)
-            .await
-            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+    #[schemars(
+        description = "Command arguments, e.g. ['user:1'] for a key; key and channel permissions are checked against them"
+    )]
+    #[serde(default)]
+    pub args: Vec<String>,
+}
 
-        let text = serde_json::to_string_pretty(&serde_json::json!({
-            "dbsize": size,
-        }))
-        .unwrap_or_else(|_| "{}".to_string());
-        Ok(CallToolResult::success(vec![Content::text(text)]))
-    }
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AclGetUserParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
 
-    pub async fn do_search_keys(&self, params: ScanParams) -> Result<CallToolResult, ErrorData> {
-        let entry = self.resolve(params.connection.as_deref()).map_err(|e| self.err(e))?;
-        let mut conn = entry.conn.clone();
-        let pattern = params.pattern.as_deref().unwrap_or("*");
+    #[schemars(description = "ACL user to describe, e.g. 'default' or 'app-ro'")]
+    pub user: String,
+}
 
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct LatencyCorrelationParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "How far back the timeline goes, in seconds (default: 3600)")]
+    #[serde(default)]
+    pub since_secs: Option<u64>,
+
+    #[schemars(
+        description = "Seconds two events may be apart and still count as coinciding (default: 2)"
+    )]
+    #[serde(default)]
+    pub tolerance_secs: Option<u64>,
+
+    #[schemars(description = "Slowlog entries to read (default: 128)")]
+    #[serde(default)]
+    pub slowlog_count: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ErrorStatsParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Read the counters twice this many seconds apart and report errors per second over the interval, to see whether errors are happening now (default: 0, cumulative counters only; max: 60)"
+    )]
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct InfoDeltaParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Seconds between the two INFO snapshots (default: 5, max: 10)")]
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SampleStatsParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Seconds to sample, one INFO read per second (default: 10, max: 60)")]
+    #[serde(default)]
+    pub duration_secs: Option<u64>,
+
+    #[schemars(
+        description = "INFO fields to record, e.g. ['used_memory_rss', 'blocked_clients'] (default: instantaneous_ops_per_sec, used_memory, connected_clients)"
+    )]
+    #[serde(default)]
+    pub fields: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct LatencyHistoryParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Latency event, as listed by latency_latest (e.g. 'command', 'fork', 'aof-fsync-always')"
+    )]
+    pub event: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AnalyzeNamespacesParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Only sample keys matching this pattern. Default: *")]
+    #[serde(default)]
+    pub pattern: Option<String>,
+
+    #[schemars(description = "Separator between namespace levels (default: ':')")]
+    #[serde(default)]
+    pub delimiter: Option<String>,
+
+    #[schemars(description = "Number of keys to sample (default: 500, max: 1000)")]
+    #[serde(default)]
+    pub sample: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ValidateKeyParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Key to check against the contract for its pattern")]
+    pub key: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ValidateNamespaceParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Glob pattern of the keys to check, usually a contract's pattern (e.g. 'session:*')"
+    )]
+    pub pattern: String,
+
+    #[schemars(description = "Number of keys to sample (default: 200, max: 1000)")]
+    #[serde(default)]
+    pub sample: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RdbScanParams {
+    #[schemars(
+        description = "Connection to compare against (optional if only one Redis instance is connected; only used with compare_live or only_missing)"
+    )]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Glob pattern to match keys in the snapshot (default: '*')")]
+    #[serde(default)]
+    pub pattern: Option<String>,
+
+    #[schemars(description = "Database number in the snapshot (default: 0)")]
+    #[serde(default)]
+    pub db: Option<u64>,
+
+    #[schemars(description = "Maximum keys to list (default: 100, max: 1000)")]
+    #[serde(default)]
+    pub count: Option<u32>,
+
+    #[schemars(
+        description = "Report each listed key's current type on the live server (default: false)"
+    )]
+    #[serde(default)]
+    pub compare_live: Option<bool>,
+
+    #[schemars(
+        description = "List only keys that no longer exist on the live server (implies compare_live)"
+    )]
+    #[serde(default)]
+    pub only_missing: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RdbGetParams {
+    #[schemars(
+        description = "Connection to compare against (optional if only one Redis instance is connected; only used with compare_live)"
+    )]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Key to read from the snapshot")]
+    pub key: String,
+
+    #[schemars(description = "Database number in the snapshot (default: 0)")]
+    #[serde(default)]
+    pub db: Option<u64>,
+
+    #[schemars(
+        description = "Also read the live value and report whether it changed (default: false)"
+    )]
+    #[serde(default)]
+    pub compare_live: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct StreamRangeParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Stream key name")]
+    pub key: String,
+
+    #[schemars(
+        description = "Start: '-', an entry ID, an ISO 8601 time ('2024-05-01T12:00:00Z') or a relative time ('10 minutes ago') (default: '-')"
+    )]
+    #[serde(default)]
+    pub start: Option<String>,
+
+    #[schemars(description = "End, in the same forms as start, or '+' (default: '+')")]
+    #[serde(default)]
+    pub end: Option<String>,
+
+    #[schemars(description = "Maximum number of entries to return (default: 20, max: 1000)")]
+    #[serde(default)]
+    pub count: Option<u64>,
+
+    #[schemars(description = "Newest entries first (XREVRANGE) (default: false)")]
+    #[serde(default)]
+    pub reverse: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SlowlogParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Number of entries to return (default: 10)")]
+    #[serde(default)]
+    pub count: Option<u32>,
+
+    #[schemars(
+        description = "Text rendering: 'json' (default), 'table' (markdown) or 'plain' (one line per item). Structured content is always JSON"
+    )]
+    #[serde(default)]
+    pub format: Option<format::Format>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ClientListParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Only clients whose name matches this glob, e.g. 'worker-*'; '' for unnamed clients"
+    )]
+    #[serde(default)]
+    pub name: Option<String>,
+
+    #[schemars(
+        description = "Only clients with all of these flag letters, e.g. 'P' (Pub/Sub), 'b' (blocked), 'x' (in MULTI), 'S' (replica)"
+    )]
+    #[serde(default)]
+    pub flags: Option<String>,
+
+    #[schemars(description = "Only clients idle for at least this many seconds")]
+    #[serde(default)]
+    pub min_idle_secs: Option<u64>,
+
+    #[schemars(description = "Only clients of this type: normal, master, replica or pubsub")]
+    #[serde(default, rename = "type")]
+    pub client_type: Option<String>,
+
+    #[schemars(
+        description = "Return groups instead of clients: 'name' (by client name) or 'addr' (by host), with count, summed omem and tot-mem, longest idle and commands"
+    )]
+    #[serde(default)]
+    pub group_by: Option<String>,
+
+    #[schemars(
+        description = "Text rendering: 'json' (default), 'table' (markdown) or 'plain' (one line per item). Structured content is always JSON"
+    )]
+    #[serde(default)]
+    pub format: Option<format::Format>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ClientKillParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Client ID to kill, as 'id' in client_list")]
+    #[serde(default)]
+    pub id: Option<u64>,
+
+    #[schemars(
+        description = "Client address to kill, as 'addr' in client_list (ip:port); with id, only a client matching both is killed"
+    )]
+    #[serde(default)]
+    pub addr: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ClientPauseParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "How long to pause clients, in milliseconds (at most max_client_pause_ms)"
+    )]
+    pub timeout_ms: u64,
+
+    #[schemars(
+        description = "'write' to hold only commands that may write, so reads keep flowing (Redis 6.2+), or 'all' to hold every command (default: 'write')"
+    )]
+    #[serde(default)]
+    pub mode: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ClientBufferReportParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Sort by output buffer memory ('omem') or queued replies ('oll') (default: 'omem')"
+    )]
+    #[serde(default)]
+    pub sort_by: Option<String>,
+
+    #[schemars(description = "Number of clients to list (default: 20)")]
+    #[serde(default)]
+    pub count: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MonitorSampleParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "How long to run MONITOR, in seconds (default: 2, max: 10)")]
+    #[serde(default)]
+    pub duration_secs: Option<u64>,
+
+    #[schemars(
+        description = "Stop after this many commands (default: 10000 aggregated, 100 raw; max: 100000)"
+    )]
+    #[serde(default)]
+    pub max_commands: Option<usize>,
+
+    #[schemars(
+        description = "Return a histogram of commands, keys and clients rather than the raw MONITOR lines (default: true)"
+    )]
+    #[serde(default)]
+    pub aggregate: Option<bool>,
+
+    #[schemars(description = "Keys and clients listed in the aggregate (default: 10)")]
+    #[serde(default)]
+    pub top: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SubscribeSampleParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Channel names (or glob patterns when 'pattern' is true). A comma-separated string is also accepted"
+    )]
+    #[serde(deserialize_with = "string_list")]
+    pub channels: Vec<String>,
+
+    #[schemars(
+        description = "Treat channels as glob patterns and use PSUBSCRIBE (default: false)"
+    )]
+    #[serde(default)]
+    pub pattern: Option<bool>,
+
+    #[schemars(description = "How long to listen, in seconds (default: 5, max: 10)")]
+    #[serde(default)]
+    pub duration_secs: Option<u64>,
+
+    #[schemars(description = "Stop after this many messages (default: 100, max: 1000)")]
+    #[serde(default)]
+    pub max_messages: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AutoclaimParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Stream key name")]
+    pub key: String,
+
+    #[schemars(description = "Consumer group name")]
+    pub group: String,
+
+    #[schemars(description = "Consumer that will own the claimed entries")]
+    pub consumer: String,
+
+    #[schemars(description = "Only claim entries idle for at least this many milliseconds")]
+    pub min_idle_ms: u64,
+
+    #[schemars(
+        description = "Entry ID, ISO 8601 time or relative time ('1 hour ago') to start scanning the pending list from (default: 0-0)"
+    )]
+    #[serde(default)]
+    pub start: Option<String>,
+
+    #[schemars(description = "Maximum number of entries to claim (default: 10, max: 1000)")]
+    #[serde(default)]
+    pub count: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BgsaveParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "If an AOF rewrite is running, schedule the save for when it finishes instead of being refused (BGSAVE SCHEDULE, default: false)"
+    )]
+    #[serde(default)]
+    pub schedule: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct PingParams {
+    #[schemars(description = "Connection name (default: every connection)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Round trips to measure per connection (default: 5, max: 100)")]
+    #[serde(default)]
+    pub count: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct KeyspaceSummaryParams {
+    #[schemars(description = "Connection name (default: every connection)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ReplicationStatusParams {
+    #[schemars(description = "Connection name (default: every connection)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ReplicaConsistencyParams {
+    #[schemars(
+        description = "Master connection to sample keys from (optional if only one Redis instance is connected)"
+    )]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Connection name of the replica to compare against")]
+    pub replica: String,
+
+    #[schemars(description = "Keys to compare (default: 100, max: 1000)")]
+    #[serde(default)]
+    pub samples: Option<u32>,
+
+    #[schemars(
+        description = "Only compare keys matching this pattern, taken in SCAN order (default: keys sampled at random from the whole keyspace)"
+    )]
+    #[serde(default)]
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AnalysisReplicaParams {
+    #[schemars(
+        description = "Connection to replicate (optional if only one Redis instance is connected)"
+    )]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Name to register the replica under (default: '<connection>-analysis')"
+    )]
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ReplaceConnectionParams {
+    #[schemars(
+        description = "Connection to repoint (optional if only one Redis instance is connected)"
+    )]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "New endpoint, e.g. 'redis://:password@10.0.0.7:6379'")]
+    pub url: String,
+
+    #[schemars(
+        description = "Accept a replica as the new endpoint of a writable connection (default: false, since writes would fail)"
+    )]
+    #[serde(default)]
+    pub allow_replica: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MaintenanceNoteParams {
+    #[schemars(
+        description = "Connection to annotate (optional if only one Redis instance is connected)"
+    )]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Note attached to every result for the connection, e.g. 'failover in progress, expect stale reads'. Omit or leave empty to clear the current note"
+    )]
+    #[serde(default)]
+    pub note: Option<String>,
+
+    #[schemars(
+        description = "Seconds until the note expires (default: 3600, capped by max_maintenance_secs)"
+    )]
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SaveCursorParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Name to save the cursor under, e.g. 'audit-sessions'")]
+    pub name: String,
+
+    #[schemars(description = "Tool the cursor came from: scan_keys, search_keys or find_member")]
+    #[serde(default)]
+    pub tool: Option<String>,
+
+    #[schemars(
+        description = "Cursor returned by the tool. Omit, or pass '0' (a finished scan), to remove the saved cursor"
+    )]
+    #[serde(default)]
+    pub cursor: Option<String>,
+
+    #[schemars(
+        description = "Other arguments of the call to repeat when resuming, e.g. {\"pattern\": \"session:*\"}"
+    )]
+    #[serde(default)]
+    pub arguments: Option<serde_json::Map<String, serde_json::Value>>,
+
+    #[schemars(description = "Note for whoever resumes, e.g. 'checking TTLs, half done'")]
+    #[serde(default)]
+    pub note: Option<String>,
+
+    #[schemars(
+        description = "Seconds the cursor is kept (default: 86400, capped by max_cursor_ttl_secs)"
+    )]
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+
+    #[schemars(
+        description = "Also store the cursor in the Redis key 'mcp-redis:cursor:<name>' so it outlives this server (requires --allow-write; default: false)"
+    )]
+    #[serde(default)]
+    pub persist: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ListCursorsParams {
+    #[schemars(
+        description = "Connection whose persisted cursors are included (optional if only one Redis instance is connected)"
+    )]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Only the cursor with this name")]
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BulkDeleteParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Connection tag: delete across every connection carrying it instead of a single connection"
+    )]
+    #[serde(default)]
+    pub group: Option<String>,
+
+    #[schemars(description = "Glob pattern of the keys to delete (e.g. 'session:*')")]
+    pub pattern: String,
+
+    #[schemars(
+        description = "Token returned by the planning call. Omit it to plan; pass it to run the deletion"
+    )]
+    #[serde(default)]
+    pub confirm_token: Option<String>,
+
+    #[schemars(description = "Keys UNLINKed per pipeline (default: 500, max: 5000)")]
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct EstimateParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Key pattern to estimate (e.g. 'session:*')")]
+    pub pattern: String,
+
+    #[schemars(
+        description = "Number of random SCAN samples of ~100 keys each (default: 20, max: 100)"
+    )]
+    #[serde(default)]
+    pub samples: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MemoryByPatternParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Key patterns to size, e.g. ['session:*', 'cache:*'] (at most 20). A key matching several patterns counts in each"
+    )]
+    pub patterns: Vec<String>,
+
+    #[schemars(
+        description = "Number of random SCAN samples of ~100 keys each (default: 20, max: 100)"
+    )]
+    #[serde(default)]
+    pub samples: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CompactionAdvisorParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(description = "Only sample keys matching this pattern. Default: *")]
+    #[serde(default)]
+    pub pattern: Option<String>,
+
+    #[schemars(description = "Number of keys to sample (default: 1000, max: 1000)")]
+    #[serde(default)]
+    pub sample: Option<u32>,
+
+    #[schemars(
+        description = "How far above its entry threshold a collection may be, in percent, to count as just above it (default: 100, i.e. up to twice the threshold)"
+    )]
+    #[serde(default)]
+    pub margin_percent: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ExportSessionParams {
+    #[schemars(
+        description = "'json' (default) or 'markdown', a document with one section per call for postmortems"
+    )]
+    #[serde(default)]
+    pub format: Option<TranscriptFormat>,
+
+    #[schemars(
+        description = "Write the transcript to a file in the export directory and return its path instead of the transcript (default: false)"
+    )]
+    #[serde(default)]
+    pub write: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct EstimateCardinalityParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Lists, sets or sorted sets whose distinct members are counted together, e.g. ['active:2024-05-01', 'active:2024-05-02'] (at most 100)"
+    )]
+    pub keys: Vec<String>,
+
+    #[schemars(
+        description = "Members to read across all keys before stopping with bounds instead of a count (default and max: 1000000)"
+    )]
+    #[serde(default)]
+    pub max_elements: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DiagnosticsParams {
+    #[schemars(description = "Connection name (optional if only one Redis instance is connected)")]
+    #[serde(default)]
+    pub connection: Option<String>,
+
+    #[schemars(
+        description = "Number of random keys to include with type and TTL (default: 20, max: 100)"
+    )]
+    #[serde(default)]
+    pub sample_keys: Option<u32>,
+}
+
+impl McpRedisServer {
+    pub fn new(connections: Vec<RedisConnection>, allow_write: bool, scan_count: u32) -> Self {
+        Self {
+            connections: Arc::new(RwLock::new(connections)),
+            sentinels: Arc::new(RwLock::new(HashMap::new())),
+            allow_write,
+            allow_trace: false,
+            allow_admin: false,
+            scan_count,
+            max_value_bytes: budget::DEFAULT_MAX_VALUE_BYTES,
+            export_dir: None,
+            rdb_path: None,
+            presets: Arc::new(RwLock::new(Arc::new(Vec::new()))),
+            views: Arc::new(RwLock::new(Arc::new(Vec::new()))),
+            contracts: Arc::new(RwLock::new(Arc::new(Vec::new()))),
+            references: Arc::new(RwLock::new(Arc::new(references::Matcher::default()))),
+            config_set_allow: Arc::new(RwLock::new(Arc::new(settings::default_allowed()))),
+            tool_defaults: Arc::new(RwLock::new(Arc::new(ToolDefaults::new()))),
+            replica_hook: None,
+            limits: Arc::new(RwLock::new(Limits::default())),
+            reloader: None,
+            node_conns: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            stats: ToolStats::default(),
+            pending: Arc::new(std::sync::Mutex::new(Vec::new())),
+            shutdown: Shutdown::default(),
+            pending_deletes: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            maintenance: Notes::default(),
+            notifier: None,
+            transcripts: None,
+            subscriptions: Subscriptions::default(),
+            cursors: Cursors::default(),
+            db_connections: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            client_ids: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    /// Directory where `capture_diagnostics` and `export_session` write.
+    /// Tools that write files are unavailable without it.
+    pub fn with_export_dir(mut self, export_dir: Option<PathBuf>) -> Self {
+        self.export_dir = export_dir;
+        self
+    }
+
+    /// Local RDB dump that `rdb_scan` and `rdb_get` read. Those tools are
+    /// unavailable without it.
+    pub fn with_rdb(mut self, rdb_path: Option<PathBuf>) -> Self {
+        self.rdb_path = rdb_path;
+        self
+    }
+
+    /// Allow callers to pass `trace: true` to any tool to get a dump of the
+    /// Redis commands it issued, with per-command timings.
+    pub fn with_trace(mut self, allow_trace: bool) -> Self {
+        self.allow_trace = allow_trace;
+        self
+    }
+
+    /// Allow tools that provision infrastructure.
+    pub fn with_admin(mut self, allow_admin: bool) -> Self {
+        self.allow_admin = allow_admin;
+        self
+    }
+
+    /// Bytes of value data `get` returns unless a call asks for more or less.
+    pub fn with_max_value_bytes(mut self, max_value_bytes: u64) -> Self {
+        self.max_value_bytes = max_value_bytes;
+        self
+    }
+
+    /// Webhook notified of every write or admin tool call.
+    pub fn with_notifier(mut self, notifier: Option<Notifier>) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
+    /// Record every tool call per session for `export_session`.
+    pub fn with_transcripts(mut self, transcripts: Option<Transcripts>) -> Self {
+        self.transcripts = transcripts;
+        self
+    }
+
+    /// External command behind `create_analysis_replica`.
+    pub fn with_replica_hook(mut self, hook: Option<ReplicaHook>) -> Self {
+        self.replica_hook = hook;
+        self
+    }
+
+    /// Upper bounds for tool parameters (sample sizes, listen durations).
+    pub fn with_limits(self, limits: Limits) -> Self {
+        self.set_limits(limits);
+        self
+    }
+
+    /// Replace the limits of every session, e.g. after a config reload.
+    pub fn set_limits(&self, limits: Limits) {
+        *self.limits.write().unwrap_or_else(|e| e.into_inner()) = limits;
+    }
+
+    fn limits(&self) -> Limits {
+        self.limits
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Saved tool sequences to expose as additional tools. Names must not
+    /// clash with built-in tools (see [`McpRedisServer::is_builtin_tool`]).
+    pub fn with_presets(self, presets: Vec<Preset>) -> Self {
+        self.set_presets(presets);
+        self
+    }
+
+    /// Replace the presets of every session, e.g. after a config reload.
+    pub fn set_presets(&self, presets: Vec<Preset>) {
+        *self.presets.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(presets);
+    }
+
+    fn presets(&self) -> Arc<Vec<Preset>> {
+        self.presets
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Views to expose as additional tools. Names must not clash with
+    /// built-in tools or presets.
+    pub fn with_views(self, views: Vec<View>) -> Self {
+        self.set_views(views);
+        self
+    }
+
+    pub fn set_views(&self, views: Vec<View>) {
+        *self.views.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(views);
+    }
+
+    fn views(&self) -> Arc<Vec<View>> {
+        self.views.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Key contracts checked by `validate_key` and `validate_namespace`.
+    pub fn with_contracts(self, contracts: Vec<KeyContract>) -> Self {
+        self.set_contracts(contracts);
+        self
+    }
+
+    pub fn set_contracts(&self, contracts: Vec<KeyContract>) {
+        *self.contracts.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(contracts);
+    }
+
+    fn contracts(&self) -> Arc<Vec<KeyContract>> {
+        self.contracts
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Patterns of key names inside values, followed by `get` with
+    /// `follow_references`. Invalid patterns are rejected when the config
+    /// file is loaded; here they would leave no patterns at all.
+    pub fn with_references(self, references: Vec<KeyReference>) -> Self {
+        self.set_references(references);
+        self
+    }
+
+    pub fn set_references(&self, references: Vec<KeyReference>) {
+        let matcher = references::Matcher::new(&references).unwrap_or_default();
+        *self.references.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(matcher);
+    }
+
+    fn references(&self) -> Arc<references::Matcher> {
+        self.references
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// CONFIG parameters `config_set` may change (names or globs).
+    pub fn with_config_set_allow(self, allow: Vec<String>) -> Self {
+        self.set_config_set_allow(allow);
+        self
+    }
+
+    pub fn set_config_set_allow(&self, allow: Vec<String>) {
+        *self
+            .config_set_allow
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Arc::new(allow);
+    }
+
+    fn config_set_allow(&self) -> Arc<Vec<String>> {
+        self.config_set_allow
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Arguments filled in when a call leaves them out. Check them with
+    /// [`McpRedisServer::check_tool_defaults`] first.
+    pub fn with_tool_defaults(self, defaults: ToolDefaults) -> Self {
+        self.set_tool_defaults(defaults);
+        self
+    }
+
+    pub fn set_tool_defaults(&self, defaults: ToolDefaults) {
+        *self
+            .tool_defaults
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Arc::new(defaults);
+    }
+
+    fn tool_defaults(&self) -> Arc<ToolDefaults> {
+        self.tool_defaults
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Tool defaults may only name built-in tools (or `"*"`), and only
+    /// arguments those tools take.
+    pub fn check_tool_defaults(&self, defaults: &ToolDefaults) -> Result<(), String> {
+        let tools: Vec<Tool> = self
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| self.with_trace_param(tool))
+            .map(Self::with_db_param)
+            .collect();
+        let takes = |tool: &Tool, argument: &str| {
+            tool.input_schema
+                .get("properties")
+                .and_then(|p| p.get(argument))
+                .is_some()
+        };
+        for (name, arguments) in defaults {
+            if name == "*" {
+                if let Some(argument) = arguments
+                    .keys()
+                    .find(|a| !tools.iter().any(|tool| takes(tool, a)))
+                {
+                    return Err(format!(
+                        "Default argument '{}' is not taken by any tool",
+                        argument
+                    ));
+                }
+                continue;
+            }
+            let Some(tool) = tools.iter().find(|tool| tool.name == *name) else {
+                return Err(format!("Defaults for unknown tool '{}'", name));
+            };
+            if let Some(argument) = arguments.keys().find(|a| !takes(tool, a)) {
+                return Err(format!(
+                    "Tool '{}' has no argument '{}' to default",
+                    name, argument
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill in the configured defaults for a call to `tool`: its own entry
+    /// first, then `"*"` for the arguments it takes. Arguments passed in
+    /// the call are kept.
+    pub fn apply_tool_defaults(&self, tool: &str, arguments: &mut JsonObject) {
+        let defaults = self.tool_defaults();
+        if let Some(own) = defaults.get(tool) {
+            for (name, value) in own {
+                arguments
+                    .entry(name.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+        let Some(shared) = defaults.get("*") else {
+            return;
+        };
+        let Some(properties) = self
+            .get_tool(tool)
+            .and_then(|t| t.input_schema.get("properties").cloned())
+        else {
+            return;
+        };
+        for (name, value) in shared {
+            if properties.get(name).is_some() {
+                arguments
+                    .entry(name.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+    }
+
+    /// Hook behind `reload_config`. Tool calls that reload are rejected
+    /// without it.
+    pub fn with_reloader(mut self, reloader: Reloader) -> Self {
+        self.reloader = Some(reloader);
+        self
+    }
+
+    /// Re-read the config file through the reloader, returning its summary.
+    pub async fn reload(&self) -> Result<serde_json::Value, McpRedisError> {
+        let Some(reloader) = self.reloader.clone() else {
+            return Err(McpRedisError::Other(
+                "No config file to reload (start with --config)".to_string(),
+            ));
+        };
+        reloader(self.clone()).await.map_err(McpRedisError::Other)
+    }
+
+    /// Shutdown state shared by every clone of the server.
+    pub fn shutdown(&self) -> &Shutdown {
+        &self.shutdown
+    }
+
+    /// Drop every connection, connected or pending, so their sockets close.
+    /// Called once tool calls have drained.
+    pub async fn close_connections(&self) {
+        self.connections
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        self.node_conns.lock().await.clear();
+    }
+
+    /// Call telemetry gathered so far.
+    pub fn stats(&self) -> &ToolStats {
+        &self.stats
+    }
+
+    pub fn is_builtin_tool(&self, name: &str) -> bool {
+        self.tool_router.has_route(name)
+    }
+
+    /// The tool definition advertised for a preset. Its annotations combine
+    /// those of its steps: read-only when every step is, destructive when
+    /// any step is.
+    pub fn preset_tool(&self, preset: &Preset) -> Tool {
+        let steps: Vec<&str> = preset.steps.iter().map(|s| s.tool.as_str()).collect();
+        let description = match &preset.description {
+            Some(d) => format!("{} (preset: {})", d, steps.join(", ")),
+            None => format!("Preset running {}", steps.join(", ")),
+        };
+        let schema: JsonObject = serde_json::from_value(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "connection": {
+                    "type": "string",
+                    "description": "Connection name (optional if only one Redis instance is connected)",
+                },
+            },
+        }))
+        .unwrap_or_default();
+        let steps: Vec<ToolAnnotations> = preset
+            .steps
+            .iter()
+            .map(|step| {
+                self.tool_router
+                    .get(&step.tool)
+                    .and_then(|tool| tool.annotations.clone())
+                    .unwrap_or_default()
+            })
+            .collect();
+        let read_only = steps.iter().all(|a| a.read_only_hint == Some(true));
+        let annotations =
+            if read_only {
+                ToolAnnotations {
+                    read_only_hint: Some(true),
+                    ..Default::default()
+                }
+            } else {
+                ToolAnnotations {
+                    read_only_hint: Some(false),
+                    destructive_hint: Some(steps.iter().any(|a| a.destructive_hint == Some(true))),
+                    idempotent_hint: Some(steps.iter().all(|a| {
+                        a.read_only_hint == Some(true) || a.idempotent_hint == Some(true)
+                    })),
+                    ..Default::default()
+                }
+            };
+        Tool::new(preset.name.clone(), description, Arc::new(schema)).annotate(annotations)
+    }
+
+    /// The tool definition advertised for a view: its params as required
+    /// string arguments, plus `connection`.
+    pub fn view_tool(&self, view: &View) -> Tool {
+        let keys: Vec<&str> = view.keys.iter().map(|k| k.key.as_str()).collect();
+        let description = match &view.description {
+            Some(d) => format!("{} (view of {})", d, keys.join(", ")),
+            None => format!("View of {}", keys.join(", ")),
+        };
+        let mut properties = serde_json::json!({
+            "connection": {
+                "type": "string",
+                "description": "Connection name (optional if only one Redis instance is connected)",
+            },
+        });
+        for param in &view.params {
+            properties[param] = serde_json::json!({
+                "type": "string",
+                "description": format!("Substituted for {{{}}} in the keys", param),
+            });
+        }
+        let schema: JsonObject = serde_json::from_value(serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": view.params,
+        }))
+        .unwrap_or_default();
+        Tool::new(view.name.clone(), description, Arc::new(schema)).annotate(ToolAnnotations {
+            read_only_hint: Some(true),
+            ..Default::default()
+        })
+    }
+
+    /// Read the keys of `view` for `arguments`: their types in one
+    /// pipeline, then the values of those holding the declared type (and
+    /// the length of collections) in another.
+    pub async fn do_view(
+        &self,
+        view: &View,
+        arguments: &JsonObject,
+    ) -> Result<CallToolResult, ErrorData> {
+        let connection = arguments.get("connection").and_then(|v| v.as_str());
+        let entry = self.resolve(connection).map_err(|e| self.err(e))?;
+        let keys = view
+            .keys
+            .iter()
+            .map(|spec| view::render(&spec.key, &view.params, arguments))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ErrorData::invalid_params(e, None))?;
+        let mut conn = entry.connection();
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+
+        let mut pipe = redis::pipe();
+        for key in &keys {
+            pipe.cmd("TYPE").arg(key);
+        }
+        let types: Vec<String> = pipe.query_async(&mut conn).await.map_err(redis_err)?;
+
+        let mut pipe = redis::pipe();
+        let mut reads = 0;
+        for ((spec, key), key_type) in view.keys.iter().zip(&keys).zip(&types) {
+            if *key_type != spec.key_type {
+                continue;
+            }
+            pipe.add_command(view::read_command(spec, key));
+            reads += 1;
+            if let Some(length) = view::length_command(spec, key) {
+                pipe.add_command(length);
+                reads += 1;
+            }
+        }
+        let mut replies = if reads > 0 {
+            pipe.query_async::<Vec<redis::Value>>(&mut conn)
+                .await
+                .map_err(redis_err)?
+                .into_iter()
+        } else {
+            Vec::new().into_iter()
+        };
+
+        let mut result = serde_json::Map::new();
+        for ((spec, key), key_type) in view.keys.iter().zip(keys).zip(types) {
+            let mut item = serde_json::json!({"key": key, "type": key_type});
+            if key_type == "none" {
+                item["value"] = serde_json::Value::Null;
+            } else if key_type != spec.key_type {
+                item["error"] = serde_json::json!(format!(
+                    "expected a {}, found a {}",
+                    spec.key_type, key_type
+                ));
+            } else {
+                let reply = replies.next().unwrap_or(redis::Value::Nil);
+                item["value"] = view::shape(spec, &reply);
+                if view::length_command(spec, &key).is_some() {
+                    item["length"] = resp::to_json(&replies.next().unwrap_or(redis::Value::Nil));
+                }
+            }
+            result.insert(spec.name.clone(), item);
+        }
+        Ok(json_result(serde_json::json!({
+            "view": view.name,
+            "connection": entry.name,
+            "keys": result,
+        })))
+    }
+
+    /// Sentinel settings for connections opened from `redis+sentinel://` URLs.
+    /// Enables `sentinel_masters` for those connections.
+    pub fn with_sentinels(self, sentinels: HashMap<String, SentinelUrl>) -> Self {
+        *self.sentinels.write().unwrap_or_else(|e| e.into_inner()) = sentinels;
+        self
+    }
+
+    /// Set or clear the Sentinel settings of one connection.
+    pub fn set_sentinel(&self, name: &str, url: Option<SentinelUrl>) {
+        let mut sentinels = self.sentinels.write().unwrap_or_else(|e| e.into_inner());
+        match url {
+            Some(url) => sentinels.insert(name.to_string(), url),
+            None => sentinels.remove(name),
+        };
+    }
+
+    fn connections(&self) -> std::sync::RwLockReadGuard<'_, Vec<RedisConnection>> {
+        self.connections.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Connections still waiting to be opened.
+    pub fn with_pending(self, pending: Vec<PendingConnection>) -> Self {
+        *self.pending.lock().unwrap_or_else(|e| e.into_inner()) = pending;
+        self
+    }
+
+    /// Register a connection to open on first use while the server is running.
+    pub fn add_pending(&self, connection: PendingConnection) -> Result<(), McpRedisError> {
+        let exists = self.connections().iter().any(|c| c.name == connection.name);
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if exists || pending.iter().any(|p| p.name == connection.name) {
+            return Err(McpRedisError::Other(format!(
+                "Connection '{}' already exists",
+                connection.name
+            )));
+        }
+        pending.push(connection);
+        Ok(())
+    }
+
+    /// Drop a connection, connected or pending, along with its cached cluster
+    /// node connections. Calls already in flight keep their handle. Returns
+    /// whether it existed.
+    pub async fn remove_connection(&self, name: &str) -> bool {
+        let removed = {
+            let mut connections = self.connections.write().unwrap_or_else(|e| e.into_inner());
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            let before = connections.len() + pending.len();
+            connections.retain(|c| c.name != name);
+            pending.retain(|p| p.name != name);
+            connections.len() + pending.len() < before
+        };
+        self.set_sentinel(name, None);
+        let prefix = format!("{}/", name);
+        self.node_conns
+            .lock()
+            .await
+            .retain(|key, _| !key.starts_with(&prefix));
+        removed
+    }
+
+    /// Whether `name` is configured but not connected yet.
+    pub fn is_pending(&self, name: &str) -> bool {
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|p| p.name == name)
+    }
+
+    fn resolve(&self, name: Option<&str>) -> Result<RedisConnection, McpRedisError> {
+        let connections = self.connections();
+        let pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let not_connected = |p: &PendingConnection| {
+            McpRedisError::Other(match &p.last_error {
+                Some(e) => format!("Connection '{}' is not available: {}", p.name, e),
+                None => format!("Connection '{}' is not connected yet", p.name),
+            })
+        };
+        match name {
+            Some(n) => match connections.iter().find(|c| c.name == n) {
+                Some(c) => Ok(Self::in_selected_db(c.clone())),
+                None => Err(pending
+                    .iter()
+                    .find(|p| p.name == n)
+                    .map(not_connected)
+                    .unwrap_or_else(|| McpRedisError::ConnectionNotFound(n.to_string()))),
+            },
+            None if connections.len() == 1 && pending.is_empty() => {
+                Ok(Self::in_selected_db(connections[0].clone()))
+            }
+            None if connections.is_empty() && pending.len() == 1 => Err(not_connected(&pending[0])),
+            None => Err(McpRedisError::AmbiguousConnection),
+        }
+    }
+
+    /// `entry` switched to the database the running call selected for it,
+    /// if any.
+    fn in_selected_db(entry: RedisConnection) -> RedisConnection {
+        let selected = SELECTED_DB
+            .try_with(|s| (s.name == entry.name).then(|| s.db.clone()))
+            .ok()
+            .flatten();
+        match selected {
+            Some(db) => RedisConnection {
+                conn: db.conn,
+                client: db.client,
+                ..entry
+            },
+            None => entry,
+        }
+    }
+
+    /// Run `fut` with database `db` selected on the connection `name`:
+    /// everything it resolves on that connection uses `db`. The connection
+    /// to `db` is opened on first use and kept for later calls.
+    pub async fn in_db<F: std::future::Future>(
+        &self,
+        name: Option<&str>,
+        db: i64,
+        fut: F,
+    ) -> Result<F::Output, McpRedisError> {
+        if db < 0 {
+            return Err(McpRedisError::Other(format!(
+                "Invalid db {}: database indexes start at 0",
+                db
+            )));
+        }
+        let entry = self.resolve(name)?;
+        let info = entry.client.get_connection_info();
+        if info.redis.db == db {
+            return Ok(fut.await);
+        }
+
+        let slot = (entry.name.clone(), db);
+        let cached = self
+            .db_connections
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&slot)
+            .filter(|c| c.derived_from(info))
+            .cloned();
+        let db_conn = match cached {
+            Some(cached) => cached,
+            None => {
+                let mut selected = info.clone();
+                selected.redis.db = db;
+                let client = redis::Client::open(selected)?;
+                // The connection SELECTs the database on connect
+                let conn = tokio::time::timeout(
+                    LAZY_CONNECT_TIMEOUT,
+                    redis::aio::ConnectionManager::new(client.clone()),
+                )
+                .await
+                .map_err(|_| {
+                    McpRedisError::Other(format!(
+                        "Timed out selecting db {} on '{}'",
+                        db, entry.name
+                    ))
+                })??;
+                let db_conn = DbConnection {
+                    base: info.clone(),
+                    conn,
+                    client,
+                };
+                self.db_connections
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(slot, db_conn.clone());
+                db_conn
+            }
+        };
+        let selected = SelectedDb {
+            name: entry.name,
+            db: db_conn,
+        };
+        Ok(SELECTED_DB.scope(selected, fut).await)
+    }
+
+    /// Run `call` once in every database of the connection `name` that holds
+    /// keys (per INFO keyspace) and collect the results by db. Empty
+    /// databases are listed, not visited; how many there are is only known
+    /// when CONFIG GET is allowed.
+    pub async fn across_dbs<F, Fut>(
+        &self,
+        name: Option<&str>,
+        call: F,
+    ) -> Result<serde_json::Value, McpRedisError>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<CallToolResult, ErrorData>>,
+    {
+        let entry = self.resolve(name)?;
+        let mut conn = entry.connection();
+        let keyspace: String = redis::cmd("INFO")
+            .arg("keyspace")
+            .query_async(&mut conn)
+            .await?;
+        let populated: Vec<i64> = info::parse_keyspace(&keyspace)
+            .iter()
+            .filter_map(|db| db["db"].as_i64())
+            .collect();
+        let databases = redis::cmd("CONFIG")
+            .arg("GET")
+            .arg("databases")
+            .query_async::<Vec<String>>(&mut conn)
+            .await
+            .ok()
+            .and_then(|reply| reply.get(1).and_then(|n| n.parse::<i64>().ok()));
+
+        let mut results = Vec::new();
+        for &db in &populated {
+            let item = match self.in_db(Some(&entry.name), db, call()).await {
+                Ok(Ok(result)) if result.is_error != Some(true) => serde_json::json!({
+                    "db": db,
+                    "result": result.structured_content.unwrap_or_default(),
+                }),
+                Ok(Ok(result)) => serde_json::json!({
+                    "db": db,
+                    "error": result
+                        .content
+                        .first()
+                        .and_then(|c| c.as_text())
+                        .map(|t| t.text.clone()),
+                }),
+                Ok(Err(e)) => serde_json::json!({"db": db, "error": e.message}),
+                Err(e) => serde_json::json!({"db": db, "error": e.to_string()}),
+            };
+            results.push(item);
+        }
+        let mut summary = serde_json::json!({
+            "connection": entry.name,
+            "databases": results,
+        });
+        if let Some(count) = databases {
+            summary["database_count"] = serde_json::json!(count);
+            summary["empty_dbs"] = serde_json::json!((0..count)
+                .filter(|db| !populated.contains(db))
+                .collect::<Vec<_>>());
+        }
+        Ok(summary)
+    }
+
+    /// Try to open pending connections a call may use: the named one, or
+    /// every one when no name is given. Connections still backing off after a
+    /// failure are skipped.
+    pub async fn connect_pending(&self, name: Option<&str>) {
+        let attempts: Vec<(String, Connector)> = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            pending
+                .iter_mut()
+                .filter(|p| name.map_or(true, |n| p.name == n))
+                .filter(|p| !p.in_progress && p.retry_at.map_or(true, |at| now >= at))
+                .map(|p| {
+                    p.in_progress = true;
+                    (p.name.clone(), p.connector.clone())
+                })
+                .collect()
+        };
+        if attempts.is_empty() {
+            return;
+        }
+
+        let results = futures_util::future::join_all(attempts.into_iter().map(
+            |(name, connector)| async move {
+                let result = match tokio::time::timeout(LAZY_CONNECT_TIMEOUT, connector()).await {
+                    Ok(result) => result,
+                    Err(_) => Err(format!(
+                        "timed out after {}s",
+                        LAZY_CONNECT_TIMEOUT.as_secs()
+                    )),
+                };
+                (name, result)
+            },
+        ))
+        .await;
+
+        for (name, result) in results {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(index) = pending.iter().position(|p| p.name == name) else {
+                continue;
+            };
+            match result {
+                Ok(connection) => {
+                    pending.remove(index);
+                    drop(pending);
+                    tracing::info!(connection = %name, "Connected to Redis");
+                    if let Err(e) = self.add_connection(connection) {
+                        tracing::warn!(connection = %name, error = %e, "Cannot register connection");
+                    }
+                }
+                Err(e) => {
+                    let entry = &mut pending[index];
+                    entry.in_progress = false;
+                    entry.attempts += 1;
+                    let backoff = LAZY_CONNECT_BACKOFF
+                        .saturating_mul(1 << (entry.attempts - 1).min(6))
+                        .min(LAZY_CONNECT_MAX_BACKOFF);
+                    entry.retry_at = Some(Instant::now() + backoff);
+                    tracing::warn!(connection = %name, error = %e, attempts = entry.attempts, "Cannot connect to Redis");
+                    entry.last_error = Some(e);
+                }
+            }
+        }
+    }
+
+    /// Address the named connection currently points at.
+    pub fn connection_addr(&self, name: &str) -> Option<redis::ConnectionAddr> {
+        self.connections()
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| c.client.get_connection_info().addr.clone())
+    }
+
+    /// Repoint the named connection, e.g. at a new master after a Sentinel
+    /// failover or with rotated credentials. Calls already in flight keep
+    /// their old handle. Cached cluster node connections are dropped so they
+    /// are reopened with the new settings.
+    pub async fn swap_connection(
+        &self,
+        name: &str,
+        conn: redis::aio::ConnectionManager,
+        client: redis::Client,
+    ) {
+        {
+            let mut connections = self.connections.write().unwrap_or_else(|e| e.into_inner());
+            if let Some(entry) = connections.iter_mut().find(|c| c.name == name) {
+                entry.conn = conn;
+                entry.client = client;
+                // A different server: take a new baseline rather than
+                // reporting a restart
+                entry.identity = None;
+                entry.restart = None;
+                entry.replaced += 1;
+            }
+        }
+        let prefix = format!("{}/", name);
+        self.node_conns
+            .lock()
+            .await
+            .retain(|key, _| !key.starts_with(&prefix));
+    }
+
+    /// Register a connection opened while the server is running.
+    pub fn add_connection(&self, connection: RedisConnection) -> Result<(), McpRedisError> {
+        let mut connections = self.connections.write().unwrap_or_else(|e| e.into_inner());
+        if connections.iter().any(|c| c.name == connection.name) {
+            return Err(McpRedisError::Other(format!(
+                "Connection '{}' already exists",
+                connection.name
+            )));
+        }
+        connections.push(connection);
+        Ok(())
+    }
+
+    /// Re-read every connection's `run_id` and record a restart where it
+    /// changed. Run periodically by [`restart::watch`].
+    pub async fn check_restarts(&self) {
+        let entries: Vec<RedisConnection> = self.connections().clone();
+        for entry in entries {
+            let Some(current) = restart::detect(&mut entry.conn.clone()).await else {
+                continue;
+            };
+            let mut connections = self.connections.write().unwrap_or_else(|e| e.into_inner());
+            let Some(stored) = connections.iter_mut().find(|c| c.name == entry.name) else {
+                continue;
+            };
+            if let Some(previous) = &stored.identity {
+                if previous.run_id != current.run_id {
+                    tracing::warn!(connection = %stored.name, "Server restarted; counters were reset");
+                    stored.restart = Some(Restart {
+                        previous_run_id: previous.run_id.clone(),
+                        at: current.started_at,
+                    });
+                }
+            }
+            stored.identity = Some(current);
+        }
+    }
+
+    /// Names of every configured connection, connected or pending.
+    fn connection_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.connections().iter().map(|c| c.name.clone()).collect();
+        names.extend(
+            self.pending
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .map(|p| p.name.clone()),
+        );
+        names
+    }
+
+    /// Read a `redis://{connection}/{key}` resource: the key's value as
+    /// `get` returns it.
+    pub async fn read_key_resource(&self, uri: &str) -> Result<ReadResourceResult, ErrorData> {
+        let Some((connection, key)) = resource::parse_key_uri(uri) else {
+            return Err(ErrorData::invalid_params(
+                format!("Expected a {} URI, got '{}'", resource::KEY_TEMPLATE, uri),
+                None,
+            ));
+        };
+        self.connect_pending(Some(&connection)).await;
+        let result = self
+            .do_get(GetParams {
+                connection: Some(connection),
+                key,
+                max_bytes: None,
+                summary_only: None,
+                follow_references: None,
+                reference_depth: None,
+            })
+            .await?;
+        let text = result
+            .content
+            .first()
+            .and_then(|c| c.as_text())
+            .map(|t| t.text.clone())
+            .unwrap_or_default();
+        if serde_json::from_str::<serde_json::Value>(&text)
+            .is_ok_and(|v| v["error"] == "Key does not exist")
+        {
+            return Err(ErrorData::resource_not_found(
+                format!("Key does not exist: {}", uri),
+                None,
+            ));
+        }
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: uri.to_string(),
+                mime_type: Some("application/json".to_string()),
+                text,
+                meta: None,
+            }],
+        })
+    }
+
+    /// Watch a key or pattern URI for `session`: keyspace notifications for
+    /// matching keys become `notifications/resources/updated` sent to `peer`
+    /// with the changed key's URI, until the session unsubscribes or ends.
+    /// Requires `notify-keyspace-events` with `K` on the server.
+    pub async fn subscribe_resource(
+        &self,
+        uri: &str,
+        session: &str,
+        peer: Peer<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        let Some((connection, key)) = resource::parse_key_uri(uri) else {
+            return Err(ErrorData::invalid_params(
+                format!(
+                    "Expected a {} or {} URI, got '{}'",
+                    resource::KEY_TEMPLATE,
+                    resource::PATTERN_TEMPLATE,
+                    uri
+                ),
+                None,
+            ));
+        };
+        if self.subscriptions.contains(session, uri) {
+            return Ok(());
+        }
+        let max = self.limits().max_resource_subscriptions;
+        if self.subscriptions.len() >= max {
+            return Err(self.err(McpRedisError::Other(format!(
+                "Too many resource subscriptions ({}, max_resource_subscriptions); unsubscribe from some first",
+                max
+            ))));
+        }
+        let pattern = if resource::is_pattern(&key) {
+            Self::validate_pattern(&key).map_err(|e| self.err(e))?;
+            key
+        } else {
+            glob::escape(&key)
+        };
+
+        self.connect_pending(Some(&connection)).await;
+        let entry = self.resolve(Some(&connection)).map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let raw: String = redis::cmd("INFO")
+            .arg("cluster")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        if info::parse_fields(&raw).get("cluster_enabled") == Some(&serde_json::json!(1)) {
+            return Err(self.err(McpRedisError::Other(
+                "Resource subscriptions are not supported on clusters: keyspace notifications are only published on the node that owns the key".to_string(),
+            )));
+        }
+        // Managed services may refuse CONFIG; subscribe anyway then
+        let flags: Result<Vec<String>, _> = redis::cmd("CONFIG")
+            .arg("GET")
+            .arg("notify-keyspace-events")
+            .query_async(&mut conn)
+            .await;
+        if let Some(flags) = flags.ok().and_then(|f| f.get(1).cloned()) {
+            if !keyspace::events_enabled(&flags) {
+                return Err(self.err(McpRedisError::Other(format!(
+                    "Keyspace notifications are off on '{}' (notify-keyspace-events is '{}'): enable them with CONFIG SET notify-keyspace-events KA to subscribe",
+                    entry.name, flags
+                ))));
+            }
+        }
+
+        let db = entry.client.get_connection_info().redis.db;
+        let mut pubsub = entry
+            .client
+            .get_async_pubsub()
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        pubsub
+            .psubscribe(keyspace::channel_pattern(db, &pattern))
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let shutdown = self.shutdown.clone();
+        let name = entry.name.clone();
+        let task = tokio::spawn(async move {
+            let mut messages = pubsub.into_on_message();
+            let stopping = shutdown.stopping();
+            tokio::pin!(stopping);
+            let mut last_sent: HashMap<String, Instant> = HashMap::new();
+            loop {
+                let msg = tokio::select! {
+                    _ = &mut stopping => break,
+                    msg = messages.next() => msg,
+                };
+                let Some(msg) = msg else { break };
+                let Some(key) = keyspace::key_from_channel(msg.get_channel_name()) else {
+                    continue;
+                };
+                let now = Instant::now();
+                if last_sent
+                    .get(key)
+                    .is_some_and(|at| now.duration_since(*at) < RESOURCE_NOTIFY_INTERVAL)
+                {
+                    continue;
+                }
+                if last_sent.len() > COMPLETION_SCAN_KEYS {
+                    last_sent.retain(|_, at| now.duration_since(*at) < RESOURCE_NOTIFY_INTERVAL);
+                }
+                last_sent.insert(key.to_string(), now);
+                let updated = ResourceUpdatedNotificationParam {
+                    uri: resource::key_uri(&name, key),
+                };
+                // The session is gone
+                if peer.notify_resource_updated(updated).await.is_err() {
+                    break;
+                }
+            }
+        });
+        self.subscriptions.insert(session, uri, task.abort_handle());
+        tracing::debug!(uri, session, "Resource subscription started");
+        Ok(())
+    }
+
+    /// Stop watching `uri` for `session`; unknown subscriptions are ignored.
+    pub fn unsubscribe_resource(&self, uri: &str, session: &str) {
+        if self.subscriptions.remove(session, uri) {
+            tracing::debug!(uri, session, "Resource subscription stopped");
+        }
+    }
+
+    /// Completion values for a resource template or prompt argument:
+    /// connection names, keys starting with `value` (`key`), or namespace
+    /// prefixes one segment past it (`prefix`, and `pattern` with a trailing
+    /// `*`). Keys come from a short SCAN on `connection`, so large keyspaces
+    /// give a partial answer with `has_more` set.
+    pub async fn complete_argument(
+        &self,
+        argument: &str,
+        value: &str,
+        connection: Option<&str>,
+    ) -> Result<CompletionInfo, ErrorData> {
+        let (mut values, has_more) = match argument {
+            "connection" => {
+                let mut names: Vec<String> = self
+                    .connection_names()
+                    .into_iter()
+                    .filter(|n| n.starts_with(value))
+                    .collect();
+                names.sort();
+                (names, false)
+            }
+            "key" | "prefix" | "pattern" => {
+                // A pattern being typed is completed from its literal start
+                let typed = match argument {
+                    "pattern" => value.trim_end_matches('*'),
+                    _ => value,
+                };
+                self.connect_pending(connection).await;
+                let entry = self.resolve(connection).map_err(|e| self.err(e))?;
+                let mut outcome = scan::scan_bounded(
+                    &mut entry.connection(),
+                    &glob::prefix(typed),
+                    COMPLETION_SCAN_KEYS,
+                    scan::ScanBudget {
+                        max_iterations: COMPLETION_SCAN_ITERATIONS,
+                        ..self.limits().scan_budget()
+                    },
+                )
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+                let values = match argument {
+                    "key" => {
+                        outcome.keys.sort();
+                        outcome.keys
+                    }
+                    "prefix" => scan::next_prefixes(&outcome.keys, typed),
+                    _ => scan::next_prefixes(&outcome.keys, typed)
+                        .into_iter()
+                        .map(|prefix| glob::prefix(&prefix))
+                        .collect(),
+                };
+                (values, outcome.cursor != 0)
+            }
+            _ => (Vec::new(), false),
+        };
+        let has_more = has_more || values.len() > CompletionInfo::MAX_VALUES;
+        values.truncate(CompletionInfo::MAX_VALUES);
+        CompletionInfo::with_pagination(values, None, has_more)
+            .map_err(|e| ErrorData::internal_error(e, None))
+    }
+
+    /// Notice to attach to a tool result for `connection` when its server
+    /// restarted within [`restart::NOTICE_WINDOW_SECS`].
+    fn restart_notice(&self, connection: Option<&str>) -> Option<serde_json::Value> {
+        let entry = self.resolve(connection).ok()?;
+        let restart = entry.restart.as_ref()?;
+        let now = restart::now();
+        if now.saturating_sub(restart.at) > restart::NOTICE_WINDOW_SECS {
+            return None;
+        }
+        Some(serde_json::json!({
+            "restart_notice": {
+                "connection": entry.name,
+                "message": restart.notice(now),
+                "previous_run_id": restart.previous_run_id,
+                "run_id": entry.identity.as_ref().map(|i| i.run_id.clone()),
+            }
+        }))
+    }
+
+    /// Notice to attach to a tool result for `connection` while it has an
+    /// unexpired maintenance note.
+    fn maintenance_notice(&self, connection: Option<&str>) -> Option<serde_json::Value> {
+        let entry = self.resolve(connection).ok()?;
+        let now = restart::now();
+        let note = self.maintenance.get(&entry.name, now)?;
+        Some(serde_json::json!({ "maintenance_note": note.to_json(&entry.name, now) }))
+    }
+
+    /// Guard for tools that change infrastructure rather than data.
+    fn check_admin(&self, operation: &str) -> Result<(), McpRedisError> {
+        if !self.allow_admin {
+            return Err(McpRedisError::Admin(format!(
+                "'{}' requires --allow-admin flag",
+                operation
+            )));
+        }
+        Ok(())
+    }
+
+    /// Guard for write operations. Every tool that modifies data must call
+    /// this first.
+    fn check_read_only(
+        &self,
+        entry: &RedisConnection,
+        operation: &str,
+    ) -> Result<(), McpRedisError> {
+        if !self.allow_write {
+            return Err(McpRedisError::ReadOnly(format!(
+                "'{}' requires --allow-write flag",
+                operation
+            )));
+        }
+        if entry.read_only {
+            return Err(McpRedisError::ReadOnly(format!(
+                "'{}' is not allowed: connection '{}' is configured read-only",
+                operation, entry.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Validate that a pattern doesn't contain null bytes.
+    fn validate_pattern(pattern: &str) -> Result<(), McpRedisError> {
+        if pattern.contains('\0') {
+            return Err(McpRedisError::Other(
+                "Pattern must not contain null bytes".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn err(&self, e: McpRedisError) -> ErrorData {
+        e.to_mcp_error()
+    }
+
+    /// Map CLUSTER command failures on standalone instances to a clear message.
+    fn cluster_err(&self, e: redis::RedisError) -> ErrorData {
+        if e.to_string().contains("cluster support disabled") {
+            self.err(McpRedisError::Other(
+                "Cluster mode is not enabled on this Redis instance".to_string(),
+            ))
+        } else {
+            self.err(McpRedisError::Redis(e))
+        }
+    }
+
+    /// SCAN `entry` for keys matching `pattern`. On a cluster every master
+    /// is scanned in turn (SCAN only covers the node it is sent to), sharing
+    /// the `max_keys` budget.
+    async fn scan_across(
+        &self,
+        entry: &RedisConnection,
+        pattern: &str,
+        max_keys: usize,
+    ) -> Result<Vec<ShardScan>, McpRedisError> {
+        let (results, _) = self
+            .scan_page_across(
+                entry,
+                pattern,
+                &scan::KeyFilter::default(),
+                max_keys,
+                None,
+                self.limits().scan_budget(),
+            )
+            .await?;
+        Ok(results)
+    }
+
+    /// [`Self::scan_across`] resuming at `start` within `budget`, also
+    /// returning where the next page starts (`None` once every node was
+    /// fully scanned). Cluster masters are visited in address order so a
+    /// cursor stays meaningful between calls.
+    async fn scan_page_across(
+        &self,
+        entry: &RedisConnection,
+        pattern: &str,
+        filter: &scan::KeyFilter,
+        max_keys: usize,
+        start: Option<&scan::ScanCursor>,
+        budget: scan::ScanBudget,
+    ) -> Result<(Vec<ShardScan>, Option<scan::ScanCursor>), McpRedisError> {
+        let mut targets = self.scan_targets(entry).await?;
+        targets.sort_by(|a, b| a.0.cmp(&b.0));
+        let begin = match start {
+            None => 0,
+            Some(start) => targets
+                .iter()
+                .position(|(shard, _)| *shard == start.node)
+                .ok_or_else(|| {
+                    McpRedisError::Other(match &start.node {
+                        Some(node) => format!(
+                            "Cursor refers to node {} which no longer owns slots; start over without a cursor",
+                            node
+                        ),
+                        None => "Cursor is from a standalone server but this is a cluster; start over without a cursor".to_string(),
+                    })
+                })?,
+        };
+
+        let mut results: Vec<ShardScan> = Vec::new();
+        let mut next = None;
+        let mut found = 0;
+        for (i, (shard, mut conn)) in targets.into_iter().enumerate().skip(begin) {
+            if found >= max_keys {
+                next = Some(scan::ScanCursor {
+                    node: shard,
+                    position: scan::Position::default(),
+                });
+                break;
+            }
+            let position = match start {
+                Some(start) if i == begin => start.position,
+                _ => scan::Position::default(),
+            };
+            let outcome = scan::scan_page_filtered(
+                &mut conn,
+                pattern,
+                filter,
+                position,
+                max_keys - found,
+                budget,
+            )
+            .await?;
+            found += outcome.keys.len();
+            let resume = outcome.next.map(|position| scan::ScanCursor {
+                node: shard.clone(),
+                position,
+            });
+            results.push(ShardScan {
+                shard,
+                conn,
+                outcome,
+            });
+            if resume.is_some() {
+                next = resume;
+                break;
+            }
+        }
+        Ok((results, next))
+    }
+
+    /// A page of `scan_keys` or `search_keys`. A rare pattern on a big
+    /// keyspace can use up the iteration budget before matching anything;
+    /// such an empty, unfinished page is continued with a growing COUNT for
+    /// up to [`EMPTY_SCAN_RETRY_TIME`]. Also returns how much of the
+    /// keyspace the scan has covered when it is unfinished.
+    async fn scan_page_adaptive(
+        &self,
+        entry: &RedisConnection,
+        pattern: &str,
+        filter: &scan::KeyFilter,
+        max_keys: usize,
+        start: Option<&scan::ScanCursor>,
+    ) -> Result<
+        (
+            Vec<ShardScan>,
+            Option<scan::ScanCursor>,
+            Option<serde_json::Value>,
+        ),
+        McpRedisError,
+    > {
+        let started = Instant::now();
+        let mut budget = self.limits().scan_budget();
+        let (mut scans, mut next) = self
+            .scan_page_across(entry, pattern, filter, max_keys, start, budget)
+            .await?;
+        let mut calls: usize = scans.iter().map(|s| s.outcome.iterations).sum();
+        let mut retries = 0;
+        while next.is_some()
+            && scans.iter().all(|s| s.outcome.keys.is_empty())
+            && started.elapsed() < EMPTY_SCAN_RETRY_TIME
+        {
+            budget = scan::ScanBudget {
+                max_iterations: EMPTY_SCAN_RETRY_ITERATIONS,
+                batch_count: budget
+                    .batch_count
+                    .saturating_mul(10)
+                    .min(MAX_RETRY_SCAN_COUNT)
+                    .max(budget.batch_count),
+            };
+            // Nothing was found so far, so the earlier scans hold no keys
+            (scans, next) = self
+                .scan_page_across(entry, pattern, filter, max_keys, next.as_ref(), budget)
+                .await?;
+            calls += scans.iter().map(|s| s.outcome.iterations).sum::<usize>();
+            retries += 1;
+        }
+
+        let coverage = next.as_ref().map(|next| {
+            let mut coverage = serde_json::json!({
+                "scanned_percent": (scan::scanned_fraction(next.position.cursor) * 10_000.0)
+                    .round()
+                    / 100.0,
+                "scan_calls": calls,
+                "count_hint": budget.batch_count,
+                "retries": retries,
+            });
+            if let Some(node) = &next.node {
+                coverage["node"] = serde_json::json!(node);
+            }
+            coverage
+        });
+        Ok((scans, next, coverage))
+    }
+
+    /// Warn on a scan `result` that is unfinished and matched nothing, and
+    /// add an estimate of the keys `filter` keeps of those matching
+    /// `pattern` from random samples of `conn`'s keyspace: an empty page is
+    /// not evidence that there are none.
+    async fn note_empty_scan(
+        &self,
+        result: &mut serde_json::Value,
+        conn: &mut TracedConnection,
+        pattern: &str,
+        filter: &scan::KeyFilter,
+    ) {
+        result["warning"] = serde_json::json!(format!(
+            "No keys matched in the {}% of the keyspace scanned so far; that does not mean there are none. Continue with the cursor to scan further",
+            result["coverage"]["scanned_percent"].as_f64().unwrap_or(0.0)
+        ));
+        let Ok(dbsize) = redis::cmd("DBSIZE").query_async::<u64>(conn).await else {
+            return;
+        };
+        if let Ok(mut estimate) = self
+            .sample_estimate(conn, pattern, filter, dbsize, EMPTY_SCAN_SAMPLES)
+            .await
+        {
+            if result.get("type").is_some() {
+                estimate["note"] = serde_json::json!("Sampled keys of every type");
+            }
+            result["estimate"] = estimate;
+        }
+    }
+
+    /// Estimate how many of `dbsize` keys match `pattern` and pass the
+    /// name checks of `filter` from `samples` SCAN calls at random cursors,
+    /// with a 95% confidence interval.
+    async fn sample_estimate<C: redis::aio::ConnectionLike + Send>(
+        &self,
+        conn: &mut C,
+        pattern: &str,
+        filter: &scan::KeyFilter,
+        dbsize: u64,
+        samples: usize,
+    ) -> redis::RedisResult<serde_json::Value> {
+        let sampled =
+            scan::sample_random_keys(conn, dbsize, samples, self.limits().scan_batch_count).await?;
+        let matched = sampled
+            .iter()
+            .filter(|key| filter.keeps_name(pattern, key))
+            .count();
+
+        let ratio = if sampled.is_empty() {
+            0.0
+        } else {
+            matched as f64 / sampled.len() as f64
+        };
+        let (low, high) = scan::wilson_interval(matched, sampled.len(), 1.96);
+        Ok(serde_json::json!({
+            "dbsize": dbsize,
+            "method": "sampled",
+            "estimate": (ratio * dbsize as f64).round() as u64,
+            "ci_low": (low * dbsize as f64).floor() as u64,
+            "ci_high": (high * dbsize as f64).ceil() as u64,
+            "confidence": 0.95,
+            "sampled_keys": sampled.len(),
+            "matched_keys": matched,
+        }))
+    }
+
+    /// The `type` parameter of a paged scan. Servers without SCAN TYPE
+    /// (before Redis 6, or of unknown version) are filtered client-side.
+    fn type_filter(
+        entry: &RedisConnection,
+        key_type: Option<&str>,
+    ) -> Result<Option<scan::TypeFilter>, McpRedisError> {
+        let Some(key_type) = key_type else {
+            return Ok(None);
+        };
+        let client_side = !entry
+            .version
+            .as_ref()
+            .is_some_and(|v| v.satisfies(scan::SCAN_TYPE_VERSION));
+        scan::TypeFilter::new(key_type, client_side)
+            .map(Some)
+            .ok_or_else(|| {
+                McpRedisError::Other(format!(
+                    "Invalid type '{}': expected a key type such as hash or stream",
+                    key_type
+                ))
+            })
+    }
+
+    /// The MATCH pattern of a scan: `pattern` (default `*`), escaped to
+    /// match one exact key with `escape_glob`. With several `patterns`, the
+    /// literal prefix they share.
+    fn scan_pattern(params: &ScanParams, patterns: &[String]) -> String {
+        if !patterns.is_empty() {
+            return glob::prefix(&glob::shared_prefix(patterns));
+        }
+        match (params.pattern.as_deref(), params.escape_glob) {
+            (Some(pattern), Some(true)) => glob::escape(pattern),
+            (pattern, _) => pattern.unwrap_or("*").to_string(),
+        }
+    }
+
+    /// The `patterns` of a scan, each escaped with `escape_glob`; empty
+    /// for a single-pattern scan.
+    fn scan_patterns(params: &ScanParams) -> Result<Vec<String>, McpRedisError> {
+        let patterns = params.patterns.clone().unwrap_or_default();
+        if patterns.is_empty() {
+            return Ok(patterns);
+        }
+        if params.pattern.is_some() {
+            return Err(McpRedisError::Other(
+                "Give either pattern or patterns, not both".to_string(),
+            ));
+        }
+        if patterns.len() > MAX_SCAN_PATTERNS {
+            return Err(McpRedisError::Other(format!(
+                "Give at most {} patterns",
+                MAX_SCAN_PATTERNS
+            )));
+        }
+        for pattern in &patterns {
+            Self::validate_pattern(pattern)?;
+        }
+        Ok(match params.escape_glob {
+            Some(true) => patterns.iter().map(|p| glob::escape(p)).collect(),
+            _ => patterns,
+        })
+    }
+
+    /// The `exclude_patterns` of a scan.
+    fn exclude_patterns(params: &ScanParams) -> Result<Vec<String>, McpRedisError> {
+        let patterns = params.exclude_patterns.clone().unwrap_or_default();
+        if patterns.len() > MAX_SCAN_PATTERNS {
+            return Err(McpRedisError::Other(format!(
+                "Give at most {} exclude_patterns",
+                MAX_SCAN_PATTERNS
+            )));
+        }
+        for pattern in &patterns {
+            Self::validate_pattern(pattern)?;
+        }
+        Ok(patterns)
+    }
+
+    /// `{pattern, count, keys}` for each of `patterns`, from the keys a
+    /// multi-pattern scan returned.
+    fn pattern_groups(patterns: &[String], keys: &[serde_json::Value]) -> serde_json::Value {
+        let names: Vec<&str> = keys
+            .iter()
+            .filter_map(|k| k.as_str().or_else(|| k["key"].as_str()))
+            .collect();
+        scan::group_by_pattern(patterns, &names)
+            .into_iter()
+            .map(|(pattern, keys)| {
+                serde_json::json!({"pattern": pattern, "count": keys.len(), "keys": keys})
+            })
+            .collect()
+    }
+
+    /// How a type filter was applied, as reported in scan results.
+    fn type_filter_mode(filter: &scan::TypeFilter) -> &'static str {
+        if filter.client_side {
+            "client"
+        } else {
+            "server"
+        }
+    }
+
+    /// The `cursor` parameter of a paged scan; `None` (or "0") starts over.
+    fn scan_start(cursor: Option<&str>) -> Result<Option<scan::ScanCursor>, McpRedisError> {
+        match cursor.map(str::trim) {
+            None | Some("") | Some("0") => Ok(None),
+            Some(raw) => scan::ScanCursor::parse(raw).map(Some).ok_or_else(|| {
+                McpRedisError::Other(format!(
+                    "Invalid cursor '{}': pass the cursor returned by the previous call",
+                    raw
+                ))
+            }),
+        }
+    }
+
+    /// The nodes a keyspace-wide command must visit: the connection itself for
+    /// standalone instances, or every master that owns slots for a cluster.
+    async fn scan_targets(
+        &self,
+        entry: &RedisConnection,
+    ) -> Result<Vec<(Option<String>, TracedConnection)>, McpRedisError> {
+        let mut conn = entry.connection();
+        let raw: String = redis::cmd("INFO")
+            .arg("cluster")
+            .query_async(&mut conn)
+            .await?;
+        if info::parse_fields(&raw).get("cluster_enabled") != Some(&serde_json::json!(1)) {
+            return Ok(vec![(None, conn)]);
+        }
+
+        let nodes: String = redis::cmd("CLUSTER")
+            .arg("NODES")
+            .query_async(&mut conn)
+            .await?;
+        let mut targets = Vec::new();
+        for node in cluster::parse_nodes(&nodes) {
+            let flags = node["flags"].as_array().cloned().unwrap_or_default();
+            if node["role"] != "master"
+                || node["slot_count"] == 0
+                || flags.iter().any(|f| f == "fail" || f == "noaddr")
+            {
+                continue;
+            }
+            let address = node["address"].as_str().unwrap_or_default().to_string();
+            if node["myself"] == true {
+                targets.push((Some(address), entry.connection()));
+                continue;
+            }
+            let Some((host, port)) = cluster::split_address(&address) else {
+                continue;
+            };
+            let node_conn = self.node_connection(entry, host, port).await?;
+            targets.push((Some(address), TracedConnection::new(node_conn)));
+        }
+        Ok(targets)
+    }
+
+    /// Cached connection to another node of the cluster `entry` belongs to.
+    async fn node_connection(
+        &self,
+        entry: &RedisConnection,
+        host: &str,
+        port: u16,
+    ) -> Result<redis::aio::ConnectionManager, McpRedisError> {
+        let cache_key = format!("{}/{}:{}", entry.name, host, port);
+        if let Some(conn) = self.node_conns.lock().await.get(&cache_key) {
+            return Ok(conn.clone());
+        }
+        // Connected outside the lock, so an unreachable node only stalls
+        // the calls that need it, and for LAZY_CONNECT_TIMEOUT at most
+        let info = cluster::node_connection_info(entry.client.get_connection_info(), host, port);
+        let client = redis::Client::open(info)?;
+        let conn = tokio::time::timeout(
+            LAZY_CONNECT_TIMEOUT,
+            redis::aio::ConnectionManager::new(client),
+        )
+        .await
+        .map_err(|_| {
+            McpRedisError::Other(format!(
+                "Timed out connecting to cluster node {}:{} of '{}'",
+                host, port, entry.name
+            ))
+        })??;
+        // Another call may have connected meanwhile; keep the first
+        Ok(self
+            .node_conns
+            .lock()
+            .await
+            .entry(cache_key)
+            .or_insert(conn)
+            .clone())
+    }
+
+    /// Connections a `bulk_delete` applies to: every connection tagged
+    /// `group`, or the single resolved connection.
+    fn delete_targets(
+        &self,
+        connection: Option<&str>,
+        group: Option<&str>,
+    ) -> Result<Vec<RedisConnection>, McpRedisError> {
+        match (connection, group) {
+            (Some(_), Some(_)) => Err(McpRedisError::Other(
+                "Pass either 'connection' or 'group', not both".to_string(),
+            )),
+            (None, Some(group)) => {
+                let targets: Vec<RedisConnection> = self
+                    .connections()
+                    .iter()
+                    .filter(|c| c.tags.iter().any(|t| t == group))
+                    .cloned()
+                    .collect();
+                if targets.is_empty() {
+                    return Err(McpRedisError::Other(format!(
+                        "No connection is tagged '{}'",
+                        group
+                    )));
+                }
+                Ok(targets)
+            }
+            (connection, None) => Ok(vec![self.resolve(connection)?]),
+        }
+    }
+
+    /// Count keys matching `pattern` on every shard of `entry`, keeping the
+    /// first few as a sample. Keys SCAN returns twice are counted once; the
+    /// count is incomplete when a shard hits the `max_scan_iterations` limit.
+    async fn count_matching(
+        &self,
+        entry: &RedisConnection,
+        pattern: &str,
+    ) -> Result<(u64, bool, Vec<String>), McpRedisError> {
+        let mut matches = std::collections::HashSet::new();
+        let mut complete = true;
+        let mut sample = Vec::new();
+        for (_, mut conn) in self.scan_targets(entry).await? {
+            let mut cursor: u64 = 0;
+            let max_iterations = self.limits().max_scan_iterations;
+            let mut iterations = 0;
+            loop {
+                let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(pattern)
+                    .arg("COUNT")
+                    .arg(self.scan_count)
+                    .query_async(&mut conn)
+                    .await?;
+                for key in keys {
+                    if matches.insert(key.clone()) && sample.len() < DELETE_PLAN_SAMPLE {
+                        sample.push(key);
+                    }
+                }
+                cursor = next;
+                iterations += 1;
+                if cursor == 0 {
+                    break;
+                }
+                if iterations >= max_iterations {
+                    complete = false;
+                    break;
+                }
+            }
+        }
+        Ok((matches.len() as u64, complete, sample))
+    }
+
+    /// UNLINK every key matching `pattern` on every shard of `entry`, in
+    /// pipelines of `batch_size` single-key UNLINKs (so keys in different
+    /// cluster slots never share a command). `deleted` is the running total
+    /// across all connections of the operation, reported after each batch.
+    async fn unlink_matching(
+        &self,
+        entry: &RedisConnection,
+        pattern: &str,
+        batch_size: usize,
+        progress: Option<&ProgressReporter>,
+        deleted: &AtomicU64,
+        total: u64,
+    ) -> (u64, Vec<String>) {
+        let mut unlinked = 0;
+        let mut errors = Vec::new();
+        let targets = match self.scan_targets(entry).await {
+            Ok(targets) => targets,
+            Err(e) => return (0, vec![e.to_string()]),
+        };
+        'shards: for (shard, mut conn) in targets {
+            let shard = shard.unwrap_or_else(|| entry.name.clone());
+            let mut cursor: u64 = 0;
+            let mut pending: Vec<String> = Vec::new();
+            loop {
+                let scanned: redis::RedisResult<(u64, Vec<String>)> = redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(pattern)
+                    .arg("COUNT")
+                    .arg(self.scan_count)
+                    .query_async(&mut conn)
+                    .await;
+                match scanned {
+                    Ok((next, keys)) => {
+                        cursor = next;
+                        pending.extend(keys);
+                    }
+                    Err(e) => {
+                        errors.push(format!("{}: {}", shard, e));
+                        continue 'shards;
+                    }
+                }
+                while pending.len() >= batch_size || (cursor == 0 && !pending.is_empty()) {
+                    let chunk: Vec<String> =
+                        pending.drain(..pending.len().min(batch_size)).collect();
+                    let mut pipe = redis::pipe();
+                    for key in &chunk {
+                        pipe.cmd("UNLINK").arg(key);
+                    }
+                    let counts: Vec<u64> = match pipe.query_async(&mut conn).await {
+                        Ok(counts) => counts,
+                        Err(e) => {
+                            errors.push(format!("{}: {}", shard, e));
+                            continue 'shards;
+                        }
+                    };
+                    let n: u64 = counts.iter().sum();
+                    unlinked += n;
+                    let done = deleted.fetch_add(n, Ordering::Relaxed) + n;
+                    if let Some(progress) = progress {
+                        progress
+                            .report(
+                                done,
+                                Some(total.max(done)),
+                                format!("{}: {} keys unlinked", entry.name, unlinked),
+                            )
+                            .await;
+                    }
+                }
+                if cursor == 0 {
+                    break;
+                }
+            }
+        }
+        (unlinked, errors)
+    }
+
+    /// Declare the structured content every tool returns: a JSON object
+    /// holding the data of its text blocks.
+    fn with_output_schema(mut tool: Tool) -> Tool {
+        let mut schema = serde_json::Map::new();
+        schema.insert("type".to_string(), serde_json::json!("object"));
+        tool.output_schema = Some(Arc::new(schema));
+        tool
+    }
+
+    /// Add the `trace` flag to a tool's input schema when tracing is enabled.
+    fn with_trace_param(&self, mut tool: Tool) -> Tool {
+        if !self.allow_trace {
+            return tool;
+        }
+        let mut schema = (*tool.input_schema).clone();
+        let properties = schema
+            .entry("properties")
+            .or_insert_with(|| serde_json::json!({}));
+        if let Some(properties) = properties.as_object_mut() {
+            properties.insert(
+                "trace".to_string(),
+                serde_json::json!({
+                    "type": "boolean",
+                    "description": "Debug: attach the Redis commands issued by this call (values redacted) with per-command timings",
+                }),
+            );
+        }
+        tool.input_schema = Arc::new(schema);
+        tool
+    }
+
+    /// Every tool as listed to clients: built-in tools, presets and views,
+    /// with the `trace`, `db` and `all_dbs` arguments they take.
+    pub fn tools(&self) -> Vec<Tool> {
+        self.tool_router
+            .list_all()
+            .into_iter()
+            .chain(self.presets().iter().map(|preset| self.preset_tool(preset)))
+            .chain(self.views().iter().map(|view| self.view_tool(view)))
+            .map(|tool| self.with_trace_param(tool))
+            .map(Self::with_db_param)
+            .map(Self::with_output_schema)
+            .collect()
+    }
+
+    /// Advertise the `db` argument on tools that work on keys, and
+    /// `all_dbs` on those that can run across databases.
+    fn with_db_param(mut tool: Tool) -> Tool {
+        if !DB_TOOLS.contains(&tool.name.as_ref()) {
+            return tool;
+        }
+        let all_dbs = ALL_DBS_TOOLS.contains(&tool.name.as_ref());
+        let mut schema = (*tool.input_schema).clone();
+        let properties = schema
+            .entry("properties")
+            .or_insert_with(|| serde_json::json!({}));
+        if let Some(properties) = properties.as_object_mut() {
+            properties.insert(
+                "db".to_string(),
+                serde_json::json!({
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Logical database to run in (default: the connection's own)",
+                }),
+            );
+            if all_dbs {
+                properties.insert(
+                    "all_dbs".to_string(),
+                    serde_json::json!({
+                        "type": "boolean",
+                        "description": "Run once in every database that holds keys and return the results per db (default: false)",
+                    }),
+                );
+            }
+        }
+        tool.input_schema = Arc::new(schema);
+        tool
+    }
+}
+
+// -- Read-only Redis commands for reference --
+// INFO, SCAN, TYPE, GET, LRANGE, SMEMBERS, ZRANGE, HGETALL, TTL, OBJECT, MEMORY, DBSIZE
+// Write commands that need check_read_only: XAUTOCLAIM, SET, DEL, FLUSHDB, EXPIRE, etc.
+
+// -- Public methods for testability --
+
+impl McpRedisServer {
+    /// What this server allows and the limits it applies, so a client can
+    /// tell why a call was refused or cut short.
+    pub async fn do_server_policy(&self) -> Result<CallToolResult, ErrorData> {
+        let read_only: Vec<String> = self
+            .connections()
+            .iter()
+            .filter(|c| c.read_only)
+            .map(|c| c.name.clone())
+            .collect();
+        Ok(json_result(serde_json::json!({
+            "allow_write": self.allow_write,
+            "allow_trace": self.allow_trace,
+            "allow_admin": self.allow_admin,
+            "read_only_connections": read_only,
+            "record_session": self.transcripts.is_some(),
+            "export_dir": self.export_dir.is_some(),
+            "rdb": self.rdb_path.is_some(),
+            "notify_webhook": self.notifier.is_some(),
+            "scan_count": self.scan_count,
+            "max_value_bytes": self.max_value_bytes,
+            "limits": self.limits(),
+            "config_set_allow": *self.config_set_allow(),
+            "note": "Limits come from [limits] in the config file and --limit NAME=VALUE; reload_config applies config file changes",
+        })))
+    }
+
+    pub async fn do_list_connections(&self) -> Result<CallToolResult, ErrorData> {
+        let now = restart::now();
+        let mut connections: Vec<serde_json::Value> = self
+            .connections()
+            .iter()
+            .map(|c| {
+                let mut item = serde_json::json!({
+                    "name": c.name,
+                    "url": c.url_redacted,
+                    "status": "connected",
+                });
+                if c.read_only {
+                    item["read_only"] = serde_json::json!(true);
+                }
+                if !c.tags.is_empty() {
+                    item["tags"] = serde_json::json!(c.tags);
+                }
+                let db = c.client.get_connection_info().redis.db;
+                if db != 0 {
+                    item["db"] = serde_json::json!(db);
+                }
+                if let Some(identity) = &c.identity {
+                    item["run_id"] = serde_json::json!(identity.run_id);
+                    item["uptime_secs"] = serde_json::json!(identity.uptime_secs(now));
+                }
+                if let Some(restart) = &c.restart {
+                    item["restarted"] = serde_json::json!({
+                        "message": restart.notice(now),
+                        "previous_run_id": restart.previous_run_id,
+                    });
+                }
+                if let Some(note) = self.maintenance.get(&c.name, now) {
+                    item["maintenance_note"] = serde_json::json!({
+                        "message": note.message,
+                        "expires_in_secs": note.expires_at.saturating_sub(now),
+                    });
+                }
+                if let Some(version) = &c.version {
+                    item["flavor"] = serde_json::json!(version.flavor);
+                    item["version"] = serde_json::json!(version.version);
+                    let warnings = version.warnings();
+                    if !warnings.is_empty() {
+                        item["warnings"] = serde_json::json!(warnings);
+                    }
+                }
+                item
+            })
+            .collect();
+
+        let pending: Vec<serde_json::Value> = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|p| {
+                let mut item = serde_json::json!({
+                    "name": p.name,
+                    "url": p.url_redacted,
+                    "status": p.status(),
+                    "attempts": p.attempts,
+                });
+                if let Some(error) = &p.last_error {
+                    item["error"] = serde_json::json!(error);
+                }
+                if let Some(at) = p.retry_at {
+                    let wait = at.saturating_duration_since(Instant::now());
+                    item["retry_in_secs"] = serde_json::json!(wait.as_secs());
+                }
+                item
+            })
+            .collect();
+        connections.extend(pending);
+
+        // The text stays a bare array; structured content must be an object
+        let text =
+            serde_json::to_string_pretty(&connections).unwrap_or_else(|_| "[]".to_string());
+        let mut result = CallToolResult::success(vec![Content::text(text)]);
+        result.structured_content = Some(serde_json::json!({ "connections": connections }));
+        Ok(result)
+    }
+
+    pub async fn do_ping(&self, params: PingParams) -> Result<CallToolResult, ErrorData> {
+        let targets = match params.connection.as_deref() {
+            Some(name) => vec![self.resolve(Some(name)).map_err(|e| self.err(e))?],
+            None => self.connections().clone(),
+        };
+        let count = params
+            .count
+            .unwrap_or(5)
+            .clamp(1, self.limits().max_ping_count);
+
+        let results = futures_util::future::join_all(targets.iter().map(|entry| async move {
+            let mut conn = entry.connection();
+            let mut samples: Vec<f64> = Vec::new();
+            let mut errors: Vec<String> = Vec::new();
+            for _ in 0..count {
+                let started = Instant::now();
+                let reply = tokio::time::timeout(
+                    PING_TIMEOUT,
+                    redis::cmd("PING").query_async::<String>(&mut conn),
+                )
+                .await;
+                match reply {
+                    Ok(Ok(_)) => samples.push(started.elapsed().as_secs_f64() * 1000.0),
+                    Ok(Err(e)) => errors.push(e.to_string()),
+                    Err(_) => errors.push(format!("timed out after {}s", PING_TIMEOUT.as_secs())),
+                }
+            }
+            let status = if errors.is_empty() {
+                "ok"
+            } else if samples.is_empty() {
+                "down"
+            } else {
+                "degraded"
+            };
+            let mut item = serde_json::json!({
+                "connection": entry.name,
+                "status": status,
+                "sent": count,
+                "received": samples.len(),
+            });
+            if !samples.is_empty() {
+                samples.sort_by(|a, b| a.total_cmp(b));
+                let avg = samples.iter().sum::<f64>() / samples.len() as f64;
+                item["latency_ms"] = serde_json::json!({
+                    "min": round_ms(samples[0]),
+                    "avg": round_ms(avg),
+                    "p99": round_ms(percentile(&samples, 99.0)),
+                    "max": round_ms(samples[samples.len() - 1]),
+                });
+            }
+            if !errors.is_empty() {
+                errors.dedup();
+                item["errors"] = serde_json::json!(errors);
+            }
+            item
+        }))
+        .await;
+
+        Ok(json_result(serde_json::json!({ "connections": results })))
+    }
+
+    pub async fn do_info(&self, params: InfoParams) -> Result<CallToolResult, ErrorData> {
+        let entry = self.resolve(params.connection.as_deref()).map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        if let Some(section) = params.section {
+            let info: String = redis::cmd("INFO")
+                .arg(&section)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+            let parsed = serde_json::json!({ "sections": info::parse_sections(&info) });
+            let mut result = if params.raw.unwrap_or(false) {
+                // The text stays raw; the structured form is parsed
+                let mut result = CallToolResult::success(vec![Content::text(info.clone())]);
+                result.structured_content = Some(parsed);
+                result
+            } else {
+                json_result(parsed)
+            };
+            if let Some(explanations) = Self::info_explanations(&info::parse_fields(&info)) {
+                append_json(&mut result, explanations);
+            }
+            return Ok(result);
+        }
+
+        // Plain INFO leaves out commandstats and latencystats; INFO all is
+        // only needed when one of them is wanted
+        let include_large = params.include_large.unwrap_or(false);
+        let wants_large = include_large
+            || params
+                .sections
+                .iter()
+                .flatten()
+                .any(|name| info::LARGE_SECTIONS.contains(&name.to_lowercase().as_str()));
+        let mut cmd = redis::cmd("INFO");
+        if wants_large {
+            cmd.arg("all");
+        }
+        let info: String = cmd
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let selection = info::select_sections(
+            info::parse_sections(&info),
+            params.sections.as_deref(),
+            include_large,
+        );
+        let fields: serde_json::Map<String, serde_json::Value> = selection
+            .sections
+            .values()
+            .filter_map(|section| section.as_object())
+            .flat_map(|section| section.clone())
+            .collect();
+        let mut result = serde_json::json!({ "sections": selection.sections });
+        if !selection.omitted.is_empty() {
+            result["omitted"] = serde_json::json!(selection.omitted);
+        }
+        if !selection.missing.is_empty() {
+            result["missing"] = serde_json::json!(selection.missing);
+        }
+        let mut result = json_result(result);
+        if let Some(explanations) = Self::info_explanations(&fields) {
+            append_json(&mut result, explanations);
+        }
+        // Large sections get a text block each, and join the others in the
+        // structured content
+        for (name, fields) in selection.large {
+            let text = serde_json::to_string_pretty(&serde_json::json!({
+                "section": name,
+                "fields": fields,
+            }))
+            .unwrap_or_else(|_| "{}".to_string());
+            result.content.push(Content::text(text));
+            if let Some(sections) = result
+                .structured_content
+                .as_mut()
+                .and_then(|structured| structured["sections"].as_object_mut())
+            {
+                sections.insert(name, fields);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Memory, replication and eviction metrics among `fields` (parsed
+    /// INFO), with what they mean and their healthy ranges, as an
+    /// `{"explanations": ...}` object (none when there are no such metrics).
+    fn info_explanations(
+        fields: &serde_json::Map<String, serde_json::Value>,
+    ) -> Option<serde_json::Value> {
+        let explanations = glossary::explain(fields);
+        if explanations.is_empty() {
+            return None;
+        }
+        Some(serde_json::json!({ "explanations": explanations }))
+    }
+
+    pub async fn do_scan_keys(&self, params: ScanParams) -> Result<CallToolResult, ErrorData> {
+        let entry = self.resolve(params.connection.as_deref()).map_err(|e| self.err(e))?;
+        let patterns = Self::scan_patterns(&params).map_err(|e| self.err(e))?;
+        let matched = Self::scan_pattern(&params, &patterns);
+        let matched = matched.as_str();
+        // Several patterns are reported by the MATCH they share
+        let pattern = if patterns.is_empty() {
+            params.pattern.as_deref().unwrap_or("*")
+        } else {
+            matched
+        };
+
+        Self::validate_pattern(matched).map_err(|e| self.err(e))?;
+
+        // Cap max_keys to scan_count to prevent unbounded iteration
+        let max_keys = std::cmp::min(
+            params.count.unwrap_or(self.scan_count) as usize,
+            self.scan_count as usize,
+        );
+
+        let start = Self::scan_start(params.cursor.as_deref()).map_err(|e| self.err(e))?;
+        let filter = scan::KeyFilter {
+            key_type: Self::type_filter(&entry, params.key_type.as_deref())
+                .map_err(|e| self.err(e))?,
+            any_of: patterns.clone(),
+            none_of: Self::exclude_patterns(&params).map_err(|e| self.err(e))?,
+        };
+        let (scans, next, coverage) = self
+            .scan_page_adaptive(&entry, matched, &filter, max_keys, start.as_ref())
+            .await
+            .map_err(|e| self.err(e))?;
+        let mut last_conn = scans.last().map(|s| s.conn.clone());
+
+        let include_metadata = params.include_metadata.unwrap_or(false);
+        let mut keys: Vec<serde_json::Value> = Vec::new();
+        let mut duplicates_filtered = 0;
+        let mut excluded = 0;
+        let mut shards = Vec::new();
+        for mut scanned in scans {
+            duplicates_filtered += scanned.outcome.duplicates_filtered;
+            excluded += scanned.outcome.excluded;
+            if let Some(shard) = &scanned.shard {
+                shards.push(serde_json::json!({
+                    "node": shard,
+                    "keys": scanned.outcome.keys,
+                    "count": scanned.outcome.keys.len(),
+                }));
+            }
+            if !include_metadata {
+                keys.extend(scanned.outcome.keys.iter().map(|k| serde_json::json!(k)));
+                continue;
+            }
+            let mut described = key_metadata(&mut scanned.conn, &scanned.outcome.keys, true).await;
+            if let Some(shard) = &scanned.shard {
+                for item in &mut described {
+                    item["shard"] = serde_json::json!(shard);
+                }
+            }
+            keys.extend(described);
+        }
+
+        let mut result = serde_json::json!({
+            "pattern": pattern,
+            "keys": keys,
+            "count": keys.len(),
+            "duplicates_filtered": duplicates_filtered,
+            "cursor": next.as_ref().map_or_else(|| "0".to_string(), scan::ScanCursor::encode),
+            "complete": next.is_none(),
+        });
+        if let Some(filter) = &filter.key_type {
+            result["type"] = serde_json::json!(filter.key_type);
+            result["type_filter"] = serde_json::json!(Self::type_filter_mode(filter));
+        }
+        if !filter.none_of.is_empty() {
+            result["exclude_patterns"] = serde_json::json!(filter.none_of);
+            result["excluded"] = serde_json::json!(excluded);
+        }
+        if matched != pattern {
+            result["match"] = serde_json::json!(matched);
+        }
+        if !shards.is_empty() {
+            result["shards"] = serde_json::Value::Array(shards);
+        }
+        if let Some(coverage) = coverage {
+            result["coverage"] = coverage;
+            match last_conn.as_mut() {
+                Some(conn) if keys.is_empty() => {
+                    self.note_empty_scan(&mut result, conn, matched, &filter)
+                        .await
+                }
+                _ => {}
+            }
+        }
+        if !patterns.is_empty() {
+            result["patterns"] = Self::pattern_groups(&patterns, &keys);
+        }
+
+        let columns: &[&str] = if include_metadata {
+            &["key", "type", "ttl", "memory_bytes", "shard"]
+        } else {
+            &["key"]
+        };
+        Ok(formatted_result(result, params.format, "keys", columns))
+    }
+
+    pub async fn do_get(&self, params: GetParams) -> Result<CallToolResult, ErrorData> {
+        let entry = self.resolve(params.connection.as_deref()).map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let max_bytes = params.max_bytes.unwrap_or(self.max_value_bytes);
+        let mut budget = budget::Budget::new(max_bytes);
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+        let follow = params.follow_references.unwrap_or(false);
+        if follow && self.references().is_empty() {
+            return Err(self.err(McpRedisError::Other(
+                "No reference patterns configured (add them under [[references]] in the config file)"
+                    .to_string(),
+            )));
+        }
+
+        // Get key type first
+        let key_type: String = redis::cmd("TYPE")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        if params.summary_only.unwrap_or(false) && key_type != "none" {
+            return self
+                .get_summary(&mut conn, &params.key, &key_type, budget)
+                .await;
+        }
+
+        // Giant keys are refused before any of them is read
+        let length_command = match key_type.as_str() {
+            "string" | "list" | "set" | "zset" | "hash" => census::length_command(&key_type),
+            _ => None,
+        };
+        let length: u64 = match length_command {
+            Some(command) => redis::cmd(command)
+                .arg(&params.key)
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_err)?,
+            None => 0,
+        };
+        let (limit, name, unit) = if key_type == "string" {
+            let limit = self.limits().max_get_string_bytes;
+            (limit, "max_get_string_bytes", "bytes")
+        } else {
+            (
+                self.limits().max_get_elements,
+                "max_get_elements",
+                "elements",
+            )
+        };
+        if length > limit {
+            return Err(self.err(McpRedisError::Other(format!(
+                "'{}' is a {} of {} {}, over {} ({}); not reading it with get. {}, or pass summary_only: true",
+                params.key,
+                key_type,
+                length,
+                unit,
+                name,
+                limit,
+                budget::paging_hint(&key_type)
+            ))));
+        }
+
+        // Collections are read in batches until the budget runs out
+        let batch = self.limits().get_batch.max(1);
+        let value: serde_json::Value = match key_type.as_str() {
+            "string" => {
+                // One byte past the limit tells whether there is more
+                let bytes: Vec<u8> = redis::cmd("GETRANGE")
+                    .arg(&params.key)
+                    .arg(0)
+                    .arg(max_bytes)
+                    .query_async(&mut conn)
+                    .await
+                    .map_err(redis_err)?;
+                let kept = bytes.len().min(max_bytes as usize);
+                budget.admit(bytes.len());
+                serde_json::Value::String(budget::utf8_prefix(&bytes[..kept]))
+            }
+            "list" => {
+                let mut items = Vec::new();
+                let mut start = 0;
+                loop {
+                    let page: Vec<String> = redis::cmd("LRANGE")
+                        .arg(&params.key)
+                        .arg(start)
+                        .arg(start + batch as isize - 1)
+                        .query_async(&mut conn)
+                        .await
+                        .map_err(redis_err)?;
+                    let done = page.len() < batch;
+                    items.extend(page.into_iter().take_while(|v| budget.admit(v.len())));
+                    if done || budget.exhausted() {
+                        break;
+                    }
+                    start += batch as isize;
+                }
+                serde_json::json!(items)
+            }
+            "set" => {
+                let mut members = Vec::new();
+                let mut seen = std::collections::HashSet::new();
+                let mut cursor = 0u64;
+                loop {
+                    let (next, page): (u64, Vec<String>) = redis::cmd("SSCAN")
+                        .arg(&params.key)
+                        .arg(cursor)
+                        .arg("COUNT")
+                        .arg(batch)
+                        .query_async(&mut conn)
+                        .await
+                        .map_err(redis_err)?;
+                    for member in page {
+                        if seen.contains(&member) {
+                            continue;
+                        }
+                        if !budget.admit(member.len()) {
+                            break;
+                        }
+                        seen.insert(member.clone());
+                        members.push(member);
+                    }
+                    cursor = next;
+                    if cursor == 0 || budget.exhausted() {
+                        break;
+                    }
+                }
+                serde_json::json!(members)
+            }
+            "zset" => {
+                let mut members = Vec::new();
+                let mut start = 0;
+                loop {
+                    let page: Vec<(String, f64)> = redis::cmd("ZRANGE")
+                        .arg(&params.key)
+                        .arg(start)
+                        .arg(start + batch as isize - 1)
+                        .arg("WITHSCORES")
+                        .query_async(&mut conn)
+                        .await
+                        .map_err(redis_err)?;
+                    let done = page.len() < batch;
+                    members.extend(
+                        page.into_iter()
+                            .take_while(|(m, _)| budget.admit(m.len() + 8))
+                            .map(|(m, s)| serde_json::json!({"member": m, "score": s})),
+                    );
+                    if done || budget.exhausted() {
+                        break;
+                    }
+                    start += batch as isize;
+                }
+                serde_json::json!(members)
+            }
+            "hash" => {
+                let mut map = serde_json::Map::new();
+                let mut cursor = 0u64;
+                loop {
+                    let (next, page): (u64, Vec<(String, String)>) = redis::cmd("HSCAN")
+                        .arg(&params.key)
+                        .arg(cursor)
+                        .arg("COUNT")
+                        .arg(batch)
+                        .query_async(&mut conn)
+                        .await
+                        .map_err(redis_err)?;
+                    for (field, value) in page {
+                        if map.contains_key(&field) {
+                            continue;
+                        }
+                        if !budget.admit(field.len() + value.len()) {
+                            break;
+                        }
+                        map.insert(field, serde_json::Value::String(value));
+                    }
+                    cursor = next;
+                    if cursor == 0 || budget.exhausted() {
+                        break;
+                    }
+                }
+                serde_json::Value::Object(map)
+            }
+            "none" => {
+                return Ok(json_result(
+                    serde_json::json!({"error": "Key does not exist", "key": params.key}),
+                ));
+            }
+            other => serde_json::json!({"type": other, "note": "Unsupported type"}),
+        };
+
+        let mut result = serde_json::json!({
+            "key": params.key,
+            "type": key_type,
+            "value": value,
+        });
+        if budget.exhausted() {
+            let (size, returned) = if key_type == "string" {
+                (
+                    serde_json::json!({ "bytes": length }),
+                    serde_json::json!({ "bytes": result["value"].as_str().map_or(0, str::len) }),
+                )
+            } else {
+                let returned = match &result["value"] {
+                    serde_json::Value::Array(items) => items.len(),
+                    serde_json::Value::Object(map) => map.len(),
+                    _ => 0,
+                };
+                (
+                    serde_json::json!({ "length": length }),
+                    serde_json::json!({ "length": returned, "bytes": budget.used() }),
+                )
+            };
+            result["truncated"] = serde_json::json!(true);
+            result["size"] = size;
+            result["returned"] = returned;
+            result["max_bytes"] = serde_json::json!(max_bytes);
+            result["hint"] = serde_json::json!(budget::paging_hint(&key_type));
+        }
+        if follow {
+            let depth = params
+                .reference_depth
+                .unwrap_or(1)
+                .clamp(1, MAX_REFERENCE_DEPTH);
+            let (followed, truncated) = self
+                .follow_references(&mut conn, &params.key, &result["value"], depth, max_bytes)
+                .await?;
+            result["references"] = serde_json::json!(followed);
+            if truncated {
+                result["references_truncated"] = serde_json::json!(true);
+                result["max_followed_references"] =
+                    serde_json::json!(self.limits().max_followed_references);
+            }
+        }
+        Ok(json_result(result))
+    }
+
+    /// Keys named inside `value` by the `[[references]]` patterns, read
+    /// breadth first for `depth` hops, each at most [`REFERENCE_ENTRIES`]
+    /// elements or `max_bytes` of a string. Each hop is one pipeline of
+    /// TYPE and one of reads. Returns the keys read and whether
+    /// `max_followed_references` cut the walk short.
+    async fn follow_references(
+        &self,
+        conn: &mut TracedConnection,
+        root: &str,
+        value: &serde_json::Value,
+        depth: u32,
+        max_bytes: u64,
+    ) -> Result<(Vec<serde_json::Value>, bool), ErrorData> {
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+        let matcher = self.references();
+        let limit = self.limits().max_followed_references;
+        let mut seen = std::collections::HashSet::from([root.to_string()]);
+        let mut frontier: Vec<(String, String)> = matcher
+            .find(value)
+            .into_iter()
+            .map(|key| (key, root.to_string()))
+            .collect();
+        let mut followed = Vec::new();
+        let mut truncated = false;
+
+        for level in 1..=depth {
+            frontier.retain(|(key, _)| seen.insert(key.clone()));
+            if followed.len() + frontier.len() > limit {
+                frontier.truncate(limit - followed.len());
+                truncated = true;
+            }
+            if frontier.is_empty() {
+                break;
+            }
+
+            let mut pipe = redis::pipe();
+            for (key, _) in &frontier {
+                pipe.cmd("TYPE").arg(key);
+            }
+            let types: Vec<String> = pipe.query_async(conn).await.map_err(redis_err)?;
+
+            // Length and value of each key of a type that is read
+            let mut pipe = redis::pipe();
+            let mut read = Vec::with_capacity(frontier.len());
+            for ((key, _), key_type) in frontier.iter().zip(&types) {
+                let commands = census::length_command(key_type).zip(references::read_command(
+                    key_type,
+                    key,
+                    REFERENCE_ENTRIES,
+                    max_bytes,
+                ));
+                read.push(commands.is_some());
+                if let Some((length, value)) = commands {
+                    pipe.cmd(length).arg(key);
+                    pipe.add_command(value);
+                }
+            }
+            let replies: Vec<redis::Value> = if read.contains(&true) {
+                pipe.query_async(conn).await.map_err(redis_err)?
+            } else {
+                Vec::new()
+            };
+            let mut replies = replies.chunks(2);
+
+            let mut next = Vec::new();
+            for (((key, referenced_by), key_type), read) in frontier.drain(..).zip(types).zip(read)
+            {
+                let mut item = serde_json::json!({
+                    "key": key,
+                    "type": key_type,
+                    "depth": level,
+                    "referenced_by": referenced_by,
+                });
+                if read {
+                    let Some([length, raw]) = replies.next() else {
+                        break;
+                    };
+                    let length: u64 = redis::from_redis_value(length).map_err(redis_err)?;
+                    let value = references::value_json(&key_type, raw);
+                    if references::returned(&value) < length {
+                        item["truncated"] = serde_json::json!(true);
+                    }
+                    if level < depth {
+                        next.extend(matcher.find(&value).into_iter().map(|k| (k, key.clone())));
+                    }
+                    item["length"] = serde_json::json!(length);
+                    item["value"] = value;
+                }
+                followed.push(item);
+            }
+            frontier = next;
+        }
+        Ok((followed, truncated))
+    }
+
+    pub async fn do_get_multiple(
+        &self,
+        params: GetMultipleParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let max_keys = self.limits().max_get_keys;
+        if params.keys.is_empty() || params.keys.len() > max_keys {
+            return Err(self.err(McpRedisError::Other(format!(
+                "Pass between 1 and max_get_keys ({}) keys",
+                max_keys
+            ))));
+        }
+        let mut conn = entry.connection();
+        let max_bytes = params.max_bytes.unwrap_or(self.max_value_bytes);
+        let mut budget = budget::Budget::new(max_bytes);
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+
+        let mut pipe = redis::pipe();
+        for key in &params.keys {
+            pipe.cmd("TYPE").arg(key);
+        }
+        let types: Vec<String> = pipe.query_async(&mut conn).await.map_err(redis_err)?;
+
+        // Strings in one MGET; every other type with its length and first
+        // entries in one pipeline
+        let strings: Vec<&String> = params
+            .keys
+            .iter()
+            .zip(&types)
+            .filter(|(_, t)| *t == "string")
+            .map(|(k, _)| k)
+            .collect();
+        let mut string_values: std::vec::IntoIter<Option<Vec<u8>>> = if strings.is_empty() {
+            Vec::new()
+        } else {
+            redis::cmd("MGET")
+                .arg(&strings)
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_err)?
+        }
+        .into_iter();
+        let mut pipe = redis::pipe();
+        let mut collections = 0;
+        for (key, key_type) in params.keys.iter().zip(&types) {
+            if key_type == "string" {
+                continue;
+            }
+            let commands = census::length_command(key_type).zip(references::read_command(
+                key_type,
+                key,
+                MULTI_GET_ENTRIES,
+                max_bytes,
+            ));
+            if let Some((length, value)) = commands {
+                pipe.cmd(length).arg(key);
+                pipe.add_command(value);
+                collections += 1;
+            }
+        }
+        let replies: Vec<redis::Value> = if collections > 0 {
+            pipe.query_async(&mut conn).await.map_err(redis_err)?
+        } else {
+            Vec::new()
+        };
+        let mut replies = replies.chunks(2);
+
+        let mut items = Vec::with_capacity(params.keys.len());
+        let mut missing = Vec::new();
+        for (key, key_type) in params.keys.iter().zip(types) {
+            let mut item = serde_json::json!({
+                "key": key,
+                "exists": key_type != "none",
+                "type": key_type,
+            });
+            match key_type.as_str() {
+                "none" => missing.push(key.clone()),
+                "string" => {
+                    // A key deleted between TYPE and MGET reads back as nil
+                    let bytes = string_values.next().flatten().unwrap_or_default();
+                    item["length"] = serde_json::json!(bytes.len());
+                    let left = max_bytes.saturating_sub(budget.used()) as usize;
+                    let kept = bytes.len().min(left);
+                    budget.admit(bytes.len());
+                    if kept < bytes.len() {
+                        item["truncated"] = serde_json::json!(true);
+                    }
+                    item["value"] = serde_json::json!(budget::utf8_prefix(&bytes[..kept]));
+                }
+                _ => {
+                    if census::length_command(&key_type).is_some() {
+                        let Some([length, raw]) = replies.next() else {
+                            break;
+                        };
+                        let length: u64 = redis::from_redis_value(length).map_err(redis_err)?;
+                        let value = references::value_json(&key_type, raw);
+                        item["length"] = serde_json::json!(length);
+                        if references::returned(&value) < length {
+                            item["truncated"] = serde_json::json!(true);
+                        }
+                        if budget.admit(value.to_string().len()) {
+                            item["value"] = value;
+                        } else {
+                            item["truncated"] = serde_json::json!(true);
+                        }
+                    }
+                }
+            }
+            items.push(item);
+        }
+
+        let mut result = serde_json::json!({
+            "connection": entry.name,
+            "count": items.len(),
+            "found": items.len() - missing.len(),
+            "missing": missing,
+            "keys": items,
+        });
+        if budget.exhausted() {
+            result["truncated"] = serde_json::json!(true);
+            result["hint"] = serde_json::json!(format!(
+                "Values past {} bytes were cut or left out; read those keys with get",
+                max_bytes
+            ));
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_exists(&self, params: ExistsParams) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let max_keys = self.limits().max_exists_keys;
+        if params.keys.is_empty() || params.keys.len() > max_keys {
+            return Err(self.err(McpRedisError::Other(format!(
+                "Pass between 1 and max_exists_keys ({}) keys",
+                max_keys
+            ))));
+        }
+        let mut conn = entry.connection();
+
+        // EXISTS with several keys only counts them; one per key tells which
+        let mut pipe = redis::pipe();
+        for key in &params.keys {
+            pipe.cmd("EXISTS").arg(key);
+        }
+        let found: Vec<bool> = pipe
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        // A key passed twice is reported once
+        let mut exists = serde_json::Map::new();
+        let mut missing = Vec::new();
+        for (key, found) in params.keys.iter().zip(found) {
+            if exists
+                .insert(key.clone(), serde_json::json!(found))
+                .is_none()
+                && !found
+            {
+                missing.push(key.clone());
+            }
+        }
+        let existing = exists.len() - missing.len();
+        Ok(json_result(serde_json::json!({
+            "connection": entry.name,
+            "count": exists.len(),
+            "existing": existing,
+            "missing": missing,
+            "exists": exists,
+        })))
+    }
+
+    /// `get` with `summary_only`: length, TTL, encoding, memory and the
+    /// first few entries (random members for sets) in place of the value.
+    /// The sample still counts against the byte budget.
+    async fn get_summary(
+        &self,
+        conn: &mut TracedConnection,
+        key: &str,
+        key_type: &str,
+        mut budget: budget::Budget,
+    ) -> Result<CallToolResult, ErrorData> {
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+        let entries = SUMMARY_SAMPLE_ENTRIES;
+        let mut pipe = redis::pipe();
+        pipe.cmd("TTL").arg(key);
+        let length_command = census::length_command(key_type);
+        if let Some(command) = length_command {
+            pipe.cmd(command).arg(key);
+        }
+        match key_type {
+            "string" => {
+                pipe.cmd("GETRANGE")
+                    .arg(key)
+                    .arg(0)
+                    .arg(SUMMARY_STRING_PREFIX - 1);
+            }
+            "list" => {
+                pipe.cmd("LRANGE").arg(key).arg(0).arg(entries - 1);
+            }
+            "set" => {
+                pipe.cmd("SRANDMEMBER").arg(key).arg(entries);
+            }
+            "zset" => {
+                pipe.cmd("ZRANGE")
+                    .arg(key)
+                    .arg(0)
+                    .arg(entries - 1)
+                    .arg("WITHSCORES");
+            }
+            "hash" => {
+                pipe.cmd("HSCAN").arg(key).arg(0).arg("COUNT").arg(entries);
+            }
+            "stream" => {
+                pipe.cmd("XRANGE")
+                    .arg(key)
+                    .arg("-")
+                    .arg("+")
+                    .arg("COUNT")
+                    .arg(entries);
+            }
+            _ => {}
+        }
+        let reply: Vec<redis::Value> = pipe.query_async(conn).await.map_err(redis_err)?;
+        let ttl: i64 = redis::from_redis_value(&reply[0]).map_err(redis_err)?;
+        let length: Option<u64> = match length_command {
+            Some(_) => Some(redis::from_redis_value(&reply[1]).map_err(redis_err)?),
+            None => None,
+        };
+
+        let sample = match (key_type, reply.get(2)) {
+            ("string", Some(raw)) => {
+                let bytes: Vec<u8> = redis::from_redis_value(raw).map_err(redis_err)?;
+                budget.admit(bytes.len());
+                serde_json::json!(budget::utf8_prefix(&bytes))
+            }
+            ("list" | "set", Some(raw)) => {
+                let items: Vec<String> = redis::from_redis_value(raw).map_err(redis_err)?;
+                let items: Vec<String> = items
+                    .into_iter()
+                    .take_while(|v| budget.admit(v.len()))
+                    .collect();
+                serde_json::json!(items)
+            }
+            ("zset", Some(raw)) => {
+                let members: Vec<(String, f64)> =
+                    redis::from_redis_value(raw).map_err(redis_err)?;
+                serde_json::Value::Array(
+                    members
+                        .into_iter()
+                        .take_while(|(m, _)| budget.admit(m.len() + 8))
+                        .map(|(m, s)| serde_json::json!({"member": m, "score": s}))
+                        .collect(),
+                )
+            }
+            ("hash", Some(raw)) => {
+                let (_, fields): (u64, Vec<(String, String)>) =
+                    redis::from_redis_value(raw).map_err(redis_err)?;
+                serde_json::Value::Object(
+                    fields
+                        .into_iter()
+                        .take(entries)
+                        .take_while(|(f, v)| budget.admit(f.len() + v.len()))
+                        .map(|(f, v)| (f, serde_json::Value::String(v)))
+                        .collect(),
+                )
+            }
+            ("stream", Some(raw)) => match resp::to_json(raw) {
+                serde_json::Value::Array(items) => serde_json::Value::Array(
+                    items
+                        .into_iter()
+                        .take_while(|entry| budget.admit(entry.to_string().len()))
+                        .collect(),
+                ),
+                other => other,
+            },
+            _ => serde_json::Value::Null,
+        };
+        let sampled = match &sample {
+            serde_json::Value::Array(items) => items.len(),
+            serde_json::Value::Object(map) => map.len(),
+            _ => 0,
+        };
+
+        // Both are refused on some managed services
+        let encoding: Option<String> = redis::cmd("OBJECT")
+            .arg("ENCODING")
+            .arg(key)
+            .query_async(conn)
+            .await
+            .ok();
+        let memory: Option<u64> = redis::cmd("MEMORY")
+            .arg("USAGE")
+            .arg(key)
+            .arg("SAMPLES")
+            .arg(METADATA_MEMORY_SAMPLES)
+            .query_async::<Option<u64>>(conn)
+            .await
+            .ok()
+            .flatten();
+
+        let mut result = serde_json::json!({
+            "key": key,
+            "type": key_type,
+            "summary_only": true,
+            "ttl": ttl,
+            "encoding": encoding,
+            "memory_bytes": memory,
+        });
+        if key_type == "string" {
+            result["bytes"] = serde_json::json!(length);
+            result["prefix"] = sample;
+        } else {
+            result["length"] = serde_json::json!(length);
+            result["sample"] = sample;
+            result["sampled"] = serde_json::json!(sampled);
+        }
+        let complete = match length {
+            Some(length) if key_type == "string" => {
+                length as usize <= SUMMARY_STRING_PREFIX && !budget.exhausted()
+            }
+            Some(length) => length as usize == sampled,
+            None => false,
+        };
+        if !complete {
+            result["hint"] = serde_json::json!(budget::paging_hint(key_type));
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_key_info(&self, params: KeyParams) -> Result<CallToolResult, ErrorData> {
+        let entry = self.resolve(params.connection.as_deref()).map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let key_type: String = redis::cmd("TYPE")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let ttl: i64 = redis::cmd("TTL")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let encoding: Result<String, _> = redis::cmd("OBJECT")
+            .arg("ENCODING")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await;
+
+        let memory: Result<i64, _> = redis::cmd("MEMORY")
+            .arg("USAGE")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await;
+
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "type": key_type,
+            "ttl": if ttl == -1 { "no expiry".to_string() } else if ttl == -2 { "key not found".to_string() } else { format!("{}s", ttl) },
+            "encoding": encoding.unwrap_or_else(|_| "unknown".to_string()),
+            "memory_bytes": memory.unwrap_or(-1),
+        })))
+    }
+
+    pub async fn do_key_length(&self, params: KeyParams) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let key_type: String = redis::cmd("TYPE")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        if key_type == "none" {
+            return Ok(json_result(
+                serde_json::json!({"error": "Key does not exist", "key": params.key}),
+            ));
+        }
+        let Some(command) = census::length_command(&key_type) else {
+            return Ok(json_result(serde_json::json!({
+                "key": params.key,
+                "type": key_type,
+                "note": "No length command for this type",
+            })));
+        };
+
+        let length: u64 = redis::cmd(command)
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let unit = match key_type.as_str() {
+            "string" => "bytes",
+            "hash" => "fields",
+            "set" | "zset" => "members",
+            "stream" => "entries",
+            _ => "elements",
+        };
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "type": key_type,
+            "length": length,
+            "unit": unit,
+            "command": command,
+        })))
+    }
+
+    pub async fn do_dbsize(&self, params: ConnectionParam) -> Result<CallToolResult, ErrorData> {
+        let entry = self.resolve(params.connection.as_deref()).map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let size: i64 = redis::cmd("DBSIZE")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        Ok(json_result(serde_json::json!({
+            "dbsize": size,
+        })))
+    }
+
+    pub async fn do_keyspace_summary(
+        &self,
+        params: KeyspaceSummaryParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let targets = match params.connection.as_deref() {
+            Some(name) => vec![self.resolve(Some(name)).map_err(|e| self.err(e))?],
+            None => self.connections().clone(),
+        };
+
+        let results = futures_util::future::join_all(targets.iter().map(|entry| async move {
+            let mut conn = entry.connection();
+            let mut item = serde_json::json!({
+                "connection": entry.name,
+                "selected_db": entry.client.get_connection_info().redis.db,
+            });
+            let raw: Result<String, _> = redis::cmd("INFO")
+                .arg("keyspace")
+                .query_async(&mut conn)
+                .await;
+            match raw {
+                Ok(raw) => {
+                    let databases = info::parse_keyspace(&raw);
+                    let total: i64 = databases.iter().filter_map(|d| d["keys"].as_i64()).sum();
+                    item["total_keys"] = serde_json::json!(total);
+                    item["populated"] = serde_json::json!(databases.len());
+                    item["databases"] = serde_json::Value::Array(databases);
+                }
+                Err(e) => item["error"] = serde_json::json!(e.to_string()),
+            }
+            item
+        }))
+        .await;
+
+        Ok(json_result(serde_json::json!({ "connections": results })))
+    }
+
+    pub async fn do_search_keys(&self, params: ScanParams) -> Result<CallToolResult, ErrorData> {
+        let entry = self.resolve(params.connection.as_deref()).map_err(|e| self.err(e))?;
+        let patterns = Self::scan_patterns(&params).map_err(|e| self.err(e))?;
+        let matched = Self::scan_pattern(&params, &patterns);
+        let matched = matched.as_str();
+        // Several patterns are reported by the MATCH they share
+        let pattern = if patterns.is_empty() {
+            params.pattern.as_deref().unwrap_or("*")
+        } else {
+            matched
+        };
+
+        Self::validate_pattern(matched).map_err(|e| self.err(e))?;
+
+        // Cap max_keys to scan_count to prevent unbounded iteration
+        let max_keys = std::cmp::min(
+            params.count.unwrap_or(self.scan_count) as usize,
+            self.scan_count as usize,
+        );
+
+        let start = Self::scan_start(params.cursor.as_deref()).map_err(|e| self.err(e))?;
+        let filter = scan::KeyFilter {
+            key_type: Self::type_filter(&entry, params.key_type.as_deref())
+                .map_err(|e| self.err(e))?,
+            any_of: patterns.clone(),
+            none_of: Self::exclude_patterns(&params).map_err(|e| self.err(e))?,
+        };
+        let (scans, next, coverage) = self
+            .scan_page_adaptive(&entry, matched, &filter, max_keys, start.as_ref())
+            .await
+            .map_err(|e| self.err(e))?;
+        let mut last_conn = scans.last().map(|s| s.conn.clone());
+
+        let include_metadata = params.include_metadata.unwrap_or(false);
+        let mut results = Vec::new();
+        let mut duplicates_filtered = 0;
+        let mut excluded = 0;
+        for mut scanned in scans {
+            duplicates_filtered += scanned.outcome.duplicates_filtered;
+            excluded += scanned.outcome.excluded;
+            let keys = &scanned.outcome.keys;
+            if keys.is_empty() {
+                continue;
+            }
+
+            // On a cluster each pipeline goes to the node that owns the keys
+            for mut item in key_metadata(&mut scanned.conn, keys, include_metadata).await {
+                if let Some(shard) = &scanned.shard {
+                    item["shard"] = serde_json::Value::String(shard.clone());
+                }
+                results.push(item);
+            }
+        }
+
+        let mut result = serde_json::json!({
+            "pattern": pattern,
+            "keys": results,
+            "count": results.len(),
+            "duplicates_filtered": duplicates_filtered,
+            "cursor": next.as_ref().map_or_else(|| "0".to_string(), scan::ScanCursor::encode),
+            "complete": next.is_none(),
+        });
+        if let Some(filter) = &filter.key_type {
+            result["type"] = serde_json::json!(filter.key_type);
+            result["type_filter"] = serde_json::json!(Self::type_filter_mode(filter));
+        }
+        if !filter.none_of.is_empty() {
+            result["exclude_patterns"] = serde_json::json!(filter.none_of);
+            result["excluded"] = serde_json::json!(excluded);
+        }
+        if matched != pattern {
+            result["match"] = serde_json::json!(matched);
+        }
+        if let Some(coverage) = coverage {
+            result["coverage"] = coverage;
+            match last_conn.as_mut() {
+                Some(conn) if results.is_empty() => {
+                    self.note_empty_scan(&mut result, conn, matched, &filter)
+                        .await
+                }
+                _ => {}
+            }
+        }
+        if !patterns.is_empty() {
+            result["patterns"] = Self::pattern_groups(&patterns, &results);
+        }
+        let columns: &[&str] = if include_metadata {
+            &["key", "type", "ttl", "memory_bytes", "shard"]
+        } else {
+            &["key", "type", "shard"]
+        };
+        Ok(formatted_result(result, params.format, "keys", columns))
+    }
+
+    pub async fn do_get_hash_fields(
+        &self,
+        params: HashFieldParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let fields = &params.fields;
+        if fields.is_empty() {
+            return Err(self.err(McpRedisError::Other(
+                "At least one field is required".to_string(),
+            )));
+        }
+
+        let mut cmd = redis::cmd("HMGET");
+        cmd.arg(&params.key);
+        for field in fields {
+            cmd.arg(field);
+        }
+
+        let values: Vec<Option<String>> = cmd
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let result: Vec<serde_json::Value> = fields
+            .iter()
+            .zip(values.iter())
+            .map(|(field, value)| {
+                serde_json::json!({
+                    "field": field,
+                    "value": value.as_deref().unwrap_or_default(),
+                    "exists": value.is_some(),
+                })
+            })
+            .collect();
+
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "fields": result,
+        })))
+    }
+
+    pub async fn do_hash_overview(
+        &self,
+        params: HashOverviewParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let key_type: String = redis::cmd("TYPE")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        match key_type.as_str() {
+            "hash" => {}
+            "none" => {
+                return Ok(json_result(
+                    serde_json::json!({"error": "Key does not exist", "key": params.key}),
+                ))
+            }
+            other => {
+                return Ok(json_result(
+                    serde_json::json!({"error": format!("Key is type '{}', not a hash", other), "key": params.key}),
+                ))
+            }
+        }
+
+        let length: u64 = redis::cmd("HLEN")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        // HSCAN NOVALUES (Redis 7.4+) returns field names only; older servers
+        // reject it, in which case values are fetched and dropped.
+        let sample_size = params
+            .sample
+            .unwrap_or(20)
+            .min(self.limits().max_hash_sample);
+        let mut sample: Vec<String> = Vec::new();
+        let mut novalues = true;
+        let mut cursor: u64 = 0;
+        let max_iterations = self.limits().max_scan_iterations;
+        let mut iterations = 0;
+        while (sample.len() as u64) < sample_size {
+            let mut cmd = redis::cmd("HSCAN");
+            cmd.arg(&params.key).arg(cursor).arg("COUNT").arg(100);
+            if novalues {
+                cmd.arg("NOVALUES");
+            }
+            let (next, batch): (u64, Vec<String>) = match cmd.query_async(&mut conn).await {
+                Ok(reply) => reply,
+                Err(e) if novalues && e.kind() == redis::ErrorKind::ResponseError => {
+                    novalues = false;
+                    continue;
+                }
+                Err(e) => return Err(self.err(McpRedisError::Redis(e))),
+            };
+            let names = if novalues {
+                batch
+            } else {
+                batch.into_iter().step_by(2).collect()
+            };
+            let room = (sample_size - sample.len() as u64) as usize;
+            sample.extend(names.into_iter().take(room));
+            cursor = next;
+            iterations += 1;
+            if cursor == 0 || iterations >= max_iterations {
+                break;
+            }
+        }
+
+        let mut fields: Vec<serde_json::Value> = Vec::new();
+        if !params.fields.is_empty() {
+            let mut pipe = redis::pipe();
+            for field in &params.fields {
+                pipe.cmd("HEXISTS").arg(&params.key).arg(field);
+                pipe.cmd("HSTRLEN").arg(&params.key).arg(field);
+            }
+            let replies: Vec<u64> = pipe
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+            fields = params
+                .fields
+                .iter()
+                .zip(replies.chunks(2))
+                .map(|(field, reply)| {
+                    serde_json::json!({
+                        "field": field,
+                        "exists": reply[0] == 1,
+                        "value_length": reply[1],
+                    })
+                })
+                .collect();
+        }
+
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "length": length,
+            "sampled_fields": sample,
+            "sample_complete": sample.len() as u64 >= length,
+            "novalues": novalues,
+            "fields": fields,
+        })))
+    }
+
+    pub async fn do_get_list_range(
+        &self,
+        params: ListRangeParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let start = params.start.unwrap_or(0);
+        let stop = params.stop.unwrap_or(-1);
+
+        let elements: Vec<String> = redis::cmd("LRANGE")
+            .arg(&params.key)
+            .arg(start)
+            .arg(stop)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "start": start,
+            "stop": stop,
+            "elements": elements,
+            "count": elements.len(),
+        })))
+    }
+
+    pub async fn do_get_string_range(
+        &self,
+        params: StringRangeParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+
+        let key_type: String = redis::cmd("TYPE")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        match key_type.as_str() {
+            "string" => {}
+            "none" => {
+                return Ok(json_result(
+                    serde_json::json!({"error": "Key does not exist", "key": params.key}),
+                ));
+            }
+            other => {
+                return Err(self.err(McpRedisError::Other(format!(
+                    "'{}' is a {}, not a string; use get or key_length",
+                    params.key, other
+                ))));
+            }
+        }
+        let strlen: u64 = redis::cmd("STRLEN")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+
+        // Resolved against STRLEN so the reply can say where the next read starts
+        let offset = params.offset.unwrap_or(0);
+        let start = if offset < 0 {
+            strlen.saturating_sub(offset.unsigned_abs())
+        } else {
+            offset as u64
+        };
+        let length = params
+            .length
+            .unwrap_or(self.max_value_bytes)
+            .min(self.max_value_bytes);
+        let bytes: Vec<u8> = if length == 0 || start >= strlen {
+            Vec::new()
+        } else {
+            redis::cmd("GETRANGE")
+                .arg(&params.key)
+                .arg(start)
+                .arg(start + length - 1)
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_err)?
+        };
+
+        let end = start + bytes.len() as u64;
+        let mut result = serde_json::json!({
+            "key": params.key,
+            "strlen": strlen,
+            "offset": start,
+            "bytes": bytes.len(),
+            "value": budget::utf8_prefix(&bytes),
+        });
+        if end < strlen {
+            result["next_offset"] = serde_json::json!(end);
+        }
+        if params.length.is_some_and(|requested| requested > length) {
+            result["capped"] = serde_json::json!(format!(
+                "length capped to --max-value-bytes ({})",
+                self.max_value_bytes
+            ));
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_get_set_members(
+        &self,
+        params: SetMembersParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let offset = params.offset.unwrap_or(0);
+        let limit = params
+            .limit
+            .or(params.count.map(|c| c.max(0) as u64))
+            .unwrap_or(self.scan_count as u64);
+
+        // Detect key type to handle sets vs sorted sets
+        let key_type: String = redis::cmd("TYPE")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let (members, total): (serde_json::Value, u64) = match key_type.as_str() {
+            "set" => {
+                let total: u64 = redis::cmd("SCARD")
+                    .arg(&params.key)
+                    .query_async(&mut conn)
+                    .await
+                    .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+                // Skipping `offset` members re-scans them for every page, and
+                // SSCAN may return a member twice (or a changed set may move
+                // one past the cursor), so pages are not stable; the result
+                // points at scan_set_members for paging.
+                let mut members: Vec<String> = Vec::new();
+                let mut skipped: u64 = 0;
+                let mut cursor: u64 = 0;
+                let max_iterations = self.limits().max_scan_iterations;
+                let mut iterations = 0;
+                loop {
+                    let (next, batch): (u64, Vec<String>) = redis::cmd("SSCAN")
+                        .arg(&params.key)
+                        .arg(cursor)
+                        .arg("COUNT")
+                        .arg(100)
+                        .query_async(&mut conn)
+                        .await
+                        .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+                    for member in batch {
+                        if skipped < offset {
+                            skipped += 1;
+                        } else if (members.len() as u64) < limit {
+                            members.push(member);
+                        }
+                    }
+                    cursor = next;
+                    iterations += 1;
+                    if cursor == 0 || members.len() as u64 >= limit || iterations >= max_iterations
+                    {
+                        break;
+                    }
+                }
+                (serde_json::json!(members), total)
+            }
+            "zset" => {
+                let total: u64 = redis::cmd("ZCARD")
+                    .arg(&params.key)
+                    .query_async(&mut conn)
+                    .await
+                    .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+                // Rank ranges page in score order on every supported
+                // version; ZRANGE BYSCORE ... LIMIT needs Redis 6.2
+                let reverse = params.reverse.unwrap_or(false);
+                let members: Vec<(String, f64)> = if limit == 0 {
+                    Vec::new()
+                } else {
+                    redis::cmd(if reverse { "ZREVRANGE" } else { "ZRANGE" })
+                        .arg(&params.key)
+                        .arg(offset)
+                        .arg(offset.saturating_add(limit - 1))
+                        .arg("WITHSCORES")
+                        .query_async(&mut conn)
+                        .await
+                        .map_err(|e| self.err(McpRedisError::Redis(e)))?
+                };
+
+                let result: Vec<serde_json::Value> = members
+                    .iter()
+                    .map(|(m, s)| serde_json::json!({"member": m, "score": s}))
+                    .collect();
+                (serde_json::json!(result), total)
+            }
+            "none" => {
+                return Ok(json_result(
+                    serde_json::json!({"error": "Key does not exist", "key": params.key}),
+                ))
+            }
+            other => {
+                return Ok(json_result(
+                    serde_json::json!({"error": format!("Key is type '{}', not a set or zset", other), "key": params.key}),
+                ))
+            }
+        };
+
+        let count = members.as_array().map(|m| m.len()).unwrap_or(0) as u64;
+        let mut result = serde_json::json!({
+            "key": params.key,
+            "type": key_type,
+            "members": members,
+            "count": count,
+            "total": total,
+            "offset": offset,
+            "limit": limit,
+            "has_more": offset + count < total,
+        });
+        if key_type == "set" && offset + count < total {
+            result["hint"] = serde_json::json!(
+                "Offset pages of a set are not stable; page through it with scan_set_members and its cursor"
+            );
+        }
+        Ok(formatted_result(
+            result,
+            params.format,
+            "members",
+            &["member", "score"],
+        ))
+    }
+
+    pub async fn do_zset_lex_range(
+        &self,
+        params: LexRangeParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let (min, max) = match &params.prefix {
+            Some(_) if params.min.is_some() || params.max.is_some() => {
+                return Err(self.err(McpRedisError::Other(
+                    "Pass either 'prefix' or 'min'/'max', not both".to_string(),
+                )))
+            }
+            Some(prefix) => prefix_lex_range(prefix),
+            None => (
+                lex_bound(params.min.as_deref().unwrap_or("-")),
+                lex_bound(params.max.as_deref().unwrap_or("+")),
+            ),
+        };
+        let offset = params.offset.unwrap_or(0);
+        let limit = params
+            .limit
+            .unwrap_or(self.scan_count as u64)
+            .clamp(1, self.limits().max_lex_range_count);
+        let reverse = params.reverse.unwrap_or(false);
+
+        let key_type: String = redis::cmd("TYPE")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        match key_type.as_str() {
+            "zset" => {}
+            "none" => {
+                return Ok(json_result(
+                    serde_json::json!({"error": "Key does not exist", "key": params.key}),
+                ))
+            }
+            other => {
+                return Ok(json_result(
+                    serde_json::json!({"error": format!("Key is type '{}', not a zset", other), "key": params.key}),
+                ))
+            }
+        }
+
+        let matching: u64 = redis::cmd("ZLEXCOUNT")
+            .arg(&params.key)
+            .arg(&min)
+            .arg(&max)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        // ZREVRANGEBYLEX takes the bounds high to low
+        let mut cmd = if reverse {
+            let mut cmd = redis::cmd("ZREVRANGEBYLEX");
+            cmd.arg(&params.key).arg(&max).arg(&min);
+            cmd
+        } else {
+            let mut cmd = redis::cmd("ZRANGEBYLEX");
+            cmd.arg(&params.key).arg(&min).arg(&max);
+            cmd
+        };
+        let members: Vec<String> = cmd
+            .arg("LIMIT")
+            .arg(offset)
+            .arg(limit)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let count = members.len() as u64;
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "min": String::from_utf8_lossy(&min),
+            "max": String::from_utf8_lossy(&max),
+            "reverse": reverse,
+            "members": members,
+            "count": count,
+            "matching": matching,
+            "offset": offset,
+            "limit": limit,
+            "has_more": offset + count < matching,
+        })))
+    }
+
+    pub async fn do_find_member(
+        &self,
+        params: FindMemberParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let pattern = match (&params.contains, &params.pattern) {
+            (Some(needle), None) => format!("*{}*", glob::escape(needle)),
+            (None, Some(pattern)) => pattern.clone(),
+            _ => {
+                return Err(self.err(McpRedisError::Other(
+                    "Pass exactly one of 'contains' and 'pattern'".to_string(),
+                )))
+            }
+        };
+        Self::validate_pattern(&pattern).map_err(|e| self.err(e))?;
+        let max_matches = params
+            .count
+            .unwrap_or(50)
+            .clamp(1, self.limits().max_member_matches);
+
+        let key_type: String = redis::cmd("TYPE")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let command = match key_type.as_str() {
+            "set" => "SSCAN",
+            "zset" => "ZSCAN",
+            "hash" => "HSCAN",
+            "none" => {
+                return Ok(json_result(
+                    serde_json::json!({"error": "Key does not exist", "key": params.key}),
+                ))
+            }
+            other => {
+                return Ok(json_result(
+                    serde_json::json!({"error": format!("Key is type '{}', not a set, zset or hash", other), "key": params.key}),
+                ))
+            }
+        };
+
+        // MATCH is applied after each batch is read, so sparse matches can
+        // take many iterations; the cursor lets the caller continue.
+        let mut matches: Vec<serde_json::Value> = Vec::new();
+        let mut cursor = params.cursor.unwrap_or(0);
+        let max_iterations = self.limits().max_scan_iterations;
+        let mut iterations = 0;
+        loop {
+            let (next, batch): (u64, Vec<String>) = redis::cmd(command)
+                .arg(&params.key)
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(1000)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+            match command {
+                "SSCAN" => matches.extend(batch.into_iter().map(serde_json::Value::String)),
+                // Hash values may be large or sensitive: only fields are returned
+                "HSCAN" => {
+                    matches.extend(batch.into_iter().step_by(2).map(serde_json::Value::String))
+                }
+                _ => matches.extend(batch.chunks(2).map(|pair| {
+                    serde_json::json!({
+                        "member": pair[0],
+                        "score": pair.get(1).map(|s| info::parse_value(s)),
+                    })
+                })),
+            }
+            cursor = next;
+            iterations += 1;
+            if cursor == 0 || matches.len() >= max_matches || iterations >= max_iterations {
+                break;
+            }
+        }
+        // The last batch may go past `count`; it is returned whole because the
+        // cursor resumes after it.
+        let count = matches.len();
+
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "type": key_type,
+            "pattern": pattern,
+            "matches": matches,
+            "count": count,
+            "cursor": cursor,
+            "complete": cursor == 0,
+            "iterations": iterations,
+        })))
+    }
+
+    pub async fn do_scan_set_members(
+        &self,
+        params: MemberScanParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.scan_members(params, "set").await
+    }
+
+    pub async fn do_scan_zset_members(
+        &self,
+        params: MemberScanParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.scan_members(params, "zset").await
+    }
+
+    /// One page of SSCAN (`set`) or ZSCAN (`zset`) from the caller's cursor:
+    /// batches are read until `count` members came back, the scan ended, or
+    /// `max_scan_iterations` ran out.
+    async fn scan_members(
+        &self,
+        params: MemberScanParams,
+        expected: &str,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+        if let Some(pattern) = &params.pattern {
+            Self::validate_pattern(pattern).map_err(|e| self.err(e))?;
+        }
+        let limits = self.limits();
+        let count = params
+            .count
+            .unwrap_or(100)
+            .clamp(1, limits.max_scan_members);
+
+        let key_type: String = redis::cmd("TYPE")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        if key_type == "none" {
+            return Ok(json_result(
+                serde_json::json!({"error": "Key does not exist", "key": params.key}),
+            ));
+        }
+        if key_type != expected {
+            return Ok(json_result(serde_json::json!({
+                "error": format!("Key is type '{}', not a {}", key_type, expected),
+                "key": params.key,
+            })));
+        }
+        let command = if expected == "set" { "SSCAN" } else { "ZSCAN" };
+
+        let mut members: Vec<serde_json::Value> = Vec::new();
+        let mut cursor = params.cursor.unwrap_or(0);
+        let mut iterations = 0;
+        loop {
+            let mut cmd = redis::cmd(command);
+            cmd.arg(&params.key).arg(cursor);
+            if let Some(pattern) = &params.pattern {
+                cmd.arg("MATCH").arg(pattern);
+            }
+            let (next, batch): (u64, Vec<String>) = cmd
+                .arg("COUNT")
+                .arg(limits.scan_batch_count)
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_err)?;
+            if command == "SSCAN" {
+                members.extend(batch.into_iter().map(serde_json::Value::String));
+            } else {
+                members.extend(batch.chunks(2).map(|pair| {
+                    serde_json::json!({
+                        "member": pair[0],
+                        "score": pair.get(1).map(|s| info::parse_value(s)),
+                    })
+                }));
+            }
+            cursor = next;
+            iterations += 1;
+            if cursor == 0 || members.len() >= count || iterations >= limits.max_scan_iterations {
+                break;
+            }
+        }
+        // As in find_member, the last batch is kept whole: the cursor
+        // resumes after it, so cutting it would lose members.
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "type": key_type,
+            "pattern": params.pattern,
+            "members": members,
+            "count": members.len(),
+            "cursor": cursor,
+            "complete": cursor == 0,
+            "iterations": iterations,
+        })))
+    }
+
+    pub async fn do_sample_members(
+        &self,
+        params: SampleMembersParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let count = params
+            .count
+            .unwrap_or(10)
+            .clamp(1, self.limits().max_sample_members);
+        let with_values = params.with_values.unwrap_or(false);
+
+        let key_type: String = redis::cmd("TYPE")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        // A positive count returns distinct members, the whole key when small
+        let (length, mut sample) = match key_type.as_str() {
+            "set" => ("SCARD", redis::cmd("SRANDMEMBER")),
+            "hash" => ("HLEN", redis::cmd("HRANDFIELD")),
+            "zset" => ("ZCARD", redis::cmd("ZRANDMEMBER")),
+            "none" => {
+                return Ok(json_result(
+                    serde_json::json!({"error": "Key does not exist", "key": params.key}),
+                ))
+            }
+            other => {
+                return Ok(json_result(
+                    serde_json::json!({"error": format!("Key is type '{}', not a set, hash or zset", other), "key": params.key}),
+                ))
+            }
+        };
+        sample.arg(&params.key).arg(count);
+        if with_values {
+            match key_type.as_str() {
+                "hash" => {
+                    sample.arg("WITHVALUES");
+                }
+                "zset" => {
+                    sample.arg("WITHSCORES");
+                }
+                _ => {}
+            }
+        }
+        let (total, reply): (u64, redis::Value) = redis::pipe()
+            .cmd(length)
+            .arg(&params.key)
+            .add_command(sample)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| match e.kind() {
+                redis::ErrorKind::ResponseError if key_type != "set" => {
+                    self.err(McpRedisError::Other(format!(
+                        "Sampling a {} failed ({}); HRANDFIELD and ZRANDMEMBER need Redis 6.2 or later, use scan_set_members or find_member on older servers",
+                        key_type, e
+                    )))
+                }
+                _ => self.err(McpRedisError::Redis(e)),
+            })?;
+
+        let members = match key_type.as_str() {
+            "zset" if with_values => references::value_json("zset", &reply),
+            "hash" if with_values => {
+                // RESP2 flattens field/value pairs; RESP3 nests them
+                let items = match resp::to_json(&reply) {
+                    serde_json::Value::Array(items) => items,
+                    _ => Vec::new(),
+                };
+                let flat: Vec<serde_json::Value> = if items.iter().all(|i| i.is_array()) {
+                    items
+                        .into_iter()
+                        .flat_map(|pair| match pair {
+                            serde_json::Value::Array(pair) => pair,
+                            _ => Vec::new(),
+                        })
+                        .collect()
+                } else {
+                    items
+                };
+                serde_json::Value::Array(
+                    flat.chunks(2)
+                        .filter(|pair| pair.len() == 2)
+                        .map(|pair| serde_json::json!({"field": pair[0], "value": pair[1]}))
+                        .collect(),
+                )
+            }
+            _ => resp::to_json(&reply),
+        };
+        let returned = members.as_array().map_or(0, Vec::len);
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "type": key_type,
+            "total": total,
+            "count": returned,
+            "complete": returned as u64 >= total,
+            "members": members,
+        })))
+    }
+
+    pub async fn do_estimate_cardinality(
+        &self,
+        params: EstimateCardinalityParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let limits = self.limits();
+        if params.keys.is_empty() || params.keys.len() > limits.max_cardinality_keys {
+            return Err(self.err(McpRedisError::Other(format!(
+                "Give between 1 and {} keys",
+                limits.max_cardinality_keys
+            ))));
+        }
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+        let max_elements = params
+            .max_elements
+            .unwrap_or(limits.max_cardinality_elements)
+            .clamp(1, limits.max_cardinality_elements);
+
+        let mut pipe = redis::pipe();
+        for key in &params.keys {
+            pipe.cmd("TYPE").arg(key);
+        }
+        let types: Vec<String> = pipe.query_async(&mut conn).await.map_err(redis_err)?;
+        let mut pipe = redis::pipe();
+        let mut readable = Vec::new();
+        let mut keys = Vec::new();
+        for (key, key_type) in params.keys.iter().zip(&types) {
+            let command = match key_type.as_str() {
+                "set" => "SCARD",
+                "zset" => "ZCARD",
+                "list" => "LLEN",
+                "none" => {
+                    keys.push(serde_json::json!({"key": key, "error": "Key does not exist"}));
+                    continue;
+                }
+                other => {
+                    keys.push(serde_json::json!({
+                        "key": key,
+                        "type": other,
+                        "error": format!("Key is type '{}', not a list, set or zset", other),
+                    }));
+                    continue;
+                }
+            };
+            pipe.cmd(command).arg(key);
+            readable.push((key, key_type.as_str()));
+        }
+        let lengths: Vec<u64> = if readable.is_empty() {
+            Vec::new()
+        } else {
+            pipe.query_async(&mut conn).await.map_err(redis_err)?
+        };
+
+        // Members of one set or zset are distinct already
+        if let [(key, key_type @ ("set" | "zset"))] = readable.as_slice() {
+            keys.push(serde_json::json!({"key": key, "type": key_type, "length": lengths[0]}));
+            return Ok(json_result(serde_json::json!({
+                "keys": keys,
+                "distinct": lengths[0],
+                "method": "exact",
+                "complete": true,
+            })));
+        }
+
+        let batch = self.limits().get_batch.max(1);
+        let mut counter = cardinality::DistinctCounter::new(cardinality::EXACT_DISTINCT_LIMIT);
+        let mut read = 0u64;
+        let mut complete = true;
+        for ((key, key_type), length) in readable.iter().zip(&lengths) {
+            let mut key_read = 0u64;
+            let mut cursor = 0u64;
+            loop {
+                if read >= max_elements {
+                    complete = false;
+                    break;
+                }
+                let members: Vec<Vec<u8>> = match *key_type {
+                    "list" => {
+                        let start = key_read as isize;
+                        let members: Vec<Vec<u8>> = redis::cmd("LRANGE")
+                            .arg(key)
+                            .arg(start)
+                            .arg(start + batch as isize - 1)
+                            .query_async(&mut conn)
+                            .await
+                            .map_err(redis_err)?;
+                        // Non-zero while the list may hold more
+                        cursor = if members.len() < batch { 0 } else { 1 };
+                        members
+                    }
+                    scan_type => {
+                        let command = if scan_type == "set" { "SSCAN" } else { "ZSCAN" };
+                        let (next, batch): (u64, Vec<Vec<u8>>) = redis::cmd(command)
+                            .arg(key)
+                            .arg(cursor)
+                            .arg("COUNT")
+                            .arg(1000)
+                            .query_async(&mut conn)
+                            .await
+                            .map_err(redis_err)?;
+                        cursor = next;
+                        // ZSCAN replies member, score pairs
+                        if scan_type == "zset" {
+                            batch.into_iter().step_by(2).collect()
+                        } else {
+                            batch
+                        }
+                    }
+                };
+                for member in &members {
+                    counter.insert(member);
+                }
+                key_read += members.len() as u64;
+                read += members.len() as u64;
+                if cursor == 0 {
+                    break;
+                }
+            }
+            keys.push(serde_json::json!({
+                "key": key,
+                "type": key_type,
+                "length": length,
+                "read": key_read,
+            }));
+        }
+
+        let (distinct, exact) = counter.count();
+        let mut result = serde_json::json!({
+            "keys": keys,
+            "elements_read": read,
+            "distinct": distinct,
+            "method": if exact { "exact" } else { "hyperloglog" },
+            "complete": complete,
+        });
+        if !exact {
+            result["standard_error_percent"] =
+                serde_json::json!((cardinality::STANDARD_ERROR * 1000.0).round() / 10.0);
+        }
+        if !complete {
+            // Set and zset members are distinct; any member may repeat across keys
+            let largest_set = readable
+                .iter()
+                .zip(&lengths)
+                .filter(|((_, key_type), _)| *key_type != "list")
+                .map(|(_, length)| *length)
+                .max()
+                .unwrap_or(0);
+            result["bounds"] = serde_json::json!({
+                "low": distinct.max(largest_set),
+                "high": lengths.iter().sum::<u64>(),
+            });
+            result["hint"] = serde_json::json!(format!(
+                "Stopped after {} members; 'distinct' counts only those. Raise max_elements for a full count",
+                read
+            ));
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_slowlog(&self, params: SlowlogParams) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let count = params.count.unwrap_or(10);
+
+        // SLOWLOG GET returns an array of arrays
+        let raw: Vec<Vec<redis::Value>> = redis::cmd("SLOWLOG")
+            .arg("GET")
+            .arg(count)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let entries = parse_slowlog(&raw);
+
+        Ok(formatted_result(
+            serde_json::json!({
+                "entries": entries,
+                "count": entries.len(),
+            }),
+            params.format,
+            "entries",
+            &["id", "timestamp", "duration_us", "command"],
+        ))
+    }
+
+    pub async fn do_acl_check(&self, params: AclCheckParams) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let dryrun_unsupported = || {
+            self.err(McpRedisError::Other(format!(
+                "acl_check needs ACL DRYRUN (Redis {} or later), which '{}' does not support",
+                ACL_DRYRUN_VERSION, entry.name
+            )))
+        };
+        let too_old = entry
+            .version
+            .as_ref()
+            .and_then(|v| v.redis_version)
+            .is_some_and(|v| v < ACL_DRYRUN_VERSION);
+        if too_old {
+            return Err(dryrun_unsupported());
+        }
+        let mut conn = entry.connection();
+
+        // OK when allowed, otherwise the reason as a bulk string
+        let reply: redis::Value = redis::cmd("ACL")
+            .arg("DRYRUN")
+            .arg(&params.user)
+            .arg(&params.command)
+            .arg(&params.args)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| {
+                let message = e.to_string().to_ascii_lowercase();
+                if message.contains("unknown subcommand") {
+                    dryrun_unsupported()
+                } else {
+                    self.err(McpRedisError::Redis(e))
+                }
+            })?;
+        let denial = match reply {
+            redis::Value::Okay => None,
+            redis::Value::SimpleString(s) if s == "OK" => None,
+            other => Some(resp::to_json(&other)),
+        };
+
+        let mut result = serde_json::json!({
+            "user": params.user,
+            "command": std::iter::once(params.command.as_str())
+                .chain(params.args.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(" "),
+            "allowed": denial.is_none(),
+        });
+        if let Some(reason) = denial {
+            result["reason"] = reason;
+        }
+        Ok(json_result(result))
+    }
+
+    /// Error for a failed ACL command: servers before Redis 6 have no ACL
+    /// at all, and a user without `@admin` gets NOPERM.
+    fn acl_err(&self, e: redis::RedisError) -> ErrorData {
+        let message = e.to_string().to_ascii_lowercase();
+        if message.contains("unknown command") {
+            self.err(McpRedisError::Other(
+                "ACL commands need Redis 6 or later".to_string(),
+            ))
+        } else {
+            self.err(McpRedisError::Redis(e))
+        }
+    }
+
+    pub async fn do_acl_whoami(
+        &self,
+        params: ConnectionParam,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let user: String = redis::cmd("ACL")
+            .arg("WHOAMI")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.acl_err(e))?;
+        Ok(json_result(serde_json::json!({
+            "connection": entry.name,
+            "user": user,
+            "hint": "Use acl_getuser for this user's permissions, or acl_check for one command",
+        })))
+    }
+
+    pub async fn do_acl_list(&self, params: ConnectionParam) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let lines: Vec<String> = redis::cmd("ACL")
+            .arg("LIST")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.acl_err(e))?;
+        let users: Vec<serde_json::Value> = lines
+            .iter()
+            .filter_map(|line| acl::parse_list_line(line))
+            .collect();
+        let disabled: Vec<&serde_json::Value> = users
+            .iter()
+            .filter(|u| u["enabled"] == false)
+            .map(|u| &u["user"])
+            .collect();
+        let nopass: Vec<&serde_json::Value> = users
+            .iter()
+            .filter(|u| u["enabled"] == true && u["nopass"] == true)
+            .map(|u| &u["user"])
+            .collect();
+        Ok(json_result(serde_json::json!({
+            "connection": entry.name,
+            "count": users.len(),
+            "disabled": disabled,
+            "enabled_without_password": nopass,
+            "users": users,
+        })))
+    }
+
+    pub async fn do_acl_getuser(
+        &self,
+        params: AclGetUserParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let reply: redis::Value = redis::cmd("ACL")
+            .arg("GETUSER")
+            .arg(&params.user)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.acl_err(e))?;
+        let Some(fields) = resp::to_object(&reply) else {
+            return Err(self.err(McpRedisError::Other(format!(
+                "No ACL user '{}' on '{}'",
+                params.user, entry.name
+            ))));
+        };
+        let mut result = acl::parse_getuser(&params.user, &fields);
+        result["connection"] = serde_json::json!(entry.name);
+        Ok(json_result(result))
+    }
+
+    pub async fn do_client_list(
+        &self,
+        params: ClientListParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let group_by = match params.group_by.as_deref() {
+            Some(text) => Some(clients::GroupBy::parse(text).ok_or_else(|| {
+                self.err(McpRedisError::Other(format!(
+                    "Unknown group_by '{}'; use 'name' or 'addr'",
+                    text
+                )))
+            })?),
+            None => None,
+        };
+        let client_type = params.client_type.as_deref().map(str::to_ascii_lowercase);
+        if let Some(client_type) = &client_type {
+            if !clients::TYPES.contains(&client_type.as_str()) {
+                return Err(self.err(McpRedisError::Other(format!(
+                    "Unknown client type '{}'; use one of {}",
+                    client_type,
+                    clients::TYPES.join(", ")
+                ))));
+            }
+        }
+        let filter = clients::ClientFilter {
+            name: params.name,
+            flags: params.flags,
+            min_idle_secs: params.min_idle_secs,
+        };
+        let mut conn = entry.connection();
+
+        let mut cmd = redis::cmd("CLIENT");
+        cmd.arg("LIST");
+        if let Some(client_type) = &client_type {
+            cmd.arg("TYPE").arg(client_type);
+        }
+        let raw: String = cmd
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let all = parse_client_list(&raw);
+        let total = all.len();
+        let clients: Vec<serde_json::Value> = all.into_iter().filter(|c| filter.keeps(c)).collect();
+        let filtered = !filter.is_empty() || client_type.is_some();
+
+        if let Some(by) = group_by {
+            let groups = clients::group(&clients, by);
+            let mut result = serde_json::json!({
+                "group_by": params.group_by,
+                "groups": groups,
+                "group_count": groups.len(),
+                "count": clients.len(),
+            });
+            if filtered {
+                result["total"] = serde_json::json!(total);
+            }
+            return Ok(formatted_result(
+                result,
+                params.format,
+                "groups",
+                &["group", "count", "omem", "tot_mem", "max_idle"],
+            ));
+        }
+
+        let mut result = serde_json::json!({
+            "clients": clients,
+            "count": clients.len(),
+        });
+        if filtered {
+            result["total"] = serde_json::json!(total);
+        }
+        Ok(formatted_result(
+            result,
+            params.format,
+            "clients",
+            &[
+                "id", "addr", "name", "age", "idle", "flags", "db", "cmd", "omem",
+            ],
+        ))
+    }
+
+    pub async fn do_client_kill(
+        &self,
+        params: ClientKillParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        self.check_read_only(&entry, "client_kill")
+            .map_err(|e| self.err(e))?;
+        if params.id.is_none() && params.addr.is_none() {
+            return Err(self.err(McpRedisError::Other(
+                "Pass id or addr of the client to kill (see client_list)".to_string(),
+            )));
+        }
+        let mut conn = entry.connection();
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+
+        let (own_id, raw): (u64, String) = redis::pipe()
+            .cmd("CLIENT")
+            .arg("ID")
+            .cmd("CLIENT")
+            .arg("LIST")
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        if params.id == Some(own_id) {
+            return Err(self.err(McpRedisError::Other(
+                "Refusing to kill this server's own connection".to_string(),
+            )));
+        }
+        let id = params.id.map(|id| id.to_string());
+        let matched: Vec<serde_json::Value> = parse_client_list(&raw)
+            .into_iter()
+            .filter(|c| c["id"] != own_id.to_string().as_str())
+            .filter(|c| id.as_deref().map_or(true, |id| c["id"] == id))
+            .filter(|c| {
+                params
+                    .addr
+                    .as_deref()
+                    .map_or(true, |addr| c["addr"] == addr)
+            })
+            .map(|c| {
+                let field = |name: &str| c.get(name).cloned().unwrap_or(serde_json::Value::Null);
+                serde_json::json!({
+                    "id": field("id"),
+                    "addr": field("addr"),
+                    "name": field("name"),
+                    "user": field("user"),
+                    "age": field("age"),
+                    "idle": field("idle"),
+                    "cmd": field("cmd"),
+                })
+            })
+            .collect();
+
+        // The filter form returns a count; SKIPME (on by default) spares
+        // this connection when killing by address
+        let mut kill = redis::cmd("CLIENT");
+        kill.arg("KILL");
+        if let Some(id) = params.id {
+            kill.arg("ID").arg(id);
+        }
+        if let Some(addr) = &params.addr {
+            kill.arg("ADDR").arg(addr);
+        }
+        let killed: u64 = kill
+            .arg("SKIPME")
+            .arg("yes")
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+
+        let after: String = redis::cmd("CLIENT")
+            .arg("LIST")
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        let remaining: std::collections::HashSet<String> = parse_client_list(&after)
+            .iter()
+            .filter_map(|c| c["id"].as_str().map(str::to_string))
+            .collect();
+        let mut verification = verify::Verification::new("killed clients are disconnected");
+        for client in &matched {
+            let gone = client["id"]
+                .as_str()
+                .map_or(true, |id| !remaining.contains(id));
+            verification.observe(
+                serde_json::json!({"id": client["id"], "connected": !gone}),
+                gone,
+            );
+        }
+
+        let mut result = serde_json::json!({
+            "connection": entry.name,
+            "filter": {"id": params.id, "addr": params.addr},
+            "killed": killed,
+            "clients": matched,
+            "verification": verification.to_json(),
+        });
+        if killed == 0 {
+            result["hint"] = serde_json::json!(
+                "No client matched; client IDs and ports change on reconnect, so check client_list again"
+            );
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_client_pause(
+        &self,
+        params: ClientPauseParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        self.check_read_only(&entry, "client_pause")
+            .map_err(|e| self.err(e))?;
+        let mode = params
+            .mode
+            .as_deref()
+            .unwrap_or("write")
+            .to_ascii_lowercase();
+        if mode != "write" && mode != "all" {
+            return Err(self.err(McpRedisError::Other(format!(
+                "Unknown pause mode '{}'; use 'write' or 'all'",
+                mode
+            ))));
+        }
+        let max = self.limits().max_client_pause_ms;
+        if params.timeout_ms == 0 || params.timeout_ms > max {
+            return Err(self.err(McpRedisError::Other(format!(
+                "timeout_ms must be between 1 and max_client_pause_ms ({})",
+                max
+            ))));
+        }
+        let mut conn = entry.connection();
+
+        let started = time::now_ms();
+        let mut pause = redis::cmd("CLIENT");
+        pause.arg("PAUSE").arg(params.timeout_ms);
+        // Redis before 6.2 takes no mode and always pauses every command
+        if mode == "write" {
+            pause.arg("WRITE");
+        }
+        pause
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| match e.kind() {
+                redis::ErrorKind::ResponseError if mode == "write" => {
+                    self.err(McpRedisError::Other(format!(
+                        "CLIENT PAUSE WRITE failed ({}); it needs Redis 6.2 or later, use mode 'all' on older servers",
+                        e
+                    )))
+                }
+                _ => self.err(McpRedisError::Redis(e)),
+            })?;
+
+        let mut result = serde_json::json!({
+            "connection": entry.name,
+            "mode": mode,
+            "timeout_ms": params.timeout_ms,
+            "until_ms": started + params.timeout_ms,
+            "undo": {"tool": "client_unpause", "arguments": {"connection": entry.name}},
+        });
+        result["note"] = serde_json::json!(if mode == "write" {
+            "Writes, EVAL and PUBLISH wait until the pause ends; reads keep being served. Replicas are not paused"
+        } else {
+            "Every client command waits until the pause ends, calls through this server included, so client_unpause may only run once it expires"
+        });
+        Ok(json_result(result))
+    }
+
+    pub async fn do_client_unpause(
+        &self,
+        params: ConnectionParam,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        self.check_read_only(&entry, "client_unpause")
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        redis::cmd("CLIENT")
+            .arg("UNPAUSE")
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| match e.kind() {
+                redis::ErrorKind::ResponseError => self.err(McpRedisError::Other(format!(
+                    "CLIENT UNPAUSE failed ({}); it needs Redis 6.2 or later, where a pause can only run out",
+                    e
+                ))),
+                _ => self.err(McpRedisError::Redis(e)),
+            })?;
+        Ok(json_result(serde_json::json!({
+            "connection": entry.name,
+            "unpaused": true,
+        })))
+    }
+
+    pub async fn do_backlog_health(
+        &self,
+        params: ConnectionParam,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let (replication, persistence, clients): (String, String, String) = redis::pipe()
+            .cmd("INFO")
+            .arg("replication")
+            .cmd("INFO")
+            .arg("persistence")
+            .cmd("CLIENT")
+            .arg("LIST")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        // CONFIG is often disabled on managed services; the buffer sizes are
+        // still reported, only without their limits
+        let limits = redis::cmd("CONFIG")
+            .arg("GET")
+            .arg("client-output-buffer-limit")
+            .query_async::<Vec<String>>(&mut conn)
+            .await
+            .ok()
+            .and_then(|reply| reply.get(1).map(|raw| backlog::parse_output_limits(raw)));
+
+        let info = info::parse_sections(&format!("{}\n{}", replication, persistence));
+        let mut result = backlog::assess(&info, &parse_client_list(&clients), limits.as_ref());
+        if limits.is_none() {
+            result["output_buffers"]["limits"] = serde_json::json!(
+                "CONFIG GET is not available on this server; buffer limits are unknown"
+            );
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_replication_status(
+        &self,
+        params: ReplicationStatusParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let targets = match params.connection.as_deref() {
+            Some(name) => vec![self.resolve(Some(name)).map_err(|e| self.err(e))?],
+            None => self.connections().clone(),
+        };
+
+        let results = futures_util::future::join_all(targets.iter().map(|entry| async move {
+            let mut conn = entry.connection();
+            // ROLE can be denied by ACLs; INFO replication alone still
+            // gives the offsets and lag
+            let role = redis::cmd("ROLE")
+                .query_async::<redis::Value>(&mut conn)
+                .await
+                .ok()
+                .and_then(|reply| replication::parse_role(&resp::to_json(&reply)));
+            let raw: Result<String, _> = redis::cmd("INFO")
+                .arg("replication")
+                .query_async(&mut conn)
+                .await;
+            let mut item = match raw {
+                Ok(raw) => {
+                    let sections = info::parse_sections(&raw);
+                    let section = sections
+                        .get("replication")
+                        .and_then(|s| s.as_object())
+                        .cloned()
+                        .unwrap_or_default();
+                    replication::status(role.as_ref(), &section)
+                }
+                Err(e) if role.is_some() => {
+                    let mut item = replication::status(role.as_ref(), &Default::default());
+                    item["info_error"] = serde_json::json!(e.to_string());
+                    item
+                }
+                Err(e) => serde_json::json!({ "error": e.to_string() }),
+            };
+            item["connection"] = serde_json::json!(entry.name);
+            item
+        }))
+        .await;
+
+        Ok(json_result(serde_json::json!({ "connections": results })))
+    }
+
+    pub async fn do_check_replica_consistency(
+        &self,
+        params: ReplicaConsistencyParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let master = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let replica = self
+            .resolve(Some(&params.replica))
+            .map_err(|e| self.err(e))?;
+        if master.name == replica.name {
+            return Err(self.err(McpRedisError::Other(format!(
+                "'{}' is both the master and the replica; name another connection as replica",
+                master.name
+            ))));
+        }
+        let samples = params
+            .samples
+            .unwrap_or(100)
+            .clamp(1, self.limits().max_consistency_keys) as usize;
+        let max_elements = self.limits().max_compare_elements;
+        let mut master_conn = master.connection();
+        let mut replica_conn = replica.connection();
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+
+        let mut keys = match params.pattern.as_deref() {
+            Some(pattern) => {
+                Self::validate_pattern(pattern).map_err(|e| self.err(e))?;
+                scan::scan_bounded(
+                    &mut master_conn,
+                    pattern,
+                    samples,
+                    self.limits().scan_budget(),
+                )
+                .await
+                .map_err(redis_err)?
+                .keys
+            }
+            None => {
+                let dbsize: u64 = redis::cmd("DBSIZE")
+                    .query_async(&mut master_conn)
+                    .await
+                    .map_err(redis_err)?;
+                let batch = self.limits().scan_batch_count.max(1);
+                let calls = samples.div_ceil(batch as usize);
+                scan::sample_random_keys(&mut master_conn, dbsize, calls, batch)
+                    .await
+                    .map_err(redis_err)?
+            }
+        };
+        keys.truncate(samples);
+
+        // DEBUG DIGEST-VALUE digests any type server-side, but DEBUG is often
+        // disabled; values are then read back and digested here
+        let probe = &keys[..keys.len().min(1)];
+        let server_digests = debug_digests(&mut master_conn, probe).await.is_some()
+            && debug_digests(&mut replica_conn, probe).await.is_some();
+        let max_bytes = self.max_value_bytes;
+        let read = |mut conn: TracedConnection, keys: Vec<String>| async move {
+            read_key_states(&mut conn, &keys, server_digests, max_elements, max_bytes).await
+        };
+        let (on_master, on_replica) = tokio::try_join!(
+            read(master_conn.clone(), keys.clone()),
+            read(replica_conn.clone(), keys.clone())
+        )
+        .map_err(redis_err)?;
+
+        let differing: Vec<String> = keys
+            .iter()
+            .zip(on_master.iter().zip(&on_replica))
+            .filter(|(_, (m, r))| consistency::mismatch(m, r).is_some())
+            .map(|(key, _)| key.clone())
+            .collect();
+        // A key written a moment ago may not have reached the replica yet;
+        // only what still differs after a pause is reported
+        let mut mismatches = Vec::new();
+        if !differing.is_empty() {
+            tokio::time::sleep(CONSISTENCY_RECHECK_DELAY).await;
+            let (again_master, again_replica) = tokio::try_join!(
+                read(master_conn.clone(), differing.clone()),
+                read(replica_conn.clone(), differing.clone())
+            )
+            .map_err(redis_err)?;
+            for (key, (m, r)) in differing
+                .iter()
+                .zip(again_master.iter().zip(&again_replica))
+            {
+                if let Some(reason) = consistency::mismatch(m, r) {
+                    mismatches.push(serde_json::json!({
+                        "key": key,
+                        "reason": reason,
+                        "master": m.to_json(),
+                        "replica": r.to_json(),
+                    }));
+                }
+            }
+        }
+
+        let replication = redis::cmd("INFO")
+            .arg("replication")
+            .query_async::<String>(&mut replica_conn)
+            .await
+            .map(|raw| info::parse_fields(&raw))
+            .unwrap_or_default();
+        let length_only = on_master
+            .iter()
+            .filter(|s| s.key_type != "none" && s.digest.is_none())
+            .count();
+        let mut result = serde_json::json!({
+            "master": master.name,
+            "replica": replica.name,
+            "replica_role": replication.get("role"),
+            "master_link_status": replication.get("master_link_status"),
+            "compared": keys.len(),
+            "consistent": mismatches.is_empty(),
+            "mismatch_count": mismatches.len(),
+            "mismatches": mismatches,
+            "settled_on_recheck": differing.len() - mismatches.len(),
+            "digest": if server_digests { "DEBUG DIGEST-VALUE" } else { "values read back" },
+            "length_only": length_only,
+        });
+        if replication.get("role").and_then(|r| r.as_str()) == Some("master") {
+            result["warning"] = serde_json::json!(format!(
+                "'{}' reports role master: it is not a replica, so differences are expected",
+                replica.name
+            ));
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_error_stats(
+        &self,
+        params: ErrorStatsParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let snapshot = || {
+            redis::pipe()
+                .cmd("INFO")
+                .arg("errorstats")
+                .cmd("INFO")
+                .arg("stats")
+                .cmd("INFO")
+                .arg("commandstats")
+                .clone()
+        };
+
+        let (errorstats, stats, commandstats): (String, String, String) = snapshot()
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let errors = info::parse_errorstats(&errorstats);
+        let stats = info::parse_fields(&stats);
+        let total_errors = stats.get("total_error_replies").and_then(|v| v.as_u64());
+        let total_commands = stats
+            .get("total_commands_processed")
+            .and_then(|v| v.as_u64());
+        let error_total: u64 = errors.iter().map(|(_, count)| count).sum();
+
+        let mut commands = info::command_failures(&commandstats);
+        commands.truncate(MAX_FAILING_COMMANDS);
+        let mut result = serde_json::json!({
+            "errors": errors
+                .iter()
+                .map(|(prefix, count)| serde_json::json!({
+                    "prefix": prefix,
+                    "count": count,
+                    "percent": if error_total == 0 {
+                        0.0
+                    } else {
+                        (*count as f64 * 1000.0 / error_total as f64).round() / 10.0
+                    },
+                }))
+                .collect::<Vec<_>>(),
+            "total_error_replies": total_errors,
+            "total_commands_processed": total_commands,
+            "commands": commands,
+            "note": "Counters are cumulative since startup or the last CONFIG RESETSTAT",
+        });
+        if let (Some(errors), Some(commands)) = (total_errors, total_commands) {
+            if commands > 0 {
+                result["error_rate_percent"] =
+                    serde_json::json!((errors as f64 * 10_000.0 / commands as f64).round() / 100.0);
+            }
+        }
+        if errorstats
+            .lines()
+            .all(|l| l.trim().is_empty() || l.starts_with('#'))
+            && total_errors.is_none()
+        {
+            result["hint"] = serde_json::json!(
+                "INFO errorstats needs Redis 6.2 or later; no error counters are available"
+            );
+        }
+
+        let interval = params
+            .interval_secs
+            .unwrap_or(0)
+            .min(MAX_ERROR_STATS_INTERVAL);
+        if interval > 0 {
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            let (errorstats, stats, _): (String, String, String) = snapshot()
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+            let before: HashMap<String, u64> = errors.into_iter().collect();
+            let mut delta: Vec<serde_json::Value> = info::parse_errorstats(&errorstats)
+                .into_iter()
+                .filter_map(|(prefix, count)| {
+                    let new = count.saturating_sub(before.get(&prefix).copied().unwrap_or(0));
+                    (new > 0).then(|| {
+                        serde_json::json!({
+                            "prefix": prefix,
+                            "count": new,
+                            "per_sec": (new as f64 * 100.0 / interval as f64).round() / 100.0,
+                        })
+                    })
+                })
+                .collect();
+            delta.sort_by_key(|d| std::cmp::Reverse(d["count"].as_u64()));
+            let stats = info::parse_fields(&stats);
+            let commands_delta = stats
+                .get("total_commands_processed")
+                .and_then(|v| v.as_u64())
+                .zip(total_commands)
+                .map(|(after, before)| after.saturating_sub(before));
+            result["interval"] = serde_json::json!({
+                "secs": interval,
+                "errors": delta,
+                "commands_processed": commands_delta,
+            });
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_config_get(
+        &self,
+        params: ConfigGetParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let pattern = params.pattern.as_deref().unwrap_or("*");
+        Self::validate_pattern(pattern).map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let mut pairs: Vec<(String, String)> = redis::cmd("CONFIG")
+            .arg("GET")
+            .arg(pattern)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| {
+                self.err(McpRedisError::Other(format!(
+                    "CONFIG GET failed: {}. CONFIG is often renamed or disabled on managed services; the info tool shows some settings (maxmemory, maxmemory_policy, aof_enabled)",
+                    e
+                )))
+            })?;
+        pairs.sort();
+        let redacted: Vec<&str> = pairs
+            .iter()
+            .filter(|(name, value)| redact::is_sensitive_config(name) && !value.is_empty())
+            .map(|(name, _)| name.as_str())
+            .collect();
+        let parameters: serde_json::Map<String, serde_json::Value> = pairs
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.clone(),
+                    serde_json::Value::String(redact::config_value(name, value)),
+                )
+            })
+            .collect();
+
+        let mut result = serde_json::json!({
+            "pattern": pattern,
+            "count": parameters.len(),
+            "parameters": parameters,
+        });
+        if !redacted.is_empty() {
+            result["redacted"] = serde_json::json!(redacted);
+        }
+        if parameters.is_empty() {
+            result["hint"] = serde_json::json!(
+                "No parameter matches; names are lower case with dashes, e.g. 'maxmemory-policy'"
+            );
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_config_set(
+        &self,
+        params: ConfigSetParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        self.check_read_only(&entry, "config_set")
+            .map_err(|e| self.err(e))?;
+        let parameter = params.parameter.trim().to_ascii_lowercase();
+        if let Some(reason) = settings::refusal(&self.config_set_allow(), &parameter) {
+            return Err(self.err(McpRedisError::Other(reason)));
+        }
+        let mut conn = entry.connection();
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+
+        let read = |pairs: Vec<(String, String)>| {
+            pairs
+                .into_iter()
+                .find(|(name, _)| *name == parameter)
+                .map(|(_, value)| value)
+        };
+        let previous: Vec<(String, String)> = redis::cmd("CONFIG")
+            .arg("GET")
+            .arg(&parameter)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        let Some(previous) = read(previous) else {
+            return Err(self.err(McpRedisError::Other(format!(
+                "Unknown parameter '{}' on this server",
+                parameter
+            ))));
+        };
+
+        let _: () = redis::cmd("CONFIG")
+            .arg("SET")
+            .arg(&parameter)
+            .arg(&params.value)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+
+        let current: Vec<(String, String)> = redis::cmd("CONFIG")
+            .arg("GET")
+            .arg(&parameter)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        let current = read(current);
+        let mut verification =
+            verify::Verification::new(format!("{} set to '{}'", parameter, params.value));
+        let matches = current
+            .as_deref()
+            .is_some_and(|v| settings::same_value(&params.value, v));
+        verification.observe(
+            serde_json::json!({"parameter": parameter, "value": current}),
+            matches,
+        );
+        tracing::info!(
+            connection = %entry.name,
+            parameter = %parameter,
+            previous = %previous,
+            value = %params.value,
+            "CONFIG SET by config_set"
+        );
+
+        Ok(json_result(serde_json::json!({
+            "connection": entry.name,
+            "parameter": parameter,
+            "previous": previous,
+            "value": current,
+            "changed": current.as_deref() != Some(previous.as_str()),
+            "revert": {
+                "tool": "config_set",
+                "arguments": {"connection": entry.name, "parameter": parameter, "value": previous},
+            },
+            "verification": verification.to_json(),
+            "note": "CONFIG SET changes the running server only; CONFIG REWRITE (not available here) would persist it to redis.conf",
+        })))
+    }
+
+    pub async fn do_info_delta(
+        &self,
+        params: InfoDeltaParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let interval = params
+            .interval_secs
+            .unwrap_or(5)
+            .clamp(1, MAX_INFO_DELTA_INTERVAL);
+
+        let before: String = redis::cmd("INFO")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let started = Instant::now();
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        let after: String = redis::cmd("INFO")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let mut result = info::delta(
+            &info::parse_fields(&before),
+            &info::parse_fields(&after),
+            started.elapsed().as_secs_f64(),
+        );
+        if result["counters_reset"] == true {
+            result["note"] = serde_json::json!(
+                "Some counters went down during the interval (CONFIG RESETSTAT or a restart); they count as zero"
+            );
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_sample_stats(
+        &self,
+        params: SampleStatsParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let duration = params
+            .duration_secs
+            .unwrap_or(10)
+            .clamp(1, MAX_SAMPLE_STATS_SECS);
+        let fields: Vec<String> = match params.fields {
+            Some(fields) if !fields.is_empty() => fields,
+            _ => info::SAMPLE_FIELDS.iter().map(|f| f.to_string()).collect(),
+        };
+
+        let started = Instant::now();
+        let mut ticks = tokio::time::interval(Duration::from_secs(1));
+        let stopping = self.shutdown.stopping();
+        tokio::pin!(stopping);
+        let mut samples = Vec::new();
+        let mut series = Vec::new();
+        let mut interrupted = false;
+        // The first tick fires at once, so `duration` seconds take one more read
+        for _ in 0..=duration {
+            tokio::select! {
+                _ = ticks.tick() => {}
+                _ = &mut stopping => {
+                    interrupted = true;
+                    break;
+                }
+            }
+            let raw: String = redis::cmd("INFO")
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+            let snapshot = info::parse_fields(&raw);
+            let mut row = serde_json::Map::new();
+            row.insert(
+                "t_secs".to_string(),
+                serde_json::json!((started.elapsed().as_secs_f64() * 100.0).round() / 100.0),
+            );
+            row.insert(
+                "at".to_string(),
+                serde_json::json!(time::iso8601_ms(time::now_ms())),
+            );
+            for field in &fields {
+                row.insert(
+                    field.clone(),
+                    snapshot
+                        .get(field)
+                        .cloned()
+                        .unwrap_or(serde_json::Value::Null),
+                );
+            }
+            series.push(serde_json::Value::Object(row));
+            samples.push(snapshot);
+        }
+
+        let unknown: Vec<&String> = fields
+            .iter()
+            .filter(|f| samples.iter().all(|s| !s.contains_key(f.as_str())))
+            .collect();
+        let mut result = serde_json::json!({
+            "duration_secs": (started.elapsed().as_secs_f64() * 100.0).round() / 100.0,
+            "samples": series.len(),
+            "fields": fields,
+            "summary": info::series_summary(&fields, &samples),
+            "series": series,
+        });
+        if !unknown.is_empty() {
+            result["unknown_fields"] = serde_json::json!(unknown);
+        }
+        if interrupted {
+            result["interrupted"] = serde_json::json!("server shutting down");
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_connection_diagnostics(
+        &self,
+        params: ConnectionParam,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let mut pings = Vec::with_capacity(DIAGNOSTIC_PINGS);
+        for _ in 0..DIAGNOSTIC_PINGS {
+            let started = Instant::now();
+            redis::cmd("PING")
+                .query_async::<String>(&mut conn)
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+            pings.push((started.elapsed().as_secs_f64() * 100_000.0).round() / 100.0);
+        }
+
+        // CLIENT INFO needs Redis 6.2; older servers list every client
+        let client = match redis::cmd("CLIENT")
+            .arg("INFO")
+            .query_async::<String>(&mut conn)
+            .await
+        {
+            Ok(raw) => parse_client_list(&raw).into_iter().next(),
+            Err(_) => {
+                let (id, raw): (i64, String) = redis::pipe()
+                    .cmd("CLIENT")
+                    .arg("ID")
+                    .cmd("CLIENT")
+                    .arg("LIST")
+                    .query_async(&mut conn)
+                    .await
+                    .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+                let id = id.to_string();
+                parse_client_list(&raw)
+                    .into_iter()
+                    .find(|c| c["id"].as_str() == Some(id.as_str()))
+            }
+        };
+        let client = client.unwrap_or_else(|| serde_json::json!({}));
+        let field = |name: &str| client[name].as_str().and_then(|v| v.parse::<i64>().ok());
+
+        let seen = field("id").map(|id| {
+            let mut ids = self.client_ids.lock().unwrap_or_else(|e| e.into_inner());
+            let previous = ids.get(&entry.name).copied();
+            let changed = previous.is_some_and(|p| p.id != id);
+            let changes = previous.map_or(0, |p| p.changes) + u32::from(changed);
+            ids.insert(entry.name.clone(), SeenClientId { id, changes });
+            (previous.is_some().then_some(changed), changes)
+        });
+        let reconnected = seen.and_then(|(changed, _)| changed);
+
+        let info = entry.client.get_connection_info();
+        let protocol = match info.redis.protocol {
+            redis::ProtocolVersion::RESP2 => 2,
+            redis::ProtocolVersion::RESP3 => 3,
+        };
+        let prefix = format!("{}/", entry.name);
+        let node_connections = self
+            .node_conns
+            .lock()
+            .await
+            .keys()
+            .filter(|key| key.starts_with(&prefix))
+            .count();
+        let db_connections = self
+            .db_connections
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .filter(|(name, _)| *name == entry.name)
+            .count();
+
+        let min = pings.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = pings.iter().copied().fold(0.0, f64::max);
+        let avg = (pings.iter().sum::<f64>() / pings.len() as f64 * 100.0).round() / 100.0;
+        let mut hints = Vec::new();
+        if reconnected == Some(true) {
+            hints.push("The connection was re-established since the last check (new CLIENT ID): the socket dropped, or the server closed it (timeout, CLIENT KILL, maxclients)".to_string());
+        }
+        if min > SLOW_PING_MS {
+            hints.push(format!(
+                "Every PING took over {} ms: the network path to the server is slow, or the server is busy (compare slowlog and latency_latest)",
+                SLOW_PING_MS
+            ));
+        } else if max > SLOW_PING_MS {
+            hints.push(format!(
+                "PINGs vary from {} to {} ms: other calls share this socket, or the server stalled briefly",
+                min, max
+            ));
+        }
+        if field("omem").is_some_and(|omem| omem > 0) {
+            hints.push("Replies are queued in this connection's output buffer on the server: the MCP server reads them slower than they are produced".to_string());
+        }
+        if let Some(resp) = field("resp") {
+            if resp != protocol {
+                hints.push(format!(
+                    "The server reports RESP{} but the client was configured for RESP{}",
+                    resp, protocol
+                ));
+            }
+        }
+        if hints.is_empty() {
+            hints.push("The connection looks healthy: if calls are slow, look at the server (slowlog, latency_latest, error_stats)".to_string());
+        }
+
+        Ok(json_result(serde_json::json!({
+            "connection": entry.name,
+            "url": entry.url_redacted,
+            "server_side": {
+                "id": field("id"),
+                "addr": client["addr"],
+                "laddr": client["laddr"],
+                "resp": field("resp"),
+                "total_memory_bytes": field("tot-mem"),
+                "query_buffer_bytes": field("qbuf"),
+                "output_memory_bytes": field("omem"),
+                "age_secs": field("age"),
+                "idle_secs": field("idle"),
+                "db": field("db"),
+                "flags": client["flags"],
+                "last_command": client["cmd"],
+                "lib_name": client["lib-name"],
+                "lib_ver": client["lib-ver"],
+                "raw": client,
+            },
+            "local": {
+                "protocol": format!("RESP{}", protocol),
+                "addr": info.addr.to_string(),
+                "db": info.redis.db,
+                "multiplexed": "All calls on this connection share one socket; ConnectionManager reconnects transparently when it drops",
+                "calls_in_flight": self.shutdown.in_flight(),
+                "replaced": entry.replaced,
+                "reconnected_since_last_check": reconnected,
+                "reconnects_observed": seen.map(|(_, changes)| changes),
+                "node_connections": node_connections,
+                "db_connections": db_connections,
+            },
+            "ping_ms": {
+                "samples": pings,
+                "min": min,
+                "max": max,
+                "avg": avg,
+            },
+            "hints": hints,
+        })))
+    }
+
+    pub async fn do_latency_latest(
+        &self,
+        params: ConnectionParam,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let rows: Vec<Vec<redis::Value>> = redis::cmd("LATENCY")
+            .arg("LATEST")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let events = latency::parse_latest(&rows);
+
+        let mut result = serde_json::json!({
+            "events": events,
+            "count": events.len(),
+        });
+        if events.is_empty() {
+            let threshold = latency_monitor_threshold(&mut conn).await;
+            if let Some(ms) = threshold {
+                result["latency_monitor_threshold_ms"] = serde_json::json!(ms);
+            }
+            result["hint"] = serde_json::json!(if threshold == Some(0) {
+                "The latency monitor is off (latency-monitor-threshold 0); set latency-monitor-threshold (e.g. 100) to record latency events"
+            } else {
+                "No latency events recorded since startup or the last LATENCY RESET"
+            });
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_latency_history(
+        &self,
+        params: LatencyHistoryParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let samples: Vec<(u64, u64)> = redis::cmd("LATENCY")
+            .arg("HISTORY")
+            .arg(&params.event)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let mut result = latency::history_summary(&params.event, &samples);
+        if samples.is_empty() {
+            result["hint"] = serde_json::json!(
+                "No samples for this event; latency_latest lists the events that have some"
+            );
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_latency_doctor(
+        &self,
+        params: ConnectionParam,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let (report, rows): (String, Vec<Vec<redis::Value>>) = redis::pipe()
+            .cmd("LATENCY")
+            .arg("DOCTOR")
+            .cmd("LATENCY")
+            .arg("LATEST")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        Ok(json_result(serde_json::json!({
+            "report": report,
+            "events": latency::parse_latest(&rows),
+        })))
+    }
+
+    pub async fn do_latency_correlation(
+        &self,
+        params: LatencyCorrelationParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+
+        let (time, persistence, stats): ((u64, u64), String, String) = redis::pipe()
+            .cmd("TIME")
+            .cmd("INFO")
+            .arg("persistence")
+            .cmd("INFO")
+            .arg("stats")
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        let now = time.0;
+        let (latest, slowlog): (Vec<Vec<redis::Value>>, Vec<Vec<redis::Value>>) = redis::pipe()
+            .cmd("LATENCY")
+            .arg("LATEST")
+            .cmd("SLOWLOG")
+            .arg("GET")
+            .arg(params.slowlog_count.unwrap_or(128))
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+
+        // LATENCY LATEST rows are [event, timestamp, latest_ms, max_ms]
+        let names: Vec<String> = latest
+            .iter()
+            .filter_map(|row| redis::from_redis_value(row.first()?).ok())
+            .collect();
+        let mut events = Vec::new();
+        if !names.is_empty() {
+            let mut pipe = redis::pipe();
+            for name in &names {
+                pipe.cmd("LATENCY").arg("HISTORY").arg(name);
+            }
+            let histories: Vec<Vec<(u64, u64)>> =
+                pipe.query_async(&mut conn).await.map_err(redis_err)?;
+            for (name, history) in names.iter().zip(histories) {
+                events.extend(history.into_iter().map(|(at, ms)| latency::Event {
+                    at,
+                    source: "latency",
+                    name: name.clone(),
+                    duration_ms: ms as f64,
+                }));
+            }
+        }
+        events.extend(
+            parse_slowlog(&slowlog)
+                .iter()
+                .filter_map(latency::Event::from_slowlog),
+        );
+        let since = now.saturating_sub(params.since_secs.unwrap_or(3600));
+        events.retain(|e| e.at >= since);
+        events.sort_by_key(|e| e.at);
+
+        let persistence = info::parse_fields(&persistence);
+        let stats = info::parse_fields(&stats);
+        let windows = latency::persistence_windows(&persistence, now);
+        let coincidences =
+            latency::correlate(&events, &windows, params.tolerance_secs.unwrap_or(2));
+        let (timeline, truncated) = latency::timeline(&events, &windows, since, MAX_TIMELINE);
+
+        let field = |fields: &serde_json::Map<String, serde_json::Value>, name: &str| {
+            fields.get(name).cloned().unwrap_or(serde_json::Value::Null)
+        };
+        let mut result = serde_json::json!({
+            "server_time": now,
+            "since": since,
+            "coincidences": coincidences,
+            "timeline": timeline,
+            "persistence": {
+                "rdb_last_bgsave_status": field(&persistence, "rdb_last_bgsave_status"),
+                "rdb_last_bgsave_time_sec": field(&persistence, "rdb_last_bgsave_time_sec"),
+                "aof_enabled": field(&persistence, "aof_enabled"),
+                "aof_last_rewrite_time_sec": field(&persistence, "aof_last_rewrite_time_sec"),
+                "latest_fork_usec": field(&stats, "latest_fork_usec"),
+            },
+        });
+        if truncated {
+            result["timeline_truncated"] = serde_json::json!(true);
+        }
+        if names.is_empty() {
+            // CONFIG is often disabled on managed services; the hint is
+            // given either way, with the threshold when it can be read
+            let threshold = latency_monitor_threshold(&mut conn).await;
+            if let Some(ms) = threshold {
+                result["latency_monitor_threshold_ms"] = serde_json::json!(ms);
+            }
+            result["hint"] = serde_json::json!(if threshold == Some(0) {
+                "The latency monitor is off (latency-monitor-threshold 0), so only slowlog and persistence events are shown; set latency-monitor-threshold (e.g. 100) to record latency events"
+            } else {
+                "No latency events recorded; only slowlog and persistence events are shown"
+            });
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_client_buffer_report(
+        &self,
+        params: ClientBufferReportParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let sort = match params.sort_by.as_deref().unwrap_or("omem") {
+            "omem" => backlog::BufferSort::Memory,
+            "oll" => backlog::BufferSort::Length,
+            other => {
+                return Err(self.err(McpRedisError::Other(format!(
+                    "Unknown sort_by '{}': expected 'omem' or 'oll'",
+                    other
+                ))))
+            }
+        };
+        let count = params.count.unwrap_or(20) as usize;
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let raw: String = redis::cmd("CLIENT")
+            .arg("LIST")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let limits = redis::cmd("CONFIG")
+            .arg("GET")
+            .arg("client-output-buffer-limit")
+            .query_async::<Vec<String>>(&mut conn)
+            .await
+            .ok()
+            .and_then(|reply| reply.get(1).map(|raw| backlog::parse_output_limits(raw)));
+
+        let mut result =
+            backlog::buffer_report(&parse_client_list(&raw), limits.as_ref(), sort, count);
+        if limits.is_none() {
+            result["limits"] = serde_json::json!(
+                "CONFIG GET is not available on this server; buffer limits are unknown"
+            );
+        }
+        Ok(json_result(result))
+    }
+
+    /// TYPE, PTTL, OBJECT ENCODING, MEMORY USAGE and length of `keys`, in
+    /// order. Keys that no longer exist have type "none". MEMORY USAGE can be
+    /// disabled on managed services; once it fails, `memory_supported` is
+    /// cleared and later batches skip it.
+    async fn read_key_samples(
+        &self,
+        conn: &mut TracedConnection,
+        keys: &[String],
+        memory_supported: &mut bool,
+    ) -> Result<Vec<census::KeySample>, ErrorData> {
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("TYPE").arg(key);
+            pipe.cmd("PTTL").arg(key);
+            pipe.cmd("OBJECT").arg("ENCODING").arg(key);
+        }
+        let reply: Vec<redis::Value> = pipe
+            .query_async(conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let mut batch: Vec<census::KeySample> = reply
+            .chunks(3)
+            .map(|parts| census::KeySample {
+                key_type: redis::from_redis_value(&parts[0]).unwrap_or_default(),
+                ttl_ms: redis::from_redis_value(&parts[1]).unwrap_or(-2),
+                encoding: redis::from_redis_value(&parts[2]).ok(),
+                ..Default::default()
+            })
+            .collect();
+
+        if *memory_supported {
+            let mut pipe = redis::pipe();
+            for key in keys {
+                pipe.cmd("MEMORY").arg("USAGE").arg(key);
+            }
+            match pipe.query_async::<Vec<Option<u64>>>(conn).await {
+                Ok(usage) => {
+                    for (sample, bytes) in batch.iter_mut().zip(usage) {
+                        sample.memory = bytes;
+                    }
+                }
+                Err(_) => *memory_supported = false,
+            }
+        }
+
+        let mut pipe = redis::pipe();
+        let mut measured = Vec::new();
+        for (i, (key, sample)) in keys.iter().zip(&batch).enumerate() {
+            if let Some(cmd) = census::length_command(&sample.key_type) {
+                pipe.cmd(cmd).arg(key);
+                measured.push(i);
+            }
+        }
+        if !measured.is_empty() {
+            let lengths: Vec<u64> = pipe
+                .query_async(conn)
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+            for (i, length) in measured.into_iter().zip(lengths) {
+                batch[i].length = Some(length);
+            }
+        }
+
+        Ok(batch)
+    }
+
+    /// Required hash fields that each key lacks, per the contract it was
+    /// matched to. Keys that are not hashes are left to the type rule.
+    async fn missing_fields(
+        &self,
+        conn: &mut TracedConnection,
+        keys: &[String],
+        samples: &[census::KeySample],
+        contracts: &[Option<&KeyContract>],
+    ) -> Result<Vec<Vec<String>>, ErrorData> {
+        let mut missing = vec![Vec::new(); keys.len()];
+        let mut pipe = redis::pipe();
+        let mut checked = Vec::new();
+        for (i, key) in keys.iter().enumerate() {
+            let Some(contract) = contracts[i] else {
+                continue;
+            };
+            if samples[i].key_type != "hash" {
+                continue;
+            }
+            for field in &contract.required_fields {
+                pipe.cmd("HEXISTS").arg(key).arg(field);
+                checked.push((i, field));
+            }
+        }
+        if checked.is_empty() {
+            return Ok(missing);
+        }
+        let exists: Vec<bool> = pipe
+            .query_async(conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        for ((i, field), exists) in checked.into_iter().zip(exists) {
+            if !exists {
+                missing[i].push(field.clone());
+            }
+        }
+        Ok(missing)
+    }
+
+    pub async fn do_namespace_census(
+        &self,
+        params: NamespaceCensusParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let pattern = glob::prefix(&params.prefix);
+        Self::validate_pattern(&pattern).map_err(|e| self.err(e))?;
+        let sample = params
+            .sample
+            .unwrap_or(200)
+            .clamp(1, self.limits().max_census_sample) as usize;
+
+        let scans = self
+            .scan_across(&entry, &pattern, sample)
+            .await
+            .map_err(|e| self.err(e))?;
+
+        let mut samples: Vec<census::KeySample> = Vec::new();
+        let mut complete = true;
+        let mut memory_supported = true;
+        for mut scanned in scans {
+            complete &= scanned.outcome.cursor == 0;
+            let keys = &scanned.outcome.keys;
+            if keys.is_empty() {
+                continue;
+            }
+
+            let batch = self
+                .read_key_samples(&mut scanned.conn, keys, &mut memory_supported)
+                .await?;
+
+            // Keys that expired or were deleted while sampling
+            samples.extend(batch.into_iter().filter(|s| s.key_type != "none"));
+        }
+
+        let mut summary = census::summarize(&samples);
+        summary["prefix"] = serde_json::json!(params.prefix);
+        summary["sampled"] = serde_json::json!(samples.len());
+        // Every matching key was read, so the figures are exact
+        summary["complete"] = serde_json::json!(complete && samples.len() < sample);
+        if !memory_supported {
+            summary["memory"] = serde_json::json!("MEMORY USAGE is not available on this server");
+        }
+        Ok(json_result(summary))
+    }
+
+    pub async fn do_find_big_keys(
+        &self,
+        params: BigKeysParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let pattern = params.pattern.as_deref().unwrap_or("*");
+        Self::validate_pattern(pattern).map_err(|e| self.err(e))?;
+        let top = params.top.unwrap_or(10).clamp(1, MAX_BIG_KEYS as u32) as usize;
+        let samples = params.samples.unwrap_or(5);
+        let limit = self.limits().max_big_keys_scan;
+        let max_keys = params.max_keys.unwrap_or(limit).clamp(1, limit);
+
+        let scans = self
+            .scan_across(&entry, pattern, max_keys)
+            .await
+            .map_err(|e| self.err(e))?;
+
+        let mut scanned = 0;
+        let pipeline_batch = self.limits().pipeline_batch.max(1);
+        let mut complete = true;
+        // (bytes, key, shard index)
+        let mut sizes: Vec<(u64, String, usize)> = Vec::new();
+        let mut shards = Vec::new();
+        for (index, mut shard) in scans.into_iter().enumerate() {
+            complete &= shard.outcome.cursor == 0;
+            scanned += shard.outcome.keys.len();
+            for batch in shard.outcome.keys.chunks(pipeline_batch) {
+                let mut pipe = redis::pipe();
+                for key in batch {
+                    pipe.cmd("MEMORY")
+                        .arg("USAGE")
+                        .arg(key)
+                        .arg("SAMPLES")
+                        .arg(samples);
+                }
+                let usage: Vec<Option<u64>> =
+                    pipe.query_async(&mut shard.conn).await.map_err(|e| {
+                        self.err(McpRedisError::Other(format!(
+                            "MEMORY USAGE is not available on this server: {}",
+                            e
+                        )))
+                    })?;
+                // Keys deleted since the scan report nil
+                sizes.extend(
+                    batch
+                        .iter()
+                        .zip(usage)
+                        .filter_map(|(key, bytes)| Some((bytes?, key.clone(), index))),
+                );
+                sizes.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+                sizes.truncate(top);
+            }
+            shards.push((shard.shard, shard.conn));
+        }
+
+        let mut keys = Vec::new();
+        for (bytes, key, index) in sizes {
+            let (shard, conn) = &mut shards[index];
+            let key_type: String = redis::cmd("TYPE")
+                .arg(&key)
+                .query_async(conn)
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+            let mut item = serde_json::json!({
+                "key": key,
+                "type": key_type,
+                "memory_bytes": bytes,
+            });
+            if let Some(cmd) = census::length_command(&key_type) {
+                let length: Option<u64> = redis::cmd(cmd).arg(&key).query_async(conn).await.ok();
+                item["length"] = serde_json::json!(length);
+            }
+            if let Some(shard) = shard {
+                item["shard"] = serde_json::json!(shard);
+            }
+            keys.push(item);
+        }
+
+        Ok(json_result(serde_json::json!({
+            "pattern": pattern,
+            "keys": keys,
+            "scanned": scanned,
+            "complete": complete,
+            "samples": samples,
+        })))
+    }
+
+    pub async fn do_analyze_namespaces(
+        &self,
+        params: AnalyzeNamespacesParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let pattern = params.pattern.as_deref().unwrap_or("*");
+        Self::validate_pattern(pattern).map_err(|e| self.err(e))?;
+        let delimiter = params.delimiter.as_deref().unwrap_or(":");
+        if delimiter.is_empty() {
+            return Err(self.err(McpRedisError::Other(
+                "delimiter must not be empty".to_string(),
+            )));
+        }
+        let sample = params
+            .sample
+            .unwrap_or(500)
+            .clamp(1, self.limits().max_census_sample) as usize;
+
+        let scans = self
+            .scan_across(&entry, pattern, sample)
+            .await
+            .map_err(|e| self.err(e))?;
+
+        let mut keys: Vec<String> = Vec::new();
+        let mut memory: Vec<Option<u64>> = Vec::new();
+        let mut memory_supported = true;
+        let mut complete = true;
+        let mut total_keys: u64 = 0;
+        for mut scanned in scans {
+            complete &= scanned.outcome.cursor == 0;
+            let dbsize: u64 = redis::cmd("DBSIZE")
+                .query_async(&mut scanned.conn)
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+            total_keys += dbsize;
+            let batch = std::mem::take(&mut scanned.outcome.keys);
+            if batch.is_empty() {
+                continue;
+            }
+            if memory_supported {
+                let mut pipe = redis::pipe();
+                for key in &batch {
+                    pipe.cmd("MEMORY").arg("USAGE").arg(key);
+                }
+                match pipe
+                    .query_async::<Vec<Option<u64>>>(&mut scanned.conn)
+                    .await
+                {
+                    Ok(usage) => memory.extend(usage),
+                    Err(_) => memory_supported = false,
+                }
+            }
+            keys.extend(batch);
+        }
+        if !memory_supported {
+            memory.clear();
+        }
+
+        let mut prefixes = census::prefix_breakdown(&keys, &memory, delimiter);
+        // Every matching key was sampled, so the counts are exact
+        let exact = complete && keys.len() < sample;
+        if !exact && !keys.is_empty() && pattern == "*" {
+            for item in &mut prefixes {
+                let share = item["count"].as_u64().unwrap_or(0) as f64 / keys.len() as f64;
+                item["estimated_keys"] =
+                    serde_json::json!((share * total_keys as f64).round() as u64);
+            }
+        }
+        let other_prefixes = prefixes.len().saturating_sub(MAX_REPORTED_PREFIXES);
+        prefixes.truncate(MAX_REPORTED_PREFIXES);
+
+        let mut result = serde_json::json!({
+            "pattern": pattern,
+            "delimiter": delimiter,
+            "sampled": keys.len(),
+            "total_keys": total_keys,
+            "complete": exact,
+            "prefixes": prefixes,
+        });
+        if other_prefixes > 0 {
+            result["other_prefixes"] = serde_json::json!(other_prefixes);
+        }
+        if !memory_supported {
+            result["memory"] = serde_json::json!("MEMORY USAGE is not available on this server");
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_validate_key(
+        &self,
+        params: ValidateKeyParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let contracts = self.contracts();
+        let Some(contract) = contract::find(&contracts, &params.key) else {
+            return Err(self.err(McpRedisError::Other(format!(
+                "No contract matches key '{}' (add one under [[contracts]] in the config file)",
+                params.key
+            ))));
+        };
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let keys = [params.key.clone()];
+        let samples = self.read_key_samples(&mut conn, &keys, &mut true).await?;
+        let missing = self
+            .missing_fields(&mut conn, &keys, &samples, &[Some(contract)])
+            .await?;
+        let violations = contract::check(contract, &samples[0], &missing[0]);
+
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "contract": contract.pattern,
+            "description": contract.description,
+            "valid": violations.is_empty(),
+            "violations": violations,
+        })))
+    }
+
+    pub async fn do_validate_namespace(
+        &self,
+        params: ValidateNamespaceParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let contracts = self.contracts();
+        if contracts.is_empty() {
+            return Err(self.err(McpRedisError::Other(
+                "No contracts configured (add them under [[contracts]] in the config file)"
+                    .to_string(),
+            )));
+        }
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        Self::validate_pattern(&params.pattern).map_err(|e| self.err(e))?;
+        let sample = params
+            .sample
+            .unwrap_or(200)
+            .clamp(1, self.limits().max_validate_sample) as usize;
+
+        let scans = self
+            .scan_across(&entry, &params.pattern, sample)
+            .await
+            .map_err(|e| self.err(e))?;
+
+        let mut complete = true;
+        let mut memory_supported = true;
+        let mut sampled = 0;
+        let mut valid = 0;
+        let mut uncovered = 0;
+        let mut by_rule: std::collections::BTreeMap<&str, usize> = Default::default();
+        let mut invalid = Vec::new();
+        for mut scanned in scans {
+            complete &= scanned.outcome.cursor == 0;
+            let keys = &scanned.outcome.keys;
+            if keys.is_empty() {
+                continue;
+            }
+            let samples = self
+                .read_key_samples(&mut scanned.conn, keys, &mut memory_supported)
+                .await?;
+            let matched: Vec<Option<&KeyContract>> =
+                keys.iter().map(|k| contract::find(&contracts, k)).collect();
+            let missing = self
+                .missing_fields(&mut scanned.conn, keys, &samples, &matched)
+                .await?;
+
+            for (i, key) in keys.iter().enumerate() {
+                // Keys that expired or were deleted while sampling
+                if samples[i].key_type == "none" {
+                    continue;
+                }
+                sampled += 1;
+                let Some(contract) = matched[i] else {
+                    uncovered += 1;
+                    continue;
+                };
+                let violations = contract::check(contract, &samples[i], &missing[i]);
+                if violations.is_empty() {
+                    valid += 1;
+                    continue;
+                }
+                for violation in &violations {
+                    *by_rule.entry(violation.rule).or_default() += 1;
+                }
+                invalid.push(serde_json::json!({
+                    "key": key,
+                    "contract": contract.pattern,
+                    "violations": violations,
+                }));
+            }
+        }
+
+        let invalid_count = invalid.len();
+        invalid.truncate(MAX_REPORTED_VIOLATIONS);
+        let mut result = serde_json::json!({
+            "pattern": params.pattern,
+            "sampled": sampled,
+            // Every matching key was checked
+            "complete": complete && sampled < sample,
+            "valid": valid,
+            "invalid": invalid_count,
+            "uncovered": uncovered,
+            "violations_by_rule": by_rule,
+            "invalid_keys": invalid,
+        });
+        if invalid_count > MAX_REPORTED_VIOLATIONS {
+            result["invalid_keys_truncated"] = serde_json::json!(true);
+        }
+        if !memory_supported {
+            result["memory"] = serde_json::json!(
+                "MEMORY USAGE is not available on this server; max_memory_bytes was not checked"
+            );
+        }
+        Ok(json_result(result))
+    }
+
+    /// The `--rdb` dump, or an error naming the flag.
+    fn rdb_path(&self) -> Result<PathBuf, ErrorData> {
+        self.rdb_path.clone().ok_or_else(|| {
+            self.err(McpRedisError::Other(
+                "No RDB file to read (start with --rdb <PATH>)".to_string(),
+            ))
+        })
+    }
+
+    /// Where a dump came from: its path and format version, plus the Redis
+    /// version and save time recorded in its AUX fields.
+    fn rdb_snapshot(
+        path: &std::path::Path,
+        version: u32,
+        aux: &std::collections::BTreeMap<String, String>,
+    ) -> serde_json::Value {
+        let saved_at = aux
+            .get("ctime")
+            .and_then(|t| t.parse::<u64>().ok())
+            .map(|secs| time::iso8601_ms(secs * 1000));
+        serde_json::json!({
+            "path": path.display().to_string(),
+            "rdb_version": version,
+            "redis_version": aux.get("redis-ver"),
+            "saved_at": saved_at,
+        })
+    }
+
+    pub async fn do_rdb_scan(&self, params: RdbScanParams) -> Result<CallToolResult, ErrorData> {
+        let path = self.rdb_path()?;
+        let pattern = params.pattern.unwrap_or_else(|| "*".to_string());
+        let db = params.db.unwrap_or(0);
+        let count = params
+            .count
+            .unwrap_or(100)
+            .clamp(1, self.limits().max_rdb_keys) as usize;
+        let only_missing = params.only_missing.unwrap_or(false);
+        let mut live = if only_missing || params.compare_live.unwrap_or(false) {
+            let entry = self
+                .resolve(params.connection.as_deref())
+                .map_err(|e| self.err(e))?;
+            Some(entry.connection())
+        } else {
+            None
+        };
+
+        // The dump is parsed on a blocking thread that streams matching keys
+        // back in batches, so values are never held longer than one key
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<Vec<RdbKey>>(4);
+        let reader_path = path.clone();
+        let reader_pattern = pattern.clone();
+        let reader = tokio::task::spawn_blocking(move || {
+            let mut reader = rdb::Reader::open(&reader_path)?;
+            let mut matched = 0u64;
+            let mut batch = Vec::new();
+            while let Some(entry) = reader.next_entry()? {
+                let key = String::from_utf8_lossy(&entry.key).into_owned();
+                if entry.db != db || !glob::matches(&reader_pattern, &key) {
+                    continue;
+                }
+                matched += 1;
+                batch.push(RdbKey {
+                    key,
+                    key_type: entry.value.type_name(),
+                    length: entry.value.length(),
+                    expire_ms: entry.expire_ms,
+                });
+                if batch.len() == RDB_BATCH {
+                    // The receiver is gone when the call failed; keep
+                    // counting regardless
+                    let _ = tx.blocking_send(std::mem::take(&mut batch));
+                }
+            }
+            let _ = tx.blocking_send(batch);
+            Ok::<_, std::io::Error>((std::mem::take(&mut reader.aux), reader.version(), matched))
+        });
+
+        let now = time::now_ms() as i64;
+        let mut listed = Vec::new();
+        let mut considered = 0u64;
+        let mut missing = 0u64;
+        while let Some(batch) = rx.recv().await {
+            if batch.is_empty() || listed.len() >= count {
+                continue;
+            }
+            let live_types: Vec<Option<String>> = match live.as_mut() {
+                Some(conn) => {
+                    let mut pipe = redis::pipe();
+                    for item in &batch {
+                        pipe.cmd("TYPE").arg(&item.key);
+                    }
+                    let types: Vec<String> = pipe
+                        .query_async(conn)
+                        .await
+                        .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+                    types.into_iter().map(Some).collect()
+                }
+                None => vec![None; batch.len()],
+            };
+            for (item, live_type) in batch.into_iter().zip(live_types) {
+                if listed.len() >= count {
+                    break;
+                }
+                considered += 1;
+                let gone = live_type.as_deref() == Some("none");
+                if gone {
+                    missing += 1;
+                }
+                if only_missing && !gone {
+                    continue;
+                }
+                let mut json = serde_json::json!({
+                    "key": item.key,
+                    "type": item.key_type,
+                    "length": item.length,
+                });
+                if let Some(expire_ms) = item.expire_ms {
+                    json["expires_at"] =
+                        serde_json::json!(time::iso8601_ms(expire_ms.max(0) as u64));
+                    // Gone by expiry rather than deletion
+                    json["expired"] = serde_json::json!(expire_ms <= now);
+                }
+                if let Some(live_type) = live_type {
+                    json["live_type"] = serde_json::json!(live_type);
+                }
+                listed.push(json);
+            }
+        }
+        let (aux, version, matched) = reader
+            .await
+            .map_err(|e| self.err(McpRedisError::Other(e.to_string())))?
+            .map_err(|e| {
+                self.err(McpRedisError::Other(format!(
+                    "Cannot read {}: {}",
+                    path.display(),
+                    e
+                )))
+            })?;
+
+        let mut result = serde_json::json!({
+            "snapshot": Self::rdb_snapshot(&path, version, &aux),
+            "db": db,
+            "pattern": pattern,
+            "matched": matched,
+            "keys": listed,
+            // Matching keys were left unlisted (or unchecked)
+            "truncated": considered < matched,
+        });
+        if live.is_some() {
+            result["checked_live"] = serde_json::json!(considered);
+            result["missing_live"] = serde_json::json!(missing);
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_rdb_get(&self, params: RdbGetParams) -> Result<CallToolResult, ErrorData> {
+        let path = self.rdb_path()?;
+        let db = params.db.unwrap_or(0);
+        let reader_path = path.clone();
+        let key = params.key.clone();
+        let (found, aux, version) = tokio::task::spawn_blocking(move || {
+            let mut reader = rdb::Reader::open(&reader_path)?;
+            while let Some(entry) = reader.next_entry()? {
+                if entry.db == db && entry.key == key.as_bytes() {
+                    return Ok((
+                        Some(entry),
+                        std::mem::take(&mut reader.aux),
+                        reader.version(),
+                    ));
+                }
+            }
+            Ok::<_, std::io::Error>((None, std::mem::take(&mut reader.aux), reader.version()))
+        })
+        .await
+        .map_err(|e| self.err(McpRedisError::Other(e.to_string())))?
+        .map_err(|e| {
+            self.err(McpRedisError::Other(format!(
+                "Cannot read {}: {}",
+                path.display(),
+                e
+            )))
+        })?;
+        let snapshot = Self::rdb_snapshot(&path, version, &aux);
+
+        let Some(entry) = found else {
+            return Ok(json_result(serde_json::json!({
+                "error": "Key is not in the snapshot",
+                "key": params.key,
+                "db": db,
+                "snapshot": snapshot,
+            })));
+        };
+        let key_type = entry.value.type_name();
+        let (value, truncated) = rdb::value_json(&entry.value, self.limits().max_rdb_elements);
+        let mut result = serde_json::json!({
+            "snapshot": snapshot,
+            "key": params.key,
+            "db": db,
+            "type": key_type,
+            "length": entry.value.length(),
+            "value": value,
+        });
+        if truncated {
+            result["truncated"] = serde_json::json!(true);
+        }
+        if let Some(expire_ms) = entry.expire_ms {
+            result["expires_at"] = serde_json::json!(time::iso8601_ms(expire_ms.max(0) as u64));
+            result["expired"] = serde_json::json!(expire_ms <= time::now_ms() as i64);
+        }
+
+        if params.compare_live.unwrap_or(false) {
+            let live = self
+                .do_get(GetParams {
+                    connection: params.connection,
+                    key: params.key,
+                    max_bytes: None,
+                    summary_only: None,
+                    follow_references: None,
+                    reference_depth: None,
+                })
+                .await?;
+            let text = live
+                .content
+                .first()
+                .and_then(|c| c.as_text())
+                .map(|t| t.text.clone())
+                .unwrap_or_default();
+            let live: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+            if live["error"] == "Key does not exist" {
+                result["live"] = serde_json::Value::Null;
+                result["changed"] = serde_json::json!(true);
+            } else {
+                // Unknown when either value was cut short
+                result["changed"] = if truncated || live["truncated"] == true {
+                    serde_json::Value::Null
+                } else {
+                    serde_json::json!(
+                        live["type"] != key_type
+                            || !rdb::same_contents(key_type, &result["value"], &live["value"])
+                    )
+                };
+                result["live"] = serde_json::json!({"type": live["type"], "value": live["value"]});
+            }
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_estimate_matching_keys(
+        &self,
+        params: EstimateParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let pattern = params.pattern.as_str();
+
+        Self::validate_pattern(pattern).map_err(|e| self.err(e))?;
+
+        let dbsize: u64 = redis::cmd("DBSIZE")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        // Small keyspaces are cheaper to count exactly than to sample
+        if dbsize <= self.limits().exact_estimate_threshold {
+            let scanned =
+                scan::scan_bounded(&mut conn, pattern, usize::MAX, self.limits().scan_budget())
+                    .await
+                    .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+            return Ok(json_result(serde_json::json!({
+                "pattern": pattern,
+                "dbsize": dbsize,
+                "method": "exact",
+                "estimate": scanned.keys.len(),
+                "ci_low": scanned.keys.len(),
+                "ci_high": scanned.keys.len(),
+            })));
+        }
+
+        let samples = params
+            .samples
+            .unwrap_or(20)
+            .clamp(1, self.limits().max_estimate_samples);
+        let mut result = self
+            .sample_estimate(
+                &mut conn,
+                pattern,
+                &scan::KeyFilter::default(),
+                dbsize,
+                samples as usize,
+            )
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        result["pattern"] = serde_json::json!(pattern);
+        Ok(json_result(result))
+    }
+
+    pub async fn do_memory_by_pattern(
+        &self,
+        params: MemoryByPatternParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        if params.patterns.is_empty() || params.patterns.len() > MAX_MEMORY_PATTERNS {
+            return Err(self.err(McpRedisError::Other(format!(
+                "Give between 1 and {} patterns",
+                MAX_MEMORY_PATTERNS
+            ))));
+        }
+        for pattern in &params.patterns {
+            Self::validate_pattern(pattern).map_err(|e| self.err(e))?;
+        }
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+
+        let dbsize: u64 = redis::cmd("DBSIZE")
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        // Small keyspaces are cheaper to measure in full than to sample
+        let exact = dbsize <= self.limits().exact_estimate_threshold;
+        let keys = if exact {
+            scan::scan_bounded(&mut conn, "*", usize::MAX, self.limits().scan_budget())
+                .await
+                .map_err(redis_err)?
+                .keys
+        } else {
+            let samples = params
+                .samples
+                .unwrap_or(20)
+                .clamp(1, self.limits().max_estimate_samples);
+            scan::sample_random_keys(
+                &mut conn,
+                dbsize,
+                samples as usize,
+                self.limits().scan_batch_count,
+            )
+                .await
+                .map_err(redis_err)?
+        };
+
+        let mut memory = Vec::with_capacity(keys.len());
+        for batch in keys.chunks(self.limits().pipeline_batch.max(1)) {
+            let mut pipe = redis::pipe();
+            for key in batch {
+                pipe.cmd("MEMORY")
+                    .arg("USAGE")
+                    .arg(key)
+                    .arg("SAMPLES")
+                    .arg(METADATA_MEMORY_SAMPLES);
+            }
+            let usage: Vec<Option<u64>> = pipe.query_async(&mut conn).await.map_err(|e| {
+                self.err(McpRedisError::Other(format!(
+                    "MEMORY USAGE is not available on this server: {}",
+                    e
+                )))
+            })?;
+            memory.extend(usage);
+        }
+
+        let patterns = census::pattern_memory(&params.patterns, &keys, &memory, dbsize, exact);
+        let mut result = serde_json::json!({
+            "dbsize": dbsize,
+            "method": if exact { "exact" } else { "sampled" },
+            "patterns": patterns,
+        });
+        if !exact {
+            result["sampled_keys"] = serde_json::json!(keys.len());
+            result["confidence"] = serde_json::json!(0.95);
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_compaction_advisor(
+        &self,
+        params: CompactionAdvisorParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let pattern = params.pattern.as_deref().unwrap_or("*");
         Self::validate_pattern(pattern).map_err(|e| self.err(e))?;
+        let sample = params
+            .sample
+            .unwrap_or(1000)
+            .clamp(1, self.limits().max_census_sample) as usize;
+        let margin = params
+            .margin_percent
+            .unwrap_or(compaction::DEFAULT_MARGIN_PERCENT);
+
+        let mut conn = entry.connection();
+        let config = redis::cmd("CONFIG")
+            .arg("GET")
+            .arg("*-max-*")
+            .query_async::<Vec<String>>(&mut conn)
+            .await
+            .ok();
+
+        let scans = self
+            .scan_across(&entry, pattern, sample)
+            .await
+            .map_err(|e| self.err(e))?;
+        let mut keys = Vec::new();
+        let mut samples = Vec::new();
+        let mut complete = true;
+        let mut memory_supported = true;
+        for mut scanned in scans {
+            complete &= scanned.outcome.cursor == 0;
+            if scanned.outcome.keys.is_empty() {
+                continue;
+            }
+            let batch = self
+                .read_key_samples(
+                    &mut scanned.conn,
+                    &scanned.outcome.keys,
+                    &mut memory_supported,
+                )
+                .await?;
+            // Keys that expired or were deleted while sampling
+            for (key, sample) in scanned.outcome.keys.into_iter().zip(batch) {
+                if sample.key_type != "none" {
+                    keys.push(key);
+                    samples.push(sample);
+                }
+            }
+        }
+
+        let thresholds = compaction::thresholds(config.as_deref().unwrap_or_default());
+        let mut result = compaction::advise(&keys, &samples, &thresholds, margin);
+        result["pattern"] = serde_json::json!(pattern);
+        result["sampled"] = serde_json::json!(samples.len());
+        // Every matching key was read, so the counts are exact
+        result["complete"] = serde_json::json!(complete && samples.len() < sample);
+        if config.is_none() {
+            result["thresholds_note"] = serde_json::json!(
+                "CONFIG GET is not available on this server; the thresholds are Redis defaults"
+            );
+        }
+        if !memory_supported {
+            result["memory"] = serde_json::json!(
+                "MEMORY USAGE is not available on this server; savings cannot be estimated"
+            );
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_cluster_info(
+        &self,
+        params: ConnectionParam,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let raw: String = redis::cmd("CLUSTER")
+            .arg("INFO")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.cluster_err(e))?;
+
+        Ok(json_result(serde_json::Value::Object(info::parse_fields(
+            &raw,
+        ))))
+    }
+
+    pub async fn do_cluster_nodes(
+        &self,
+        params: ConnectionParam,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let raw: String = redis::cmd("CLUSTER")
+            .arg("NODES")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.cluster_err(e))?;
+
+        let nodes = cluster::parse_nodes(&raw);
+        let role_count = |role: &str| nodes.iter().filter(|n| n["role"] == role).count();
+        // Handshake, noaddr and failed nodes have neither role
+        let masters = role_count("master");
+        let replicas = role_count("replica");
+
+        Ok(json_result(serde_json::json!({
+            "nodes": nodes,
+            "count": nodes.len(),
+            "masters": masters,
+            "replicas": replicas,
+            "other": nodes.len() - masters - replicas,
+        })))
+    }
+
+    pub async fn do_cluster_shards(
+        &self,
+        params: ConnectionParam,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let raw: redis::Value = redis::cmd("CLUSTER")
+            .arg("SHARDS")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.cluster_err(e))?;
+
+        let shards = cluster::parse_shards(&raw);
+
+        Ok(json_result(serde_json::json!({
+            "shards": shards,
+            "count": shards.len(),
+        })))
+    }
+
+    pub async fn do_cluster_keyslot(&self, params: KeyParams) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let slot: u16 = redis::cmd("CLUSTER")
+            .arg("KEYSLOT")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.cluster_err(e))?;
+
+        let raw_nodes: String = redis::cmd("CLUSTER")
+            .arg("NODES")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.cluster_err(e))?;
+        let nodes = cluster::parse_nodes(&raw_nodes);
+        let owner = cluster::slot_owner(&nodes, slot);
+
+        // COUNTKEYSINSLOT only answers for slots served by the node it runs on
+        let keys_in_slot: Option<i64> = match owner {
+            Some(node) if node["myself"] == true => Some(
+                redis::cmd("CLUSTER")
+                    .arg("COUNTKEYSINSLOT")
+                    .arg(slot)
+                    .query_async(&mut conn)
+                    .await
+                    .map_err(|e| self.cluster_err(e))?,
+            ),
+            Some(node) => {
+                match cluster::split_address(node["address"].as_str().unwrap_or_default()) {
+                    Some((host, port)) => {
+                        let owner_conn = self
+                            .node_connection(&entry, host, port)
+                            .await
+                            .map_err(|e| self.err(e))?;
+                        let mut owner_conn = TracedConnection::new(owner_conn);
+                        Some(
+                            redis::cmd("CLUSTER")
+                                .arg("COUNTKEYSINSLOT")
+                                .arg(slot)
+                                .query_async(&mut owner_conn)
+                                .await
+                                .map_err(|e| self.cluster_err(e))?,
+                        )
+                    }
+                    None => None,
+                }
+            }
+            None => None,
+        };
+
+        let owner_json = owner.map(|node| {
+            let replicas: Vec<&serde_json::Value> = nodes
+                .iter()
+                .filter(|n| n["master_id"] == node["id"])
+                .map(|n| &n["address"])
+                .collect();
+            serde_json::json!({
+                "id": node["id"],
+                "address": node["address"],
+                "replicas": replicas,
+            })
+        });
+
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "slot": slot,
+            "owner": owner_json,
+            "keys_in_slot": keys_in_slot,
+        })))
+    }
+
+    pub async fn do_capture_diagnostics(
+        &self,
+        params: DiagnosticsParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let export_dir = self.export_dir.as_ref().ok_or_else(|| {
+            self.err(McpRedisError::Other(
+                "capture_diagnostics requires --export-dir".to_string(),
+            ))
+        })?;
+        let mut conn = entry.connection();
+        let redis_err = |e| self.err(McpRedisError::Redis(e));
+
+        let raw_info: String = redis::cmd("INFO")
+            .arg("all")
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+
+        // CONFIG may be renamed or disabled on managed services; keep going
+        let config: serde_json::Value = match redis::cmd("CONFIG")
+            .arg("GET")
+            .arg("*")
+            .query_async::<Vec<(String, String)>>(&mut conn)
+            .await
+        {
+            Ok(pairs) => {
+                let mut pairs = pairs;
+                pairs.sort();
+                serde_json::Value::Object(
+                    pairs
+                        .into_iter()
+                        .map(|(name, value)| {
+                            let value = redact::config_value(&name, &value);
+                            (name, serde_json::Value::String(value))
+                        })
+                        .collect(),
+                )
+            }
+            Err(e) => serde_json::json!({"error": e.to_string()}),
+        };
+
+        let slowlog: Vec<Vec<redis::Value>> = redis::cmd("SLOWLOG")
+            .arg("GET")
+            .arg(128)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+
+        let clients: String = redis::cmd("CLIENT")
+            .arg("LIST")
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+
+        let dbsize: u64 = redis::cmd("DBSIZE")
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        let sample_size = params
+            .sample_keys
+            .unwrap_or(20)
+            .min(self.limits().max_diagnostic_keys) as usize;
+        let mut sampled = if sample_size > 0 {
+            scan::sample_random_keys(&mut conn, dbsize, 1, self.limits().scan_batch_count)
+                .await
+                .map_err(redis_err)?
+        } else {
+            Vec::new()
+        };
+        sampled.truncate(sample_size);
+        let mut key_sample = Vec::new();
+        if !sampled.is_empty() {
+            let mut pipe = redis::pipe();
+            for key in &sampled {
+                pipe.cmd("TYPE").arg(key).cmd("TTL").arg(key);
+            }
+            let meta: Vec<redis::Value> = pipe.query_async(&mut conn).await.map_err(redis_err)?;
+            for (key, pair) in sampled.iter().zip(meta.chunks(2)) {
+                key_sample.push(serde_json::json!({
+                    "key": key,
+                    "type": crate::resp::to_json(&pair[0]),
+                    "ttl": crate::resp::to_json(&pair[1]),
+                }));
+            }
+        }
+
+        let captured_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let bundle = serde_json::json!({
+            "connection": entry.name,
+            "url": entry.url_redacted,
+            "captured_at": captured_at,
+            "info": info::parse_sections(&raw_info),
+            "explanations": glossary::explain(&info::parse_fields(&raw_info)),
+            "config": config,
+            "slowlog": parse_slowlog(&slowlog),
+            "clients": summarize_clients(&parse_client_list(&clients)),
+            "dbsize": dbsize,
+            "key_sample": key_sample,
+        });
+
+        let path = export_dir.join(format!(
+            "diagnostics-{}-{}.json",
+            file_safe(&entry.name),
+            captured_at
+        ));
+        let contents = serde_json::to_vec_pretty(&bundle)
+            .map_err(|e| self.err(McpRedisError::Other(e.to_string())))?;
+        let write_err = |e: std::io::Error| {
+            self.err(McpRedisError::Other(format!(
+                "Cannot write {}: {}",
+                path.display(),
+                e
+            )))
+        };
+        tokio::fs::create_dir_all(export_dir)
+            .await
+            .map_err(write_err)?;
+        tokio::fs::write(&path, &contents)
+            .await
+            .map_err(write_err)?;
+
+        Ok(json_result(serde_json::json!({
+            "path": path,
+            "bytes": contents.len(),
+            "captured_at": captured_at,
+        })))
+    }
+
+    /// The transcript of `session` (`None` over stdio) as JSON or markdown,
+    /// or written to the export directory.
+    pub async fn do_export_session(
+        &self,
+        params: ExportSessionParams,
+        session: Option<&str>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let transcripts = self.transcripts.as_ref().ok_or_else(|| {
+            self.err(McpRedisError::Other(
+                "export_session requires --record-session".to_string(),
+            ))
+        })?;
+        let recorded = transcripts.get(session).unwrap_or_default();
+        let format = params.format.unwrap_or_default();
+        let rendered = match format {
+            TranscriptFormat::Json => recorded.to_json(session),
+            TranscriptFormat::Markdown => serde_json::json!({
+                "session": session,
+                "count": recorded.calls.len(),
+                "markdown": recorded.to_markdown(session),
+            }),
+        };
+        if !params.write.unwrap_or(false) {
+            return Ok(json_result(rendered));
+        }
+
+        let export_dir = self.export_dir.as_ref().ok_or_else(|| {
+            self.err(McpRedisError::Other(
+                "Writing a transcript requires --export-dir".to_string(),
+            ))
+        })?;
+        let exported_at = time::now_ms() / 1000;
+        let (extension, contents) = match format {
+            TranscriptFormat::Json => (
+                "json",
+                serde_json::to_vec_pretty(&rendered)
+                    .map_err(|e| self.err(McpRedisError::Other(e.to_string())))?,
+            ),
+            TranscriptFormat::Markdown => ("md", recorded.to_markdown(session).into_bytes()),
+        };
+        let path = export_dir.join(format!(
+            "session-{}-{}.{}",
+            file_safe(session.unwrap_or("stdio")),
+            exported_at,
+            extension
+        ));
+        let write_err = |e: std::io::Error| {
+            self.err(McpRedisError::Other(format!(
+                "Cannot write {}: {}",
+                path.display(),
+                e
+            )))
+        };
+        tokio::fs::create_dir_all(export_dir)
+            .await
+            .map_err(write_err)?;
+        tokio::fs::write(&path, &contents)
+            .await
+            .map_err(write_err)?;
+
+        Ok(json_result(serde_json::json!({
+            "path": path,
+            "bytes": contents.len(),
+            "count": recorded.calls.len(),
+            "dropped_calls": recorded.dropped,
+        })))
+    }
+
+    pub async fn do_subscribe_sample(
+        &self,
+        params: SubscribeSampleParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+
+        let channels = params.channels;
+        if channels.is_empty() {
+            return Err(self.err(McpRedisError::Other(
+                "At least one channel is required".to_string(),
+            )));
+        }
+        let is_pattern = params.pattern.unwrap_or(false);
+        if is_pattern {
+            for pattern in &channels {
+                Self::validate_pattern(pattern).map_err(|e| self.err(e))?;
+            }
+        }
+
+        let duration = Duration::from_secs(
+            params
+                .duration_secs
+                .unwrap_or(5)
+                .min(self.limits().max_subscribe_secs),
+        );
+        let max_messages = params
+            .max_messages
+            .unwrap_or(100)
+            .min(self.limits().max_subscribe_messages);
+
+        // SUBSCRIBE puts a connection into PubSub mode, so it needs its own
+        // connection rather than the shared ConnectionManager.
+        let mut pubsub = entry
+            .client
+            .get_async_pubsub()
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        for channel in &channels {
+            if is_pattern {
+                pubsub.psubscribe(channel).await
+            } else {
+                pubsub.subscribe(channel).await
+            }
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        }
+
+        let started = std::time::Instant::now();
+        let mut messages: Vec<serde_json::Value> = Vec::new();
+        {
+            let mut stream = pubsub.on_message();
+            let deadline = tokio::time::sleep(duration);
+            tokio::pin!(deadline);
+            let stopping = self.shutdown.stopping();
+            tokio::pin!(stopping);
+
+            while messages.len() < max_messages {
+                tokio::select! {
+                    _ = &mut deadline => break,
+                    _ = &mut stopping => break,
+                    msg = stream.next() => {
+                        let Some(msg) = msg else { break };
+                        let mut item = serde_json::json!({
+                            "channel": msg.get_channel_name(),
+                            "payload": String::from_utf8_lossy(msg.get_payload_bytes()),
+                        });
+                        if msg.from_pattern() {
+                            if let Ok(p) = msg.get_pattern::<String>() {
+                                item["pattern"] = serde_json::Value::String(p);
+                            }
+                        }
+                        messages.push(item);
+                    }
+                }
+            }
+        }
+
+        Ok(json_result(serde_json::json!({
+            "channels": channels,
+            "pattern": is_pattern,
+            "messages": messages,
+            "count": messages.len(),
+            "limit_reached": messages.len() >= max_messages,
+            "elapsed_ms": started.elapsed().as_millis() as u64,
+        })))
+    }
+
+    pub async fn do_monitor_sample(
+        &self,
+        params: MonitorSampleParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let limits = self.limits();
+        let aggregate = params.aggregate.unwrap_or(true);
+        let duration = Duration::from_secs(
+            params
+                .duration_secs
+                .unwrap_or(2)
+                .min(limits.max_monitor_secs),
+        );
+        let max_commands = params
+            .max_commands
+            .unwrap_or(if aggregate { 10_000 } else { 100 })
+            .min(limits.max_monitor_commands);
+
+        // MONITOR takes over the connection it runs on, like SUBSCRIBE
+        let mut monitor = entry
+            .client
+            .get_async_monitor()
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        monitor
+            .monitor()
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let started = Instant::now();
+        let mut summary = monitor::Aggregate::new();
+        let mut lines: Vec<String> = Vec::new();
+        let mut seen = 0;
+        {
+            let mut stream = monitor.on_message::<String>();
+            let deadline = tokio::time::sleep(duration);
+            tokio::pin!(deadline);
+            let stopping = self.shutdown.stopping();
+            tokio::pin!(stopping);
+
+            while seen < max_commands {
+                tokio::select! {
+                    _ = &mut deadline => break,
+                    _ = &mut stopping => break,
+                    line = stream.next() => {
+                        let Some(line) = line else { break };
+                        seen += 1;
+                        if aggregate {
+                            if let Some(parsed) = monitor::parse_line(&line) {
+                                summary.add(&parsed);
+                            }
+                        } else {
+                            lines.push(line);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut result = if aggregate {
+            summary.to_json(params.top.unwrap_or(monitor::DEFAULT_TOP))
+        } else {
+            serde_json::json!({ "lines": lines, "count": lines.len() })
+        };
+        result["limit_reached"] = serde_json::json!(seen >= max_commands);
+        result["elapsed_ms"] = serde_json::json!(started.elapsed().as_millis() as u64);
+        Ok(json_result(result))
+    }
+
+    pub async fn do_sentinel_masters(
+        &self,
+        params: ConnectionParam,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let sentinel = self
+            .sentinels
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&entry.name)
+            .cloned();
+        let Some(sentinel) = sentinel else {
+            return Err(self.err(McpRedisError::Other(format!(
+                "Connection '{}' was not opened through Sentinel (use a redis+sentinel:// URL)",
+                entry.name
+            ))));
+        };
+        let mut conn = sentinel
+            .connect_sentinel()
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let raw: redis::Value = redis::cmd("SENTINEL")
+            .arg("MASTERS")
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let mut masters = crate::sentinel::parse_entries(&raw);
+        for master in masters.iter_mut() {
+            let Some(name) = master["name"].as_str().map(str::to_string) else {
+                continue;
+            };
+            let raw: redis::Value = redis::cmd("SENTINEL")
+                .arg("REPLICAS")
+                .arg(&name)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+            master["replicas"] = serde_json::json!(crate::sentinel::parse_entries(&raw));
+        }
+
+        Ok(json_result(serde_json::json!({
+            "connection_master": entry.client.get_connection_info().addr.to_string(),
+            "monitored_master": sentinel.master_name,
+            "masters": masters,
+            "count": masters.len(),
+        })))
+    }
+
+    pub async fn do_stream_range(
+        &self,
+        params: StreamRangeParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let now = time::now_ms();
+        let start = stream::range_bound(params.start.as_deref().unwrap_or("-"), now)
+            .map_err(|e| self.err(McpRedisError::Other(e)))?;
+        let end = stream::range_bound(params.end.as_deref().unwrap_or("+"), now)
+            .map_err(|e| self.err(McpRedisError::Other(e)))?;
+        let count = params
+            .count
+            .unwrap_or(20)
+            .clamp(1, self.limits().max_stream_entries);
+        let reverse = params.reverse.unwrap_or(false);
+
+        let key_type: String = redis::cmd("TYPE")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        match key_type.as_str() {
+            "stream" => {}
+            "none" => {
+                return Ok(json_result(
+                    serde_json::json!({"error": "Key does not exist", "key": params.key}),
+                ))
+            }
+            other => {
+                return Ok(json_result(
+                    serde_json::json!({"error": format!("Key is type '{}', not a stream", other), "key": params.key}),
+                ))
+            }
+        }
+
+        // XREVRANGE takes the bounds end first
+        let mut cmd = if reverse {
+            let mut cmd = redis::cmd("XREVRANGE");
+            cmd.arg(&params.key).arg(&end).arg(&start);
+            cmd
+        } else {
+            let mut cmd = redis::cmd("XRANGE");
+            cmd.arg(&params.key).arg(&start).arg(&end);
+            cmd
+        };
+        let reply: redis::Value = cmd
+            .arg("COUNT")
+            .arg(count)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let entries: Vec<serde_json::Value> = match &reply {
+            redis::Value::Array(items) => items
+                .iter()
+                .filter_map(|item| match item {
+                    redis::Value::Array(parts) if parts.len() == 2 => {
+                        Some(stream_entry(&parts[0], &parts[1]))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let length: u64 = redis::cmd("XLEN")
+            .arg(&params.key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "start": start,
+            "end": end,
+            "reverse": reverse,
+            "entries": entries,
+            "count": entries.len(),
+            "length": length,
+        })))
+    }
+
+    pub async fn do_stream_autoclaim(
+        &self,
+        params: AutoclaimParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        self.check_read_only(&entry, "stream_autoclaim")
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let start = match params.start.as_deref() {
+            Some(raw) => stream::range_bound(raw, time::now_ms())
+                .map_err(|e| self.err(McpRedisError::Other(e)))?,
+            None => "0-0".to_string(),
+        };
+        let count = params
+            .count
+            .unwrap_or(10)
+            .clamp(1, self.limits().max_autoclaim_count);
+
+        let reply: Vec<redis::Value> = redis::cmd("XAUTOCLAIM")
+            .arg(&params.key)
+            .arg(&params.group)
+            .arg(&params.consumer)
+            .arg(params.min_idle_ms)
+            .arg(&start)
+            .arg("COUNT")
+            .arg(count)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+
+        let next_start = reply.first().map(resp::to_json);
+        // Entries are [id, [field, value, ...]]; the fields are nil when the
+        // entry was deleted from the stream (Redis 6.2).
+        let claimed: Vec<serde_json::Value> = match reply.get(1) {
+            Some(redis::Value::Array(entries)) => entries
+                .iter()
+                .filter_map(|e| match e {
+                    redis::Value::Array(parts) if parts.len() == 2 => {
+                        Some(stream_entry(&parts[0], &parts[1]))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        // Redis 7+ also reports pending IDs whose entries no longer exist
+        let deleted = reply
+            .get(2)
+            .map(resp::to_json)
+            .unwrap_or(serde_json::json!([]));
+
+        let pending: redis::Value = redis::cmd("XPENDING")
+            .arg(&params.key)
+            .arg(&params.group)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let pending = parse_pending_summary(&pending);
+
+        // Read back who owns each claimed entry: another consumer claiming or
+        // acknowledging it in the meantime shows up as a mismatch
+        let ids: Vec<&str> = claimed.iter().filter_map(|c| c["id"].as_str()).collect();
+        let mut verification = verify::Verification::new(format!(
+            "claimed entries pending for consumer '{}'",
+            params.consumer
+        ));
+        let checked = &ids[..ids.len().min(verify::MAX_VERIFY_ITEMS)];
+        verification.skip(ids.len() - checked.len());
+        if !checked.is_empty() {
+            let mut pipe = redis::pipe();
+            for id in checked {
+                pipe.cmd("XPENDING")
+                    .arg(&params.key)
+                    .arg(&params.group)
+                    .arg(*id)
+                    .arg(*id)
+                    .arg(1);
+            }
+            let rows: Vec<redis::Value> = pipe
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+            for (id, row) in checked.iter().zip(rows) {
+                let row = resp::to_json(&row);
+                let owner = row[0][1].as_str().map(str::to_string);
+                let matches = owner.as_deref() == Some(params.consumer.as_str());
+                verification.observe(
+                    serde_json::json!({
+                        "id": id,
+                        "owner": owner,
+                        "idle_ms": row[0][2],
+                        "deliveries": row[0][3],
+                    }),
+                    matches,
+                );
+            }
+        }
+
+        Ok(json_result(serde_json::json!({
+            "key": params.key,
+            "group": params.group,
+            "consumer": params.consumer,
+            "claimed": claimed,
+            "claimed_count": claimed.len(),
+            "deleted_ids": deleted,
+            "next_start": next_start,
+            "scan_complete": next_start == Some(serde_json::json!("0-0")),
+            "pending": pending,
+            "verification": verification.to_json(),
+        })))
+    }
+
+    pub async fn do_trigger_bgsave(
+        &self,
+        params: BgsaveParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let command: &[&str] = if params.schedule.unwrap_or(false) {
+            &["BGSAVE", "SCHEDULE"]
+        } else {
+            &["BGSAVE"]
+        };
+        self.trigger_persistence(
+            params.connection.as_deref(),
+            "trigger_bgsave",
+            command,
+            ("rdb_bgsave_in_progress", "rdb_last_bgsave_status"),
+        )
+        .await
+    }
+
+    pub async fn do_trigger_aof_rewrite(
+        &self,
+        params: ConnectionParam,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.trigger_persistence(
+            params.connection.as_deref(),
+            "trigger_aof_rewrite",
+            &["BGREWRITEAOF"],
+            ("aof_rewrite_in_progress", "aof_last_bgrewrite_status"),
+        )
+        .await
+    }
+
+    /// Send BGSAVE or BGREWRITEAOF and report whether the server took it.
+    /// A refusal (a save or rewrite already running, the command renamed
+    /// away) is a result rather than an error, so the caller can wait and
+    /// retry. `fields` are the INFO persistence fields to poll afterwards:
+    /// the in-progress flag and the last status.
+    async fn trigger_persistence(
+        &self,
+        connection: Option<&str>,
+        tool: &str,
+        command: &[&str],
+        fields: (&str, &str),
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self.resolve(connection).map_err(|e| self.err(e))?;
+        self.check_read_only(&entry, tool)
+            .map_err(|e| self.err(e))?;
+        let mut conn = entry.connection();
+
+        let mut cmd = redis::cmd(command[0]);
+        cmd.arg(&command[1..]);
+        let (accepted, message) = match cmd.query_async::<String>(&mut conn).await {
+            Ok(status) => (true, status),
+            Err(e) if e.is_io_error() || e.is_timeout() || e.is_connection_dropped() => {
+                return Err(self.err(McpRedisError::Redis(e)));
+            }
+            Err(e) => (
+                false,
+                e.detail().map_or_else(|| e.to_string(), str::to_string),
+            ),
+        };
+
+        // Read after the command so the in-progress flags include this run
+        let persistence = redis::cmd("INFO")
+            .arg("persistence")
+            .query_async::<String>(&mut conn)
+            .await
+            .map(|raw| info::parse_fields(&raw))
+            .unwrap_or_default();
+        let field = |name: &str| {
+            persistence
+                .get(name)
+                .cloned()
+                .unwrap_or(serde_json::Value::Null)
+        };
+        let (in_progress, last_status) = fields;
+        let mut result = serde_json::json!({
+            "connection": entry.name,
+            "command": command.join(" "),
+            "accepted": accepted,
+            "scheduled": accepted && message.contains("scheduled"),
+            "message": message,
+            "persistence": {
+                "rdb_bgsave_in_progress": field("rdb_bgsave_in_progress"),
+                "rdb_last_bgsave_status": field("rdb_last_bgsave_status"),
+                "rdb_last_save_time": field("rdb_last_save_time"),
+                "rdb_changes_since_last_save": field("rdb_changes_since_last_save"),
+                "aof_enabled": field("aof_enabled"),
+                "aof_rewrite_in_progress": field("aof_rewrite_in_progress"),
+                "aof_rewrite_scheduled": field("aof_rewrite_scheduled"),
+                "aof_last_bgrewrite_status": field("aof_last_bgrewrite_status"),
+            },
+        });
+        result["next"] = serde_json::json!(if accepted {
+            format!(
+                "Poll info with section 'persistence' until {} is 0, then check {}",
+                in_progress, last_status
+            )
+        } else if command[0] == "BGSAVE" && message.contains("rewrite") {
+            "An AOF rewrite is running; call again with schedule: true to save when it finishes"
+                .to_string()
+        } else {
+            format!(
+                "Not started; poll info with section 'persistence' until {} is 0 and call again",
+                in_progress
+            )
+        });
+        Ok(json_result(result))
+    }
+    /// `progress` receives a notification after every UNLINK batch when the
+    /// client supplied a progress token.
+    pub async fn do_bulk_delete(
+        &self,
+        params: BulkDeleteParams,
+        progress: Option<ProgressReporter>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let targets = self
+            .delete_targets(params.connection.as_deref(), params.group.as_deref())
+            .map_err(|e| self.err(e))?;
+        for entry in &targets {
+            self.check_read_only(entry, "bulk_delete")
+                .map_err(|e| self.err(e))?;
+        }
+        Self::validate_pattern(&params.pattern).map_err(|e| self.err(e))?;
+        if params.pattern.is_empty() {
+            return Err(self.err(McpRedisError::Other(
+                "Pattern must not be empty".to_string(),
+            )));
+        }
+        let names: Vec<String> = targets.iter().map(|e| e.name.clone()).collect();
+
+        let Some(token) = params.confirm_token else {
+            let counts = futures_util::future::join_all(
+                targets
+                    .iter()
+                    .map(|entry| self.count_matching(entry, &params.pattern)),
+            )
+            .await;
+            let mut planned = Vec::new();
+            let mut per_connection = Vec::new();
+            for (name, count) in names.iter().zip(counts) {
+                let (matches, complete, sample) = count.map_err(|e| self.err(e))?;
+                planned.push((name.clone(), matches));
+                per_connection.push(serde_json::json!({
+                    "connection": name,
+                    "matches": matches,
+                    "count_complete": complete,
+                    "sample": sample,
+                }));
+            }
+            let total: u64 = planned.iter().map(|(_, n)| n).sum();
+
+            let token = format!("{:016x}", fastrand::u64(..));
+            {
+                let mut pending = self
+                    .pending_deletes
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner());
+                pending.retain(|_, plan| plan.created.elapsed() < DELETE_TOKEN_TTL);
+                pending.insert(
+                    token.clone(),
+                    DeletePlan {
+                        pattern: params.pattern.clone(),
+                        matches: planned,
+                        created: Instant::now(),
+                    },
+                );
+            }
+
+            return Ok(json_result(serde_json::json!({
+                "phase": "plan",
+                "pattern": params.pattern,
+                "connections": per_connection,
+                "total_matches": total,
+                "confirm_token": token,
+                "expires_in_secs": DELETE_TOKEN_TTL.as_secs(),
+                "next": "Call bulk_delete again with the same pattern and target plus confirm_token to delete these keys",
+            })));
+        };
+
+        // Tokens are single-use: a failed check still consumes it.
+        let plan = self
+            .pending_deletes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&token);
+        let plan =
+            match plan {
+                Some(plan) if plan.created.elapsed() < DELETE_TOKEN_TTL => plan,
+                _ => return Err(self.err(McpRedisError::Other(
+                    "Unknown or expired confirm_token; call bulk_delete without it to plan again"
+                        .to_string(),
+                ))),
+            };
+        if plan.pattern != params.pattern
+            || !plan.matches.iter().map(|(name, _)| name).eq(names.iter())
+        {
+            return Err(self.err(McpRedisError::Other(
+                "confirm_token was issued for a different pattern or target; call bulk_delete without it to plan again"
+                    .to_string(),
+            )));
+        }
+
+        let batch_size = params
+            .batch_size
+            .unwrap_or(500)
+            .clamp(1, self.limits().max_delete_batch);
+        let planned: Vec<u64> = plan.matches.iter().map(|(_, n)| *n).collect();
+        let total: u64 = planned.iter().sum();
+
+        let deleted = AtomicU64::new(0);
+        let outcomes = futures_util::future::join_all(targets.iter().map(|entry| {
+            self.unlink_matching(
+                entry,
+                &params.pattern,
+                batch_size,
+                progress.as_ref(),
+                &deleted,
+                total,
+            )
+        }))
+        .await;
+
+        let mut complete = true;
+        let mut report = Vec::new();
+        let mut verification =
+            verify::Verification::new(format!("no keys match '{}'", params.pattern));
+        for ((entry, (unlinked, errors)), planned) in targets.iter().zip(outcomes).zip(planned) {
+            let remaining = match self.count_matching(entry, &params.pattern).await {
+                Ok((n, _, _)) => serde_json::json!(n),
+                Err(e) => serde_json::json!({ "error": e.to_string() }),
+            };
+            verification.observe(
+                serde_json::json!({ "connection": entry.name, "remaining": remaining }),
+                remaining == serde_json::json!(0),
+            );
+            if remaining != serde_json::json!(0) || !errors.is_empty() {
+                complete = false;
+            }
+            report.push(serde_json::json!({
+                "connection": entry.name,
+                "planned": planned,
+                "unlinked": unlinked,
+                "remaining": remaining,
+                "errors": errors,
+            }));
+        }
+
+        Ok(json_result(serde_json::json!({
+            "phase": "execute",
+            "pattern": params.pattern,
+            "status": if complete { "complete" } else { "partial" },
+            "batch_size": batch_size,
+            "total_unlinked": deleted.load(Ordering::Relaxed),
+            "connections": report,
+            "verification": verification.to_json(),
+        })))
+    }
+
+    pub async fn do_reload_config(&self) -> Result<CallToolResult, ErrorData> {
+        self.check_admin("reload_config").map_err(|e| self.err(e))?;
+        let summary = self.reload().await.map_err(|e| self.err(e))?;
+        Ok(json_result(summary))
+    }
+
+    pub async fn do_set_maintenance_note(
+        &self,
+        params: MaintenanceNoteParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.check_admin("set_maintenance_note")
+            .map_err(|e| self.err(e))?;
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let now = restart::now();
+        let note = params
+            .note
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+
+        let Some(message) = note else {
+            let previous = self.maintenance.clear(&entry.name, now);
+            return Ok(json_result(serde_json::json!({
+                "connection": entry.name,
+                "status": if previous.is_some() { "cleared" } else { "no_note" },
+                "previous": previous.map(|p| p.message),
+            })));
+        };
+        let max = self.limits().max_maintenance_secs;
+        let ttl = params.ttl_secs.unwrap_or(3600).clamp(1, max.max(1));
+        let previous = self.maintenance.set(&entry.name, message, ttl, now);
+        let mut result = serde_json::json!({
+            "connection": entry.name,
+            "status": "set",
+            "ttl_secs": ttl,
+            "previous": previous.map(|p| p.message),
+        });
+        if params.ttl_secs.is_some_and(|requested| requested > ttl) {
+            result["capped"] =
+                serde_json::json!(format!("ttl_secs capped to max_maintenance_secs ({})", max));
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_save_cursor(
+        &self,
+        params: SaveCursorParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let name = params.name.trim().to_string();
+        if let Some(reason) = cursors::invalid_name(&name) {
+            return Err(self.err(McpRedisError::Other(reason)));
+        }
+        let persist = params.persist.unwrap_or(false);
+        if persist {
+            self.check_read_only(&entry, "save_cursor with persist")
+                .map_err(|e| self.err(e))?;
+        }
+        let key = format!("{}{}", cursors::KEY_PREFIX, name);
+        let mut conn = entry.connection();
+        let now = restart::now();
+
+        let cursor = params
+            .cursor
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty() && c != "0");
+        let Some(cursor) = cursor else {
+            let previous = self.cursors.remove(&name, now);
+            let mut removed_key = false;
+            if persist {
+                let deleted: i64 = redis::cmd("DEL")
+                    .arg(&key)
+                    .query_async(&mut conn)
+                    .await
+                    .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+                removed_key = deleted > 0;
+            }
+            return Ok(json_result(serde_json::json!({
+                "name": name,
+                "status": if previous.is_some() || removed_key { "removed" } else { "no_cursor" },
+            })));
+        };
+        let Some(tool) = params.tool.filter(|t| cursors::TOOLS.contains(&t.as_str())) else {
+            return Err(self.err(McpRedisError::Other(format!(
+                "'tool' must be one of: {}",
+                cursors::TOOLS.join(", ")
+            ))));
+        };
+        let mut arguments = params.arguments.unwrap_or_default();
+        arguments.remove("cursor");
+        arguments.remove("connection");
+
+        let max = self.limits().max_cursor_ttl_secs;
+        let ttl = params.ttl_secs.unwrap_or(86400).clamp(1, max.max(1));
+        let saved = SavedCursor {
+            tool,
+            connection: entry.name.clone(),
+            arguments,
+            cursor,
+            note: params.note.filter(|n| !n.trim().is_empty()),
+            saved_at: now,
+            expires_at: now.saturating_add(ttl),
+        };
+        if persist {
+            let value = serde_json::to_string(&saved)
+                .map_err(|e| self.err(McpRedisError::Other(e.to_string())))?;
+            redis::cmd("SET")
+                .arg(&key)
+                .arg(value)
+                .arg("EX")
+                .arg(ttl)
+                .query_async::<()>(&mut conn)
+                .await
+                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        }
+        let previous = self.cursors.save(&name, saved.clone(), now);
+        let mut result = saved.to_json(&name, now);
+        result["status"] = serde_json::json!(if previous.is_some() {
+            "replaced"
+        } else {
+            "saved"
+        });
+        if persist {
+            result["key"] = serde_json::json!(key);
+        }
+        if params.ttl_secs.is_some_and(|requested| requested > ttl) {
+            result["capped"] =
+                serde_json::json!(format!("ttl_secs capped to max_cursor_ttl_secs ({})", max));
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_list_cursors(
+        &self,
+        params: ListCursorsParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        let now = restart::now();
+        let wanted = params.name.as_deref().map(str::trim);
+        let mut found: Vec<(String, SavedCursor, bool)> = self
+            .cursors
+            .list(now)
+            .into_iter()
+            .filter(|(name, _)| wanted.map_or(true, |w| w == name))
+            .map(|(name, cursor)| (name, cursor, false))
+            .collect();
+
+        // Persisted cursors saved by this or another server; fail-soft, as
+        // the in-memory ones do not need Redis
+        let mut persisted_error = None;
+        if let Ok(entry) = self.resolve(params.connection.as_deref()) {
+            let keys = match wanted {
+                Some(name) => Ok(vec![format!("{}{}", cursors::KEY_PREFIX, name)]),
+                None => self
+                    .scan_across(
+                        &entry,
+                        &format!("{}*", cursors::KEY_PREFIX),
+                        cursors::MAX_CURSORS,
+                    )
+                    .await
+                    .map(|shards| shards.into_iter().flat_map(|s| s.outcome.keys).collect()),
+            };
+            let values: Result<Vec<(String, Option<String>)>, McpRedisError> = match keys {
+                Ok(keys) if !keys.is_empty() => {
+                    let mut conn = entry.connection();
+                    redis::cmd("MGET")
+                        .arg(&keys)
+                        .query_async::<Vec<Option<String>>>(&mut conn)
+                        .await
+                        .map(|values| keys.into_iter().zip(values).collect())
+                        .map_err(McpRedisError::Redis)
+                }
+                Ok(_) => Ok(Vec::new()),
+                Err(e) => Err(e),
+            };
+            match values {
+                Ok(values) => {
+                    for (key, value) in values {
+                        let name = key[cursors::KEY_PREFIX.len()..].to_string();
+                        let Some(cursor) =
+                            value.and_then(|v| serde_json::from_str::<SavedCursor>(&v).ok())
+                        else {
+                            continue;
+                        };
+                        match found.iter_mut().find(|(n, _, _)| *n == name) {
+                            Some(existing) if existing.1 == cursor => existing.2 = true,
+                            // The in-memory cursor was saved later
+                            Some(_) => {}
+                            None => found.push((name, cursor, true)),
+                        }
+                    }
+                }
+                Err(e) => persisted_error = Some(e.to_string()),
+            }
+        }
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut result = serde_json::json!({
+            "cursors": found
+                .iter()
+                .map(|(name, cursor, persisted)| {
+                    let mut item = cursor.to_json(name, now);
+                    item["persisted"] = serde_json::json!(persisted);
+                    item
+                })
+                .collect::<Vec<_>>(),
+            "count": found.len(),
+        });
+        if let Some(error) = persisted_error {
+            result["persisted_error"] = serde_json::json!(error);
+        }
+        Ok(json_result(result))
+    }
+
+    pub async fn do_replace_connection(
+        &self,
+        params: ReplaceConnectionParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.check_admin("replace_connection")
+            .map_err(|e| self.err(e))?;
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let sentinel_managed = self
+            .sentinels
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(&entry.name);
+        if sentinel_managed {
+            return Err(self.err(McpRedisError::Other(format!(
+                "Connection '{}' follows Sentinel failovers by itself; change its URL in the config file and reload instead",
+                entry.name
+            ))));
+        }
+
+        // Open and check the new endpoint before anything is swapped
+        let started = Instant::now();
+        let client = redis::Client::open(params.url.as_str())
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let conn = tokio::time::timeout(
+            LAZY_CONNECT_TIMEOUT,
+            redis::aio::ConnectionManager::new(client.clone()),
+        )
+        .await
+        .map_err(|_| {
+            self.err(McpRedisError::Other(format!(
+                "Timed out connecting to {}",
+                redact::url(&params.url)
+            )))
+        })?
+        .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let mut probe = conn.clone();
+        let (pong, replication): (String, String) = redis::pipe()
+            .cmd("PING")
+            .cmd("INFO")
+            .arg("replication")
+            .query_async(&mut probe)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let replication = info::parse_fields(&replication);
+        let role = replication
+            .get("role")
+            .and_then(|r| r.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        if role == "slave" && !entry.read_only && !params.allow_replica.unwrap_or(false) {
+            return Err(self.err(McpRedisError::Other(format!(
+                "{} is a replica and '{}' is writable; pass allow_replica to use it anyway",
+                redact::url(&params.url),
+                entry.name
+            ))));
+        }
+        let detected = version::detect(&mut probe).await.ok();
+        let identity = restart::detect(&mut probe).await;
+        let health_ms = started.elapsed().as_millis() as u64;
+
+        let url_redacted = redact::url(&params.url);
+        let previous = {
+            let mut connections = self.connections.write().unwrap_or_else(|e| e.into_inner());
+            let Some(stored) = connections.iter_mut().find(|c| c.name == entry.name) else {
+                return Err(self.err(McpRedisError::ConnectionNotFound(entry.name)));
+            };
+            stored.conn = conn;
+            stored.client = client;
+            stored.version = detected.clone();
+            stored.identity = identity;
+            stored.restart = None;
+            stored.replaced += 1;
+            std::mem::replace(&mut stored.url_redacted, url_redacted.clone())
+        };
+        let prefix = format!("{}/", entry.name);
+        self.node_conns
+            .lock()
+            .await
+            .retain(|key, _| !key.starts_with(&prefix));
+
+        Ok(json_result(serde_json::json!({
+            "connection": entry.name,
+            "previous_url": previous,
+            "url": url_redacted,
+            "ping": pong,
+            "role": role,
+            "version": detected.map(|v| v.version),
+            "health_check_ms": health_ms,
+            "draining": "Calls already running finish on the previous connection, which closes after them",
+        })))
+    }
+
+    pub async fn do_create_analysis_replica(
+        &self,
+        params: AnalysisReplicaParams,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.check_admin("create_analysis_replica")
+            .map_err(|e| self.err(e))?;
+        let Some(hook) = &self.replica_hook else {
+            return Err(self.err(McpRedisError::Other(
+                "No [analysis_replica] hook is configured in the --config file".to_string(),
+            )));
+        };
+        let entry = self
+            .resolve(params.connection.as_deref())
+            .map_err(|e| self.err(e))?;
+        let name = params
+            .name
+            .unwrap_or_else(|| format!("{}-analysis", entry.name));
+        if self.connections().iter().any(|c| c.name == name) {
+            return Err(self.err(McpRedisError::Other(format!(
+                "Connection '{}' already exists",
+                name
+            ))));
+        }
+
+        let started = Instant::now();
+        let mut command = tokio::process::Command::new(&hook.command[0]);
+        command
+            .args(&hook.command[1..])
+            .env("MCP_REDIS_SOURCE_CONNECTION", &entry.name)
+            .env(
+                "MCP_REDIS_SOURCE_ADDR",
+                entry.client.get_connection_info().addr.to_string(),
+            )
+            .env("MCP_REDIS_REPLICA_NAME", &name)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true);
+        let output = tokio::time::timeout(Duration::from_secs(hook.timeout_secs), command.output())
+            .await
+            .map_err(|_| {
+                self.err(McpRedisError::Other(format!(
+                    "analysis_replica hook timed out after {}s",
+                    hook.timeout_secs
+                )))
+            })?
+            .map_err(|e| {
+                self.err(McpRedisError::Other(format!(
+                    "Cannot run analysis_replica hook: {}",
+                    e
+                )))
+            })?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(self.err(McpRedisError::Other(format!(
+                "analysis_replica hook failed ({}): {}",
+                output.status,
+                stderr.trim()
+            ))));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let url = stdout
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .ok_or_else(|| {
+                self.err(McpRedisError::Other(
+                    "analysis_replica hook printed no URL".to_string(),
+                ))
+            })?;
+
+        let client = redis::Client::open(url).map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let conn = redis::aio::ConnectionManager::new(client.clone())
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let mut probe = conn.clone();
+        let raw: String = redis::cmd("INFO")
+            .arg("replication")
+            .query_async(&mut probe)
+            .await
+            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+        let replication = info::parse_fields(&raw);
+        let detected = version::detect(&mut probe).await.ok();
+
+        let url_redacted = redact::url(url);
+        let mut replica = RedisConnection::new(name.clone(), url_redacted.clone(), conn, client);
+        replica.version = detected.clone();
+        replica.identity = restart::detect(&mut probe).await;
+        replica.read_only = true;
+        replica.tags = vec!["analysis-replica".to_string()];
+        self.add_connection(replica).map_err(|e| self.err(e))?;
+
+        Ok(json_result(serde_json::json!({
+            "connection": name,
+            "source": entry.name,
+            "url": url_redacted,
+            "read_only": true,
+            "role": replication.get("role"),
+            "master_link_status": replication.get("master_link_status"),
+            "version": detected.map(|v| v.version),
+            "provision_ms": started.elapsed().as_millis() as u64,
+        })))
+    }
+}
+
+/// `name` with everything but ASCII letters, digits and `-` replaced by `_`,
+/// for use in a file name.
+fn file_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// The text of `result`'s text contents, one per line.
+fn result_text(result: &CallToolResult) -> String {
+    result
+        .content
+        .iter()
+        .filter_map(|c| c.as_text())
+        .map(|t| t.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The streamable HTTP session a request belongs to; `None` over stdio.
+fn session_id(context: &RequestContext<RoleServer>) -> Option<String> {
+    context
+        .extensions
+        .get::<axum::http::request::Parts>()
+        .and_then(|parts| parts.headers.get("mcp-session-id"))
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+// -- Tool results --
+
+/// Result carrying `value` both as pretty-printed text and as structured
+/// content, so clients can use either.
+fn json_result(value: serde_json::Value) -> CallToolResult {
+    let text = serde_json::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string());
+    let mut result = CallToolResult::success(vec![Content::text(text)]);
+    result.structured_content = Some(value);
+    result
+}
+
+/// [`json_result`] with the text rendered in `format` (see
+/// [`format::render`]).
+fn formatted_result(
+    value: serde_json::Value,
+    format: Option<format::Format>,
+    rows: &str,
+    columns: &[&str],
+) -> CallToolResult {
+    let text = format::render(&value, format.unwrap_or_default(), rows, columns);
+    let mut result = CallToolResult::success(vec![Content::text(text)]);
+    result.structured_content = Some(value);
+    result
+}
+
+/// Append `extra` (an object such as `{"trace": ...}`) to a result as its
+/// own text block, and merge its fields into the structured content.
+fn append_json(result: &mut CallToolResult, extra: serde_json::Value) {
+    let text = serde_json::to_string_pretty(&extra).unwrap_or_else(|_| "{}".to_string());
+    result.content.push(Content::text(text));
+    if let (Some(serde_json::Value::Object(structured)), serde_json::Value::Object(extra)) =
+        (result.structured_content.as_mut(), extra)
+    {
+        structured.extend(extra);
+    }
+}
+
+// -- Reply parsers shared by several tools --
+
+/// Parse the summary form of XPENDING: total count, smallest and greatest
+/// pending IDs, and pending counts per consumer.
+pub fn parse_pending_summary(value: &redis::Value) -> serde_json::Value {
+    let redis::Value::Array(parts) = value else {
+        return serde_json::Value::Null;
+    };
+    let consumers: serde_json::Map<String, serde_json::Value> = match parts.get(3) {
+        Some(redis::Value::Array(rows)) => rows
+            .iter()
+            .filter_map(|row| match resp::to_json(row) {
+                serde_json::Value::Array(pair) if pair.len() == 2 => {
+                    let name = pair[0].as_str()?.to_string();
+                    let count = match &pair[1] {
+                        serde_json::Value::String(s) => info::parse_value(s),
+                        other => other.clone(),
+                    };
+                    Some((name, count))
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => serde_json::Map::new(),
+    };
+    serde_json::json!({
+        "count": parts.first().map(resp::to_json),
+        "min_id": parts.get(1).map(resp::to_json),
+        "max_id": parts.get(2).map(resp::to_json),
+        "consumers": consumers,
+    })
+}
+
+/// A ZRANGEBYLEX bound: `-`, `+`, `[value` and `(value` are passed through;
+/// a bare value becomes an inclusive bound.
+pub fn lex_bound(raw: &str) -> Vec<u8> {
+    if raw == "-" || raw == "+" || raw.starts_with('[') || raw.starts_with('(') {
+        raw.as_bytes().to_vec()
+    } else {
+        format!("[{}", raw).into_bytes()
+    }
+}
+
+/// Bounds covering every member that starts with `prefix`: from `[prefix`
+/// up to `[prefix` followed by 0xFF, which sorts after any continuation
+/// of valid UTF-8.
+pub fn prefix_lex_range(prefix: &str) -> (Vec<u8>, Vec<u8>) {
+    if prefix.is_empty() {
+        return (b"-".to_vec(), b"+".to_vec());
+    }
+    let min = format!("[{}", prefix).into_bytes();
+    let mut max = min.clone();
+    max.push(0xff);
+    (min, max)
+}
+
+/// Nearest-rank percentile `p` (0-100) of ascending `sorted` values.
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Milliseconds rounded to microsecond precision for display.
+fn round_ms(ms: f64) -> f64 {
+    (ms * 1000.0).round() / 1000.0
+}
+
+/// A stream entry as `{"id", "timestamp", "seq", "fields"}`. Fields are nil
+/// for entries deleted from the stream while still pending.
+fn stream_entry(id: &redis::Value, fields: &redis::Value) -> serde_json::Value {
+    let id = resp::to_json(id);
+    let mut entry = match id.as_str() {
+        Some(id) => stream::describe_id(id),
+        None => serde_json::json!({ "id": id }),
+    };
+    entry["fields"] = serde_json::json!(resp::to_object(fields));
+    entry
+}
+
+/// Parse SLOWLOG GET entries (arrays of id, timestamp, duration, args, ...).
+fn parse_slowlog(raw: &[Vec<redis::Value>]) -> Vec<serde_json::Value> {
+    raw.iter()
+        .map(|entry| {
+            let id = match entry.first() {
+                Some(redis::Value::Int(i)) => *i,
+                _ => -1,
+            };
+            let timestamp = match entry.get(1) {
+                Some(redis::Value::Int(i)) => *i,
+                _ => 0,
+            };
+            let duration_us = match entry.get(2) {
+                Some(redis::Value::Int(i)) => *i,
+                _ => 0,
+            };
+            let command = match entry.get(3) {
+                Some(redis::Value::Array(args)) => args
+                    .iter()
+                    .filter_map(|a| match a {
+                        redis::Value::BulkString(s) => String::from_utf8(s.clone()).ok(),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                _ => "unknown".to_string(),
+            };
+
+            serde_json::json!({
+                "id": id,
+                "timestamp": timestamp,
+                "duration_us": duration_us,
+                "command": command,
+            })
+        })
+        .collect()
+}
+
+/// `latency-monitor-threshold` in milliseconds, or `None` when CONFIG GET is
+/// not allowed.
+async fn latency_monitor_threshold(conn: &mut TracedConnection) -> Option<u64> {
+    redis::cmd("CONFIG")
+        .arg("GET")
+        .arg("latency-monitor-threshold")
+        .query_async::<Vec<String>>(conn)
+        .await
+        .ok()
+        .and_then(|reply| reply.get(1).and_then(|v| v.parse::<u64>().ok()))
+}
+
+/// `{key, type}` for each of `keys`, plus `ttl` (seconds, -1 without expiry)
+/// and sampled `memory_bytes` with `metadata`, in one pipeline instead of N+1
+/// calls. When MEMORY USAGE is refused (managed services may disable it)
+/// memory is null; when the pipeline fails, types are "unknown".
+async fn key_metadata(
+    conn: &mut TracedConnection,
+    keys: &[String],
+    metadata: bool,
+) -> Vec<serde_json::Value> {
+    if keys.is_empty() {
+        return Vec::new();
+    }
+    let pipeline = |memory: bool| {
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("TYPE").arg(key);
+            if metadata {
+                pipe.cmd("TTL").arg(key);
+            }
+            if memory {
+                pipe.cmd("MEMORY")
+                    .arg("USAGE")
+                    .arg(key)
+                    .arg("SAMPLES")
+                    .arg(METADATA_MEMORY_SAMPLES);
+            }
+        }
+        pipe
+    };
+    let mut reply: Result<Vec<redis::Value>, _> = pipeline(metadata).query_async(conn).await;
+    let mut memory = metadata;
+    if reply.is_err() && metadata {
+        memory = false;
+        reply = pipeline(false).query_async(conn).await;
+    }
+    let Ok(reply) = reply else {
+        return keys
+            .iter()
+            .map(|key| serde_json::json!({"key": key, "type": "unknown"}))
+            .collect();
+    };
+
+    let width = 1 + metadata as usize + memory as usize;
+    keys.iter()
+        .zip(reply.chunks(width))
+        .map(|(key, parts)| {
+            let mut item = serde_json::json!({"key": key, "type": resp::to_json(&parts[0])});
+            if metadata {
+                item["ttl"] = resp::to_json(&parts[1]);
+                item["memory_bytes"] = parts.get(2).map_or(serde_json::Value::Null, resp::to_json);
+            }
+            item
+        })
+        .collect()
+}
+
+/// DEBUG DIGEST-VALUE of each of `keys`, or `None` when DEBUG is not
+/// allowed on this server.
+async fn debug_digests(conn: &mut TracedConnection, keys: &[String]) -> Option<Vec<String>> {
+    redis::cmd("DEBUG")
+        .arg("DIGEST-VALUE")
+        .arg(keys)
+        .query_async(conn)
+        .await
+        .ok()
+}
+
+/// Each of `keys` as a [`consistency::KeyState`]: type, TTL and length
+/// from one pipeline, then a digest of the value, from DEBUG DIGEST-VALUE
+/// with `server_digests` or else read back when the value holds at most
+/// `max_elements` elements (`max_bytes` for strings).
+async fn read_key_states(
+    conn: &mut TracedConnection,
+    keys: &[String],
+    server_digests: bool,
+    max_elements: u64,
+    max_bytes: u64,
+) -> redis::RedisResult<Vec<consistency::KeyState>> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut pipe = redis::pipe();
+    for key in keys {
+        pipe.cmd("TYPE").arg(key).cmd("PTTL").arg(key);
+    }
+    let reply: Vec<redis::Value> = pipe.query_async(&mut *conn).await?;
+    let mut states: Vec<consistency::KeyState> = reply
+        .chunks(2)
+        .map(|parts| consistency::KeyState {
+            key_type: redis::from_redis_value(&parts[0]).unwrap_or_else(|_| "none".to_string()),
+            volatile: redis::from_redis_value::<i64>(&parts[1]).is_ok_and(|ttl| ttl >= 0),
+            length: None,
+            digest: None,
+        })
+        .collect();
+
+    let length_command = |key_type: &str| match key_type {
+        "string" => Some("STRLEN"),
+        "list" => Some("LLEN"),
+        "set" => Some("SCARD"),
+        "zset" => Some("ZCARD"),
+        "hash" => Some("HLEN"),
+        "stream" => Some("XLEN"),
+        _ => None,
+    };
+    let measured: Vec<(usize, &str)> = (0..keys.len())
+        .filter_map(|i| Some((i, length_command(&states[i].key_type)?)))
+        .collect();
+    if !measured.is_empty() {
+        let mut pipe = redis::pipe();
+        for &(i, command) in &measured {
+            pipe.cmd(command).arg(&keys[i]);
+        }
+        let lengths: Vec<u64> = pipe.query_async(&mut *conn).await?;
+        for (&(i, _), length) in measured.iter().zip(lengths) {
+            states[i].length = Some(length);
+        }
+    }
+
+    let existing: Vec<usize> = (0..keys.len())
+        .filter(|&i| states[i].key_type != "none")
+        .collect();
+    if server_digests {
+        let names: Vec<String> = existing.iter().map(|&i| keys[i].clone()).collect();
+        if let Some(digests) = debug_digests(conn, &names).await {
+            for (&i, digest) in existing.iter().zip(digests) {
+                states[i].digest = Some(digest);
+            }
+        }
+        return Ok(states);
+    }
+
+    let readable: Vec<usize> = measured
+        .into_iter()
+        .map(|(i, _)| i)
+        .filter(|&i| {
+            let limit = if states[i].key_type == "string" {
+                max_bytes
+            } else {
+                max_elements
+            };
+            states[i].length.is_some_and(|length| length <= limit)
+        })
+        .collect();
+    if !readable.is_empty() {
+        let mut pipe = redis::pipe();
+        for &i in &readable {
+            let key = &keys[i];
+            match states[i].key_type.as_str() {
+                "string" => pipe.cmd("GET").arg(key),
+                "list" => pipe.cmd("LRANGE").arg(key).arg(0).arg(-1),
+                "set" => pipe.cmd("SMEMBERS").arg(key),
+                "zset" => pipe.cmd("ZRANGE").arg(key).arg(0).arg(-1).arg("WITHSCORES"),
+                "hash" => pipe.cmd("HGETALL").arg(key),
+                _ => pipe.cmd("XRANGE").arg(key).arg("-").arg("+"),
+            };
+        }
+        let values: Vec<redis::Value> = pipe.query_async(&mut *conn).await?;
+        for (&i, value) in readable.iter().zip(&values) {
+            states[i].digest = Some(consistency::value_digest(&states[i].key_type, value));
+        }
+    }
+    Ok(states)
+}
+
+/// Aggregate parsed CLIENT LIST entries: totals, top names and commands,
+/// idle and blocked clients, and output buffer memory.
+fn summarize_clients(clients: &[serde_json::Value]) -> serde_json::Value {
+    let field = |c: &serde_json::Value, name: &str| c[name].as_str().unwrap_or("").to_string();
+    let number = |c: &serde_json::Value, name: &str| {
+        c[name]
+            .as_str()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+
+    let mut by_name: HashMap<String, u64> = HashMap::new();
+    let mut by_command: HashMap<String, u64> = HashMap::new();
+    for client in clients {
+        *by_name.entry(field(client, "name")).or_default() += 1;
+        *by_command.entry(field(client, "cmd")).or_default() += 1;
+    }
+    let top = |counts: HashMap<String, u64>| {
+        let mut counts: Vec<(String, u64)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts.truncate(10);
+        counts
+            .into_iter()
+            .map(|(name, count)| serde_json::json!({"value": name, "count": count}))
+            .collect::<Vec<_>>()
+    };
+
+    serde_json::json!({
+        "count": clients.len(),
+        "top_names": top(by_name),
+        "top_commands": top(by_command),
+        "idle_over_300s": clients.iter().filter(|c| number(c, "idle") > 300).count(),
+        "blocked": clients.iter().filter(|c| field(c, "flags").contains('b')).count(),
+        "total_omem_bytes": clients.iter().map(|c| number(c, "omem")).sum::<u64>(),
+    })
+}
+
+/// Parse CLIENT LIST output into one object of `field=value` pairs per client.
+fn parse_client_list(raw: &str) -> Vec<serde_json::Value> {
+    raw.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let mut map = serde_json::Map::new();
+            for part in line.split(' ') {
+                if let Some((key, value)) = part.split_once('=') {
+                    map.insert(
+                        key.to_string(),
+                        serde_json::Value::String(value.to_string()),
+                    );
+                }
+            }
+            serde_json::Value::Object(map)
+        })
+        .collect()
+}
+
+// -- MCP tool handlers (thin wrappers) --
+
+#[tool_router]
+impl McpRedisServer {
+    #[tool(
+        name = "server_policy",
+        description = "What this server allows (write, trace and admin flags, read-only connections) and every limit it applies (scan iterations, batch sizes, sample sizes), to see why a call was refused or returned a partial result",
+        annotations(read_only_hint = true)
+    )]
+    async fn server_policy(&self) -> Result<CallToolResult, ErrorData> {
+        self.do_server_policy().await
+    }
+
+    #[tool(
+        name = "list_connections",
+        description = "List all connected Redis instances with names and connection info (passwords redacted)",
+        annotations(read_only_hint = true)
+    )]
+    async fn list_connections(&self) -> Result<CallToolResult, ErrorData> {
+        self.do_list_connections().await
+    }
+
+    #[tool(
+        name = "ping",
+        description = "Check connection health: PING a connection (or every connection when none is given) several times and report min/avg/p99/max round-trip latency and status",
+        annotations(read_only_hint = true)
+    )]
+    async fn ping(
+        &self,
+        Parameters(params): Parameters<PingParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_ping(params).await
+    }
 
-        // Cap max_keys to scan_count to prevent unbounded iteration
-        let max_keys = std::cmp::min(
-            params.count.unwrap_or(self.scan_count) as usize,
-            self.scan_count as usize,
-        );
+    #[tool(
+        name = "info",
+        description = "Get Redis server info parsed into JSON: sections as objects, numbers as numbers, keyspace, commandstats and latencystats lines as objects. Pick one section with section (raw=true for its INFO text) or several with sections. commandstats and latencystats are only included when named or with include_large",
+        annotations(read_only_hint = true)
+    )]
+    async fn info(
+        &self,
+        Parameters(params): Parameters<InfoParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_info(params).await
+    }
 
-        let mut keys: Vec<String> = Vec::new();
-        let mut cursor: u64 = 0;
-        let mut iterations = 0;
+    #[tool(
+        name = "config_get",
+        description = "Read server configuration with CONFIG GET: parameter/value pairs whose names match a glob, e.g. 'maxmemory*' for memory limit and eviction policy, 'save' for RDB save points, '*timeout*'. Secrets such as requirepass are redacted",
+        annotations(read_only_hint = true)
+    )]
+    async fn config_get(
+        &self,
+        Parameters(params): Parameters<ConfigGetParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_config_get(params).await
+    }
 
-        loop {
-            let (next_cursor, batch): (u64, Vec<String>) = redis::cmd("SCAN")
-                .arg(cursor)
-                .arg("MATCH")
-                .arg(pattern)
-                .arg("COUNT")
-                .arg(100)
-                .query_async(&mut conn)
-                .await
-                .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+    #[tool(
+        name = "config_set",
+        description = "Change one server configuration parameter with CONFIG SET, limited to an allowlist of safe parameters (by default memory and eviction, slowlog, latency monitor, timeouts, keyspace notifications). Returns the previous value and the call that reverts it. Requires --allow-write",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn config_set(
+        &self,
+        Parameters(params): Parameters<ConfigSetParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_config_set(params).await
+    }
 
-            keys.extend(batch);
-            cursor = next_cursor;
-            iterations += 1;
+    #[tool(
+        name = "scan_keys",
+        description = "Scan keys matching a pattern using SCAN (non-blocking). Returns key names. On a cluster, all masters are scanned and keys are grouped by shard. Pass the returned cursor back to fetch the next page until it is '0'. Use type to only return keys of one type (e.g. hash, stream). Set include_metadata to get each key's type, TTL and sampled memory in the same call. Pass patterns instead of pattern to match several patterns in one SCAN pass, with the keys grouped per pattern, and exclude_patterns to leave keys out (e.g. job:* except job:archive:*). An unfinished scan reports its coverage; an empty page is retried with a larger COUNT and, if still empty, comes with a sampled estimate of the matching keys.",
+        annotations(read_only_hint = true)
+    )]
+    async fn scan_keys(
+        &self,
+        Parameters(params): Parameters<ScanParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_scan_keys(params).await
+    }
 
-            if cursor == 0 || keys.len() >= max_keys || iterations >= MAX_SCAN_ITERATIONS {
-                break;
-            }
-        }
+    #[tool(
+        name = "get",
+        description = "Get the value of a key. Auto-detects the key type (string, hash, list, set, zset) and returns the appropriate representation. Values over max_bytes are truncated, with their full size and the tool to page through the rest; keys over max_get_elements elements (or strings over max_get_string_bytes) are refused with that tool named. summary_only: true returns length, TTL, encoding, memory and a few sampled entries instead of the value. follow_references: true also reads the keys named inside the value (by the configured [[references]] patterns), up to reference_depth hops",
+        annotations(read_only_hint = true)
+    )]
+    async fn get(
+        &self,
+        Parameters(params): Parameters<GetParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_get(params).await
+    }
+
+    #[tool(
+        name = "get_multiple",
+        description = "Read several keys in one call: MGET for strings and one pipeline for other types (first 100 elements of each collection). Returns each key with exists, type, length and value; missing keys are listed. max_bytes bounds the value data across all keys",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_multiple(
+        &self,
+        Parameters(params): Parameters<GetMultipleParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_get_multiple(params).await
+    }
+
+    #[tool(
+        name = "exists",
+        description = "Check whether each of a list of keys exists, in one pipeline. Returns a key-to-boolean map and the missing keys, e.g. to verify a cache warm-up or a deletion job",
+        annotations(read_only_hint = true)
+    )]
+    async fn exists(
+        &self,
+        Parameters(params): Parameters<ExistsParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_exists(params).await
+    }
+
+    #[tool(
+        name = "key_info",
+        description = "Get metadata about a key: type, TTL, encoding, and memory usage",
+        annotations(read_only_hint = true)
+    )]
+    async fn key_info(
+        &self,
+        Parameters(params): Parameters<KeyParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_key_info(params).await
+    }
+
+    #[tool(
+        name = "key_length",
+        description = "Get the length of a key without reading its value: bytes of a string (STRLEN), elements of a list (LLEN), members of a set or zset (SCARD/ZCARD), fields of a hash (HLEN) or entries of a stream (XLEN)",
+        annotations(read_only_hint = true)
+    )]
+    async fn key_length(
+        &self,
+        Parameters(params): Parameters<KeyParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_key_length(params).await
+    }
+
+    #[tool(
+        name = "dbsize",
+        description = "Get the number of keys in the current database",
+        annotations(read_only_hint = true)
+    )]
+    async fn dbsize(
+        &self,
+        Parameters(params): Parameters<ConnectionParam>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_dbsize(params).await
+    }
+
+    #[tool(
+        name = "keyspace_summary",
+        description = "Key counts per logical database from INFO keyspace (keys, keys with a TTL, average TTL) for a connection, or every connection when none is given. Shows which databases are populated",
+        annotations(read_only_hint = true)
+    )]
+    async fn keyspace_summary(
+        &self,
+        Parameters(params): Parameters<KeyspaceSummaryParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_keyspace_summary(params).await
+    }
+
+    #[tool(
+        name = "search_keys",
+        description = "Scan keys matching a pattern and return each key with its type (and shard, on a cluster). Pass the returned cursor back to fetch the next page until it is '0'. Use type to only return keys of one type, patterns to match several patterns in one pass and exclude_patterns to leave keys out. Like scan_keys, an empty unfinished page reports coverage and a sampled estimate",
+        annotations(read_only_hint = true)
+    )]
+    async fn search_keys(
+        &self,
+        Parameters(params): Parameters<ScanParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_search_keys(params).await
+    }
+
+    #[tool(
+        name = "get_hash_fields",
+        description = "Get specific fields from a hash key using HMGET",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_hash_fields(
+        &self,
+        Parameters(params): Parameters<HashFieldParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_get_hash_fields(params).await
+    }
+
+    #[tool(
+        name = "hash_overview",
+        description = "Learn the shape of a hash without transferring values: HLEN, a sample of field names (HSCAN NOVALUES when available), and existence plus HSTRLEN for given fields",
+        annotations(read_only_hint = true)
+    )]
+    async fn hash_overview(
+        &self,
+        Parameters(params): Parameters<HashOverviewParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_hash_overview(params).await
+    }
+
+    #[tool(
+        name = "get_list_range",
+        description = "Get a range of elements from a list key using LRANGE",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_list_range(
+        &self,
+        Parameters(params): Parameters<ListRangeParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_get_list_range(params).await
+    }
+
+    #[tool(
+        name = "get_string_range",
+        description = "Read part of a string value with GETRANGE: length bytes from offset (negative counts from the end), with the full STRLEN and next_offset to continue from. Peeks into large strings without reading them whole",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_string_range(
+        &self,
+        Parameters(params): Parameters<StringRangeParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_get_string_range(params).await
+    }
+
+    #[tool(
+        name = "get_set_members",
+        description = "Get a page of members of a sorted set (ZRANGE by rank with scores, optionally highest first) or a set (SSCAN) using offset/limit, with the total cardinality. Offset pages of a set are not stable; page sets with scan_set_members",
+        annotations(read_only_hint = true)
+    )]
+    async fn get_set_members(
+        &self,
+        Parameters(params): Parameters<SetMembersParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_get_set_members(params).await
+    }
+
+    #[tool(
+        name = "scan_set_members",
+        description = "Page through the members of a set with SSCAN: pass the returned cursor back until complete is true. Optional glob pattern (MATCH) and page size. Safe on sets of any size; members may repeat across pages if the set changes while scanning",
+        annotations(read_only_hint = true)
+    )]
+    async fn scan_set_members(
+        &self,
+        Parameters(params): Parameters<MemberScanParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_scan_set_members(params).await
+    }
+
+    #[tool(
+        name = "scan_zset_members",
+        description = "Page through the members and scores of a sorted set with ZSCAN: pass the returned cursor back until complete is true. Optional glob pattern (MATCH) and page size. Unordered; use get_set_members for score order",
+        annotations(read_only_hint = true)
+    )]
+    async fn scan_zset_members(
+        &self,
+        Parameters(params): Parameters<MemberScanParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_scan_zset_members(params).await
+    }
+
+    #[tool(
+        name = "sample_members",
+        description = "Return distinct random members of a set (SRANDMEMBER), fields of a hash (HRANDFIELD) or members of a sorted set (ZRANDMEMBER), with the total size. with_values adds hash values or zset scores. Cheap on keys of any size; a way to see representative elements of a huge collection",
+        annotations(read_only_hint = true)
+    )]
+    async fn sample_members(
+        &self,
+        Parameters(params): Parameters<SampleMembersParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_sample_members(params).await
+    }
+
+    #[tool(
+        name = "zset_lex_range",
+        description = "Read a lexicographically ordered sorted set (members with equal scores, e.g. autocomplete indexes) with ZRANGEBYLEX/ZREVRANGEBYLEX: by min/max lex bounds or by prefix, with offset/limit paging and the number of matching members",
+        annotations(read_only_hint = true)
+    )]
+    async fn zset_lex_range(
+        &self,
+        Parameters(params): Parameters<LexRangeParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_zset_lex_range(params).await
+    }
+
+    #[tool(
+        name = "estimate_cardinality",
+        description = "Count distinct members across lists, sets and sorted sets, e.g. roughly how many distinct users across 50 daily sets: exact SCARD/ZCARD for a single set, otherwise members are read with SSCAN/ZSCAN/LRANGE and counted locally (exactly up to 100k, then with a HyperLogLog at ~0.8% error) without materializing a union in Redis",
+        annotations(read_only_hint = true)
+    )]
+    async fn estimate_cardinality(
+        &self,
+        Parameters(params): Parameters<EstimateCardinalityParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_estimate_cardinality(params).await
+    }
+
+    #[tool(
+        name = "find_member",
+        description = "Search a large set, sorted set or hash for members (hash: field names) containing a substring or matching a glob, with SSCAN/ZSCAN/HSCAN MATCH. Bounded per call; pass the returned cursor to continue until complete",
+        annotations(read_only_hint = true)
+    )]
+    async fn find_member(
+        &self,
+        Parameters(params): Parameters<FindMemberParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_find_member(params).await
+    }
+
+    #[tool(
+        name = "slowlog",
+        description = "Get slow query log entries for performance debugging",
+        annotations(read_only_hint = true)
+    )]
+    async fn slowlog(
+        &self,
+        Parameters(params): Parameters<SlowlogParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_slowlog(params).await
+    }
+
+    #[tool(
+        name = "acl_check",
+        description = "Check whether an ACL user could run a command with given arguments (ACL DRYRUN, Redis 7+), and why not; for diagnosing NOPERM errors without trial and error",
+        annotations(read_only_hint = true)
+    )]
+    async fn acl_check(
+        &self,
+        Parameters(params): Parameters<AclCheckParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_acl_check(params).await
+    }
+
+    #[tool(
+        name = "acl_whoami",
+        description = "The ACL user this server's connection is authenticated as (ACL WHOAMI, Redis 6+)",
+        annotations(read_only_hint = true)
+    )]
+    async fn acl_whoami(
+        &self,
+        Parameters(params): Parameters<ConnectionParam>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_acl_whoami(params).await
+    }
+
+    #[tool(
+        name = "acl_list",
+        description = "Every ACL user with whether it is enabled, its password count (hashes are never returned), allowed and denied command categories and commands, and key and channel patterns; disabled users and enabled users without a password are listed up front (ACL LIST, Redis 6+)",
+        annotations(read_only_hint = true)
+    )]
+    async fn acl_list(
+        &self,
+        Parameters(params): Parameters<ConnectionParam>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_acl_list(params).await
+    }
+
+    #[tool(
+        name = "acl_getuser",
+        description = "One ACL user's rules: enabled, password count, command categories and commands, key patterns with their read/write access, channel patterns and selectors (ACL GETUSER, Redis 6+)",
+        annotations(read_only_hint = true)
+    )]
+    async fn acl_getuser(
+        &self,
+        Parameters(params): Parameters<AclGetUserParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_acl_getuser(params).await
+    }
+
+    #[tool(
+        name = "client_list",
+        description = "List connected Redis clients with address, name, idle time, and current command. Filter by name glob, flags, minimum idle seconds or type; on busy servers, group_by name or addr to get counts with summed output buffer and memory per group instead of every client",
+        annotations(read_only_hint = true)
+    )]
+    async fn client_list(
+        &self,
+        Parameters(params): Parameters<ClientListParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_client_list(params).await
+    }
+
+    #[tool(
+        name = "client_kill",
+        description = "Disconnect a client by ID or address as listed by client_list (CLIENT KILL ID/ADDR) and report how many were killed, with who they were. This server's own connection is never killed. Requires --allow-write",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = true
+        )
+    )]
+    async fn client_kill(
+        &self,
+        Parameters(params): Parameters<ClientKillParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_client_kill(params).await
+    }
+
+    #[tool(
+        name = "client_pause",
+        description = "Pause clients for timeout_ms (CLIENT PAUSE), e.g. for a failover drill: mode 'write' (default) holds only writes while reads continue, 'all' holds every command. Refused beyond max_client_pause_ms. Undo with client_unpause. Requires --allow-write",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
+    )]
+    async fn client_pause(
+        &self,
+        Parameters(params): Parameters<ClientPauseParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_client_pause(params).await
+    }
+
+    #[tool(
+        name = "client_unpause",
+        description = "End a client pause early (CLIENT UNPAUSE, Redis 6.2+). Requires --allow-write",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn client_unpause(
+        &self,
+        Parameters(params): Parameters<ConnectionParam>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_client_unpause(params).await
+    }
 
-        keys.truncate(max_keys);
+    #[tool(
+        name = "replication_status",
+        description = "Replication state from ROLE and INFO replication for a connection, or every connection when none is given: role, master link, connected replicas with their offsets and lag in bytes (master offset minus replica offset) and seconds since the last ACK",
+        annotations(read_only_hint = true)
+    )]
+    async fn replication_status(
+        &self,
+        Parameters(params): Parameters<ReplicationStatusParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_replication_status(params).await
+    }
 
-        // Batch TYPE queries using a pipeline instead of N+1 individual calls
-        let mut results = Vec::new();
-        if !keys.is_empty() {
-            let mut pipe = redis::pipe();
-            for key in &keys {
-                pipe.cmd("TYPE").arg(key);
-            }
-            let types: Vec<String> = pipe
-                .query_async(&mut conn)
-                .await
-                .unwrap_or_else(|_| vec!["unknown".to_string(); keys.len()]);
+    #[tool(
+        name = "check_replica_consistency",
+        description = "Compare sampled keys between a master connection and a replica connection: type, TTL presence, length and a digest of each value (DEBUG DIGEST-VALUE, or values read back when DEBUG is disabled). Mismatches are re-read after a second so writes still replicating are not reported",
+        annotations(read_only_hint = true)
+    )]
+    async fn check_replica_consistency(
+        &self,
+        Parameters(params): Parameters<ReplicaConsistencyParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_check_replica_consistency(params).await
+    }
 
-            for (key, key_type) in keys.iter().zip(types.iter()) {
-                results.push(serde_json::json!({
-                    "key": key,
-                    "type": key_type,
-                }));
-            }
-        }
+    #[tool(
+        name = "backlog_health",
+        description = "Replication backlog size and usage, per-replica lag against the backlog, AOF buffer sizes, and client output buffer pressure against their limits; flags replicas at risk of a full resync",
+        annotations(read_only_hint = true)
+    )]
+    async fn backlog_health(
+        &self,
+        Parameters(params): Parameters<ConnectionParam>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_backlog_health(params).await
+    }
 
-        let text = serde_json::to_string_pretty(&serde_json::json!({
-            "pattern": pattern,
-            "keys": results,
-            "count": results.len(),
-        }))
-        .unwrap_or_else(|_| "{}".to_string());
-        Ok(CallToolResult::success(vec![Content::text(text)]))
+    #[tool(
+        name = "error_stats",
+        description = "Error replies by prefix (WRONGTYPE, OOM, NOPERM, ...) from INFO errorstats with their share, the overall error rate, and the commands with failed or rejected calls; with interval_secs, the errors per second happening right now",
+        annotations(read_only_hint = true)
+    )]
+    async fn error_stats(
+        &self,
+        Parameters(params): Parameters<ErrorStatsParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_error_stats(params).await
     }
 
-    pub async fn do_get_hash_fields(
+    #[tool(
+        name = "info_delta",
+        description = "How busy the server is right now: two INFO snapshots a few seconds apart (max 10s) turned into ops/sec, keyspace hit ratio, network bytes/sec, expired and evicted keys/sec, connection churn, errors/sec and memory change",
+        annotations(read_only_hint = true)
+    )]
+    async fn info_delta(
         &self,
-        params: HashFieldParams,
+        Parameters(params): Parameters<InfoDeltaParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let entry = self
-            .resolve(params.connection.as_deref())
-            .map_err(|e| self.err(e))?;
-        let mut conn = entry.conn.clone();
+        self.do_info_delta(params).await
+    }
 
-        let fields: Vec<&str> = params.fields.split(',').map(|f| f.trim()).collect();
+    #[tool(
+        name = "sample_stats",
+        description = "Short-term trend of a few INFO fields: one INFO read per second for up to 60 seconds, returned as a time series (default fields: instantaneous_ops_per_sec, used_memory, connected_clients) with min/max/avg per field",
+        annotations(read_only_hint = true)
+    )]
+    async fn sample_stats(
+        &self,
+        Parameters(params): Parameters<SampleStatsParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_sample_stats(params).await
+    }
 
-        let mut cmd = redis::cmd("HMGET");
-        cmd.arg(&params.key);
-        for field in &fields {
-            cmd.arg(*field);
-        }
+    #[tool(
+        name = "connection_diagnostics",
+        description = "Diagnose the MCP server's own connection: CLIENT INFO as the server sees it (RESP version, memory, buffers, age), PING round trips, local protocol and reconnects observed, to tell a connection problem from a server one",
+        annotations(read_only_hint = true)
+    )]
+    async fn connection_diagnostics(
+        &self,
+        Parameters(params): Parameters<ConnectionParam>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_connection_diagnostics(params).await
+    }
 
-        let values: Vec<Option<String>> = cmd
-            .query_async(&mut conn)
-            .await
-            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+    #[tool(
+        name = "monitor_sample",
+        description = "Sample live traffic with MONITOR for a few seconds: a histogram of commands, the top keys and client addresses, and the command rate (or the raw lines with aggregate=false). MONITOR costs a busy server throughput while it runs",
+        annotations(read_only_hint = true)
+    )]
+    async fn monitor_sample(
+        &self,
+        Parameters(params): Parameters<MonitorSampleParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_monitor_sample(params).await
+    }
 
-        let result: Vec<serde_json::Value> = fields
-            .iter()
-            .zip(values.iter())
-            .map(|(field, value)| {
-                serde_json::json!({
-                    "field": field,
-                    "value": value.as_deref().unwrap_or_default(),
-                    "exists": value.is_some(),
-                })
-            })
-            .collect();
+    #[tool(
+        name = "latency_latest",
+        description = "Latest and all-time max latency per event recorded by the latency monitor (LATENCY LATEST), worst first, with timestamps",
+        annotations(read_only_hint = true)
+    )]
+    async fn latency_latest(
+        &self,
+        Parameters(params): Parameters<ConnectionParam>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_latency_latest(params).await
+    }
 
-        let text = serde_json::to_string_pretty(&serde_json::json!({
-            "key": params.key,
-            "fields": result,
-        }))
-        .unwrap_or_else(|_| "{}".to_string());
-        Ok(CallToolResult::success(vec![Content::text(text)]))
+    #[tool(
+        name = "latency_history",
+        description = "Timestamped latency samples of one event (LATENCY HISTORY, up to the last 160), with count, max and average",
+        annotations(read_only_hint = true)
+    )]
+    async fn latency_history(
+        &self,
+        Parameters(params): Parameters<LatencyHistoryParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_latency_history(params).await
     }
 
-    pub async fn do_get_list_range(
+    #[tool(
+        name = "latency_doctor",
+        description = "Redis's own human-readable analysis of recorded latency spikes and advice (LATENCY DOCTOR), with the parsed LATENCY LATEST events",
+        annotations(read_only_hint = true)
+    )]
+    async fn latency_doctor(
         &self,
-        params: ListRangeParams,
+        Parameters(params): Parameters<ConnectionParam>,
     ) -> Result<CallToolResult, ErrorData> {
-        let entry = self
-            .resolve(params.connection.as_deref())
-            .map_err(|e| self.err(e))?;
-        let mut conn = entry.conn.clone();
+        self.do_latency_doctor(params).await
+    }
 
-        let start = params.start.unwrap_or(0);
-        let stop = params.stop.unwrap_or(-1);
+    #[tool(
+        name = "latency_correlation",
+        description = "Merge LATENCY HISTORY events, slowlog entries and RDB/AOF persistence windows onto one timeline and point out coincidences, such as fork stalls during an RDB save or slow commands next to a latency spike",
+        annotations(read_only_hint = true)
+    )]
+    async fn latency_correlation(
+        &self,
+        Parameters(params): Parameters<LatencyCorrelationParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_latency_correlation(params).await
+    }
 
-        let elements: Vec<String> = redis::cmd("LRANGE")
-            .arg(&params.key)
-            .arg(start)
-            .arg(stop)
-            .query_async(&mut conn)
-            .await
-            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+    #[tool(
+        name = "client_buffer_report",
+        description = "Clients ranked by output buffer memory (omem) or queued replies (oll), with the command each is running, and those close to or over their client-output-buffer-limit flagged. Finds slow Pub/Sub consumers before Redis disconnects them",
+        annotations(read_only_hint = true)
+    )]
+    async fn client_buffer_report(
+        &self,
+        Parameters(params): Parameters<ClientBufferReportParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_client_buffer_report(params).await
+    }
 
-        let text = serde_json::to_string_pretty(&serde_json::json!({
-            "key": params.key,
-            "start": start,
-            "stop": stop,
-            "elements": elements,
-            "count": elements.len(),
-        }))
-        .unwrap_or_else(|_| "{}".to_string());
-        Ok(CallToolResult::success(vec![Content::text(text)]))
+    #[tool(
+        name = "estimate_matching_keys",
+        description = "Estimate how many keys match a pattern from random SCAN samples and DBSIZE, with a 95% confidence interval. Much cheaper than a full scan on large keyspaces.",
+        annotations(read_only_hint = true)
+    )]
+    async fn estimate_matching_keys(
+        &self,
+        Parameters(params): Parameters<EstimateParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_estimate_matching_keys(params).await
     }
 
-    pub async fn do_get_set_members(
+    #[tool(
+        name = "cluster_info",
+        description = "Get Redis Cluster state (CLUSTER INFO) as structured JSON: state, slots assigned/ok/failing, known nodes, epochs",
+        annotations(read_only_hint = true)
+    )]
+    async fn cluster_info(
         &self,
-        params: SetMembersParams,
+        Parameters(params): Parameters<ConnectionParam>,
     ) -> Result<CallToolResult, ErrorData> {
-        let entry = self
-            .resolve(params.connection.as_deref())
-            .map_err(|e| self.err(e))?;
-        let mut conn = entry.conn.clone();
+        self.do_cluster_info(params).await
+    }
 
-        // Detect key type to handle sets vs sorted sets
-        let key_type: String = redis::cmd("TYPE")
-            .arg(&params.key)
-            .query_async(&mut conn)
-            .await
-            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+    #[tool(
+        name = "cluster_nodes",
+        description = "List Redis Cluster nodes (CLUSTER NODES) with id, address, role, master, flags, link state, and owned slot ranges",
+        annotations(read_only_hint = true)
+    )]
+    async fn cluster_nodes(
+        &self,
+        Parameters(params): Parameters<ConnectionParam>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_cluster_nodes(params).await
+    }
 
-        match key_type.as_str() {
-            "set" => {
-                let members: Vec<String> = redis::cmd("SMEMBERS")
-                    .arg(&params.key)
-                    .query_async(&mut conn)
-                    .await
-                    .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+    #[tool(
+        name = "cluster_shards",
+        description = "List Redis Cluster shards (CLUSTER SHARDS, Redis 7+) with slot ranges and the nodes serving each shard",
+        annotations(read_only_hint = true)
+    )]
+    async fn cluster_shards(
+        &self,
+        Parameters(params): Parameters<ConnectionParam>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_cluster_shards(params).await
+    }
 
-                let limited = if let Some(count) = params.count {
-                    members.into_iter().take(count as usize).collect::<Vec<_>>()
-                } else {
-                    members
-                };
+    #[tool(
+        name = "cluster_keyslot",
+        description = "Map a key to its cluster hash slot: slot number, the master (and replicas) serving it, and how many keys live in that slot",
+        annotations(read_only_hint = true)
+    )]
+    async fn cluster_keyslot(
+        &self,
+        Parameters(params): Parameters<KeyParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_cluster_keyslot(params).await
+    }
 
-                let text = serde_json::to_string_pretty(&serde_json::json!({
-                    "key": params.key,
-                    "type": "set",
-                    "members": limited,
-                    "count": limited.len(),
-                }))
-                .unwrap_or_else(|_| "{}".to_string());
-                Ok(CallToolResult::success(vec![Content::text(text)]))
-            }
-            "zset" => {
-                let stop = params.count.map(|c| c - 1).unwrap_or(-1);
-                let members: Vec<(String, f64)> = redis::cmd("ZRANGE")
-                    .arg(&params.key)
-                    .arg(0)
-                    .arg(stop)
-                    .arg("WITHSCORES")
-                    .query_async(&mut conn)
-                    .await
-                    .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+    #[tool(
+        name = "sentinel_masters",
+        description = "List the masters monitored by Redis Sentinel (SENTINEL MASTERS) with their replicas. Only for connections opened with a redis+sentinel:// URL",
+        annotations(read_only_hint = true)
+    )]
+    async fn sentinel_masters(
+        &self,
+        Parameters(params): Parameters<ConnectionParam>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_sentinel_masters(params).await
+    }
 
-                let result: Vec<serde_json::Value> = members
-                    .iter()
-                    .map(|(m, s)| serde_json::json!({"member": m, "score": s}))
-                    .collect();
+    #[tool(
+        name = "namespace_census",
+        description = "Statistical overview of a key prefix from a bounded sample: type distribution, average memory and length per type, TTL coverage, and encoding breakdown",
+        annotations(read_only_hint = true)
+    )]
+    async fn namespace_census(
+        &self,
+        Parameters(params): Parameters<NamespaceCensusParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_namespace_census(params).await
+    }
 
-                let text = serde_json::to_string_pretty(&serde_json::json!({
-                    "key": params.key,
-                    "type": "zset",
-                    "members": result,
-                    "count": result.len(),
-                }))
-                .unwrap_or_else(|_| "{}".to_string());
-                Ok(CallToolResult::success(vec![Content::text(text)]))
-            }
-            "none" => Ok(CallToolResult::success(vec![Content::text(
-                serde_json::json!({"error": "Key does not exist", "key": params.key}).to_string(),
-            )])),
-            other => Ok(CallToolResult::success(vec![Content::text(
-                serde_json::json!({"error": format!("Key is type '{}', not a set or zset", other), "key": params.key}).to_string(),
-            )])),
-        }
+    #[tool(
+        name = "find_big_keys",
+        description = "Find the largest keys matching a pattern: scans a bounded number of keys, measures each with MEMORY USAGE (SAMPLES), and returns the top N with type, memory and length. Like redis-cli --bigkeys, scoped to a pattern",
+        annotations(read_only_hint = true)
+    )]
+    async fn find_big_keys(
+        &self,
+        Parameters(params): Parameters<BigKeysParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_find_big_keys(params).await
     }
 
-    pub async fn do_slowlog(&self, params: SlowlogParams) -> Result<CallToolResult, ErrorData> {
-        let entry = self
-            .resolve(params.connection.as_deref())
-            .map_err(|e| self.err(e))?;
-        let mut conn = entry.conn.clone();
+    #[tool(
+        name = "memory_by_pattern",
+        description = "Estimate total memory per key pattern (e.g. one per feature or namespace): MEMORY USAGE of a random keyspace sample, averaged over the keys each pattern matches and extrapolated by its estimated key count, with a 95% interval on the count. Exact on small keyspaces",
+        annotations(read_only_hint = true)
+    )]
+    async fn memory_by_pattern(
+        &self,
+        Parameters(params): Parameters<MemoryByPatternParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_memory_by_pattern(params).await
+    }
 
-        let count = params.count.unwrap_or(10);
+    #[tool(
+        name = "compaction_advisor",
+        description = "Find hashes, sets and sorted sets that just missed a compact encoding (listpack, intset) in a bounded key sample, and estimate what raising the *-max-listpack-entries thresholds or splitting large hashes into buckets would save, with the affected key counts and the CONFIG SET to run",
+        annotations(read_only_hint = true)
+    )]
+    async fn compaction_advisor(
+        &self,
+        Parameters(params): Parameters<CompactionAdvisorParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_compaction_advisor(params).await
+    }
 
-        // SLOWLOG GET returns an array of arrays
-        let raw: Vec<Vec<redis::Value>> = redis::cmd("SLOWLOG")
-            .arg("GET")
-            .arg(count)
-            .query_async(&mut conn)
-            .await
-            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
+    #[tool(
+        name = "analyze_namespaces",
+        description = "Break an unfamiliar keyspace down by top-level prefix: samples a bounded number of keys, splits names on a delimiter (default ':'), and returns key counts, share, estimated totals, and sampled memory per prefix",
+        annotations(read_only_hint = true)
+    )]
+    async fn analyze_namespaces(
+        &self,
+        Parameters(params): Parameters<AnalyzeNamespacesParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_analyze_namespaces(params).await
+    }
 
-        let entries: Vec<serde_json::Value> = raw
-            .iter()
-            .map(|entry| {
-                let id = match entry.first() {
-                    Some(redis::Value::Int(i)) => *i,
-                    _ => -1,
-                };
-                let timestamp = match entry.get(1) {
-                    Some(redis::Value::Int(i)) => *i,
-                    _ => 0,
-                };
-                let duration_us = match entry.get(2) {
-                    Some(redis::Value::Int(i)) => *i,
-                    _ => 0,
-                };
-                let command = match entry.get(3) {
-                    Some(redis::Value::Array(args)) => args
-                        .iter()
-                        .filter_map(|a| match a {
-                            redis::Value::BulkString(s) => {
-                                String::from_utf8(s.clone()).ok()
-                            }
-                            _ => None,
-                        })
-                        .collect::<Vec<_>>()
-                        .join(" "),
-                    _ => "unknown".to_string(),
-                };
+    #[tool(
+        name = "validate_key",
+        description = "Check a key against the contract configured for its pattern (type, required hash fields, TTL range, maximum length and memory) and list the rules it breaks",
+        annotations(read_only_hint = true)
+    )]
+    async fn validate_key(
+        &self,
+        Parameters(params): Parameters<ValidateKeyParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_validate_key(params).await
+    }
 
-                serde_json::json!({
-                    "id": id,
-                    "timestamp": timestamp,
-                    "duration_us": duration_us,
-                    "command": command,
-                })
-            })
-            .collect();
+    #[tool(
+        name = "validate_namespace",
+        description = "Check a bounded sample of the keys matching a pattern against their configured contracts and summarize the violations by rule, with the offending keys",
+        annotations(read_only_hint = true)
+    )]
+    async fn validate_namespace(
+        &self,
+        Parameters(params): Parameters<ValidateNamespaceParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_validate_namespace(params).await
+    }
 
-        let text = serde_json::to_string_pretty(&serde_json::json!({
-            "entries": entries,
-            "count": entries.len(),
-        }))
-        .unwrap_or_else(|_| "{}".to_string());
-        Ok(CallToolResult::success(vec![Content::text(text)]))
+    #[tool(
+        name = "rdb_scan",
+        description = "List keys matching a pattern in the local RDB snapshot given with --rdb, with type, length and expiry. Optionally check each against the live server to find keys that existed in the snapshot but are gone now",
+        annotations(read_only_hint = true)
+    )]
+    async fn rdb_scan(
+        &self,
+        Parameters(params): Parameters<RdbScanParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_rdb_scan(params).await
     }
 
-    pub async fn do_client_list(
+    #[tool(
+        name = "rdb_get",
+        description = "Read a key's value from the local RDB snapshot given with --rdb, optionally next to its live value with a changed flag",
+        annotations(read_only_hint = true)
+    )]
+    async fn rdb_get(
         &self,
-        params: ConnectionParam,
+        Parameters(params): Parameters<RdbGetParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let entry = self
-            .resolve(params.connection.as_deref())
-            .map_err(|e| self.err(e))?;
-        let mut conn = entry.conn.clone();
-
-        let raw: String = redis::cmd("CLIENT")
-            .arg("LIST")
-            .query_async(&mut conn)
-            .await
-            .map_err(|e| self.err(McpRedisError::Redis(e)))?;
-
-        let clients: Vec<serde_json::Value> = raw
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| {
-                let mut map = serde_json::Map::new();
-                for part in line.split(' ') {
-                    if let Some((key, value)) = part.split_once('=') {
-                        map.insert(
-                            key.to_string(),
-                            serde_json::Value::String(value.to_string()),
-                        );
-                    }
-                }
-                serde_json::Value::Object(map)
-            })
-            .collect();
-
-        let text = serde_json::to_string_pretty(&serde_json::json!({
-            "clients": clients,
-            "count": clients.len(),
-        }))
-        .unwrap_or_else(|_| "{}".to_string());
-        Ok(CallToolResult::success(vec![Content::text(text)]))
+        self.do_rdb_get(params).await
     }
-}
 
-// -- MCP tool handlers (thin wrappers) --
+    #[tool(
+        name = "stream_range",
+        description = "Read stream entries with XRANGE/XREVRANGE. Bounds accept entry IDs, ISO 8601 times, or relative times like '10 minutes ago'; each entry comes with its ID decoded into an ISO 8601 timestamp and sequence number",
+        annotations(read_only_hint = true)
+    )]
+    async fn stream_range(
+        &self,
+        Parameters(params): Parameters<StreamRangeParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_stream_range(params).await
+    }
 
-#[tool_router]
-impl McpRedisServer {
     #[tool(
-        name = "list_connections",
-        description = "List all connected Redis instances with names and connection info (passwords redacted)"
+        name = "stream_autoclaim",
+        description = "Recover stuck consumer-group messages: XAUTOCLAIM entries idle for at least min_idle_ms to a consumer, returning the claimed entries and updated pending-list stats. Requires --allow-write",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
     )]
-    async fn list_connections(&self) -> Result<CallToolResult, ErrorData> {
-        self.do_list_connections().await
+    async fn stream_autoclaim(
+        &self,
+        Parameters(params): Parameters<AutoclaimParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        self.do_stream_autoclaim(params).await
     }
 
     #[tool(
-        name = "info",
-        description = "Get Redis server info. Optionally specify a section: memory, stats, keyspace, server, clients, etc."
+        name = "bulk_delete",
+        description = "Delete keys matching a glob pattern on one connection or every connection with a tag. Call without confirm_token to get per-connection match counts and a token; call again with the token to UNLINK the keys in batches, with progress notifications and a final report of what remains. Requires --allow-write",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = true,
+            idempotent_hint = false
+        )
     )]
-    async fn info(
+    async fn bulk_delete(
         &self,
-        Parameters(params): Parameters<InfoParams>,
+        Parameters(params): Parameters<BulkDeleteParams>,
+        meta: Meta,
+        peer: Peer<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.do_info(params).await
+        let progress = meta
+            .get_progress_token()
+            .map(|token| ProgressReporter::new(peer, token));
+        self.do_bulk_delete(params, progress).await
     }
 
     #[tool(
-        name = "scan_keys",
-        description = "Scan keys matching a pattern using SCAN (non-blocking). Returns key names."
+        name = "export_session",
+        description = "Return this session's transcript: every tool call in order with its arguments, duration, status and result (size-capped), as JSON or a markdown document, or write it to the export directory to attach to a postmortem. Requires --record-session",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
     )]
-    async fn scan_keys(
+    async fn export_session(
         &self,
-        Parameters(params): Parameters<ScanParams>,
+        Parameters(params): Parameters<ExportSessionParams>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.do_scan_keys(params).await
+        self.do_export_session(params, session_id(&context).as_deref())
+            .await
     }
 
     #[tool(
-        name = "get",
-        description = "Get the value of a key. Auto-detects the key type (string, hash, list, set, zset) and returns the appropriate representation."
+        name = "create_analysis_replica",
+        description = "Run the configured analysis_replica hook to provision or point at a replica of a connection, then register it as a new read-only connection for heavy analysis. Requires --allow-admin",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
     )]
-    async fn get(
+    async fn create_analysis_replica(
         &self,
-        Parameters(params): Parameters<KeyParams>,
+        Parameters(params): Parameters<AnalysisReplicaParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.do_get(params).await
+        self.do_create_analysis_replica(params).await
     }
 
     #[tool(
-        name = "key_info",
-        description = "Get metadata about a key: type, TTL, encoding, and memory usage"
+        name = "replace_connection",
+        description = "Repoint a connection at a new URL without restarting, e.g. when a primary moves behind a new endpoint during failover: connects, checks PING and role, then swaps it in under the same name while calls in flight finish on the old one. Refuses a replica for a writable connection unless allow_replica is set. Requires --allow-admin",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
     )]
-    async fn key_info(
+    async fn replace_connection(
         &self,
-        Parameters(params): Parameters<KeyParams>,
+        Parameters(params): Parameters<ReplaceConnectionParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.do_key_info(params).await
+        self.do_replace_connection(params).await
     }
 
     #[tool(
-        name = "dbsize",
-        description = "Get the number of keys in the current database"
+        name = "trigger_bgsave",
+        description = "Start a background RDB snapshot with BGSAVE, e.g. before maintenance, and report whether the server accepted it (a save already running is reported, not an error) with the persistence fields to poll. schedule: true defers it until a running AOF rewrite ends. Requires --allow-write",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
     )]
-    async fn dbsize(
+    async fn trigger_bgsave(
         &self,
-        Parameters(params): Parameters<ConnectionParam>,
+        Parameters(params): Parameters<BgsaveParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.do_dbsize(params).await
+        self.do_trigger_bgsave(params).await
     }
 
     #[tool(
-        name = "search_keys",
-        description = "Scan keys matching a pattern and return each key with its type"
+        name = "trigger_aof_rewrite",
+        description = "Start a background AOF rewrite with BGREWRITEAOF and report whether the server accepted or scheduled it, with the persistence fields to poll. Requires --allow-write",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
     )]
-    async fn search_keys(
+    async fn trigger_aof_rewrite(
         &self,
-        Parameters(params): Parameters<ScanParams>,
+        Parameters(params): Parameters<ConnectionParam>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.do_search_keys(params).await
+        self.do_trigger_aof_rewrite(params).await
     }
 
     #[tool(
-        name = "get_hash_fields",
-        description = "Get specific fields from a hash key using HMGET"
+        name = "reload_config",
+        description = "Re-read the --config file: add, remove, or reconnect changed connections and apply new limits and presets without restarting. Requires --allow-admin",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
     )]
-    async fn get_hash_fields(
+    async fn reload_config(&self) -> Result<CallToolResult, ErrorData> {
+        self.do_reload_config().await
+    }
+
+    #[tool(
+        name = "save_cursor",
+        description = "Save where a scan_keys, search_keys or find_member scan stopped under a name, with the arguments to repeat, so a later session or another client can resume it (list_cursors). persist=true also stores it in a Redis key (requires --allow-write). Saving cursor '0' removes it",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    async fn save_cursor(
         &self,
-        Parameters(params): Parameters<HashFieldParams>,
+        Parameters(params): Parameters<SaveCursorParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.do_get_hash_fields(params).await
+        self.do_save_cursor(params).await
     }
 
     #[tool(
-        name = "get_list_range",
-        description = "Get a range of elements from a list key using LRANGE"
+        name = "list_cursors",
+        description = "Cursors saved with save_cursor, each with the tool call that resumes the scan, including those persisted in Redis on the connection",
+        annotations(read_only_hint = true)
     )]
-    async fn get_list_range(
+    async fn list_cursors(
         &self,
-        Parameters(params): Parameters<ListRangeParams>,
+        Parameters(params): Parameters<ListCursorsParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.do_get_list_range(params).await
+        self.do_list_cursors(params).await
     }
 
     #[tool(
-        name = "get_set_members",
-        description = "Get members of a set (SMEMBERS) or sorted set (ZRANGE with scores)"
+        name = "set_maintenance_note",
+        description = "Set or clear a maintenance note on a connection, e.g. 'failover in progress, expect stale reads'. Every tool result for that connection carries the note until it expires, so all clients of a shared server see it. Requires --allow-admin",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
     )]
-    async fn get_set_members(
+    async fn set_maintenance_note(
         &self,
-        Parameters(params): Parameters<SetMembersParams>,
+        Parameters(params): Parameters<MaintenanceNoteParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.do_get_set_members(params).await
+        self.do_set_maintenance_note(params).await
     }
 
     #[tool(
-        name = "slowlog",
-        description = "Get slow query log entries for performance debugging"
+        name = "capture_diagnostics",
+        description = "Capture an incident diagnostics bundle (parsed INFO, redacted CONFIG, slowlog, client summary, key sample) to a JSON file under the export directory and return its path",
+        annotations(
+            read_only_hint = false,
+            destructive_hint = false,
+            idempotent_hint = false
+        )
     )]
-    async fn slowlog(
+    async fn capture_diagnostics(
         &self,
-        Parameters(params): Parameters<SlowlogParams>,
+        Parameters(params): Parameters<DiagnosticsParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.do_slowlog(params).await
+        self.do_capture_diagnostics(params).await
     }
 
     #[tool(
-        name = "client_list",
-        description = "List connected Redis clients with address, name, idle time, and current command"
+        name = "subscribe_sample",
+        description = "Subscribe to channels (or patterns) for a bounded duration (max 10s) and return the captured Pub/Sub messages",
+        annotations(read_only_hint = true)
     )]
-    async fn client_list(
+    async fn subscribe_sample(
         &self,
-        Parameters(params): Parameters<ConnectionParam>,
+        Parameters(params): Parameters<SubscribeSampleParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        self.do_client_list(params).await
+        self.do_subscribe_sample(params).await
+    }
+}
+
+impl McpRedisServer {
+    /// Append a restart notice to `result` when the connection's server
+    /// restarted recently, so counter-based results are not taken at face
+    /// value.
+    fn flag_restart(&self, result: &mut CallToolResult, connection: Option<&str>) {
+        if let Some(notice) = self.restart_notice(connection) {
+            append_json(result, notice);
+        }
+    }
+
+    /// Append the connection's maintenance note to `result` until it expires.
+    fn flag_maintenance(&self, result: &mut CallToolResult, connection: Option<&str>) {
+        if let Some(notice) = self.maintenance_notice(connection) {
+            append_json(result, notice);
+        }
+    }
+
+    /// Whether `tool` (or a preset with such a step) is expensive enough to
+    /// check that its server is not busy first.
+    fn guards_busy(&self, tool: &str) -> bool {
+        let expensive = |name: &str| {
+            !OFFLINE_TOOLS.contains(&name)
+                && crate::stats::COST_HINTS
+                    .iter()
+                    .any(|hint| hint.tool == name && hint.cost == Cost::Expensive)
+        };
+        expensive(tool)
+            || self
+                .presets()
+                .iter()
+                .find(|preset| preset.name == tool)
+                .is_some_and(|preset| preset.steps.iter().any(|step| expensive(&step.tool)))
+    }
+
+    /// Whether `tool` (or a preset with such a step) writes data or changes
+    /// the server's configuration, going by its annotations.
+    pub fn is_write_tool(&self, tool: &str) -> bool {
+        let writes = |name: &str| {
+            self.tool_router
+                .get(name)
+                .and_then(|t| t.annotations.as_ref())
+                .and_then(|a| a.read_only_hint)
+                == Some(false)
+        };
+        writes(tool)
+            || self
+                .presets()
+                .iter()
+                .find(|preset| preset.name == tool)
+                .is_some_and(|preset| preset.steps.iter().any(|step| writes(&step.tool)))
+    }
+
+    /// Start the webhook event for a write or admin call: who made it, with
+    /// which arguments, and the digest of the key it names before it runs.
+    async fn begin_change(
+        &self,
+        request: &CallToolRequestParams,
+        context: &RequestContext<RoleServer>,
+        connection: Option<&str>,
+    ) -> Option<PendingChange> {
+        self.notifier.as_ref()?;
+        if !self.is_write_tool(&request.name) {
+            return None;
+        }
+        let client = context.peer.peer_info().map(|info| &info.client_info);
+        let session = session_id(context);
+        let arguments = request.arguments.clone().unwrap_or_default();
+        let key = arguments
+            .get("key")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        let mut event = serde_json::json!({
+            "event": "tool_call",
+            "tool": request.name,
+            "at": time::iso8601_ms(time::now_ms()),
+            "who": {
+                "client": client.map(|c| c.name.clone()),
+                "client_version": client.map(|c| c.version.clone()),
+                "session": session,
+            },
+            "connection": self.resolve(connection).ok().map(|c| c.name),
+            "arguments": crate::notify::redact_arguments(&arguments),
+        });
+        if let Some(key) = &key {
+            event["key"] = serde_json::json!({
+                "name": key,
+                "before": self.key_digest(connection, key).await,
+            });
+        }
+        Some(PendingChange {
+            event,
+            connection: connection.map(str::to_string),
+            key,
+            started: Instant::now(),
+        })
+    }
+
+    /// Complete the event with the call's outcome and the key's digest after
+    /// it, and post it.
+    async fn finish_change(
+        &self,
+        change: Option<PendingChange>,
+        result: &Result<CallToolResult, ErrorData>,
+    ) {
+        let (Some(mut change), Some(notifier)) = (change, &self.notifier) else {
+            return;
+        };
+        let error = match result {
+            Err(e) => Some(serde_json::json!(e.message)),
+            Ok(r) if r.is_error == Some(true) => Some(serde_json::json!("tool error")),
+            Ok(r) => r
+                .structured_content
+                .as_ref()
+                .and_then(|v| v.get("error"))
+                .cloned(),
+        };
+        let event = &mut change.event;
+        event["status"] = serde_json::json!(if error.is_some() { "error" } else { "ok" });
+        if let Some(error) = error {
+            event["error"] = error;
+        }
+        event["duration_ms"] = serde_json::json!(change.started.elapsed().as_millis() as u64);
+        if let Some(key) = &change.key {
+            event["key"]["after"] = self.key_digest(change.connection.as_deref(), key).await;
+        }
+        notifier.send(change.event);
+    }
+
+    /// Digest of `key`'s DUMP, `null` when it does not exist or cannot be
+    /// read. DUMP serializes the whole value on the Redis main thread, so keys
+    /// over `max_get_elements` (strings over `max_get_string_bytes`) are
+    /// reported by type and length instead.
+    async fn key_digest(&self, connection: Option<&str>, key: &str) -> serde_json::Value {
+        let Ok(entry) = self.resolve(connection) else {
+            return serde_json::Value::Null;
+        };
+        let mut conn = entry.connection();
+        let key_type: String = match redis::cmd("TYPE").arg(key).query_async(&mut conn).await {
+            Ok(key_type) => key_type,
+            Err(_) => return serde_json::Value::Null,
+        };
+        if key_type == "none" {
+            return serde_json::Value::Null;
+        }
+        if let Some(command) = census::length_command(&key_type) {
+            let length: u64 = redis::cmd(command)
+                .arg(key)
+                .query_async(&mut conn)
+                .await
+                .unwrap_or(0);
+            let limits = self.limits();
+            let limit = if key_type == "string" {
+                limits.max_get_string_bytes
+            } else {
+                limits.max_get_elements
+            };
+            if length > limit {
+                return serde_json::json!({"type": key_type, "length": length});
+            }
+        }
+        let dump: Option<Vec<u8>> = redis::cmd("DUMP")
+            .arg(key)
+            .query_async(&mut conn)
+            .await
+            .unwrap_or(None);
+        serde_json::json!(dump.map(|d| crate::notify::digest(&d)))
+    }
+
+    /// Result to return instead of running `tool` when its server is busy:
+    /// PING is refused with BUSY (a script past its time limit) or does not
+    /// answer within [`BUSY_PROBE_TIMEOUT`]. Cheap tools, unknown
+    /// connections and other PING errors are left to the tool itself.
+    pub async fn busy_check(&self, tool: &str, connection: Option<&str>) -> Option<CallToolResult> {
+        if !self.guards_busy(tool) {
+            return None;
+        }
+        let entry = self.resolve(connection).ok()?;
+        let mut conn = entry.connection();
+        let ping = tokio::time::timeout(
+            BUSY_PROBE_TIMEOUT,
+            redis::cmd("PING").query_async::<String>(&mut conn),
+        )
+        .await;
+        let busy = match ping {
+            Ok(Ok(_)) => return None,
+            Ok(Err(e)) if e.code() == Some("BUSY") => {
+                // FUNCTION STATS is allowed while a script runs and names it
+                let stats = tokio::time::timeout(
+                    BUSY_PROBE_TIMEOUT,
+                    redis::cmd("FUNCTION")
+                        .arg("STATS")
+                        .query_async::<redis::Value>(&mut conn),
+                )
+                .await;
+                busy::Busy::from_function_stats(stats.ok().and_then(Result::ok).as_ref())
+            }
+            Ok(Err(_)) => return None,
+            Err(_) => busy::Busy::Unresponsive {
+                waited: BUSY_PROBE_TIMEOUT,
+            },
+        };
+        Some(json_result(serde_json::json!({
+            "error": "Server busy",
+            "connection": entry.name,
+            "tool": tool,
+            "busy": busy.to_json(),
+        })))
+    }
+
+    /// Run a tool call, with a protocol trace when `trace` was requested,
+    /// and flag its result with restart and maintenance notices.
+    async fn run_call(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+        connection: Option<String>,
+        trace_requested: bool,
+        all_dbs: bool,
+    ) -> Result<CallToolResult, ErrorData> {
+        if !trace_requested {
+            let change = self
+                .begin_change(&request, &context, connection.as_deref())
+                .await;
+            let result = self
+                .dispatch_dbs(request, context, connection.as_deref(), all_dbs)
+                .await;
+            self.finish_change(change, &result).await;
+            let mut result = result?;
+            self.flag_restart(&mut result, connection.as_deref());
+            self.flag_maintenance(&mut result, connection.as_deref());
+            return Ok(result);
+        }
+        if !self.allow_trace {
+            return Err(ErrorData::invalid_params(
+                "'trace' requires --allow-trace flag",
+                None,
+            ));
+        }
+
+        let change = self
+            .begin_change(&request, &context, connection.as_deref())
+            .await;
+        let trace = CallTrace::new();
+        let result = trace
+            .scope(self.dispatch_dbs(request, context, connection.as_deref(), all_dbs))
+            .await;
+        self.finish_change(change, &result).await;
+        let mut result = result?;
+        append_json(&mut result, trace.to_json());
+        self.flag_restart(&mut result, connection.as_deref());
+        self.flag_maintenance(&mut result, connection.as_deref());
+        Ok(result)
+    }
+
+    /// Handle a tool call: the `trace`, `db` and `all_dbs` arguments, lazy
+    /// connections and busy checks, then [`Self::run_call`].
+    async fn route_call(
+        &self,
+        mut request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let trace_requested = request
+            .arguments
+            .as_mut()
+            .and_then(|args| args.remove("trace"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let db = match request.arguments.as_mut() {
+            Some(args) => self.take_db_argument(&request.name, args)?,
+            None => None,
+        };
+        let all_dbs = match request
+            .arguments
+            .as_mut()
+            .and_then(|args| args.remove("all_dbs"))
+        {
+            Some(value) => Self::all_dbs_argument(&request.name, &value)?,
+            None => false,
+        };
+        if all_dbs && db.is_some() {
+            return Err(ErrorData::invalid_params(
+                "'db' and 'all_dbs' cannot be combined",
+                None,
+            ));
+        }
+
+        let connection = request
+            .arguments
+            .as_ref()
+            .and_then(|args| args.get("connection"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        self.connect_pending(connection.as_deref()).await;
+        if let Some(busy) = self.busy_check(&request.name, connection.as_deref()).await {
+            return Ok(busy);
+        }
+
+        let Some(db) = db else {
+            return self
+                .run_call(request, context, connection, trace_requested, all_dbs)
+                .await;
+        };
+        let call = self.run_call(request, context, connection.clone(), trace_requested, false);
+        self.in_db(connection.as_deref(), db, call)
+            .await
+            .map_err(|e| self.err(e))?
+    }
+
+    /// Remove and check the `db` argument of a call to `tool`, for tools
+    /// that run in another database. Tools whose own parameters declare `db`
+    /// keep it (`rdb_scan` and `rdb_get`: the database inside the snapshot);
+    /// any other tool is refused rather than run in the default database.
+    pub fn take_db_argument(
+        &self,
+        tool: &str,
+        arguments: &mut serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Option<i64>, ErrorData> {
+        if !DB_TOOLS.contains(&tool) {
+            let own_db = self
+                .tool_router
+                .get(tool)
+                .and_then(|tool| tool.input_schema.get("properties"))
+                .and_then(|properties| properties.get("db"))
+                .is_some();
+            if arguments.contains_key("db") && !own_db {
+                return Err(ErrorData::invalid_params(
+                    format!("'{}' does not take a 'db' argument", tool),
+                    None,
+                ));
+            }
+            return Ok(None);
+        }
+        let Some(value) = arguments.remove("db") else {
+            return Ok(None);
+        };
+        value
+            .as_i64()
+            .filter(|db| *db >= 0)
+            .map(Some)
+            .ok_or_else(|| {
+                ErrorData::invalid_params("'db' must be a database index (0 or more)", None)
+            })
+    }
+
+    /// The `all_dbs` argument of a call to `tool`.
+    fn all_dbs_argument(tool: &str, value: &serde_json::Value) -> Result<bool, ErrorData> {
+        if !ALL_DBS_TOOLS.contains(&tool) {
+            return Err(ErrorData::invalid_params(
+                format!("'{}' does not take an 'all_dbs' argument", tool),
+                None,
+            ));
+        }
+        value
+            .as_bool()
+            .ok_or_else(|| ErrorData::invalid_params("'all_dbs' must be true or false", None))
+    }
+
+    /// [`Self::dispatch_timed`], once per database with keys when `all_dbs`
+    /// is set (see [`Self::across_dbs`]).
+    async fn dispatch_dbs(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+        connection: Option<&str>,
+        all_dbs: bool,
+    ) -> Result<CallToolResult, ErrorData> {
+        if !all_dbs {
+            return self.dispatch_timed(request, context).await;
+        }
+        let summary = self
+            .across_dbs(connection, || {
+                self.dispatch_timed(request.clone(), context.clone())
+            })
+            .await
+            .map_err(|e| self.err(e))?;
+        Ok(json_result(summary))
+    }
+
+    /// [`Self::dispatch`], recording the call's latency and outcome.
+    async fn dispatch_timed(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tool = request.name.to_string();
+        let started = Instant::now();
+        let result = self.dispatch(request, context).await;
+        let ok = matches!(&result, Ok(r) if r.is_error != Some(true));
+        self.stats.record(&tool, started.elapsed(), ok);
+        result
+    }
+
+    /// Route a call to a preset or a built-in tool.
+    async fn dispatch(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let presets = self.presets();
+        if let Some(preset) = presets.iter().find(|p| p.name == request.name) {
+            return self
+                .run_preset(preset, request.arguments.unwrap_or_default(), context)
+                .await;
+        }
+        let views = self.views();
+        if let Some(view) = views.iter().find(|v| v.name == request.name) {
+            return self
+                .do_view(view, &request.arguments.unwrap_or_default())
+                .await;
+        }
+        let tcc = ToolCallContext::new(self, request, context);
+        self.tool_router.call(tcc).await
+    }
+
+    /// Run each step of `preset` through the tool router and collect the
+    /// results. A failing step is reported and does not stop the others.
+    async fn run_preset(
+        &self,
+        preset: &Preset,
+        arguments: JsonObject,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let mut steps = Vec::new();
+        for step in &preset.steps {
+            let mut step_args = step.arguments.clone();
+            if let Some(connection) = arguments.get("connection") {
+                step_args
+                    .entry("connection")
+                    .or_insert_with(|| connection.clone());
+            }
+            self.apply_tool_defaults(&step.tool, &mut step_args);
+            let request = CallToolRequestParams {
+                meta: None,
+                name: step.tool.clone().into(),
+                arguments: Some(step_args.clone()),
+                task: None,
+            };
+            let tcc = ToolCallContext::new(self, request, context.clone());
+            let outcome = match self.tool_router.call(tcc).await {
+                Ok(result) => {
+                    let texts: Vec<serde_json::Value> = result
+                        .content
+                        .iter()
+                        .filter_map(|c| c.as_text())
+                        .map(|t| {
+                            serde_json::from_str(&t.text)
+                                .unwrap_or_else(|_| serde_json::json!(t.text))
+                        })
+                        .collect();
+                    let output = match <[_; 1]>::try_from(texts) {
+                        Ok([single]) => single,
+                        Err(texts) => serde_json::json!(texts),
+                    };
+                    serde_json::json!({"result": output})
+                }
+                Err(e) => serde_json::json!({"error": e.message}),
+            };
+            let mut entry = serde_json::json!({
+                "tool": step.tool,
+                "arguments": step_args,
+            });
+            if let (Some(entry), serde_json::Value::Object(outcome)) =
+                (entry.as_object_mut(), outcome)
+            {
+                entry.extend(outcome);
+            }
+            steps.push(entry);
+        }
+
+        Ok(json_result(serde_json::json!({
+            "preset": preset.name,
+            "steps": steps,
+        })))
     }
 }
 
-#[tool_handler]
 impl ServerHandler for McpRedisServer {
+    async fn call_tool(
+        &self,
+        mut request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let Some(_call) = self.shutdown.begin() else {
+            return Err(ErrorData::internal_error("Server is shutting down", None));
+        };
+        if !self.tool_defaults().is_empty() {
+            let mut arguments = request.arguments.take().unwrap_or_default();
+            self.apply_tool_defaults(&request.name, &mut arguments);
+            request.arguments = Some(arguments);
+        }
+        // The export itself stays out of the transcript it returns
+        let Some(transcripts) = self
+            .transcripts
+            .as_ref()
+            .filter(|_| request.name != "export_session")
+        else {
+            return self.route_call(request, context).await;
+        };
+
+        let session = session_id(&context);
+        let tool = request.name.to_string();
+        let arguments = request.arguments.clone().unwrap_or_default();
+        let at_ms = time::now_ms();
+        let started = Instant::now();
+        let result = self.route_call(request, context).await;
+        let outcome = match &result {
+            Ok(r) if r.is_error == Some(true) => Err(result_text(r)),
+            Ok(r) => Ok(result_text(r)),
+            Err(e) => Err(e.message.to_string()),
+        };
+        let limits = self.limits();
+        transcripts.record(
+            session.as_deref(),
+            transcript::Call::new(
+                &tool,
+                arguments,
+                at_ms,
+                started.elapsed().as_millis() as u64,
+                outcome,
+                limits.max_transcript_result_bytes,
+            ),
+            limits.max_transcript_entries,
+        );
+        result
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult {
+            tools: self.tools(),
+            meta: None,
+            next_cursor: None,
+        })
+    }
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, ErrorData> {
+        let template = RawResourceTemplate {
+            uri_template: resource::KEY_TEMPLATE.to_string(),
+            name: "redis-key".to_string(),
+            title: Some("Redis key".to_string()),
+            description: Some(
+                "Value of a key on a configured connection, as returned by the get tool"
+                    .to_string(),
+            ),
+            mime_type: Some("application/json".to_string()),
+            icons: None,
+        };
+        let pattern = RawResourceTemplate {
+            uri_template: resource::PATTERN_TEMPLATE.to_string(),
+            name: "redis-key-pattern".to_string(),
+            title: Some("Redis key pattern".to_string()),
+            description: Some(
+                "Keys matching a glob pattern (e.g. session:*). Subscribe to get resources/updated with the URI of each matching key that changes; needs notify-keyspace-events with K on the server"
+                    .to_string(),
+            ),
+            mime_type: Some("application/json".to_string()),
+            icons: None,
+        };
+        Ok(ListResourceTemplatesResult {
+            resource_templates: vec![template.no_annotation(), pattern.no_annotation()],
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        self.read_key_resource(&request.uri).await
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        let session = session_id(&context).unwrap_or_else(|| "stdio".to_string());
+        self.subscribe_resource(&request.uri, &session, context.peer)
+            .await
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), ErrorData> {
+        let session = session_id(&context).unwrap_or_else(|| "stdio".to_string());
+        self.unsubscribe_resource(&request.uri, &session);
+        Ok(())
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, ErrorData> {
+        Ok(ListPromptsResult {
+            prompts: prompts::PROMPTS.iter().map(|p| p.to_prompt()).collect(),
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        let Some(prompt) = prompts::find(&request.name) else {
+            return Err(ErrorData::invalid_params(
+                format!("Unknown prompt '{}'", request.name),
+                None,
+            ));
+        };
+        let text = prompt
+            .render(request.arguments.unwrap_or_default())
+            .map_err(|e| ErrorData::invalid_params(e, None))?;
+        Ok(GetPromptResult {
+            description: Some(prompt.description.to_string()),
+            messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+        })
+    }
+
+    async fn complete(
+        &self,
+        request: CompleteRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CompleteResult, ErrorData> {
+        match &request.r#ref {
+            Reference::Resource(reference)
+                if reference.uri != resource::KEY_TEMPLATE
+                    && reference.uri != resource::PATTERN_TEMPLATE =>
+            {
+                return Ok(CompleteResult::default());
+            }
+            Reference::Prompt(reference) if prompts::find(&reference.name).is_none() => {
+                return Ok(CompleteResult::default());
+            }
+            _ => {}
+        }
+        let connection = request
+            .context
+            .as_ref()
+            .and_then(|c| c.get_argument("connection"))
+            .cloned();
+        let completion = self
+            .complete_argument(
+                &request.argument.name,
+                &request.argument.value,
+                connection.as_deref(),
+            )
+            .await?;
+        Ok(CompleteResult { completion })
+    }
+
+    fn get_tool(&self, name: &str) -> Option<Tool> {
+        self.tool_router
+            .get(name)
+            .cloned()
+            .or_else(|| {
+                self.presets()
+                    .iter()
+                    .find(|p| p.name == name)
+                    .map(|preset| self.preset_tool(preset))
+            })
+            .or_else(|| {
+                self.views()
+                    .iter()
+                    .find(|v| v.name == name)
+                    .map(|view| self.view_tool(view))
+            })
+            .map(|tool| self.with_trace_param(tool))
+            .map(Self::with_db_param)
+            .map(Self::with_output_schema)
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_prompts()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .enable_completions()
+                .build(),
             server_info: Implementation {
                 name: "mcp-redis".to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
                 ..Default::default()
             },
-            instructions: Some(
-                "Redis server. Tools: list_connections (instances), info (server stats), \
-                 scan_keys (find keys), get (retrieve values), key_info (metadata), \
-                 dbsize (key count), search_keys (keys with types), \
-                 get_hash_fields (hash HMGET), get_list_range (list LRANGE), \
-                 get_set_members (set/zset members), slowlog (slow queries), \
-                 client_list (connected clients)."
-                    .to_string(),
-            ),
+            instructions: Some(format!(
+                "Redis server. Tools: list_connections (instances), ping (latency and health), \
+                 server_policy (permissions and limits in effect), \
+                 info (server stats), info_delta (rates over a few seconds: ops/sec, hit ratio, network, evictions), \
+                 config_get (configuration parameters matching a glob, secrets redacted), \
+                 config_set (change an allowlisted configuration parameter, returns the previous value, requires --allow-write), \
+                 sample_stats (per-second time series of ops/sec, memory and clients for up to a minute), \
+                 scan_keys (find keys), get (retrieve values), get_multiple (several keys in one call), exists (which of many keys exist), key_info (metadata), \
+                 key_length (length of a key by type, without reading it), \
+                 save_cursor / list_cursors (save where a scan stopped under a name to resume it from another session), \
+                 dbsize (key count), keyspace_summary (key counts per database), \
+                 search_keys (keys with types), \
+                 get_hash_fields (hash HMGET), hash_overview (hash size and field sample), \
+                 get_list_range (list LRANGE), get_string_range (part of a string, GETRANGE), \
+                 get_set_members (set/zset members), \
+                 scan_set_members / scan_zset_members (cursor paging through huge sets and zsets, SSCAN/ZSCAN), \
+                 sample_members (random members of a set, hash or zset), \
+                 zset_lex_range (lexicographic zset ranges and prefix lookups), \
+                 find_member (search large sets/zsets/hashes by substring), slowlog (slow queries), \
+                 client_list (connected clients), subscribe_sample (Pub/Sub sampling), \
+                 monitor_sample (command, key and client histogram of live traffic via MONITOR), \
+                 replication_status (role, replicas, offsets and lag per connection), \
+                 check_replica_consistency (compare sampled keys between a master and a replica connection), \
+                 backlog_health (replication backlog, AOF and output buffer pressure), \
+                 client_buffer_report (clients by output buffer size, against their limits), \
+                 error_stats (error replies by prefix and failing commands), \
+                 connection_diagnostics (this server's own connection as Redis sees it, round trips, reconnects), \
+                 latency_latest / latency_history / latency_doctor (latency monitor events and advice), \
+                 latency_correlation (latency events, slowlog and persistence on one timeline), \
+                 acl_check (whether an ACL user may run a command), \
+                 acl_whoami / acl_list / acl_getuser (ACL users, their command categories and key patterns), \
+                 estimate_matching_keys (approximate pattern counts), \
+                 estimate_cardinality (distinct members across lists, sets and zsets), \
+                 namespace_census (type, size, TTL and encoding mix of a key prefix), \
+                 find_big_keys (largest keys by memory for a pattern), \
+                 analyze_namespaces (key counts and memory per top-level prefix), \
+                 memory_by_pattern (estimated memory per key pattern), \
+                 compaction_advisor (collections just above listpack/intset thresholds, with savings), \
+                 validate_key / validate_namespace (check keys against configured contracts), \
+                 rdb_scan / rdb_get (keys and values in a local RDB snapshot, compared with the live server), \
+                 cluster_info / cluster_nodes / cluster_shards (cluster topology), \
+                 cluster_keyslot (key to slot and owning node), \
+                 capture_diagnostics (incident bundle to file), \
+                 export_session (this session's tool calls and results, with --record-session), \
+                 sentinel_masters (Sentinel-monitored masters and replicas), \
+                 stream_range (stream entries by ID or time range, with decoded timestamps), \
+                 stream_autoclaim (claim stuck stream entries, requires --allow-write), \
+                 bulk_delete (plan then confirm a pattern delete across connections, requires --allow-write), \
+                 client_kill (disconnect a client by ID or address, requires --allow-write), \
+                 client_pause / client_unpause (hold writes or all commands for a bounded time, requires --allow-write), \
+                 trigger_bgsave / trigger_aof_rewrite (start a background RDB save or AOF rewrite, requires --allow-write), \
+                 create_analysis_replica (provision a read-only replica connection, requires --allow-admin), \
+                 reload_config (apply config file changes, requires --allow-admin), \
+                 replace_connection (repoint a connection at a new URL, requires --allow-admin), \
+                 set_maintenance_note (attach a note to every result for a connection until it expires, requires --allow-admin). \
+                 Key and scan tools take an optional db to work on another logical database; \
+                 scan, dbsize and namespace tools also take all_dbs to run once per database with keys. \
+                 Cost hints (cheap/medium/expensive; 'samples' means results are sampled or \
+                 estimated): {}.",
+                self.stats.cost_hints()
+            )),
         }
     }
 }
@@ -0,0 +1,124 @@
+//! Guardrails behind `config_set`: which parameters it may change, and how
+//! a value read back compares with the one set. Only parameters on the
+//! `[config_set]` allowlist can be set, and [`FORBIDDEN`] ones never can,
+//! whatever the allowlist says.
+
+use crate::glob;
+use crate::redact;
+
+/// Parameters `config_set` may change when the config file does not say
+/// otherwise: memory and eviction, slowlog and latency monitoring, client
+/// timeouts, keyspace notifications and defragmentation
+pub const DEFAULT_ALLOWED: &[&str] = &[
+    "maxmemory",
+    "maxmemory-policy",
+    "maxmemory-samples",
+    "slowlog-log-slower-than",
+    "slowlog-max-len",
+    "latency-monitor-threshold",
+    "latency-tracking",
+    "timeout",
+    "tcp-keepalive",
+    "notify-keyspace-events",
+    "hz",
+    "activedefrag",
+    "lfu-log-factor",
+    "lfu-decay-time",
+];
+
+/// Parameters that write files, open ports, change who may connect or
+/// enable dangerous commands. Secrets (see [`redact::is_sensitive_config`])
+/// are refused as well.
+pub const FORBIDDEN: &[&str] = &[
+    "dir",
+    "dbfilename",
+    "appenddirname",
+    "appendfilename",
+    "logfile",
+    "pidfile",
+    "aclfile",
+    "unixsocket",
+    "unixsocketperm",
+    "bind",
+    "port",
+    "tls-port",
+    "protected-mode",
+    "enable-debug-command",
+    "enable-module-command",
+    "enable-protected-configs",
+    "replicaof",
+    "slaveof",
+    "replica-announce-ip",
+    "cluster-config-file",
+];
+
+/// The default allowlist, owned.
+pub fn default_allowed() -> Vec<String> {
+    DEFAULT_ALLOWED.iter().map(|p| p.to_string()).collect()
+}
+
+/// Whether `parameter` may never be set, allowlisted or not.
+pub fn is_forbidden(parameter: &str) -> bool {
+    let parameter = parameter.to_ascii_lowercase();
+    FORBIDDEN.contains(&parameter.as_str()) || redact::is_sensitive_config(&parameter)
+}
+
+/// Why `parameter` may not be set under `allowed` (names or globs such as
+/// `lazyfree-*`), if it may not.
+pub fn refusal(allowed: &[String], parameter: &str) -> Option<String> {
+    let parameter = parameter.to_ascii_lowercase();
+    if is_forbidden(&parameter) {
+        return Some(format!(
+            "'{}' can never be changed with config_set",
+            parameter
+        ));
+    }
+    if parameter.contains(['*', '?', '[']) {
+        return Some("config_set takes one parameter name, not a pattern".to_string());
+    }
+    if !allowed
+        .iter()
+        .any(|a| glob::matches(&a.to_ascii_lowercase(), &parameter))
+    {
+        return Some(format!(
+            "'{}' is not on the config_set allowlist ({}); add it under [config_set] allow in the config file",
+            parameter,
+            allowed.join(", ")
+        ));
+    }
+    None
+}
+
+/// Whether `read_back` is the value `requested` as Redis reports it. Redis
+/// normalizes sizes (`100mb` reads back as `104857600`) and lower-cases
+/// enums, so both sides are compared as byte counts when they parse as one.
+pub fn same_value(requested: &str, read_back: &str) -> bool {
+    if requested.trim().eq_ignore_ascii_case(read_back.trim()) {
+        return true;
+    }
+    match (memory_bytes(requested), memory_bytes(read_back)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// A size with Redis' units: `k`/`m`/`g` are powers of 1000, `kb`/`mb`/`gb`
+/// powers of 1024.
+fn memory_bytes(value: &str) -> Option<u64> {
+    let value = value.trim().to_ascii_lowercase();
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let number: u64 = value[..digits].parse().ok()?;
+    let unit = match &value[digits..] {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    number.checked_mul(unit)
+}
@@ -0,0 +1,97 @@
+//! Graceful shutdown: new tool calls are refused, calls in flight get a
+//! deadline to finish, and long-running listeners and background loops stop.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{watch, Notify};
+
+/// Shared by every clone of the server.
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    in_flight: AtomicUsize,
+    /// Signalled whenever a call finishes
+    finished: Notify,
+    /// Flips to true once draining starts
+    stopping: watch::Sender<bool>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                in_flight: AtomicUsize::new(0),
+                finished: Notify::new(),
+                stopping: watch::channel(false).0,
+            }),
+        }
+    }
+}
+
+/// Counts a tool call as in flight until dropped.
+#[derive(Debug)]
+pub struct CallGuard {
+    inner: Arc<Inner>,
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        self.inner.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.inner.finished.notify_waiters();
+    }
+}
+
+impl Shutdown {
+    /// Register a tool call, or `None` once draining has started.
+    pub fn begin(&self) -> Option<CallGuard> {
+        self.inner.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = CallGuard {
+            inner: self.inner.clone(),
+        };
+        // Checked after counting the call, so `drain` never misses it
+        if self.is_stopping() {
+            return None;
+        }
+        Some(guard)
+    }
+
+    pub fn is_stopping(&self) -> bool {
+        *self.inner.stopping.borrow()
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once draining starts. Long-running tools and background
+    /// loops select on it to end early.
+    pub async fn stopping(&self) {
+        let mut stopping = self.inner.stopping.subscribe();
+        let _ = stopping.wait_for(|stopping| *stopping).await;
+    }
+
+    /// Refuse new calls and wait up to `deadline` for the ones in flight.
+    /// Returns how many were still running at the deadline.
+    pub async fn drain(&self, deadline: Duration) -> usize {
+        self.inner.stopping.send_replace(true);
+        let wait = async {
+            loop {
+                let finished = self.inner.finished.notified();
+                tokio::pin!(finished);
+                finished.as_mut().enable();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                finished.await;
+            }
+        };
+        let _ = tokio::time::timeout(deadline, wait).await;
+        self.in_flight()
+    }
+}
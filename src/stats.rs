@@ -0,0 +1,212 @@
+//! Per-tool call telemetry and the cost hints built from it.
+//!
+//! Every tool call is timed. The instructions returned by `get_info` tell
+//! models how expensive each tool is so they can plan investigations: the
+//! observed average latency once a tool has been called, otherwise the
+//! baseline class from [`COST_HINTS`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Calls needed before observed latency replaces the baseline class
+const MIN_OBSERVED_CALLS: u64 = 3;
+
+/// Relative cost of a tool call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Cost {
+    Cheap,
+    Medium,
+    Expensive,
+}
+
+impl Cost {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Cost::Cheap => "cheap",
+            Cost::Medium => "medium",
+            Cost::Expensive => "expensive",
+        }
+    }
+
+    /// Class for an observed average latency.
+    pub fn from_latency(avg: Duration) -> Self {
+        if avg < Duration::from_millis(20) {
+            Cost::Cheap
+        } else if avg < Duration::from_millis(500) {
+            Cost::Medium
+        } else {
+            Cost::Expensive
+        }
+    }
+}
+
+/// Baseline cost of a built-in tool, before any telemetry exists.
+#[derive(Debug)]
+pub struct CostHint {
+    pub tool: &'static str,
+    pub cost: Cost,
+    /// Results are a sample or estimate rather than exhaustive
+    pub samples: bool,
+}
+
+const fn hint(tool: &'static str, cost: Cost, samples: bool) -> CostHint {
+    CostHint {
+        tool,
+        cost,
+        samples,
+    }
+}
+
+pub const COST_HINTS: &[CostHint] = &[
+    hint("list_connections", Cost::Cheap, false),
+    hint("server_policy", Cost::Cheap, false),
+    hint("ping", Cost::Cheap, false),
+    hint("info", Cost::Cheap, false),
+    hint("config_get", Cost::Cheap, false),
+    hint("config_set", Cost::Cheap, false),
+    hint("get", Cost::Cheap, false),
+    hint("get_multiple", Cost::Medium, false),
+    hint("exists", Cost::Cheap, false),
+    hint("key_info", Cost::Cheap, false),
+    hint("key_length", Cost::Cheap, false),
+    hint("dbsize", Cost::Cheap, false),
+    hint("keyspace_summary", Cost::Cheap, false),
+    hint("get_hash_fields", Cost::Cheap, false),
+    hint("get_list_range", Cost::Cheap, false),
+    hint("get_string_range", Cost::Cheap, false),
+    hint("slowlog", Cost::Cheap, false),
+    hint("error_stats", Cost::Cheap, false),
+    hint("connection_diagnostics", Cost::Cheap, false),
+    hint("latency_latest", Cost::Cheap, false),
+    hint("latency_history", Cost::Cheap, false),
+    hint("latency_doctor", Cost::Cheap, false),
+    hint("acl_check", Cost::Cheap, false),
+    hint("acl_whoami", Cost::Cheap, false),
+    hint("acl_list", Cost::Cheap, false),
+    hint("acl_getuser", Cost::Cheap, false),
+    hint("cluster_info", Cost::Cheap, false),
+    hint("cluster_keyslot", Cost::Cheap, false),
+    hint("hash_overview", Cost::Medium, true),
+    hint("get_set_members", Cost::Medium, false),
+    hint("scan_set_members", Cost::Cheap, false),
+    hint("scan_zset_members", Cost::Cheap, false),
+    hint("sample_members", Cost::Cheap, true),
+    hint("zset_lex_range", Cost::Cheap, false),
+    hint("estimate_cardinality", Cost::Expensive, true),
+    hint("find_member", Cost::Expensive, false),
+    hint("client_list", Cost::Medium, false),
+    hint("replication_status", Cost::Cheap, false),
+    hint("check_replica_consistency", Cost::Expensive, true),
+    hint("backlog_health", Cost::Medium, false),
+    hint("client_buffer_report", Cost::Medium, false),
+    hint("latency_correlation", Cost::Medium, false),
+    hint("cluster_nodes", Cost::Medium, false),
+    hint("cluster_shards", Cost::Medium, false),
+    hint("sentinel_masters", Cost::Medium, false),
+    hint("stream_range", Cost::Cheap, false),
+    hint("validate_key", Cost::Cheap, false),
+    hint("stream_autoclaim", Cost::Medium, false),
+    hint("estimate_matching_keys", Cost::Medium, true),
+    hint("namespace_census", Cost::Expensive, true),
+    hint("analyze_namespaces", Cost::Expensive, true),
+    hint("memory_by_pattern", Cost::Expensive, true),
+    hint("compaction_advisor", Cost::Expensive, true),
+    hint("find_big_keys", Cost::Expensive, false),
+    hint("validate_namespace", Cost::Expensive, true),
+    hint("scan_keys", Cost::Expensive, false),
+    hint("search_keys", Cost::Expensive, false),
+    hint("subscribe_sample", Cost::Expensive, true),
+    hint("monitor_sample", Cost::Expensive, true),
+    hint("info_delta", Cost::Expensive, false),
+    hint("sample_stats", Cost::Expensive, false),
+    hint("capture_diagnostics", Cost::Expensive, true),
+    hint("export_session", Cost::Cheap, false),
+    hint("save_cursor", Cost::Cheap, false),
+    hint("list_cursors", Cost::Cheap, false),
+    hint("rdb_scan", Cost::Expensive, false),
+    hint("rdb_get", Cost::Expensive, false),
+    hint("bulk_delete", Cost::Expensive, false),
+    hint("trigger_bgsave", Cost::Medium, false),
+    hint("client_kill", Cost::Cheap, false),
+    hint("client_pause", Cost::Cheap, false),
+    hint("client_unpause", Cost::Cheap, false),
+    hint("trigger_aof_rewrite", Cost::Medium, false),
+    hint("create_analysis_replica", Cost::Expensive, false),
+    hint("reload_config", Cost::Medium, false),
+    hint("replace_connection", Cost::Medium, false),
+    hint("set_maintenance_note", Cost::Cheap, false),
+];
+
+/// Aggregated calls of one tool.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolStat {
+    pub calls: u64,
+    pub errors: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl ToolStat {
+    pub fn average(&self) -> Duration {
+        if self.calls == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(self.total.as_secs_f64() / self.calls as f64)
+    }
+}
+
+/// Call telemetry shared by all clones of the server.
+#[derive(Debug, Clone, Default)]
+pub struct ToolStats {
+    inner: Arc<Mutex<HashMap<String, ToolStat>>>,
+}
+
+impl ToolStats {
+    pub fn record(&self, tool: &str, elapsed: Duration, ok: bool) {
+        let mut stats = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let stat = stats.entry(tool.to_string()).or_default();
+        stat.calls += 1;
+        if !ok {
+            stat.errors += 1;
+        }
+        stat.total += elapsed;
+        stat.max = stat.max.max(elapsed);
+    }
+
+    pub fn get(&self, tool: &str) -> Option<ToolStat> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(tool)
+            .cloned()
+    }
+
+    /// One entry per built-in tool: its cost class, whether it samples, and
+    /// the observed latency once enough calls were recorded.
+    pub fn cost_hints(&self) -> String {
+        COST_HINTS
+            .iter()
+            .map(|hint| {
+                let mut line = format!("{}: ", hint.tool);
+                match self.get(hint.tool) {
+                    Some(stat) if stat.calls >= MIN_OBSERVED_CALLS => {
+                        let avg = stat.average();
+                        line.push_str(&format!(
+                            "{} (observed avg {}ms over {} calls)",
+                            Cost::from_latency(avg).as_str(),
+                            avg.as_millis(),
+                            stat.calls
+                        ));
+                    }
+                    _ => line.push_str(hint.cost.as_str()),
+                }
+                if hint.samples {
+                    line.push_str(", samples");
+                }
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
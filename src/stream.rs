@@ -0,0 +1,72 @@
+//! Stream entry IDs: decoding them into timestamps, and building range
+//! bounds from human times.
+//!
+//! An entry ID is `<milliseconds>-<sequence>`, where the milliseconds are the
+//! server time the entry was added. Tools return both forms so clients do not
+//! have to decode IDs themselves.
+
+use crate::time;
+
+/// A parsed `<ms>-<seq>` entry ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    /// Parse `1700000000000-3`, or `1700000000000` (sequence 0).
+    pub fn parse(raw: &str) -> Option<Self> {
+        let (ms, seq) = match raw.split_once('-') {
+            Some((ms, seq)) => (ms, seq.parse().ok()?),
+            None => (raw, 0),
+        };
+        Some(Self {
+            ms: ms.parse().ok()?,
+            seq,
+        })
+    }
+}
+
+impl std::fmt::Display for StreamId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+/// `{"id", "timestamp", "seq"}` for an entry ID; just `{"id"}` when it does
+/// not parse.
+pub fn describe_id(id: &str) -> serde_json::Value {
+    match StreamId::parse(id) {
+        Some(parsed) => serde_json::json!({
+            "id": id,
+            "timestamp": time::iso8601_ms(parsed.ms),
+            "seq": parsed.seq,
+        }),
+        None => serde_json::json!({ "id": id }),
+    }
+}
+
+/// Turn a range bound into something XRANGE accepts. Passed through: `-`,
+/// `+`, entry IDs, and exclusive `(` IDs. Converted to a millisecond ID: ISO
+/// 8601 times (`2024-05-01T12:00:00Z`), `now`, and relative times
+/// (`10 minutes ago`).
+pub fn range_bound(raw: &str, now_ms: u64) -> Result<String, String> {
+    let raw = raw.trim();
+    if raw == "-" || raw == "+" {
+        return Ok(raw.to_string());
+    }
+    let id = raw.strip_prefix('(').unwrap_or(raw);
+    if StreamId::parse(id).is_some() {
+        return Ok(raw.to_string());
+    }
+    time::parse_relative(raw, now_ms)
+        .or_else(|| time::parse_iso8601(raw))
+        .map(|ms| ms.to_string())
+        .ok_or_else(|| {
+            format!(
+                "cannot read '{}' as a stream ID, ISO 8601 time, or relative time like '10 minutes ago'",
+                raw
+            )
+        })
+}
@@ -0,0 +1,125 @@
+//! UTC calendar conversions and human time expressions, without a date
+//! library.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Milliseconds since the epoch.
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// `(year, month, day)` for a count of days since 1970-01-01.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Days since 1970-01-01 for a calendar date (inverse of [`civil_from_days`]).
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// `2024-05-01T12:00:00.123Z` for milliseconds since the epoch.
+pub fn iso8601_ms(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60,
+        ms % 1000
+    )
+}
+
+/// Parse `2024-05-01`, `2024-05-01T12:00:00Z` or `2024-05-01T12:00:00.123Z`
+/// (UTC; the `Z` is optional) into milliseconds since the epoch.
+pub fn parse_iso8601(raw: &str) -> Option<u64> {
+    let raw = raw.trim().trim_end_matches('Z');
+    let (date, time) = match raw.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (raw, None),
+    };
+    let mut parts = date.split('-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let mut ms: u64 = 0;
+    if let Some(time) = time {
+        let (hms, frac) = match time.split_once('.') {
+            Some((hms, frac)) => (hms, Some(frac)),
+            None => (time, None),
+        };
+        let mut fields = hms.split(':');
+        let hours: u64 = fields.next()?.parse().ok()?;
+        let minutes: u64 = fields.next()?.parse().ok()?;
+        let seconds: u64 = fields.next().unwrap_or("0").parse().ok()?;
+        if fields.next().is_some() || hours > 23 || minutes > 59 || seconds > 60 {
+            return None;
+        }
+        ms = (hours * 3600 + minutes * 60 + seconds) * 1000;
+        if let Some(frac) = frac {
+            if frac.is_empty() || !frac.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            let digits: String = frac.chars().chain("000".chars()).take(3).collect();
+            ms += digits.parse::<u64>().ok()?;
+        }
+    }
+
+    let days = days_from_civil(year, month, day);
+    let day_ms = u64::try_from(days).ok()?.checked_mul(86_400_000)?;
+    day_ms.checked_add(ms)
+}
+
+/// Parse `now` or a relative time such as `10 minutes ago`, `2h ago` or
+/// `90s ago` into milliseconds since the epoch, relative to `now_ms`.
+pub fn parse_relative(raw: &str, now_ms: u64) -> Option<u64> {
+    let raw = raw.trim().to_ascii_lowercase();
+    if raw == "now" {
+        return Some(now_ms);
+    }
+    let amount = raw.strip_suffix("ago")?.trim();
+    let split = amount
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(amount.len());
+    let (number, unit) = amount.split_at(split);
+    let number: u64 = number.parse().ok()?;
+    let unit_ms: u64 = match unit.trim() {
+        "ms" | "millisecond" | "milliseconds" => 1,
+        "s" | "sec" | "secs" | "second" | "seconds" => 1000,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60_000,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 3_600_000,
+        "d" | "day" | "days" => 86_400_000,
+        "w" | "week" | "weeks" => 604_800_000,
+        _ => return None,
+    };
+    Some(now_ms.saturating_sub(number.checked_mul(unit_ms)?))
+}
@@ -0,0 +1,12 @@
+//! TLS connection settings.
+
+use redis::{ConnectionAddr, ConnectionInfo};
+
+/// Disable certificate verification on a TLS address (`--tls-insecure`).
+/// Non-TLS addresses are returned unchanged.
+pub fn insecure(mut info: ConnectionInfo) -> ConnectionInfo {
+    if let ConnectionAddr::TcpTls { insecure, .. } = &mut info.addr {
+        *insecure = true;
+    }
+    info
+}
@@ -0,0 +1,301 @@
+//! Per-call protocol tracing (debug).
+//!
+//! When a tool call is run inside [`CallTrace::scope`], every command sent
+//! through a [`TracedConnection`] is recorded with its (redacted) arguments and
+//! round-trip time, so slow composite tools can be broken down command by
+//! command.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use redis::aio::{ConnectionLike, ConnectionManager};
+use redis::{Arg, Cmd, Pipeline, RedisFuture, Value};
+
+/// Numbers longer than this are never shown, even where a count is expected
+const MAX_VISIBLE_ARG_LEN: usize = 16;
+
+tokio::task_local! {
+    static ACTIVE_TRACE: CallTrace;
+}
+
+/// Commands recorded during a single tool call.
+#[derive(Clone)]
+pub struct CallTrace {
+    started: Instant,
+    entries: Arc<Mutex<Vec<serde_json::Value>>>,
+}
+
+impl Default for CallTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CallTrace {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            entries: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Run `fut` with this trace active. Connections created inside the
+    /// future via [`TracedConnection::new`] record into it.
+    pub async fn scope<F: Future>(&self, fut: F) -> F::Output {
+        ACTIVE_TRACE.scope(self.clone(), fut).await
+    }
+
+    /// The trace active for the current task, if any.
+    fn current() -> Option<CallTrace> {
+        ACTIVE_TRACE.try_with(|t| t.clone()).ok()
+    }
+
+    fn record(&self, entry: serde_json::Value) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(entry);
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let entries = self.entries.lock().map(|e| e.clone()).unwrap_or_default();
+        let redis_us: u64 = entries
+            .iter()
+            .filter_map(|e| e["duration_us"].as_u64())
+            .sum();
+        serde_json::json!({
+            "trace": {
+                "commands": entries,
+                "command_count": entries.len(),
+                "redis_time_us": redis_us,
+                "total_time_us": self.started.elapsed().as_micros() as u64,
+            }
+        })
+    }
+}
+
+/// Render a command for the trace: the command name and its first argument
+/// (usually the key or subcommand) are shown, later arguments only when they
+/// are option keywords where the command takes options, or numbers where it
+/// takes a count, cursor, range index or expiry. Everything else is replaced
+/// by its size.
+pub fn redact_command(cmd: &Cmd) -> Vec<String> {
+    let mut name = String::new();
+    let mut options = None;
+    // Operands still to come after an option keyword such as MATCH or LIMIT
+    let mut values_due = 0;
+    let mut numbers_due = 0;
+    let mut rendered = Vec::new();
+    for (i, arg) in cmd.args_iter().enumerate() {
+        let bytes = match arg {
+            Arg::Simple(bytes) => bytes,
+            Arg::Cursor => {
+                rendered.push("<cursor>".to_string());
+                continue;
+            }
+        };
+        let shown = if i == 0 {
+            name = String::from_utf8_lossy(bytes).to_uppercase();
+            rendered.push(name.clone());
+            continue;
+        } else if i == 1 {
+            options = command_options(&name, &String::from_utf8_lossy(bytes).to_uppercase());
+            true
+        } else if values_due > 0 {
+            values_due -= 1;
+            false
+        } else if numbers_due > 0 {
+            numbers_due -= 1;
+            is_number(bytes)
+        } else if let Some(operand) = option_operand(options, i, bytes) {
+            match operand {
+                Operand::Flag => {}
+                Operand::Value => values_due = 1,
+                Operand::Numbers(n) => numbers_due = n,
+            }
+            true
+        } else {
+            is_number(bytes) && numeric_position(&name, i)
+        };
+        rendered.push(if shown {
+            String::from_utf8_lossy(bytes).into_owned()
+        } else {
+            format!("<{} bytes>", bytes.len())
+        });
+    }
+    rendered
+}
+
+/// What follows an option keyword
+#[derive(Clone, Copy)]
+enum Operand {
+    /// Nothing: the keyword is a flag such as WITHSCORES
+    Flag,
+    /// A value such as a MATCH pattern, hidden like any other value
+    Value,
+    /// Numbers such as a COUNT or a LIMIT offset and count, shown
+    Numbers(usize),
+}
+
+type Options = (usize, &'static [(&'static str, Operand)]);
+
+/// The options `command` (with first argument `first`) takes: the position
+/// they start at and their keywords. Keywords anywhere else are values.
+fn command_options(command: &str, first: &str) -> Option<Options> {
+    use Operand::*;
+    let options: Options = match (command, first) {
+        ("SCAN", _) => (
+            2,
+            &[("MATCH", Value), ("COUNT", Numbers(1)), ("TYPE", Value)],
+        ),
+        ("SSCAN" | "HSCAN" | "ZSCAN", _) => (
+            3,
+            &[("MATCH", Value), ("COUNT", Numbers(1)), ("NOVALUES", Flag)],
+        ),
+        ("SET", _) => (
+            3,
+            &[
+                ("EX", Numbers(1)),
+                ("PX", Numbers(1)),
+                ("EXAT", Numbers(1)),
+                ("PXAT", Numbers(1)),
+                ("NX", Flag),
+                ("XX", Flag),
+                ("KEEPTTL", Flag),
+                ("GET", Flag),
+            ],
+        ),
+        ("EXPIRE" | "PEXPIRE", _) => (3, &[("NX", Flag), ("XX", Flag), ("GT", Flag), ("LT", Flag)]),
+        ("ZRANGE", _) => (
+            4,
+            &[
+                ("BYSCORE", Flag),
+                ("BYLEX", Flag),
+                ("REV", Flag),
+                ("LIMIT", Numbers(2)),
+                ("WITHSCORES", Flag),
+            ],
+        ),
+        ("ZREVRANGE", _) => (4, &[("WITHSCORES", Flag)]),
+        ("ZRANGEBYSCORE" | "ZREVRANGEBYSCORE", _) => {
+            (4, &[("LIMIT", Numbers(2)), ("WITHSCORES", Flag)])
+        }
+        ("ZRANGEBYLEX" | "ZREVRANGEBYLEX", _) => (4, &[("LIMIT", Numbers(2))]),
+        ("HRANDFIELD", _) => (3, &[("WITHVALUES", Flag)]),
+        ("ZRANDMEMBER", _) => (3, &[("WITHSCORES", Flag)]),
+        ("XRANGE" | "XREVRANGE", _) => (4, &[("COUNT", Numbers(1))]),
+        ("XAUTOCLAIM", _) => (6, &[("COUNT", Numbers(1)), ("JUSTID", Flag)]),
+        ("MEMORY", "USAGE") => (3, &[("SAMPLES", Numbers(1))]),
+        ("CLIENT", "LIST") => (2, &[("TYPE", Value), ("ID", Numbers(1))]),
+        ("CLIENT", "KILL") => (
+            2,
+            &[
+                ("ID", Numbers(1)),
+                ("ADDR", Value),
+                ("TYPE", Value),
+                ("SKIPME", Value),
+            ],
+        ),
+        ("CLIENT", "PAUSE") => (3, &[("WRITE", Flag), ("ALL", Flag)]),
+        _ => return None,
+    };
+    Some(options)
+}
+
+/// The operand of argument `i` if it is one of the command's option keywords
+fn option_operand(options: Option<Options>, i: usize, arg: &[u8]) -> Option<Operand> {
+    let (start, keywords) = options?;
+    if i < start {
+        return None;
+    }
+    keywords
+        .iter()
+        .find(|(keyword, _)| arg.eq_ignore_ascii_case(keyword.as_bytes()))
+        .map(|(_, operand)| *operand)
+}
+
+fn is_number(arg: &[u8]) -> bool {
+    let digits = arg.strip_prefix(b"-").unwrap_or(arg);
+    !digits.is_empty()
+        && digits.len() <= MAX_VISIBLE_ARG_LEN
+        && digits.iter().all(u8::is_ascii_digit)
+}
+
+/// Whether argument `i` of `command` is a cursor, count or range index
+/// rather than a value.
+fn numeric_position(command: &str, i: usize) -> bool {
+    match command {
+        "SSCAN" | "HSCAN" | "ZSCAN" | "SRANDMEMBER" | "HRANDFIELD" | "ZRANDMEMBER" | "EXPIRE"
+        | "PEXPIRE" | "LINDEX" => i == 2,
+        "LRANGE" | "ZRANGE" | "ZREVRANGE" | "GETRANGE" | "LTRIM" => i == 2 || i == 3,
+        "CLIENT" => i == 2,
+        _ => false,
+    }
+}
+
+/// A ConnectionManager that records commands into the active [`CallTrace`].
+/// Without an active trace it is a zero-cost pass-through.
+#[derive(Clone)]
+pub struct TracedConnection {
+    inner: ConnectionManager,
+    trace: Option<CallTrace>,
+}
+
+impl TracedConnection {
+    pub fn new(inner: ConnectionManager) -> Self {
+        Self {
+            inner,
+            trace: CallTrace::current(),
+        }
+    }
+}
+
+impl ConnectionLike for TracedConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        Box::pin(async move {
+            let Some(trace) = self.trace.clone() else {
+                return self.inner.req_packed_command(cmd).await;
+            };
+            let started = Instant::now();
+            let result = self.inner.req_packed_command(cmd).await;
+            trace.record(serde_json::json!({
+                "command": redact_command(cmd),
+                "duration_us": started.elapsed().as_micros() as u64,
+                "ok": result.is_ok(),
+            }));
+            result
+        })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        pipeline: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        Box::pin(async move {
+            let Some(trace) = self.trace.clone() else {
+                return self
+                    .inner
+                    .req_packed_commands(pipeline, offset, count)
+                    .await;
+            };
+            let started = Instant::now();
+            let result = self
+                .inner
+                .req_packed_commands(pipeline, offset, count)
+                .await;
+            let commands: Vec<Vec<String>> = pipeline.cmd_iter().map(redact_command).collect();
+            trace.record(serde_json::json!({
+                "pipeline": commands,
+                "duration_us": started.elapsed().as_micros() as u64,
+                "ok": result.is_ok(),
+            }));
+            result
+        })
+    }
+
+    fn get_db(&self) -> i64 {
+        self.inner.get_db()
+    }
+}
@@ -0,0 +1,198 @@
+//! Session transcripts: with `--record-session`, every tool call of an MCP
+//! session is kept in order with its arguments and result, so
+//! `export_session` can attach an investigation to a postmortem verbatim.
+//! Transcripts live in server memory only, one per session (a single one
+//! over stdio).
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Map, Value};
+
+use crate::budget;
+
+/// Sessions kept at once; the one idle the longest is dropped first
+pub const MAX_SESSIONS: usize = 100;
+
+/// One tool call as it was made and answered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    /// Milliseconds since the epoch
+    pub at_ms: u64,
+    pub tool: String,
+    pub arguments: Map<String, Value>,
+    pub duration_ms: u64,
+    pub ok: bool,
+    /// Result text, or the error message, cut to the size cap
+    pub output: String,
+    /// Size of the full output in bytes
+    pub output_bytes: usize,
+}
+
+impl Call {
+    /// A call whose output is cut to `max_output_bytes`.
+    pub fn new(
+        tool: &str,
+        arguments: Map<String, Value>,
+        at_ms: u64,
+        duration_ms: u64,
+        outcome: Result<String, String>,
+        max_output_bytes: usize,
+    ) -> Self {
+        let ok = outcome.is_ok();
+        let output = outcome.unwrap_or_else(|e| e);
+        let output_bytes = output.len();
+        let output = if output_bytes > max_output_bytes {
+            budget::utf8_prefix(&output.as_bytes()[..max_output_bytes])
+        } else {
+            output
+        };
+        Self {
+            at_ms,
+            tool: tool.to_string(),
+            arguments,
+            duration_ms,
+            ok,
+            output,
+            output_bytes,
+        }
+    }
+
+    fn truncated(&self) -> bool {
+        self.output.len() < self.output_bytes
+    }
+}
+
+/// The calls of one session, oldest first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    pub calls: VecDeque<Call>,
+    /// Oldest calls dropped to stay under the entry cap
+    pub dropped: usize,
+}
+
+impl Transcript {
+    /// `{session, calls, count, dropped_calls}`, each call with its output
+    /// parsed back to JSON when it is JSON.
+    pub fn to_json(&self, session: Option<&str>) -> Value {
+        let calls: Vec<Value> = self
+            .calls
+            .iter()
+            .enumerate()
+            .map(|(i, call)| {
+                let output = if call.truncated() {
+                    json!(call.output)
+                } else {
+                    serde_json::from_str(&call.output).unwrap_or_else(|_| json!(call.output))
+                };
+                let mut item = json!({
+                    "seq": self.dropped + i + 1,
+                    "at": crate::time::iso8601_ms(call.at_ms),
+                    "tool": call.tool,
+                    "arguments": call.arguments,
+                    "duration_ms": call.duration_ms,
+                    "status": if call.ok { "ok" } else { "error" },
+                });
+                item[if call.ok { "result" } else { "error" }] = output;
+                if call.truncated() {
+                    item["truncated_from_bytes"] = json!(call.output_bytes);
+                }
+                item
+            })
+            .collect();
+        json!({
+            "session": session,
+            "count": calls.len(),
+            "dropped_calls": self.dropped,
+            "calls": calls,
+        })
+    }
+
+    /// The transcript as a markdown document, one section per call.
+    pub fn to_markdown(&self, session: Option<&str>) -> String {
+        let mut out = format!("# mcp-redis session {}\n\n", session.unwrap_or("(stdio)"));
+        if self.dropped > 0 {
+            out.push_str(&format!(
+                "_{} earlier calls were dropped to stay under the entry cap._\n\n",
+                self.dropped
+            ));
+        }
+        for (i, call) in self.calls.iter().enumerate() {
+            out.push_str(&format!(
+                "## {}. `{}` at {} ({} ms, {})\n\n",
+                self.dropped + i + 1,
+                call.tool,
+                crate::time::iso8601_ms(call.at_ms),
+                call.duration_ms,
+                if call.ok { "ok" } else { "error" }
+            ));
+            let arguments =
+                serde_json::to_string_pretty(&call.arguments).unwrap_or_else(|_| "{}".to_string());
+            out.push_str(&format!("Arguments:\n\n```json\n{}\n```\n\n", arguments));
+            out.push_str(&format!(
+                "{}:\n\n```\n{}\n```\n\n",
+                if call.ok { "Result" } else { "Error" },
+                call.output
+            ));
+            if call.truncated() {
+                out.push_str(&format!(
+                    "_Output cut from {} bytes._\n\n",
+                    call.output_bytes
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Transcripts by session id (`None` over stdio), shared by all clones of
+/// the server.
+#[derive(Debug, Clone, Default)]
+pub struct Transcripts {
+    inner: Arc<Mutex<HashMap<Option<String>, Transcript>>>,
+}
+
+impl Transcripts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `call` to the transcript of `session`, dropping its oldest
+    /// calls past `max_entries`.
+    pub fn record(&self, session: Option<&str>, call: Call, max_entries: usize) {
+        let mut sessions = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let key = session.map(str::to_string);
+        if !sessions.contains_key(&key) && sessions.len() >= MAX_SESSIONS {
+            let idle = sessions
+                .iter()
+                .min_by_key(|(_, t)| t.calls.back().map_or(0, |c| c.at_ms))
+                .map(|(k, _)| k.clone());
+            if let Some(idle) = idle {
+                sessions.remove(&idle);
+            }
+        }
+        let transcript = sessions.entry(key).or_default();
+        transcript.calls.push_back(call);
+        while transcript.calls.len() > max_entries.max(1) {
+            transcript.calls.pop_front();
+            transcript.dropped += 1;
+        }
+    }
+
+    /// A copy of the transcript of `session`.
+    pub fn get(&self, session: Option<&str>) -> Option<Transcript> {
+        let sessions = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.get(&session.map(str::to_string)).cloned()
+    }
+}
+
+/// How `export_session` renders a transcript.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    /// Calls as JSON, results parsed back to JSON
+    #[default]
+    Json,
+    /// A markdown document, one section per call
+    Markdown,
+}
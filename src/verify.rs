@@ -0,0 +1,65 @@
+//! Read-your-writes checks for write tools. Right after a write, the state
+//! it touched is read back (a bounded number of items) and compared with
+//! what the write intended, so the result shows the post-state and any
+//! concurrent overwrite without a follow-up read.
+
+use serde_json::Value;
+
+/// Items read back per check; the rest are counted as unchecked
+pub const MAX_VERIFY_ITEMS: usize = 100;
+
+/// Post-state read back after a write, item by item.
+#[derive(Debug, Clone)]
+pub struct Verification {
+    intended: String,
+    post_state: Vec<Value>,
+    mismatches: Vec<Value>,
+    unchecked: usize,
+}
+
+impl Verification {
+    /// A check of the state described by `intended`, e.g. "entries owned by
+    /// consumer 'worker-2'".
+    pub fn new(intended: impl Into<String>) -> Self {
+        Self {
+            intended: intended.into(),
+            post_state: Vec::new(),
+            mismatches: Vec::new(),
+            unchecked: 0,
+        }
+    }
+
+    /// Record one item as read back, and whether it is in the intended state.
+    pub fn observe(&mut self, state: Value, matches: bool) {
+        if !matches {
+            self.mismatches.push(state.clone());
+        }
+        self.post_state.push(state);
+    }
+
+    /// Count `n` written items that were not read back.
+    pub fn skip(&mut self, n: usize) {
+        self.unchecked += n;
+    }
+
+    /// Whether every item read back is in the intended state.
+    pub fn matches(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut json = serde_json::json!({
+            "intended": self.intended,
+            "matches": self.matches(),
+            "checked": self.post_state.len(),
+            "post_state": self.post_state,
+        });
+        if !self.mismatches.is_empty() {
+            json["mismatches"] = Value::Array(self.mismatches.clone());
+        }
+        if self.unchecked > 0 {
+            json["unchecked"] = serde_json::json!(self.unchecked);
+        }
+        json
+    }
+}
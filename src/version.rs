@@ -0,0 +1,108 @@
+//! Server version and flavor detection, checked once at connect time.
+
+use redis::aio::ConnectionLike;
+
+use crate::info;
+
+/// Oldest Redis version the tools are written against (MEMORY USAGE, UNLINK,
+/// SENTINEL REPLICAS and the current SCAN guarantees).
+pub const MIN_SUPPORTED_VERSION: Version = Version(5, 0, 0);
+
+/// A `major.minor.patch` version number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version(pub u32, pub u32, pub u32);
+
+impl Version {
+    /// Parse `7.2.4`, `7.2` or `7`. Trailing non-numeric suffixes
+    /// (`6.2.6-v3`) are ignored.
+    pub fn parse(raw: &str) -> Option<Self> {
+        let mut parts = raw.trim().split('.').map(|p| {
+            let digits: String = p.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse::<u32>().ok()
+        });
+        let major = parts.next()??;
+        let minor = parts.next().flatten().unwrap_or(0);
+        let patch = parts.next().flatten().unwrap_or(0);
+        Some(Version(major, minor, patch))
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+/// What a connection reported about itself in INFO server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerVersion {
+    /// `redis`, `valkey`, `dragonfly` or `keydb`
+    pub flavor: String,
+    /// Version string as reported by the server
+    pub version: String,
+    /// Redis-compatible version, used for the minimum version check
+    pub redis_version: Option<Version>,
+}
+
+impl ServerVersion {
+    /// Build from the fields of an INFO server reply.
+    pub fn from_info(fields: &serde_json::Map<String, serde_json::Value>) -> Self {
+        let field = |name: &str| -> Option<String> {
+            fields.get(name).map(|v| match v {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+        };
+        let redis_version = field("redis_version");
+        let (flavor, version) = if let Some(v) = field("dragonfly_version") {
+            ("dragonfly", Some(v))
+        } else if let Some(v) = field("valkey_version") {
+            ("valkey", Some(v))
+        } else if field("server_name").as_deref() == Some("valkey") {
+            ("valkey", redis_version.clone())
+        } else if fields.contains_key("mvcc_depth") {
+            ("keydb", redis_version.clone())
+        } else {
+            ("redis", redis_version.clone())
+        };
+        Self {
+            flavor: flavor.to_string(),
+            version: version.unwrap_or_else(|| "unknown".to_string()),
+            redis_version: redis_version.as_deref().and_then(Version::parse),
+        }
+    }
+
+    /// Whether the server is at least `min` (unknown versions never are).
+    pub fn satisfies(&self, min: Version) -> bool {
+        self.redis_version.is_some_and(|v| v >= min)
+    }
+
+    /// Compatibility warnings to surface in logs and `list_connections`.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        match self.redis_version {
+            Some(v) if v < MIN_SUPPORTED_VERSION => warnings.push(format!(
+                "Redis {} is below the supported minimum {}; some tools may fail or behave differently",
+                v, MIN_SUPPORTED_VERSION
+            )),
+            Some(_) => {}
+            None => warnings.push("Could not determine the Redis version".to_string()),
+        }
+        match self.flavor.as_str() {
+            "dragonfly" => warnings.push(
+                "Dragonfly: SCAN cursors are not hash-table positions, so estimate_matching_keys samples are less uniform; OBJECT ENCODING and DEBUG are limited".to_string(),
+            ),
+            "keydb" => warnings.push(
+                "KeyDB: active-replica and multi-master setups may report keys on several nodes".to_string(),
+            ),
+            _ => {}
+        }
+        warnings
+    }
+}
+
+/// Query INFO server on `conn` and identify the server.
+pub async fn detect<C: ConnectionLike + Send>(conn: &mut C) -> redis::RedisResult<ServerVersion> {
+    let raw: String = redis::cmd("INFO").arg("server").query_async(conn).await?;
+    Ok(ServerVersion::from_info(&info::parse_fields(&raw)))
+}
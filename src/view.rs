@@ -0,0 +1,147 @@
+//! Views from the config file: a named set of related keys, built from the
+//! tool's arguments and read in one pipeline, so a lookup such as
+//! `user_overview(user_id)` is one call instead of one per key.
+
+use redis::Value;
+use serde_json::{json, Map};
+
+use crate::config::ViewKey;
+use crate::resp;
+
+/// Elements read from a list, set or zset when the view sets no limit
+pub const DEFAULT_VIEW_LIMIT: u64 = 100;
+
+/// `template` with `{param}` replaced by the argument of each declared
+/// param. Numbers and booleans are used as written. Fails on the first
+/// param without a string or scalar argument.
+pub fn render(
+    template: &str,
+    params: &[String],
+    args: &Map<String, serde_json::Value>,
+) -> Result<String, String> {
+    let mut key = template.to_string();
+    for param in params {
+        let value = match args.get(param) {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(v @ (serde_json::Value::Number(_) | serde_json::Value::Bool(_))) => v.to_string(),
+            _ => return Err(format!("Missing or invalid argument '{}'", param)),
+        };
+        key = key.replace(&format!("{{{}}}", param), &value);
+    }
+    Ok(key)
+}
+
+/// Command reading `key` as described by `spec`.
+pub fn read_command(spec: &ViewKey, key: &str) -> redis::Cmd {
+    let limit = spec.limit.unwrap_or(DEFAULT_VIEW_LIMIT);
+    let mut cmd;
+    match spec.key_type.as_str() {
+        "hash" if !spec.fields.is_empty() => {
+            cmd = redis::cmd("HMGET");
+            cmd.arg(key).arg(&spec.fields);
+        }
+        "hash" => {
+            cmd = redis::cmd("HGETALL");
+            cmd.arg(key);
+        }
+        "list" if spec.reverse => {
+            cmd = redis::cmd("LRANGE");
+            cmd.arg(key).arg(-(limit as i64)).arg(-1);
+        }
+        "list" => {
+            cmd = redis::cmd("LRANGE");
+            cmd.arg(key).arg(0).arg(limit as i64 - 1);
+        }
+        // Distinct members, all of them when the set is smaller
+        "set" => {
+            cmd = redis::cmd("SRANDMEMBER");
+            cmd.arg(key).arg(limit);
+        }
+        "zset" => {
+            cmd = redis::cmd(if spec.reverse { "ZREVRANGE" } else { "ZRANGE" });
+            cmd.arg(key).arg(0).arg(limit as i64 - 1).arg("WITHSCORES");
+        }
+        _ => {
+            cmd = redis::cmd("GET");
+            cmd.arg(key);
+        }
+    }
+    cmd
+}
+
+/// Command returning the length of a list, set or zset.
+pub fn length_command(spec: &ViewKey, key: &str) -> Option<redis::Cmd> {
+    let name = match spec.key_type.as_str() {
+        "list" => "LLEN",
+        "set" => "SCARD",
+        "zset" => "ZCARD",
+        _ => return None,
+    };
+    let mut cmd = redis::cmd(name);
+    cmd.arg(key);
+    Some(cmd)
+}
+
+/// JSON for the reply of [`read_command`]: hashes as objects, zsets as
+/// `{member, score}` entries from highest or lowest score, set members
+/// sorted.
+pub fn shape(spec: &ViewKey, reply: &Value) -> serde_json::Value {
+    match spec.key_type.as_str() {
+        "hash" if !spec.fields.is_empty() => {
+            let values = match reply {
+                Value::Array(values) => values.as_slice(),
+                _ => &[],
+            };
+            serde_json::Value::Object(
+                spec.fields
+                    .iter()
+                    .zip(values)
+                    .map(|(field, value)| (field.clone(), resp::to_json(value)))
+                    .collect(),
+            )
+        }
+        "hash" => serde_json::Value::Object(resp::to_object(reply).unwrap_or_default()),
+        "set" => {
+            let mut members = match resp::to_json(reply) {
+                serde_json::Value::Array(members) => members,
+                _ => Vec::new(),
+            };
+            members.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+            json!(members)
+        }
+        "zset" => {
+            // RESP2 replies flat [member, score, ...]; RESP3 nests the pairs
+            let items = match reply {
+                Value::Array(items) => items.as_slice(),
+                _ => &[],
+            };
+            let pairs: Vec<(&Value, &Value)> = if items.iter().all(|i| matches!(i, Value::Array(_)))
+            {
+                items
+                    .iter()
+                    .filter_map(|pair| match pair {
+                        Value::Array(pair) if pair.len() == 2 => Some((&pair[0], &pair[1])),
+                        _ => None,
+                    })
+                    .collect()
+            } else {
+                items
+                    .chunks(2)
+                    .filter(|c| c.len() == 2)
+                    .map(|c| (&c[0], &c[1]))
+                    .collect()
+            };
+            json!(pairs
+                .into_iter()
+                .map(|(member, score)| {
+                    let score = match resp::to_json(score) {
+                        serde_json::Value::String(s) => json!(s.parse::<f64>().ok()),
+                        other => other,
+                    };
+                    json!({"member": resp::to_json(member), "score": score})
+                })
+                .collect::<Vec<_>>())
+        }
+        _ => resp::to_json(reply),
+    }
+}